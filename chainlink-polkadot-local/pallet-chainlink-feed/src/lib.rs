@@ -562,6 +562,37 @@ pub mod pallet {
 			}
 		}
 
+		// --- read only queries, used by the runtime api ---
+
+		/// Return the ids of all the feeds.
+		pub fn feed_ids() -> Vec<T::FeedId> {
+			let mut ids: Vec<T::FeedId> = Feeds::<T>::iter_keys().collect();
+			ids.sort();
+			ids
+		}
+
+		/// Return the statuses of all the oracles of the given feed.
+		pub fn oracle_statuses(feed_id: T::FeedId) -> Vec<(T::AccountId, OracleStatusOf<T>)> {
+			OracleStatuses::<T>::iter_prefix(feed_id).collect()
+		}
+
+		/// Return the range of rounds of the given feed that are still in storage,
+		/// i.e. the oldest round that was not pruned and the round oracles are reporting for.
+		/// Round zero only holds the seed data and is not part of the range unless no round
+		/// has been started yet.
+		pub fn round_range(feed_id: T::FeedId) -> Option<(RoundId, RoundId)> {
+			let config = Feeds::<T>::get(feed_id)?;
+			let last = config.reporting_round.max(config.latest_round);
+			Some((config.next_round_to_prune.min(last), last))
+		}
+
+		/// Return the rounds of the given feed in `[from, to]` that are still in storage.
+		pub fn rounds_in(feed_id: T::FeedId, from: RoundId, to: RoundId) -> Vec<(RoundId, RoundOf<T>)> {
+			(from..=to)
+				.filter_map(|id| Rounds::<T>::get(feed_id, id).map(|round| (id, round)))
+				.collect()
+		}
+
 		fn genesis_feeds(
 			owner: T::AccountId,
 			payment: BalanceOf<T>,
@@ -1248,3 +1248,42 @@ fn feed_life_cylce() {
 		assert_eq!(ChainlinkFeed::feed_config(id).unwrap().reporting_round, 1);
 	});
 }
+
+#[test]
+fn round_queries_should_work() {
+	new_test_ext().execute_with(|| {
+		let feed_id = 0;
+		let oracle_a = 2;
+		let oracle_b = 3;
+		let oracle_admin = 4;
+		let submission = 42;
+
+		assert_eq!(ChainlinkFeed::round_range(feed_id), None);
+
+		assert_ok!(FeedBuilder::new()
+			.timeout(1)
+			.min_submissions(2)
+			.restart_delay(0)
+			.oracles(vec![(oracle_a, oracle_admin), (oracle_b, oracle_admin)])
+			.pruning_window(2)
+			.build_and_store());
+
+		assert_eq!(ChainlinkFeed::feed_ids(), vec![feed_id]);
+		assert_eq!(ChainlinkFeed::round_range(feed_id), Some((0, 0)));
+		let statuses = ChainlinkFeed::oracle_statuses(feed_id);
+		assert_eq!(statuses.len(), 2);
+
+		for r in 1..=3 {
+			System::set_block_number(r as u64);
+			assert_ok!(ChainlinkFeed::submit(Origin::signed(oracle_a), feed_id, r, submission));
+			assert_ok!(ChainlinkFeed::submit(Origin::signed(oracle_b), feed_id, r, submission));
+		}
+
+		// round 1 is pruned
+		assert_eq!(ChainlinkFeed::round_range(feed_id), Some((2, 3)));
+		let rounds = ChainlinkFeed::rounds_in(feed_id, 0, 3);
+		let ids: Vec<RoundId> = rounds.iter().map(|(id, _)| *id).collect();
+		assert_eq!(ids, vec![0, 2, 3]);
+		assert_eq!(rounds[2].1.answer, Some(submission));
+	});
+}
@@ -135,6 +135,22 @@ pub struct RunCmd {
 	/// Note that this is the same as running with `--validator`.
 	#[structopt(long, conflicts_with = "validator")]
 	pub collator: bool,
+
+	/// The share of the relay chain's maximum PoV size, in percent, that the blocks built by this
+	/// collator may fill. The rest is headroom for the parts of the proof that are only known
+	/// once the block is built.
+	///
+	/// Default: 50
+	#[structopt(long, parse(try_from_str = parse_percent))]
+	pub pov_budget: Option<u8>,
+}
+
+/// Parse a percentage between 1 and 100.
+fn parse_percent(s: &str) -> Result<u8, String> {
+	match s.parse::<u8>() {
+		Ok(percent) if (1..=100).contains(&percent) => Ok(percent),
+		_ => Err(format!("`{}` is not a percentage between 1 and 100", s)),
+	}
 }
 
 /// A non-redundant version of the `RunCmd` that sets the `validator` field when the
@@ -42,6 +42,11 @@ use parking_lot::Mutex;
 use std::sync::Arc;
 use tracing::Instrument;
 
+mod metrics;
+
+use metrics::CollationFailure;
+pub use metrics::Metrics;
+
 /// The logging target.
 const LOG_TARGET: &str = "cumulus-collator";
 
@@ -51,6 +56,7 @@ pub struct Collator<Block: BlockT, BS, RA> {
 	parachain_consensus: Box<dyn ParachainConsensus<Block>>,
 	wait_to_announce: Arc<Mutex<WaitToAnnounce<Block>>>,
 	runtime_api: Arc<RA>,
+	metrics: Metrics,
 }
 
 impl<Block: BlockT, BS, RA> Clone for Collator<Block, BS, RA> {
@@ -60,6 +66,7 @@ impl<Block: BlockT, BS, RA> Clone for Collator<Block, BS, RA> {
 			wait_to_announce: self.wait_to_announce.clone(),
 			parachain_consensus: self.parachain_consensus.clone(),
 			runtime_api: self.runtime_api.clone(),
+			metrics: self.metrics.clone(),
 		}
 	}
 }
@@ -78,6 +85,7 @@ where
 		announce_block: Arc<dyn Fn(Block::Hash, Option<Vec<u8>>) + Send + Sync>,
 		runtime_api: Arc<RA>,
 		parachain_consensus: Box<dyn ParachainConsensus<Block>>,
+		metrics: Metrics,
 	) -> Self {
 		let wait_to_announce = Arc::new(Mutex::new(WaitToAnnounce::new(spawner, announce_block)));
 
@@ -86,6 +94,7 @@ where
 			wait_to_announce,
 			runtime_api,
 			parachain_consensus,
+			metrics,
 		}
 	}
 
@@ -147,6 +156,7 @@ where
 		}
 	}
 
+	/// Build the collation of `block`, `None` if the collation info could not be collected.
 	fn build_collation(
 		&mut self,
 		block: ParachainBlockData<Block>,
@@ -202,12 +212,14 @@ where
 					error = ?e,
 					"Could not decode the head data."
 				);
+				self.metrics.on_collation_failed(CollationFailure::InvalidParentHead);
 				return None;
 			}
 		};
 
 		let last_head_hash = last_head.hash();
 		if !self.check_block_status(last_head_hash, &last_head) {
+			self.metrics.on_collation_failed(CollationFailure::ParentUnavailable);
 			return None;
 		}
 
@@ -218,10 +230,17 @@ where
 			"Starting collation.",
 		);
 
-		let candidate = self
+		let candidate = match self
 			.parachain_consensus
 			.produce_candidate(&last_head, relay_parent, &validation_data)
-			.await?;
+			.await
+		{
+			Some(candidate) => candidate,
+			None => {
+				self.metrics.on_collation_failed(CollationFailure::NoCandidate);
+				return None;
+			}
+		};
 
 		let (header, extrinsics) = candidate.block.deconstruct();
 
@@ -231,6 +250,7 @@ where
 			Ok(proof) => proof,
 			Err(e) => {
 				tracing::error!(target: "cumulus-collator", "Failed to compact proof: {:?}", e);
+				self.metrics.on_collation_failed(CollationFailure::CompactProof);
 				return None;
 			}
 		};
@@ -247,8 +267,31 @@ where
 			b.storage_proof().encode().len() as f64 / 1024f64,
 		);
 
+		let pov_size = b.encoded_size();
+		let storage_proof_size = b.storage_proof().encoded_size();
+
+		if pov_size > validation_data.max_pov_size as usize {
+			tracing::warn!(
+				target: LOG_TARGET,
+				pov_size,
+				max_pov_size = validation_data.max_pov_size,
+				"PoV exceeds the maximum PoV size, it will be rejected if compression doesn't bring it below the limit. \
+				Consider lowering the PoV budget of the parachain consensus.",
+			);
+			self.metrics.on_pov_limit_exceeded();
+		}
+
 		let block_hash = b.header().hash();
-		let collation = self.build_collation(b, block_hash)?;
+		let collation = match self.build_collation(b, block_hash) {
+			Some(collation) => collation,
+			None => {
+				self.metrics.on_collation_failed(CollationFailure::CollationInfo);
+				return None;
+			}
+		};
+
+		self.metrics
+			.on_collation_produced(pov_size, storage_proof_size, validation_data.max_pov_size);
 
 		let (result_sender, signed_stmt_recv) = oneshot::channel();
 
@@ -279,6 +322,7 @@ pub struct StartCollatorParams<Block: BlockT, RA, BS, Spawner> {
 	pub spawner: Spawner,
 	pub key: CollatorPair,
 	pub parachain_consensus: Box<dyn ParachainConsensus<Block>>,
+	pub metrics: Metrics,
 }
 
 /// Start the collator.
@@ -292,6 +336,7 @@ pub async fn start_collator<Block, RA, BS, Spawner>(
 		key,
 		parachain_consensus,
 		runtime_api,
+		metrics,
 	}: StartCollatorParams<Block, RA, BS, Spawner>,
 ) where
 	Block: BlockT,
@@ -306,6 +351,7 @@ pub async fn start_collator<Block, RA, BS, Spawner>(
 		announce_block,
 		runtime_api,
 		parachain_consensus,
+		metrics,
 	);
 
 	let span = tracing::Span::current();
@@ -346,6 +392,7 @@ mod tests {
 	use sp_core::{testing::TaskExecutor, Pair};
 	use sp_runtime::traits::BlakeTwo256;
 	use sp_state_machine::Backend;
+	use substrate_prometheus_endpoint::{prometheus::proto::MetricFamily, Registry};
 
 	struct AlwaysSupportsParachains;
 	impl HeadSupportsParachains for AlwaysSupportsParachains {
@@ -388,15 +435,10 @@ mod tests {
 		}
 	}
 
-	#[test]
-	fn collates_produces_a_block_and_storage_proof_does_not_contains_code() {
-		sp_tracing::try_init_simple();
-
+	fn start_test_collator(client: Arc<Client>, registry: &Registry) -> CollationGenerationConfig {
 		let spawner = TaskExecutor::new();
 		let para_id = ParaId::from(100);
 		let announce_block = |_, _| ();
-		let client = Arc::new(TestClientBuilder::new().build());
-		let header = client.header(&BlockId::Number(0)).unwrap().unwrap();
 
 		let (sub_tx, sub_rx) = mpsc::channel(64);
 
@@ -424,6 +466,7 @@ mod tests {
 			parachain_consensus: Box::new(DummyParachainConsensus {
 				client: client.clone(),
 			}),
+			metrics: Metrics::register(Some(registry)).expect("Registers the metrics"),
 		});
 		block_on(collator_start);
 
@@ -431,12 +474,32 @@ mod tests {
 			.0
 			.expect("message should be send by `start_collator` above.");
 
-		let config = match msg {
+		match msg {
 			CollationGenerationMessage::Initialize(config) => config,
-		};
+		}
+	}
+
+	fn metric_family(registry: &Registry, name: &str) -> MetricFamily {
+		registry
+			.gather()
+			.into_iter()
+			.find(|family| family.get_name() == name)
+			.expect("Collation metrics are registered")
+	}
+
+	#[test]
+	fn collates_produces_a_block_and_storage_proof_does_not_contains_code() {
+		sp_tracing::try_init_simple();
+
+		let client = Arc::new(TestClientBuilder::new().build());
+		let header = client.header(&BlockId::Number(0)).unwrap().unwrap();
+
+		let registry = Registry::new();
+		let config = start_test_collator(client, &registry);
 
 		let mut validation_data = PersistedValidationData::default();
 		validation_data.parent_head = header.encode().into();
+		validation_data.max_pov_size = 5 * 1024 * 1024;
 		let relay_parent = Default::default();
 
 		let collation = block_on((config.collator)(relay_parent, &validation_data))
@@ -450,6 +513,19 @@ mod tests {
 
 		assert_eq!(1, *block.header().number());
 
+		let collations_produced =
+			metric_family(&registry, "cumulus_collator_collations_produced_total");
+		assert_eq!(1.0, collations_produced.get_metric()[0].get_counter().get_value());
+
+		let pov_usage = metric_family(&registry, "cumulus_collator_pov_usage_ratio");
+		let pov_usage = pov_usage.get_metric()[0].get_histogram();
+		assert_eq!(1, pov_usage.get_sample_count());
+		assert!(pov_usage.get_sample_sum() < 0.5);
+
+		let pov_limit_exceeded =
+			metric_family(&registry, "cumulus_collator_pov_limit_exceeded_total");
+		assert_eq!(0.0, pov_limit_exceeded.get_metric()[0].get_counter().get_value());
+
 		// Ensure that we did not include `:code` in the proof.
 		let db = block.storage_proof()
 			.to_storage_proof::<BlakeTwo256>(Some(header.state_root()))
@@ -465,4 +541,30 @@ mod tests {
 			.unwrap_err()
 			.contains("Trie lookup error: Database missing expected key"));
 	}
+
+	#[test]
+	fn pov_exceeding_the_maximum_pov_size_is_reported() {
+		sp_tracing::try_init_simple();
+
+		let client = Arc::new(TestClientBuilder::new().build());
+		let header = client.header(&BlockId::Number(0)).unwrap().unwrap();
+
+		let registry = Registry::new();
+		let config = start_test_collator(client, &registry);
+
+		let mut validation_data = PersistedValidationData::default();
+		validation_data.parent_head = header.encode().into();
+		validation_data.max_pov_size = 16;
+
+		// The collation is still handed to the relay chain node, as compression could bring it
+		// below the limit.
+		assert!(block_on((config.collator)(Default::default(), &validation_data)).is_some());
+
+		let pov_limit_exceeded =
+			metric_family(&registry, "cumulus_collator_pov_limit_exceeded_total");
+		assert_eq!(1.0, pov_limit_exceeded.get_metric()[0].get_counter().get_value());
+
+		let pov_usage = metric_family(&registry, "cumulus_collator_pov_usage_ratio");
+		assert!(pov_usage.get_metric()[0].get_histogram().get_sample_sum() > 1.0);
+	}
 }
@@ -0,0 +1,173 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the collator.
+
+use substrate_prometheus_endpoint::{
+	exponential_buckets, register, Counter, CounterVec, Histogram, HistogramOpts, Opts,
+	PrometheusError, Registry, U64,
+};
+
+/// The reason why the production of a collation failed.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum CollationFailure {
+	/// The parent head from the relay chain could not be decoded.
+	InvalidParentHead,
+	/// The parent block is not available to build on.
+	ParentUnavailable,
+	/// The parachain consensus did not produce a candidate.
+	NoCandidate,
+	/// The storage proof could not be compacted.
+	CompactProof,
+	/// The collation info could not be collected from the runtime.
+	CollationInfo,
+}
+
+impl CollationFailure {
+	fn as_label(&self) -> &'static str {
+		match self {
+			Self::InvalidParentHead => "invalid_parent_head",
+			Self::ParentUnavailable => "parent_unavailable",
+			Self::NoCandidate => "no_candidate",
+			Self::CompactProof => "compact_proof",
+			Self::CollationInfo => "collation_info",
+		}
+	}
+}
+
+#[derive(Clone)]
+struct MetricsInner {
+	collations_produced: Counter<U64>,
+	collations_failed: CounterVec<U64>,
+	pov_size: Histogram,
+	storage_proof_size: Histogram,
+	pov_usage: Histogram,
+	pov_limit_exceeded: Counter<U64>,
+}
+
+/// Collator metrics.
+///
+/// Doesn't record anything if it wasn't registered on a prometheus registry.
+#[derive(Clone, Default)]
+pub struct Metrics(Option<MetricsInner>);
+
+impl Metrics {
+	/// Register the metrics on the given `registry`.
+	///
+	/// Returns metrics that don't record anything if `registry` is `None`.
+	pub fn register(registry: Option<&Registry>) -> Result<Self, PrometheusError> {
+		let registry = match registry {
+			Some(registry) => registry,
+			None => return Ok(Self(None)),
+		};
+
+		let size_buckets = || exponential_buckets(1024.0, 2.0, 13).expect("Valid buckets; qed");
+
+		Ok(Self(Some(MetricsInner {
+			collations_produced: register(
+				Counter::new(
+					"cumulus_collator_collations_produced_total",
+					"Number of collations produced by the collator.",
+				)?,
+				registry,
+			)?,
+			collations_failed: register(
+				CounterVec::new(
+					Opts::new(
+						"cumulus_collator_collations_failed_total",
+						"Number of collations the collator failed to produce.",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
+			pov_size: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_collator_pov_size_bytes",
+						"Size of the encoded proof-of-validity blocks produced by the collator.",
+					)
+					.buckets(size_buckets()),
+				)?,
+				registry,
+			)?,
+			storage_proof_size: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_collator_storage_proof_size_bytes",
+						"Size of the encoded storage proofs in the proof-of-validity blocks.",
+					)
+					.buckets(size_buckets()),
+				)?,
+				registry,
+			)?,
+			pov_usage: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_collator_pov_usage_ratio",
+						"Size of the proof-of-validity blocks relative to the maximum PoV size of the relay chain.",
+					)
+					.buckets(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.5]),
+				)?,
+				registry,
+			)?,
+			pov_limit_exceeded: register(
+				Counter::new(
+					"cumulus_collator_pov_limit_exceeded_total",
+					"Number of proof-of-validity blocks that exceeded the maximum PoV size of the relay chain.",
+				)?,
+				registry,
+			)?,
+		})))
+	}
+
+	/// Note a produced collation with the given encoded sizes.
+	pub(crate) fn on_collation_produced(
+		&self,
+		pov_size: usize,
+		storage_proof_size: usize,
+		max_pov_size: u32,
+	) {
+		if let Some(metrics) = &self.0 {
+			metrics.collations_produced.inc();
+			metrics.pov_size.observe(pov_size as f64);
+			metrics.storage_proof_size.observe(storage_proof_size as f64);
+
+			if max_pov_size > 0 {
+				metrics
+					.pov_usage
+					.observe(pov_size as f64 / max_pov_size as f64);
+			}
+		}
+	}
+
+	/// Note that a proof-of-validity block exceeded the maximum PoV size.
+	pub(crate) fn on_pov_limit_exceeded(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.pov_limit_exceeded.inc();
+		}
+	}
+
+	/// Note that producing a collation failed.
+	pub(crate) fn on_collation_failed(&self, reason: CollationFailure) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.collations_failed
+				.with_label_values(&[reason.as_label()])
+				.inc();
+		}
+	}
+}
@@ -24,7 +24,7 @@
 
 use codec::{Decode, Encode};
 use cumulus_client_consensus_common::{
-	ParachainBlockImport, ParachainCandidate, ParachainConsensus,
+	ParachainBlockImport, ParachainCandidate, ParachainConsensus, PoVBudget,
 };
 use cumulus_primitives_core::{
 	relay_chain::v1::{Block as PBlock, Hash as PHash, ParachainHost},
@@ -72,6 +72,7 @@ pub struct AuraConsensus<B, RClient, RBackend, CIDP> {
 		>,
 	>,
 	slot_duration: SlotDuration,
+	pov_budget: PoVBudget,
 }
 
 impl<B, RClient, RBackend, CIDP> Clone for AuraConsensus<B, RClient, RBackend, CIDP> {
@@ -82,6 +83,7 @@ impl<B, RClient, RBackend, CIDP> Clone for AuraConsensus<B, RClient, RBackend, C
 			relay_chain_client: self.relay_chain_client.clone(),
 			aura_worker: self.aura_worker.clone(),
 			slot_duration: self.slot_duration,
+			pov_budget: self.pov_budget,
 		}
 	}
 }
@@ -110,6 +112,7 @@ where
 		slot_duration: SlotDuration,
 		telemetry: Option<TelemetryHandle>,
 		block_proposal_slot_portion: SlotProportion,
+		pov_budget: PoVBudget,
 	) -> Self
 	where
 		Client: ProvideRuntimeApi<B>
@@ -157,6 +160,7 @@ where
 			relay_chain_client: polkadot_client,
 			aura_worker: Arc::new(Mutex::new(worker)),
 			slot_duration,
+			pov_budget,
 		}
 	}
 
@@ -222,11 +226,7 @@ where
 			inherent_data,
 			self.slot_duration.slot_duration(),
 			parent.clone(),
-			// Set the block limit to 50% of the maximum PoV size.
-			//
-			// TODO: If we got benchmarking that includes the proof size,
-			// we should be able to use the maximum pov size.
-			Some((validation_data.max_pov_size / 2) as usize),
+			Some(self.pov_budget.block_size_limit(validation_data)),
 		);
 
 		let res = self.aura_worker.lock().await.on_slot(info).await?;
@@ -253,6 +253,8 @@ pub struct BuildAuraConsensusParams<PF, BI, RBackend, CIDP, Client, BS, SO> {
 	pub slot_duration: SlotDuration,
 	pub telemetry: Option<TelemetryHandle>,
 	pub block_proposal_slot_portion: SlotProportion,
+	/// The share of the maximum PoV size the produced blocks may use.
+	pub pov_budget: PoVBudget,
 }
 
 /// Build the [`AuraConsensus`].
@@ -273,6 +275,7 @@ pub fn build_aura_consensus<P, Block, PF, BI, RBackend, CIDP, Client, SO, BS, Er
 		slot_duration,
 		telemetry,
 		block_proposal_slot_portion,
+		pov_budget,
 	}: BuildAuraConsensusParams<PF, BI, RBackend, CIDP, Client, BS, SO>,
 ) -> Box<dyn ParachainConsensus<Block>>
 where
@@ -327,6 +330,7 @@ where
 		slot_duration,
 		telemetry,
 		block_proposal_slot_portion,
+		pov_budget,
 	)
 	.build()
 }
@@ -352,6 +356,7 @@ struct AuraConsensusBuilder<P, Block, PF, BI, RBackend, CIDP, Client, SO, BS, Er
 	slot_duration: SlotDuration,
 	telemetry: Option<TelemetryHandle>,
 	block_proposal_slot_portion: SlotProportion,
+	pov_budget: PoVBudget,
 }
 
 impl<Block, PF, BI, RBackend, CIDP, Client, SO, BS, P, Error>
@@ -409,6 +414,7 @@ where
 		slot_duration: SlotDuration,
 		telemetry: Option<TelemetryHandle>,
 		block_proposal_slot_portion: SlotProportion,
+		pov_budget: PoVBudget,
 	) -> Self {
 		Self {
 			_phantom: PhantomData,
@@ -425,6 +431,7 @@ where
 			slot_duration,
 			telemetry,
 			block_proposal_slot_portion,
+			pov_budget,
 		}
 	}
 
@@ -498,6 +505,7 @@ where
 			self.slot_duration,
 			self.telemetry,
 			self.block_proposal_slot_portion,
+			self.pov_budget,
 		))
 	}
 }
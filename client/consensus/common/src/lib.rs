@@ -16,7 +16,7 @@
 
 use polkadot_primitives::v1::{Hash as PHash, PersistedValidationData};
 use sp_consensus::BlockImport;
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::{traits::Block as BlockT, Percent};
 
 mod parachain_consensus;
 #[cfg(test)]
@@ -31,6 +31,37 @@ pub struct ParachainCandidate<B> {
 	pub proof: sp_trie::StorageProof,
 }
 
+/// The share of the relay chain's maximum PoV size that the proposer may fill with a block.
+///
+/// The resulting limit is passed as block size limit to the proposer. A proposer that records the
+/// storage proof counts the recorded proof into the block size while applying extrinsics, so it
+/// stops including transactions once the proof-of-validity would exceed the budget. The remaining
+/// share is headroom for the parts of the proof that are not known before the block is finalized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoVBudget(Percent);
+
+impl PoVBudget {
+	/// Create a new budget that allows blocks to use `max_pov_share` of the maximum PoV size.
+	pub fn new(max_pov_share: Percent) -> Self {
+		Self(max_pov_share)
+	}
+
+	/// Returns the block size limit for a block that is built on top of `validation_data`.
+	pub fn block_size_limit(&self, validation_data: &PersistedValidationData) -> usize {
+		self.0.mul_floor(validation_data.max_pov_size) as usize
+	}
+}
+
+impl Default for PoVBudget {
+	/// Use 50% of the maximum PoV size.
+	///
+	/// As long as the weights don't account for the proof size, this leaves enough room for
+	/// the proof of the transaction that pushes the block over the limit.
+	fn default() -> Self {
+		Self(Percent::from_percent(50))
+	}
+}
+
 /// A specific parachain consensus implementation that can be used by a collator to produce candidates.
 ///
 /// The collator will call [`Self::produce_candidate`] every time there is a free core for the parachain
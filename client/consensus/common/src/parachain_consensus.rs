@@ -14,20 +14,19 @@
 // You should have received a copy of the GNU General Public License
 // along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
 
+use async_trait::async_trait;
 use sc_client_api::{
 	Backend, BlockBackend, BlockImportNotification, BlockchainEvents, Finalizer, UsageProvider,
 };
-use sp_api::ProvideRuntimeApi;
-use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_blockchain::Error as ClientError;
 use sp_consensus::{BlockImport, BlockImportParams, BlockOrigin, BlockStatus, ForkChoiceStrategy};
 use sp_runtime::{
 	generic::BlockId,
 	traits::{Block as BlockT, Header as HeaderT},
 };
 
-use polkadot_primitives::v1::{
-	Block as PBlock, Id as ParaId, OccupiedCoreAssumption, ParachainHost,
-};
+use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult, RelayHeaderStream};
+use polkadot_primitives::v1::{Hash as PHash, Id as ParaId, OccupiedCoreAssumption};
 
 use codec::Decode;
 use futures::{future, select, FutureExt, Stream, StreamExt};
@@ -35,6 +34,7 @@ use futures::{future, select, FutureExt, Stream, StreamExt};
 use std::{pin::Pin, sync::Arc};
 
 /// Helper for the relay chain client. This is expected to be a lightweight handle like an `Arc`.
+#[async_trait]
 pub trait RelaychainClient: Clone + 'static {
 	/// The error type for interacting with the Polkadot client.
 	type Error: std::fmt::Debug + Send;
@@ -48,12 +48,12 @@ pub trait RelaychainClient: Clone + 'static {
 	/// Get a stream of finalized heads for the given parachain.
 	fn finalized_heads(&self, para_id: ParaId) -> Self::HeadStream;
 
-	/// Returns the parachain head for the given `para_id` at the given block id.
-	fn parachain_head_at(
+	/// Returns the parachain head for the given `para_id` at the given relay chain block.
+	async fn parachain_head_at(
 		&self,
-		at: &BlockId<PBlock>,
+		at: PHash,
 		para_id: ParaId,
-	) -> ClientResult<Option<Vec<u8>>>;
+	) -> Result<Option<Vec<u8>>, Self::Error>;
 }
 
 /// Follow the finalized head of the given parachain.
@@ -364,55 +364,71 @@ where
 	}
 }
 
-impl<T> RelaychainClient for Arc<T>
+/// Turns the header stream returned by `stream` into a stream of heads of `para_id`.
+fn parachain_heads<F>(
+	relay_chain: Arc<dyn RelayChainInterface>,
+	para_id: ParaId,
+	stream: F,
+) -> Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>
 where
-	T: sc_client_api::BlockchainEvents<PBlock> + ProvideRuntimeApi<PBlock> + 'static + Send + Sync,
-	<T as ProvideRuntimeApi<PBlock>>::Api: ParachainHost<PBlock>,
+	F: future::Future<Output = RelayChainResult<RelayHeaderStream>> + Send + 'static,
 {
-	type Error = ClientError;
+	stream
+		.map(|res| match res {
+			Ok(stream) => stream,
+			Err(err) => {
+				tracing::error!(
+					target: "cumulus-consensus",
+					error = ?err,
+					"Failed to subscribe to relay chain headers.",
+				);
+				futures::stream::empty().boxed()
+			}
+		})
+		.flatten_stream()
+		.filter_map(move |header| {
+			let relay_chain = relay_chain.clone();
+
+			async move {
+				relay_chain
+					.parachain_head_at(header.hash(), para_id)
+					.await
+					.ok()
+					.flatten()
+			}
+		})
+		.boxed()
+}
+
+#[async_trait]
+impl RelaychainClient for Arc<dyn RelayChainInterface> {
+	type Error = cumulus_relay_chain_interface::RelayChainError;
 
 	type HeadStream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
 
 	fn new_best_heads(&self, para_id: ParaId) -> Self::HeadStream {
 		let relay_chain = self.clone();
 
-		self.import_notification_stream()
-			.filter_map(move |n| {
-				future::ready(if n.is_new_best {
-					relay_chain
-						.parachain_head_at(&BlockId::hash(n.hash), para_id)
-						.ok()
-						.flatten()
-				} else {
-					None
-				})
-			})
-			.boxed()
+		parachain_heads(self.clone(), para_id, async move {
+			relay_chain.new_best_notification_stream().await
+		})
 	}
 
 	fn finalized_heads(&self, para_id: ParaId) -> Self::HeadStream {
 		let relay_chain = self.clone();
 
-		self.finality_notification_stream()
-			.filter_map(move |n| {
-				future::ready(
-					relay_chain
-						.parachain_head_at(&BlockId::hash(n.hash), para_id)
-						.ok()
-						.flatten(),
-				)
-			})
-			.boxed()
+		parachain_heads(self.clone(), para_id, async move {
+			relay_chain.finality_notification_stream().await
+		})
 	}
 
-	fn parachain_head_at(
+	async fn parachain_head_at(
 		&self,
-		at: &BlockId<PBlock>,
+		at: PHash,
 		para_id: ParaId,
-	) -> ClientResult<Option<Vec<u8>>> {
-		self.runtime_api()
-			.persisted_validation_data(at, para_id, OccupiedCoreAssumption::TimedOut)
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.persisted_validation_data(at, para_id, OccupiedCoreAssumption::TimedOut)
+			.await
 			.map(|s| s.map(|s| s.parent_head.0))
-			.map_err(Into::into)
 	}
 }
@@ -23,9 +23,9 @@ use cumulus_test_client::{
 };
 use futures::{channel::mpsc, executor::block_on, select, FutureExt, Stream, StreamExt};
 use futures_timer::Delay;
-use polkadot_primitives::v1::{Block as PBlock, Id as ParaId};
+use polkadot_primitives::v1::{Hash as PHash, Id as ParaId};
 use sc_client_api::UsageProvider;
-use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_blockchain::Error as ClientError;
 use sp_consensus::{BlockImport, BlockImportParams, BlockOrigin, ForkChoiceStrategy};
 use sp_runtime::generic::BlockId;
 use std::{
@@ -67,6 +67,7 @@ impl Relaychain {
 	}
 }
 
+#[async_trait::async_trait]
 impl crate::parachain_consensus::RelaychainClient for Relaychain {
 	type Error = ClientError;
 
@@ -96,7 +97,7 @@ impl crate::parachain_consensus::RelaychainClient for Relaychain {
 		Box::new(stream.map(|v| v.encode()))
 	}
 
-	fn parachain_head_at(&self, _: &BlockId<PBlock>, _: ParaId) -> ClientResult<Option<Vec<u8>>> {
+	async fn parachain_head_at(&self, _: PHash, _: ParaId) -> Result<Option<Vec<u8>>, ClientError> {
 		unimplemented!("Not required for tests")
 	}
 }
@@ -336,3 +337,24 @@ fn follow_new_best_sets_best_after_it_is_imported() {
 		}
 	});
 }
+
+#[test]
+fn pov_budget_limits_block_size_to_share_of_max_pov_size() {
+	let validation_data = PersistedValidationData {
+		max_pov_size: 5 * 1024 * 1024,
+		..Default::default()
+	};
+
+	assert_eq!(
+		PoVBudget::default().block_size_limit(&validation_data),
+		(validation_data.max_pov_size / 2) as usize,
+	);
+	assert_eq!(
+		PoVBudget::new(Percent::from_percent(80)).block_size_limit(&validation_data),
+		4 * 1024 * 1024,
+	);
+	assert_eq!(
+		PoVBudget::new(Percent::from_percent(80)).block_size_limit(&Default::default()),
+		0,
+	);
+}
@@ -34,7 +34,7 @@
 //! 5. After the parachain candidate got backed and included, all collators start at 1.
 
 use cumulus_client_consensus_common::{
-	ParachainBlockImport, ParachainCandidate, ParachainConsensus,
+	ParachainBlockImport, ParachainCandidate, ParachainConsensus, PoVBudget,
 };
 use cumulus_primitives_core::{
 	relay_chain::v1::{Block as PBlock, Hash as PHash, ParachainHost},
@@ -66,6 +66,7 @@ pub struct RelayChainConsensus<B, PF, BI, RClient, RBackend, CIDP> {
 	block_import: Arc<futures::lock::Mutex<ParachainBlockImport<BI>>>,
 	relay_chain_client: Arc<RClient>,
 	relay_chain_backend: Arc<RBackend>,
+	pov_budget: PoVBudget,
 }
 
 impl<B, PF, BI, RClient, RBackend, CIDP> Clone
@@ -80,6 +81,7 @@ impl<B, PF, BI, RClient, RBackend, CIDP> Clone
 			block_import: self.block_import.clone(),
 			relay_chain_backend: self.relay_chain_backend.clone(),
 			relay_chain_client: self.relay_chain_client.clone(),
+			pov_budget: self.pov_budget,
 		}
 	}
 }
@@ -100,6 +102,7 @@ where
 		block_import: BI,
 		polkadot_client: Arc<RClient>,
 		polkadot_backend: Arc<RBackend>,
+		pov_budget: PoVBudget,
 	) -> Self {
 		Self {
 			para_id,
@@ -110,6 +113,7 @@ where
 			))),
 			relay_chain_backend: polkadot_backend,
 			relay_chain_client: polkadot_client,
+			pov_budget,
 			_phantom: PhantomData,
 		}
 	}
@@ -194,11 +198,7 @@ where
 				Default::default(),
 				//TODO: Fix this.
 				Duration::from_millis(500),
-				// Set the block limit to 50% of the maximum PoV size.
-				//
-				// TODO: If we got benchmarking that includes that encapsulates the proof size,
-				// we should be able to use the maximum pov size.
-				Some((validation_data.max_pov_size / 2) as usize),
+				Some(self.pov_budget.block_size_limit(validation_data)),
 			)
 			.await
 			.map_err(|e| tracing::error!(target: LOG_TARGET, error = ?e, "Proposing failed."))
@@ -239,6 +239,8 @@ pub struct BuildRelayChainConsensusParams<PF, BI, RBackend, CIDP> {
 	pub block_import: BI,
 	pub relay_chain_client: polkadot_client::Client,
 	pub relay_chain_backend: Arc<RBackend>,
+	/// The share of the maximum PoV size the produced blocks may use.
+	pub pov_budget: PoVBudget,
 }
 
 /// Build the [`RelayChainConsensus`].
@@ -252,6 +254,7 @@ pub fn build_relay_chain_consensus<Block, PF, BI, RBackend, CIDP>(
 		block_import,
 		relay_chain_client,
 		relay_chain_backend,
+		pov_budget,
 	}: BuildRelayChainConsensusParams<PF, BI, RBackend, CIDP>,
 ) -> Box<dyn ParachainConsensus<Block>>
 where
@@ -276,6 +279,7 @@ where
 		create_inherent_data_providers,
 		relay_chain_client,
 		relay_chain_backend,
+		pov_budget,
 	)
 	.build()
 }
@@ -294,6 +298,7 @@ struct RelayChainConsensusBuilder<Block, PF, BI, RBackend, CIDP> {
 	block_import: BI,
 	relay_chain_backend: Arc<RBackend>,
 	relay_chain_client: polkadot_client::Client,
+	pov_budget: PoVBudget,
 }
 
 impl<Block, PF, BI, RBackend, CIDP> RelayChainConsensusBuilder<Block, PF, BI, RBackend, CIDP>
@@ -320,6 +325,7 @@ where
 		create_inherent_data_providers: CIDP,
 		relay_chain_client: polkadot_client::Client,
 		relay_chain_backend: Arc<RBackend>,
+		pov_budget: PoVBudget,
 	) -> Self {
 		Self {
 			para_id,
@@ -329,6 +335,7 @@ where
 			create_inherent_data_providers,
 			relay_chain_backend,
 			relay_chain_client,
+			pov_budget,
 		}
 	}
 
@@ -372,6 +379,7 @@ where
 			self.block_import,
 			client.clone(),
 			self.relay_chain_backend,
+			self.pov_budget,
 		))
 	}
 }
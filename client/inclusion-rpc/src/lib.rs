@@ -0,0 +1,164 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parachain inclusion RPC
+//!
+//! The parachain consensus sets the best and finalized parachain blocks based on what the relay
+//! chain included and finalized, but applications can not observe when this happens. This crate
+//! provides the [`InclusionTracker`] that follows the relay chain and reports for every parachain
+//! block when it was backed, included and finalized by the relay chain, together with the relay
+//! chain block at which this was observed. The events are exposed over the
+//! `parachain_subscribeInclusion` RPC subscription.
+//!
+//! Applications that need to wait for true finality should wait for the
+//! [`InclusionStatus::Finalized`] event of their block or one of its descendants. A relay chain
+//! reorg that drops the inclusion of a block is reported as [`InclusionStatus::Retracted`].
+
+use std::sync::Arc;
+
+use futures::{FutureExt, SinkExt, StreamExt};
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use polkadot_primitives::v1::{BlockNumber as RelayBlockNumber, Hash as PHash};
+use serde::{Deserialize, Serialize};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+mod tracker;
+#[cfg(test)]
+mod tests;
+
+pub use tracker::{InclusionEventStream, InclusionTracker};
+
+/// The inclusion status of a parachain block on the relay chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InclusionStatus {
+	/// The candidate of the block was backed and is pending availability.
+	Backed,
+	/// The block was included and is the new head of the parachain.
+	Included,
+	/// The relay chain block that included the block, or one of its descendants, was finalized.
+	///
+	/// All ancestors of the block are finalized as well.
+	Finalized,
+	/// The block was included before, but a relay chain reorg replaced it as head of the parachain.
+	Retracted,
+}
+
+/// A change of the [`InclusionStatus`] of a parachain block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionEvent<Hash, Number> {
+	/// The hash of the parachain block.
+	pub block_hash: Hash,
+	/// The number of the parachain block.
+	pub block_number: Number,
+	/// The new status of the parachain block.
+	pub status: InclusionStatus,
+	/// The hash of the relay chain block at which the status was observed.
+	pub relay_block_hash: PHash,
+	/// The number of the relay chain block at which the status was observed.
+	pub relay_block_number: RelayBlockNumber,
+}
+
+/// The [`InclusionEvent`] of a parachain block of `Block`.
+pub type InclusionEventFor<Block> = InclusionEvent<<Block as BlockT>::Hash, NumberFor<Block>>;
+
+/// Parachain inclusion RPC methods.
+#[rpc(server)]
+pub trait InclusionApi<Hash, Number> {
+	/// RPC metadata
+	type Metadata;
+
+	/// Subscribe to the relay chain inclusion status of the parachain blocks.
+	#[pubsub(
+		subscription = "parachain_inclusion",
+		subscribe,
+		name = "parachain_subscribeInclusion"
+	)]
+	fn subscribe_inclusion(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<InclusionEvent<Hash, Number>>,
+	);
+
+	/// Unsubscribe from the relay chain inclusion status of the parachain blocks.
+	#[pubsub(
+		subscription = "parachain_inclusion",
+		unsubscribe,
+		name = "parachain_unsubscribeInclusion"
+	)]
+	fn unsubscribe_inclusion(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool>;
+}
+
+/// Implementation of [`InclusionApi`] that forwards the events of an [`InclusionTracker`].
+pub struct Inclusion<Block: BlockT> {
+	tracker: InclusionTracker<Block>,
+	subscriptions: SubscriptionManager,
+}
+
+impl<Block: BlockT> Inclusion<Block> {
+	/// Create a new instance that drives the subscriptions on `executor`.
+	///
+	/// This is usually the subscription task executor passed to the RPC extensions builder.
+	pub fn new(
+		tracker: InclusionTracker<Block>,
+		executor: Arc<dyn futures::task::Spawn + Send + Sync>,
+	) -> Self {
+		Self {
+			tracker,
+			subscriptions: SubscriptionManager::new(executor),
+		}
+	}
+}
+
+impl<Block: BlockT> InclusionApi<Block::Hash, NumberFor<Block>> for Inclusion<Block> {
+	type Metadata = sc_rpc_api::Metadata;
+
+	fn subscribe_inclusion(
+		&self,
+		_: Self::Metadata,
+		subscriber: Subscriber<InclusionEventFor<Block>>,
+	) {
+		let events = self.tracker.subscribe();
+
+		self.subscriptions.add(subscriber, |sink| {
+			events
+				.map(|event| Ok::<_, ()>(Ok(event)))
+				.forward(sink.sink_map_err(|e| {
+					tracing::debug!(
+						target: tracker::LOG_TARGET,
+						error = ?e,
+						"Failed to send inclusion event.",
+					)
+				}))
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_inclusion(
+		&self,
+		_: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
+}
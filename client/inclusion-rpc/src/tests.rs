@@ -0,0 +1,400 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::tracker::SUBSCRIBER_BUFFER;
+use async_trait::async_trait;
+use codec::Encode;
+use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult, RelayHeaderStream};
+use cumulus_test_runtime::{Block, Hash, Header};
+use futures::channel::mpsc;
+use parking_lot::Mutex;
+use polkadot_overseer::OverseerHandler;
+use polkadot_primitives::v1::{
+	CandidateCommitments, CommittedCandidateReceipt, HeadData, Header as PHeader, Id as ParaId,
+	OccupiedCoreAssumption, PersistedValidationData, SessionIndex, ValidatorId,
+};
+use sp_runtime::traits::Header as HeaderT;
+use std::collections::HashMap;
+
+/// The state of the parachain at a relay chain block.
+struct ParachainState {
+	head: Header,
+	pending: Option<Header>,
+}
+
+struct TestRelayChain {
+	headers: Mutex<HashMap<PHash, PHeader>>,
+	states: Mutex<HashMap<PHash, ParachainState>>,
+	new_best: Mutex<Option<mpsc::UnboundedReceiver<PHeader>>>,
+	finalized: Mutex<Option<mpsc::UnboundedReceiver<PHeader>>>,
+}
+
+#[async_trait]
+impl RelayChainInterface for TestRelayChain {
+	async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn header(&self, hash: PHash) -> RelayChainResult<Option<PHeader>> {
+		Ok(self.headers.lock().get(&hash).cloned())
+	}
+
+	async fn persisted_validation_data(
+		&self,
+		block: PHash,
+		_: ParaId,
+		_: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>> {
+		Ok(self.states.lock().get(&block).map(|state| PersistedValidationData {
+			parent_head: HeadData(state.head.encode()),
+			..Default::default()
+		}))
+	}
+
+	async fn candidate_pending_availability(
+		&self,
+		block: PHash,
+		_: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+		Ok(self
+			.states
+			.lock()
+			.get(&block)
+			.and_then(|state| state.pending.as_ref())
+			.map(|pending| CommittedCandidateReceipt {
+				descriptor: Default::default(),
+				commitments: CandidateCommitments {
+					head_data: HeadData(pending.encode()),
+					..Default::default()
+				},
+			}))
+	}
+
+	async fn session_index_for_child(&self, _: PHash) -> RelayChainResult<SessionIndex> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn validators(&self, _: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn wait_for_block(&self, _: PHash) -> RelayChainResult<()> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn import_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn new_best_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		Ok(self.new_best.lock().take().expect("Subscribed only once").boxed())
+	}
+
+	async fn finality_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		Ok(self.finalized.lock().take().expect("Subscribed only once").boxed())
+	}
+
+	fn overseer_handler(&self) -> RelayChainResult<OverseerHandler> {
+		unimplemented!("Not required in tests")
+	}
+}
+
+struct TestSetup {
+	relay_chain: Arc<TestRelayChain>,
+	new_best: mpsc::UnboundedSender<PHeader>,
+	finalized: mpsc::UnboundedSender<PHeader>,
+	events: InclusionEventStream<Block>,
+}
+
+impl TestSetup {
+	fn start() -> Self {
+		let (new_best, new_best_rx) = mpsc::unbounded();
+		let (finalized, finalized_rx) = mpsc::unbounded();
+		let relay_chain = Arc::new(TestRelayChain {
+			headers: Default::default(),
+			states: Default::default(),
+			new_best: Mutex::new(Some(new_best_rx)),
+			finalized: Mutex::new(Some(finalized_rx)),
+		});
+
+		let tracker = InclusionTracker::<Block>::new();
+		let events = tracker.subscribe();
+		tokio::spawn(tracker.run(ParaId::from(100), relay_chain.clone()));
+
+		Self {
+			relay_chain,
+			new_best,
+			finalized,
+			events,
+		}
+	}
+
+	/// Import a relay chain block on top of `parent` that includes `head` and has `pending`
+	/// pending availability, without notifying the tracker.
+	///
+	/// `fork` is used to create different relay chain blocks at the same height.
+	fn import(
+		&self,
+		parent: Option<&PHeader>,
+		fork: u8,
+		head: &Header,
+		pending: Option<&Header>,
+	) -> PHeader {
+		let relay_block = relay_header(parent, fork);
+
+		self.relay_chain.states.lock().insert(
+			relay_block.hash(),
+			ParachainState {
+				head: head.clone(),
+				pending: pending.cloned(),
+			},
+		);
+		self.relay_chain
+			.headers
+			.lock()
+			.insert(relay_block.hash(), relay_block.clone());
+
+		relay_block
+	}
+
+	/// Import a relay chain block like [`Self::import`] and make it the new best block.
+	fn new_best(
+		&self,
+		parent: Option<&PHeader>,
+		fork: u8,
+		head: &Header,
+		pending: Option<&Header>,
+	) -> PHeader {
+		let relay_block = self.import(parent, fork, head, pending);
+		self.new_best.unbounded_send(relay_block.clone()).unwrap();
+
+		relay_block
+	}
+
+	fn finalize(&self, relay_block: &PHeader) {
+		self.finalized.unbounded_send(relay_block.clone()).unwrap();
+	}
+
+	async fn next_event(&mut self) -> (Hash, InclusionStatus, u32) {
+		let event = self.events.next().await.expect("Tracker is running");
+		(event.block_hash, event.status, event.relay_block_number)
+	}
+}
+
+/// Creates a relay chain header on top of `parent` or a header at height one if there is no
+/// `parent`.
+///
+/// `fork` is used to create different headers at the same height.
+fn relay_header(parent: Option<&PHeader>, fork: u8) -> PHeader {
+	PHeader {
+		parent_hash: parent.map(|p| p.hash()).unwrap_or_default(),
+		number: parent.map_or(1, |p| p.number + 1),
+		state_root: PHash::repeat_byte(fork),
+		extrinsics_root: Default::default(),
+		digest: Default::default(),
+	}
+}
+
+/// Creates a parachain header on top of `parent` or a genesis header if there is no `parent`.
+///
+/// `fork` is used to create different headers at the same height.
+fn para_header(parent: Option<&Header>, fork: u8) -> Header {
+	Header::new(
+		parent.map_or(0, |p| p.number() + 1),
+		Default::default(),
+		Hash::repeat_byte(fork),
+		parent.map(|p| p.hash()).unwrap_or_default(),
+		Default::default(),
+	)
+}
+
+#[tokio::test]
+async fn reports_backed_included_and_finalized_blocks() {
+	let mut setup = TestSetup::start();
+	let genesis = para_header(None, 0);
+	let block1 = para_header(Some(&genesis), 0);
+	let block2 = para_header(Some(&block1), 0);
+
+	let relay_block = setup.new_best(None, 0, &genesis, Some(&block1));
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Backed, 1)
+	);
+	assert_eq!(
+		setup.next_event().await,
+		(genesis.hash(), InclusionStatus::Included, 1)
+	);
+
+	let relay_block = setup.new_best(Some(&relay_block), 0, &block1, Some(&block2));
+	assert_eq!(
+		setup.next_event().await,
+		(block2.hash(), InclusionStatus::Backed, 2)
+	);
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Included, 2)
+	);
+
+	setup.finalize(&relay_block);
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Finalized, 2)
+	);
+}
+
+#[tokio::test]
+async fn unchanged_status_is_not_reported_again() {
+	let mut setup = TestSetup::start();
+	let genesis = para_header(None, 0);
+	let block1 = para_header(Some(&genesis), 0);
+
+	let relay_block = setup.new_best(None, 0, &genesis, Some(&block1));
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Backed, 1)
+	);
+	assert_eq!(
+		setup.next_event().await,
+		(genesis.hash(), InclusionStatus::Included, 1)
+	);
+	setup.finalize(&relay_block);
+	assert_eq!(
+		setup.next_event().await,
+		(genesis.hash(), InclusionStatus::Finalized, 1)
+	);
+
+	// Still pending availability and nothing new included.
+	let relay_block = setup.new_best(Some(&relay_block), 0, &genesis, Some(&block1));
+	setup.finalize(&relay_block);
+
+	let relay_block = setup.new_best(Some(&relay_block), 0, &block1, None);
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Included, 3)
+	);
+	setup.finalize(&relay_block);
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Finalized, 3)
+	);
+}
+
+#[tokio::test]
+async fn relay_chain_reorg_retracts_included_block() {
+	let mut setup = TestSetup::start();
+	let genesis = para_header(None, 0);
+	let block1 = para_header(Some(&genesis), 0);
+	let block1_fork = para_header(Some(&genesis), 1);
+
+	let relay_block1 = setup.import(None, 0, &genesis, None);
+	setup.new_best(Some(&relay_block1), 0, &block1, None);
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Included, 2)
+	);
+
+	// A relay chain fork that included a different block at the same height became the best.
+	let relay_block2 = setup.new_best(Some(&relay_block1), 1, &block1_fork, None);
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Retracted, 2)
+	);
+	assert_eq!(
+		setup.next_event().await,
+		(block1_fork.hash(), InclusionStatus::Included, 2)
+	);
+
+	// Moving forward on top of the included block is not a retraction.
+	let block2 = para_header(Some(&block1_fork), 0);
+	setup.new_best(Some(&relay_block2), 1, &block2, None);
+	assert_eq!(
+		setup.next_event().await,
+		(block2.hash(), InclusionStatus::Included, 3)
+	);
+}
+
+#[tokio::test]
+async fn relay_chain_reorg_retracts_blocks_missing_on_the_new_chain() {
+	let mut setup = TestSetup::start();
+	let genesis = para_header(None, 0);
+	let block1 = para_header(Some(&genesis), 0);
+	let block2 = para_header(Some(&block1), 0);
+	let block2_fork = para_header(Some(&block1), 1);
+	let block3_fork = para_header(Some(&block2_fork), 0);
+
+	let relay_block1 = setup.import(None, 0, &genesis, None);
+	let relay_block2 = setup.new_best(Some(&relay_block1), 0, &block1, None);
+	assert_eq!(
+		setup.next_event().await,
+		(block1.hash(), InclusionStatus::Included, 2)
+	);
+	setup.new_best(Some(&relay_block2), 0, &block2, None);
+	assert_eq!(
+		setup.next_event().await,
+		(block2.hash(), InclusionStatus::Included, 3)
+	);
+
+	// A longer relay chain fork, which included `block1` as well, became the best. Only the
+	// last of its blocks is notified and the new head is higher than the retracted one.
+	let fork2 = setup.import(Some(&relay_block1), 1, &block1, None);
+	let fork3 = setup.import(Some(&fork2), 1, &block2_fork, None);
+	setup.new_best(Some(&fork3), 1, &block3_fork, None);
+	assert_eq!(
+		setup.next_event().await,
+		(block2.hash(), InclusionStatus::Retracted, 4)
+	);
+	assert_eq!(
+		setup.next_event().await,
+		(block3_fork.hash(), InclusionStatus::Included, 4)
+	);
+}
+
+#[tokio::test]
+async fn lagging_subscriber_is_dropped() {
+	let tracker = InclusionTracker::<Block>::new();
+	let lagging = tracker.subscribe();
+	let head = para_header(None, 0);
+	let relay_block = relay_header(None, 0);
+
+	for _ in 0..2 * SUBSCRIBER_BUFFER {
+		tracker.notify(&head, InclusionStatus::Backed, &relay_block);
+	}
+
+	// The stream ends with the events that were buffered before the subscriber was dropped.
+	let received = lagging.collect::<Vec<_>>().await.len();
+	assert!(received > 0 && received < 2 * SUBSCRIBER_BUFFER);
+}
+
+#[test]
+fn inclusion_event_is_serialized_in_camel_case() {
+	let event = InclusionEvent {
+		block_hash: Hash::repeat_byte(1),
+		block_number: 5u32,
+		status: InclusionStatus::Finalized,
+		relay_block_hash: PHash::repeat_byte(2),
+		relay_block_number: 10,
+	};
+
+	let json = serde_json::to_value(&event).unwrap();
+	assert_eq!(json["blockNumber"], 5);
+	assert_eq!(json["status"], "finalized");
+	assert_eq!(json["relayBlockNumber"], 10);
+}
@@ -0,0 +1,383 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Provides the [`InclusionTracker`].
+
+use crate::{InclusionEvent, InclusionEventFor, InclusionStatus};
+
+use codec::Decode;
+use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult, RelayHeaderStream};
+use futures::{channel::mpsc, select, stream, StreamExt};
+use parking_lot::Mutex;
+use polkadot_primitives::v1::{Header as PHeader, Id as ParaId, OccupiedCoreAssumption};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{collections::HashSet, sync::Arc};
+
+pub(crate) const LOG_TARGET: &str = "cumulus-inclusion";
+
+/// The number of events a subscriber may lag behind before it is dropped.
+pub(crate) const SUBSCRIBER_BUFFER: usize = 1024;
+
+/// The maximum number of relay chain blocks the tracker walks to find the blocks a reorg retracted.
+const MAX_TREE_ROUTE: usize = 64;
+
+/// A stream of [`InclusionEvent`]s.
+pub type InclusionEventStream<Block> = mpsc::Receiver<InclusionEventFor<Block>>;
+
+/// Follows the relay chain and reports the inclusion status of the parachain blocks.
+///
+/// For every new best relay chain block, the candidate pending availability is reported as
+/// [`InclusionStatus::Backed`] and a changed parachain head as [`InclusionStatus::Included`]. If the
+/// new best relay chain block is not a descendant of the previous one, the parachain heads that
+/// were included by the retracted relay chain blocks, but not by the new best chain, are reported
+/// as [`InclusionStatus::Retracted`] first. For every finalized relay chain block, a changed
+/// parachain head is reported as [`InclusionStatus::Finalized`].
+///
+/// Events are only generated for the relay chain blocks the tracker is notified about, so not every
+/// parachain block is guaranteed to get all events. The parachain head at the first notified relay
+/// chain blocks is reported as well, even if it was included or finalized before.
+///
+/// Subscribers that lag too many events behind are dropped, which ends their stream.
+pub struct InclusionTracker<Block: BlockT> {
+	subscribers: Arc<Mutex<Vec<mpsc::Sender<InclusionEventFor<Block>>>>>,
+}
+
+impl<Block: BlockT> Clone for InclusionTracker<Block> {
+	fn clone(&self) -> Self {
+		Self {
+			subscribers: self.subscribers.clone(),
+		}
+	}
+}
+
+impl<Block: BlockT> Default for InclusionTracker<Block> {
+	fn default() -> Self {
+		Self {
+			subscribers: Default::default(),
+		}
+	}
+}
+
+/// What the tracker has seen so far.
+struct TrackerState<Block: BlockT> {
+	best: Option<PHeader>,
+	backed: Option<Block::Hash>,
+	included: Option<Block::Header>,
+	finalized: Option<Block::Hash>,
+}
+
+impl<Block: BlockT> Default for TrackerState<Block> {
+	fn default() -> Self {
+		Self {
+			best: None,
+			backed: None,
+			included: None,
+			finalized: None,
+		}
+	}
+}
+
+/// The relay chain blocks between two blocks.
+struct TreeRoute {
+	/// The blocks that are no longer part of the chain, newest first.
+	retracted: Vec<PHeader>,
+	/// The last block both chains have in common.
+	common: PHeader,
+	/// The blocks that are part of the chain now, oldest first.
+	enacted: Vec<PHeader>,
+}
+
+impl<Block: BlockT> InclusionTracker<Block> {
+	/// Create a new tracker without any subscribers.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns a stream of all events the tracker reports from now on.
+	pub fn subscribe(&self) -> InclusionEventStream<Block> {
+		let (sender, receiver) = mpsc::channel(SUBSCRIBER_BUFFER);
+		self.subscribers.lock().push(sender);
+		receiver
+	}
+
+	pub(crate) fn notify(
+		&self,
+		header: &Block::Header,
+		status: InclusionStatus,
+		relay_block: &PHeader,
+	) {
+		let event = InclusionEvent {
+			block_hash: header.hash(),
+			block_number: *header.number(),
+			status,
+			relay_block_hash: relay_block.hash(),
+			relay_block_number: relay_block.number,
+		};
+
+		tracing::trace!(target: LOG_TARGET, ?event, "Parachain block inclusion status changed.");
+
+		let mut subscribers = self.subscribers.lock();
+		*subscribers = std::mem::take(&mut *subscribers)
+			.into_iter()
+			.filter_map(|mut subscriber| match subscriber.try_send(event.clone()) {
+				Ok(()) => Some(subscriber),
+				Err(e) => {
+					if e.is_full() {
+						tracing::debug!(target: LOG_TARGET, "Dropping lagging inclusion subscriber.");
+					}
+					None
+				}
+			})
+			.collect();
+	}
+
+	/// Follow the relay chain and report the inclusion status of the blocks of `para_id`.
+	///
+	/// Returns when one of the relay chain notification streams ends.
+	pub async fn run(self, para_id: ParaId, relay_chain: Arc<dyn RelayChainInterface>) {
+		let mut new_best = subscribe(relay_chain.new_best_notification_stream().await).fuse();
+		let mut finalized = subscribe(relay_chain.finality_notification_stream().await).fuse();
+		let mut state = TrackerState::default();
+
+		loop {
+			select! {
+				h = new_best.next() => match h {
+					Some(h) => self.on_new_best(&*relay_chain, para_id, &h, &mut state).await,
+					None => break,
+				},
+				h = finalized.next() => match h {
+					Some(h) => self.on_finalized(&*relay_chain, para_id, &h, &mut state).await,
+					None => break,
+				},
+			}
+		}
+
+		tracing::debug!(target: LOG_TARGET, "Stopping following the relay chain.");
+	}
+
+	async fn on_new_best(
+		&self,
+		relay_chain: &dyn RelayChainInterface,
+		para_id: ParaId,
+		relay_block: &PHeader,
+		state: &mut TrackerState<Block>,
+	) {
+		let pending = relay_chain
+			.candidate_pending_availability(relay_block.hash(), para_id)
+			.await
+			.map(|c| c.and_then(|c| decode_head::<Block>(&c.commitments.head_data.0)));
+
+		match pending {
+			Ok(Some(header)) if state.backed != Some(header.hash()) => {
+				state.backed = Some(header.hash());
+				self.notify(&header, InclusionStatus::Backed, relay_block);
+			}
+			Ok(_) => {}
+			Err(e) => tracing::debug!(
+				target: LOG_TARGET,
+				error = ?e,
+				relay_block = ?relay_block.hash(),
+				"Failed to fetch the candidate pending availability.",
+			),
+		}
+
+		let previous_best = state.best.replace(relay_block.clone());
+
+		let head = match parachain_head_at::<Block>(relay_chain, para_id, relay_block).await {
+			Some(head) => head,
+			None => return,
+		};
+
+		if let Some(previous) = state.included.take() {
+			if previous.hash() == head.hash() {
+				state.included = Some(previous);
+				return;
+			}
+
+			let route = match previous_best {
+				Some(previous_best) => {
+					tree_route(relay_chain, previous_best, relay_block.clone()).await
+				}
+				None => None,
+			};
+			match route {
+				Some(route) => {
+					self.notify_retracted(relay_chain, para_id, &route, &head, relay_block)
+						.await
+				}
+				// Without the route, only the previous head can be retracted.
+				None => {
+					if head.parent_hash() != &previous.hash() && head.number() <= previous.number() {
+						self.notify(&previous, InclusionStatus::Retracted, relay_block);
+					}
+				}
+			}
+		}
+
+		self.notify(&head, InclusionStatus::Included, relay_block);
+		state.included = Some(head);
+	}
+
+	/// Report the parachain heads included by the retracted blocks of `route` that are not
+	/// included by its common or enacted blocks as retracted.
+	async fn notify_retracted(
+		&self,
+		relay_chain: &dyn RelayChainInterface,
+		para_id: ParaId,
+		route: &TreeRoute,
+		head: &Block::Header,
+		relay_block: &PHeader,
+	) {
+		if route.retracted.is_empty() {
+			return;
+		}
+
+		let mut kept = HashSet::new();
+		kept.insert(head.hash());
+		for block in route.enacted.iter().chain(std::iter::once(&route.common)) {
+			if let Some(kept_head) = parachain_head_at::<Block>(relay_chain, para_id, block).await {
+				kept.insert(kept_head.hash());
+			}
+		}
+
+		for block in &route.retracted {
+			if let Some(retracted) = parachain_head_at::<Block>(relay_chain, para_id, block).await {
+				// Marking it as kept reports every head only once.
+				if kept.insert(retracted.hash()) {
+					self.notify(&retracted, InclusionStatus::Retracted, relay_block);
+				}
+			}
+		}
+	}
+
+	async fn on_finalized(
+		&self,
+		relay_chain: &dyn RelayChainInterface,
+		para_id: ParaId,
+		relay_block: &PHeader,
+		state: &mut TrackerState<Block>,
+	) {
+		let head = match parachain_head_at::<Block>(relay_chain, para_id, relay_block).await {
+			Some(head) => head,
+			None => return,
+		};
+
+		if state.finalized != Some(head.hash()) {
+			state.finalized = Some(head.hash());
+			self.notify(&head, InclusionStatus::Finalized, relay_block);
+		}
+	}
+}
+
+/// Returns the route from the relay chain block `from` to `to`.
+///
+/// Returns `None` if a header is not available or the route is longer than [`MAX_TREE_ROUTE`].
+async fn tree_route(
+	relay_chain: &dyn RelayChainInterface,
+	mut from: PHeader,
+	mut to: PHeader,
+) -> Option<TreeRoute> {
+	let parent = |header: PHeader| async move {
+		relay_chain
+			.header(header.parent_hash)
+			.await
+			.map_err(|e| {
+				tracing::debug!(
+					target: LOG_TARGET,
+					error = ?e,
+					relay_block = ?header.parent_hash,
+					"Failed to fetch the relay chain header.",
+				)
+			})
+			.ok()
+			.flatten()
+	};
+
+	let mut retracted = Vec::new();
+	let mut enacted = Vec::new();
+	while from.hash() != to.hash() {
+		if retracted.len() + enacted.len() >= MAX_TREE_ROUTE {
+			tracing::debug!(
+				target: LOG_TARGET,
+				from = ?from.hash(),
+				to = ?to.hash(),
+				"Relay chain tree route is too long.",
+			);
+			return None;
+		}
+
+		if from.number >= to.number {
+			let next = parent(from.clone()).await?;
+			retracted.push(std::mem::replace(&mut from, next));
+		} else {
+			let next = parent(to.clone()).await?;
+			enacted.push(std::mem::replace(&mut to, next));
+		}
+	}
+	enacted.reverse();
+
+	Some(TreeRoute {
+		retracted,
+		common: from,
+		enacted,
+	})
+}
+
+/// Returns the stream of `subscription` or an empty stream if subscribing failed.
+fn subscribe(subscription: RelayChainResult<RelayHeaderStream>) -> RelayHeaderStream {
+	subscription.unwrap_or_else(|e| {
+		tracing::error!(
+			target: LOG_TARGET,
+			error = ?e,
+			"Failed to subscribe to relay chain headers.",
+		);
+		stream::empty().boxed()
+	})
+}
+
+fn decode_head<Block: BlockT>(head: &[u8]) -> Option<Block::Header> {
+	Block::Header::decode(&mut &head[..])
+		.map_err(|e| {
+			tracing::debug!(
+				target: LOG_TARGET,
+				error = ?e,
+				"Could not decode parachain head.",
+			)
+		})
+		.ok()
+}
+
+/// Returns the included head of `para_id` at `relay_block`.
+async fn parachain_head_at<Block: BlockT>(
+	relay_chain: &dyn RelayChainInterface,
+	para_id: ParaId,
+	relay_block: &PHeader,
+) -> Option<Block::Header> {
+	let validation_data = relay_chain
+		.persisted_validation_data(relay_block.hash(), para_id, OccupiedCoreAssumption::TimedOut)
+		.await
+		.map_err(|e| {
+			tracing::debug!(
+				target: LOG_TARGET,
+				error = ?e,
+				relay_block = ?relay_block.hash(),
+				"Failed to fetch the parachain head.",
+			)
+		})
+		.ok()??;
+
+	decode_head::<Block>(&validation_data.parent_head.0)
+}
@@ -20,41 +20,30 @@
 //! that use the relay chain provided consensus. See [`BlockAnnounceValidator`]
 //! and [`WaitToAnnounce`] for more information about this implementation.
 
-use sc_client_api::{Backend, BlockchainEvents};
-use sp_api::ProvideRuntimeApi;
-use sp_blockchain::HeaderBackend;
-use sp_consensus::{
-	block_validation::{BlockAnnounceValidator as BlockAnnounceValidatorT, Validation},
-	SyncOracle,
+use sp_consensus::block_validation::{
+	BlockAnnounceValidator as BlockAnnounceValidatorT, Validation,
 };
 use sp_core::traits::SpawnNamed;
-use sp_runtime::{
-	generic::BlockId,
-	traits::{Block as BlockT, HashFor, Header as HeaderT},
-};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 
+use cumulus_relay_chain_interface::RelayChainInterface;
 use polkadot_node_primitives::{SignedFullStatement, Statement};
 use polkadot_parachain::primitives::HeadData;
 use polkadot_primitives::v1::{
-	Block as PBlock, Hash as PHash, CandidateReceipt, CompactStatement, Id as ParaId,
-	OccupiedCoreAssumption, ParachainHost, UncheckedSigned, SigningContext,
+	Hash as PHash, CandidateReceipt, CompactStatement, Id as ParaId, OccupiedCoreAssumption,
+	UncheckedSigned, SigningContext,
 };
-use polkadot_client::ClientHandle;
 
 use codec::{Decode, Encode};
-use futures::{
-	channel::oneshot,
-	future::{ready, FutureExt},
-	Future,
-};
+use futures::{channel::oneshot, future::FutureExt, Future};
 
 use std::{convert::TryFrom, fmt, marker::PhantomData, pin::Pin, sync::Arc};
 
-use wait_on_relay_chain_block::WaitOnRelayChainBlock;
-
+mod metrics;
 #[cfg(test)]
 mod tests;
-mod wait_on_relay_chain_block;
+
+pub use metrics::Metrics;
 
 const LOG_TARGET: &str = "sync::cumulus";
 
@@ -117,37 +106,31 @@ impl BlockAnnounceData {
 	/// Check the signature of the statement.
 	///
 	/// Returns an `Err(_)` if it failed.
-	fn check_signature<P>(
+	async fn check_signature<RCInterface>(
 		self,
-		relay_chain_client: &Arc<P>,
+		relay_chain_interface: &RCInterface,
 	) -> Result<Validation, BlockAnnounceError>
 	where
-		P: ProvideRuntimeApi<PBlock> + Send + Sync + 'static,
-		P::Api: ParachainHost<PBlock>,
+		RCInterface: RelayChainInterface + 'static,
 	{
-		let runtime_api = relay_chain_client.runtime_api();
 		let validator_index = self.statement.unchecked_validator_index();
+		let relay_parent = self.receipt.descriptor.relay_parent;
 
-		let runtime_api_block_id = BlockId::Hash(self.receipt.descriptor.relay_parent);
-		let session_index = match runtime_api.session_index_for_child(&runtime_api_block_id) {
-			Ok(r) => r,
-			Err(e) => {
-				return Err(BlockAnnounceError(format!("{:?}", e)));
-			}
-		};
+		let session_index = relay_chain_interface
+			.session_index_for_child(relay_parent)
+			.await
+			.map_err(|e| BlockAnnounceError(format!("{:?}", e)))?;
 
 		let signing_context = SigningContext {
-			parent_hash: self.receipt.descriptor.relay_parent,
+			parent_hash: relay_parent,
 			session_index,
 		};
 
 		// Check that the signer is a legit validator.
-		let authorities = match runtime_api.validators(&runtime_api_block_id) {
-			Ok(r) => r,
-			Err(e) => {
-				return Err(BlockAnnounceError(format!("{:?}", e)));
-			}
-		};
+		let authorities = relay_chain_interface
+			.validators(relay_parent)
+			.await
+			.map_err(|e| BlockAnnounceError(format!("{:?}", e)))?;
 		let signer = match authorities.get(validator_index.0 as usize) {
 			Some(r) => r,
 			None => {
@@ -222,55 +205,38 @@ impl TryFrom<&'_ SignedFullStatement> for BlockAnnounceData {
 /// chain. If it is at the tip, it is required to provide a justification or otherwise we reject
 /// it. However, if the announcement is for a block below the tip the announcement is accepted
 /// as it probably comes from a node that is currently syncing the chain.
-pub struct BlockAnnounceValidator<Block, R, B, BCE> {
+pub struct BlockAnnounceValidator<Block, RCInterface> {
 	phantom: PhantomData<Block>,
-	relay_chain_client: Arc<R>,
-	relay_chain_backend: Arc<B>,
+	relay_chain_interface: RCInterface,
 	para_id: ParaId,
-	relay_chain_sync_oracle: Box<dyn SyncOracle + Send>,
-	wait_on_relay_chain_block: WaitOnRelayChainBlock<B, BCE>,
+	metrics: Metrics,
 }
 
-impl<Block, R, B, BCE> BlockAnnounceValidator<Block, R, B, BCE> {
+impl<Block, RCInterface> BlockAnnounceValidator<Block, RCInterface> {
 	/// Create a new [`BlockAnnounceValidator`].
-	pub fn new(
-		relay_chain_client: Arc<R>,
-		para_id: ParaId,
-		relay_chain_sync_oracle: Box<dyn SyncOracle + Send>,
-		relay_chain_backend: Arc<B>,
-		relay_chain_blockchain_events: Arc<BCE>,
-	) -> Self {
+	pub fn new(relay_chain_interface: RCInterface, para_id: ParaId, metrics: Metrics) -> Self {
 		Self {
 			phantom: Default::default(),
-			relay_chain_client,
+			relay_chain_interface,
 			para_id,
-			relay_chain_sync_oracle,
-			relay_chain_backend: relay_chain_backend.clone(),
-			wait_on_relay_chain_block: WaitOnRelayChainBlock::new(
-				relay_chain_backend,
-				relay_chain_blockchain_events,
-			),
+			metrics,
 		}
 	}
 }
 
-impl<Block: BlockT, R, B, BCE> BlockAnnounceValidator<Block, R, B, BCE>
+impl<Block: BlockT, RCInterface> BlockAnnounceValidator<Block, RCInterface>
 where
-	R: ProvideRuntimeApi<PBlock> + Send + Sync + 'static,
-	R::Api: ParachainHost<PBlock>,
-	B: Backend<PBlock> + 'static,
-	// Rust bug: https://github.com/rust-lang/rust/issues/24159
-	sc_client_api::StateBackendFor<B, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
+	RCInterface: RelayChainInterface + Clone + 'static,
 {
 	/// Get the included block of the given parachain in the relay chain.
-	fn included_block(
-		relay_chain_client: &R,
-		block_id: &BlockId<PBlock>,
+	async fn included_block(
+		relay_chain_interface: &RCInterface,
+		hash: PHash,
 		para_id: ParaId,
 	) -> Result<Block::Header, BoxedError> {
-		let validation_data = relay_chain_client
-			.runtime_api()
-			.persisted_validation_data(block_id, para_id, OccupiedCoreAssumption::TimedOut)
+		let validation_data = relay_chain_interface
+			.persisted_validation_data(hash, para_id, OccupiedCoreAssumption::TimedOut)
+			.await
 			.map_err(|e| Box::new(BlockAnnounceError(format!("{:?}", e))) as Box<_>)?
 			.ok_or_else(|| {
 				Box::new(BlockAnnounceError(
@@ -289,122 +255,134 @@ where
 	}
 
 	/// Get the backed block hash of the given parachain in the relay chain.
-	fn backed_block_hash(
-		relay_chain_client: &R,
-		block_id: &BlockId<PBlock>,
+	async fn backed_block_hash(
+		relay_chain_interface: &RCInterface,
+		hash: PHash,
 		para_id: ParaId,
 	) -> Result<Option<PHash>, BoxedError> {
-		let candidate_receipt = relay_chain_client
-			.runtime_api()
-			.candidate_pending_availability(block_id, para_id)
+		let candidate_receipt = relay_chain_interface
+			.candidate_pending_availability(hash, para_id)
+			.await
 			.map_err(|e| Box::new(BlockAnnounceError(format!("{:?}", e))) as Box<_>)?;
 
 		Ok(candidate_receipt.map(|cr| cr.descriptor.para_head))
 	}
 
 	/// Handle a block announcement with empty data (no statement) attached to it.
-	fn handle_empty_block_announce_data(
-		&self,
+	async fn handle_empty_block_announce_data(
+		relay_chain_interface: RCInterface,
+		para_id: ParaId,
 		header: Block::Header,
-	) -> impl Future<Output = Result<Validation, BoxedError>> {
-		let relay_chain_client = self.relay_chain_client.clone();
-		let relay_chain_backend = self.relay_chain_backend.clone();
-		let para_id = self.para_id;
+	) -> Result<Validation, BoxedError> {
+		// Check if block is equal or higher than best (this requires a justification)
+		let relay_chain_best_hash = relay_chain_interface
+			.best_block_hash()
+			.await
+			.map_err(|e| Box::new(BlockAnnounceError(format!("{:?}", e))) as Box<_>)?;
+		let block_number = header.number();
 
-		async move {
-			// Check if block is equal or higher than best (this requires a justification)
-			let relay_chain_info = relay_chain_backend.blockchain().info();
-			let runtime_api_block_id = BlockId::Hash(relay_chain_info.best_hash);
-			let block_number = header.number();
-
-			let best_head =
-				Self::included_block(&*relay_chain_client, &runtime_api_block_id, para_id)?;
-			let known_best_number = best_head.number();
-			let backed_block = ||
-				Self::backed_block_hash(&*relay_chain_client, &runtime_api_block_id, para_id);
-
-			if best_head == header {
-				tracing::debug!(
-					target: LOG_TARGET,
-					"Announced block matches best block.",
-				);
+		let best_head =
+			Self::included_block(&relay_chain_interface, relay_chain_best_hash, para_id).await?;
+		let known_best_number = best_head.number();
 
-				Ok(Validation::Success { is_new_best: true })
-			} else if Some(HeadData(header.encode()).hash()) == backed_block()? {
-				tracing::debug!(
-					target: LOG_TARGET,
-					"Announced block matches latest backed block.",
-				);
+		if best_head == header {
+			tracing::debug!(
+				target: LOG_TARGET,
+				"Announced block matches best block.",
+			);
 
-				Ok(Validation::Success { is_new_best: true })
-			} else if block_number >= known_best_number {
-				tracing::debug!(
-					target: LOG_TARGET,
-					"Validation failed because a justification is needed if the block at the top of the chain."
-				);
+			return Ok(Validation::Success { is_new_best: true });
+		}
 
-				Ok(Validation::Failure { disconnect: false })
-			} else {
-				Ok(Validation::Success { is_new_best: false })
-			}
+		let backed_block =
+			Self::backed_block_hash(&relay_chain_interface, relay_chain_best_hash, para_id)
+				.await?;
+
+		if Some(HeadData(header.encode()).hash()) == backed_block {
+			tracing::debug!(
+				target: LOG_TARGET,
+				"Announced block matches latest backed block.",
+			);
+
+			Ok(Validation::Success { is_new_best: true })
+		} else if block_number >= known_best_number {
+			tracing::debug!(
+				target: LOG_TARGET,
+				"Validation failed because a justification is needed if the block at the top of the chain."
+			);
+
+			Ok(Validation::Failure { disconnect: false })
+		} else {
+			Ok(Validation::Success { is_new_best: false })
 		}
 	}
 }
 
-impl<Block: BlockT, P, B, BCE> BlockAnnounceValidatorT<Block>
-	for BlockAnnounceValidator<Block, P, B, BCE>
+impl<Block: BlockT, RCInterface> BlockAnnounceValidatorT<Block>
+	for BlockAnnounceValidator<Block, RCInterface>
 where
-	P: ProvideRuntimeApi<PBlock> + Send + Sync + 'static,
-	P::Api: ParachainHost<PBlock>,
-	B: Backend<PBlock> + 'static,
-	BCE: BlockchainEvents<PBlock> + 'static + Send + Sync,
-	// Rust bug: https://github.com/rust-lang/rust/issues/24159
-	sc_client_api::StateBackendFor<B, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
+	RCInterface: RelayChainInterface + Clone + 'static,
 {
 	fn validate(
 		&mut self,
 		header: &Block::Header,
-		mut data: &[u8],
+		data: &[u8],
 	) -> Pin<Box<dyn Future<Output = Result<Validation, BoxedError>> + Send>> {
-		if self.relay_chain_sync_oracle.is_major_syncing() {
-			return ready(Ok(Validation::Success { is_new_best: false })).boxed();
-		}
+		let relay_chain_interface = self.relay_chain_interface.clone();
+		let para_id = self.para_id;
+		let header = header.clone();
+		let data = data.to_vec();
+		let metrics = self.metrics.clone();
 
-		if data.is_empty() {
-			return self
-				.handle_empty_block_announce_data(header.clone())
-				.boxed();
-		}
+		let validation = async move {
+			let relay_chain_is_syncing = relay_chain_interface
+				.is_major_syncing()
+				.await
+				.map_err(|e| Box::new(BlockAnnounceError(format!("{:?}", e))) as Box<_>)?;
 
-		let block_announce_data = match BlockAnnounceData::decode(&mut data) {
-			Ok(r) => r,
-			Err(_) => {
-				return ready(Err(Box::new(BlockAnnounceError(
-					"Can not decode the `BlockAnnounceData`".into(),
-				)) as Box<_>))
-				.boxed()
+			if relay_chain_is_syncing {
+				return Ok(Validation::Success { is_new_best: false });
 			}
-		};
 
-		let relay_chain_client = self.relay_chain_client.clone();
-		let header_encoded = header.encode();
-		let wait_on_relay_chain_block = self.wait_on_relay_chain_block.clone();
+			if data.is_empty() {
+				return Self::handle_empty_block_announce_data(
+					relay_chain_interface,
+					para_id,
+					header,
+				)
+				.await;
+			}
 
-		async move {
-			if let Err(e) = block_announce_data.validate(header_encoded) {
+			let block_announce_data = match BlockAnnounceData::decode(&mut &data[..]) {
+				Ok(r) => r,
+				Err(_) => {
+					return Err(Box::new(BlockAnnounceError(
+						"Can not decode the `BlockAnnounceData`".into(),
+					)) as Box<_>)
+				}
+			};
+
+			if let Err(e) = block_announce_data.validate(header.encode()) {
 				return Ok(e);
 			}
 
 			let relay_parent = block_announce_data.receipt.descriptor.relay_parent;
 
-			wait_on_relay_chain_block
-				.wait_on_relay_chain_block(relay_parent)
+			relay_chain_interface
+				.wait_for_block(relay_parent)
 				.await
 				.map_err(|e| Box::new(BlockAnnounceError(e.to_string())) as Box<_>)?;
 
 			block_announce_data
-				.check_signature(&relay_chain_client)
+				.check_signature(&relay_chain_interface)
+				.await
 				.map_err(|e| Box::new(e) as Box<_>)
+		};
+
+		async move {
+			let result = validation.await;
+			metrics.on_validation(&result);
+			result
 		}
 		.boxed()
 	}
@@ -413,94 +391,15 @@ where
 /// Build a block announce validator instance.
 ///
 /// Returns a boxed [`BlockAnnounceValidator`].
-pub fn build_block_announce_validator<Block: BlockT, B>(
-	relay_chain_client: polkadot_client::Client,
+pub fn build_block_announce_validator<Block: BlockT, RCInterface>(
+	relay_chain_interface: RCInterface,
 	para_id: ParaId,
-	relay_chain_sync_oracle: Box<dyn SyncOracle + Send>,
-	relay_chain_backend: Arc<B>,
+	metrics: Metrics,
 ) -> Box<dyn BlockAnnounceValidatorT<Block> + Send>
 where
-	B: Backend<PBlock> + Send + 'static,
-	// Rust bug: https://github.com/rust-lang/rust/issues/24159
-	sc_client_api::StateBackendFor<B, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
+	RCInterface: RelayChainInterface + Clone + 'static,
 {
-	BlockAnnounceValidatorBuilder::new(
-		relay_chain_client,
-		para_id,
-		relay_chain_sync_oracle,
-		relay_chain_backend,
-	)
-	.build()
-}
-
-/// Block announce validator builder.
-///
-/// Builds a [`BlockAnnounceValidator`] for a parachain. As this requires
-/// a concrete relay chain client instance, the builder takes a [`polkadot_client::Client`]
-/// that wraps this concrete instanace. By using [`polkadot_client::ExecuteWithClient`]
-/// the builder gets access to this concrete instance.
-struct BlockAnnounceValidatorBuilder<Block, B> {
-	phantom: PhantomData<Block>,
-	relay_chain_client: polkadot_client::Client,
-	para_id: ParaId,
-	relay_chain_sync_oracle: Box<dyn SyncOracle + Send>,
-	relay_chain_backend: Arc<B>,
-}
-
-impl<Block: BlockT, B> BlockAnnounceValidatorBuilder<Block, B>
-where
-	B: Backend<PBlock> + Send + 'static,
-	// Rust bug: https://github.com/rust-lang/rust/issues/24159
-	sc_client_api::StateBackendFor<B, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
-{
-	/// Create a new instance of the builder.
-	fn new(
-		relay_chain_client: polkadot_client::Client,
-		para_id: ParaId,
-		relay_chain_sync_oracle: Box<dyn SyncOracle + Send>,
-		relay_chain_backend: Arc<B>,
-	) -> Self {
-		Self {
-			relay_chain_client,
-			para_id,
-			relay_chain_sync_oracle,
-			relay_chain_backend,
-			phantom: PhantomData,
-		}
-	}
-
-	/// Build the block announce validator.
-	fn build(self) -> Box<dyn BlockAnnounceValidatorT<Block> + Send> {
-		self.relay_chain_client.clone().execute_with(self)
-	}
-}
-
-impl<Block: BlockT, B> polkadot_client::ExecuteWithClient
-	for BlockAnnounceValidatorBuilder<Block, B>
-where
-	B: Backend<PBlock> + Send + 'static,
-	// Rust bug: https://github.com/rust-lang/rust/issues/24159
-	sc_client_api::StateBackendFor<B, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
-{
-	type Output = Box<dyn BlockAnnounceValidatorT<Block> + Send>;
-
-	fn execute_with_client<PClient, Api, PBackend>(self, client: Arc<PClient>) -> Self::Output
-	where
-		<Api as sp_api::ApiExt<PBlock>>::StateBackend:
-			sp_api::StateBackend<sp_runtime::traits::BlakeTwo256>,
-		PBackend: Backend<PBlock>,
-		PBackend::State: sp_api::StateBackend<sp_runtime::traits::BlakeTwo256>,
-		Api: polkadot_client::RuntimeApiCollection<StateBackend = PBackend::State>,
-		PClient: polkadot_client::AbstractClient<PBlock, PBackend, Api = Api> + 'static,
-	{
-		Box::new(BlockAnnounceValidator::new(
-			client.clone(),
-			self.para_id,
-			self.relay_chain_sync_oracle,
-			self.relay_chain_backend,
-			client,
-		))
-	}
+	Box::new(BlockAnnounceValidator::new(relay_chain_interface, para_id, metrics))
 }
 
 /// Wait before announcing a block that a candidate message has been received for this block, then
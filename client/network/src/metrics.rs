@@ -0,0 +1,73 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the block announce validation.
+
+use sp_consensus::block_validation::Validation;
+use substrate_prometheus_endpoint::{
+	register, CounterVec, Opts, PrometheusError, Registry, U64,
+};
+
+#[derive(Clone)]
+struct MetricsInner {
+	validations: CounterVec<U64>,
+}
+
+/// Block announce validation metrics.
+///
+/// Doesn't record anything if it wasn't registered on a prometheus registry.
+#[derive(Clone, Default)]
+pub struct Metrics(Option<MetricsInner>);
+
+impl Metrics {
+	/// Register the metrics on the given `registry`.
+	///
+	/// Returns metrics that don't record anything if `registry` is `None`.
+	pub fn register(registry: Option<&Registry>) -> Result<Self, PrometheusError> {
+		let registry = match registry {
+			Some(registry) => registry,
+			None => return Ok(Self(None)),
+		};
+
+		Ok(Self(Some(MetricsInner {
+			validations: register(
+				CounterVec::new(
+					Opts::new(
+						"cumulus_block_announce_validations_total",
+						"Number of validated block announcements by outcome.",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+		})))
+	}
+
+	/// Note the outcome of a block announcement validation.
+	pub(crate) fn on_validation<E>(&self, result: &Result<Validation, E>) {
+		if let Some(metrics) = &self.0 {
+			let outcome = match result {
+				Ok(Validation::Success { is_new_best: true }) => "new_best",
+				Ok(Validation::Success { is_new_best: false }) => "accepted",
+				Ok(Validation::Failure { disconnect: false }) => "rejected",
+				Ok(Validation::Failure { disconnect: true }) => "rejected_disconnect",
+				Err(_) => "error",
+			};
+
+			metrics.validations.with_label_values(&[outcome]).inc();
+		}
+	}
+}
@@ -15,28 +15,33 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::*;
+use async_trait::async_trait;
+use cumulus_relay_chain_interface::{RelayChainError, RelayChainResult, RelayHeaderStream};
+use cumulus_relay_chain_local::WaitOnRelayChainBlock;
 use cumulus_test_service::runtime::{Block, Hash, Header};
 use futures::{executor::block_on, poll, task::Poll};
 use parking_lot::Mutex;
 use polkadot_node_primitives::{SignedFullStatement, Statement};
+use polkadot_overseer::OverseerHandler;
 use polkadot_primitives::v1::{
 	Block as PBlock, BlockNumber, CandidateCommitments, CandidateDescriptor, CandidateEvent,
-	CommittedCandidateReceipt, CoreState, GroupRotationInfo, Hash as PHash, HeadData, Id as ParaId,
-	InboundDownwardMessage, InboundHrmpMessage, OccupiedCoreAssumption, ParachainHost,
-	PersistedValidationData, SessionIndex, SessionInfo, SigningContext, ValidationCode,
-	ValidationCodeHash, ValidatorId, ValidatorIndex,
+	CommittedCandidateReceipt, CoreState, GroupRotationInfo, Hash as PHash, HeadData,
+	Header as PHeader, Id as ParaId, InboundDownwardMessage, InboundHrmpMessage,
+	OccupiedCoreAssumption, ParachainHost, PersistedValidationData, SessionIndex, SessionInfo,
+	SigningContext, ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
 };
 use polkadot_test_client::{
 	Client as PClient, ClientBlockImportExt, DefaultTestClientBuilderExt, FullBackend as PBackend,
 	InitPolkadotBlockBuilder, TestClientBuilder, TestClientBuilderExt,
 };
+use sc_client_api::Backend;
 use sp_api::{ApiRef, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_consensus::BlockOrigin;
 use sp_core::H256;
 use sp_keyring::Sr25519Keyring;
 use sp_keystore::{testing::KeyStore, SyncCryptoStore, SyncCryptoStorePtr};
-use sp_runtime::RuntimeAppPublic;
+use sp_runtime::{generic::BlockId, RuntimeAppPublic};
 use std::collections::BTreeMap;
 
 fn check_error(error: crate::BoxedError, check_error: impl Fn(&BlockAnnounceError) -> bool) {
@@ -49,31 +54,107 @@ fn check_error(error: crate::BoxedError, check_error: impl Fn(&BlockAnnounceErro
 }
 
 #[derive(Clone)]
-struct DummyCollatorNetwork;
+struct DummyRelayChainInterface {
+	api: Arc<TestApi>,
+}
+
+fn runtime_api_error(error: impl std::fmt::Debug) -> RelayChainError {
+	RelayChainError::RuntimeApi(format!("{:?}", error))
+}
+
+#[async_trait]
+impl RelayChainInterface for DummyRelayChainInterface {
+	async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+		Ok(self.api.relay_backend.blockchain().info().best_hash)
+	}
 
-impl SyncOracle for DummyCollatorNetwork {
-	fn is_major_syncing(&mut self) -> bool {
-		false
+	async fn header(&self, hash: PHash) -> RelayChainResult<Option<PHeader>> {
+		self.api
+			.relay_backend
+			.blockchain()
+			.header(BlockId::Hash(hash))
+			.map_err(|e| RelayChainError::Blockchain(e.to_string()))
 	}
 
-	fn is_offline(&mut self) -> bool {
+	async fn persisted_validation_data(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+		assumption: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>> {
+		self.api
+			.runtime_api()
+			.persisted_validation_data(&BlockId::Hash(block), para_id, assumption)
+			.map_err(runtime_api_error)
+	}
+
+	async fn candidate_pending_availability(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+		self.api
+			.runtime_api()
+			.candidate_pending_availability(&BlockId::Hash(block), para_id)
+			.map_err(runtime_api_error)
+	}
+
+	async fn session_index_for_child(&self, block: PHash) -> RelayChainResult<SessionIndex> {
+		self.api
+			.runtime_api()
+			.session_index_for_child(&BlockId::Hash(block))
+			.map_err(runtime_api_error)
+	}
+
+	async fn validators(&self, block: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+		self.api
+			.runtime_api()
+			.validators(&BlockId::Hash(block))
+			.map_err(runtime_api_error)
+	}
+
+	async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+		Ok(false)
+	}
+
+	async fn wait_for_block(&self, hash: PHash) -> RelayChainResult<()> {
+		let wait = WaitOnRelayChainBlock::new(
+			self.api.relay_backend.clone(),
+			self.api.relay_client.clone(),
+		)
+		.wait_on_relay_chain_block(hash);
+
+		wait.await.map_err(Into::into)
+	}
+
+	async fn import_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn new_best_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn finality_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		unimplemented!("Not required in tests")
+	}
+
+	fn overseer_handler(&self) -> RelayChainResult<OverseerHandler> {
 		unimplemented!("Not required in tests")
 	}
 }
 
 fn make_validator_and_api() -> (
-	BlockAnnounceValidator<Block, TestApi, PBackend, PClient>,
+	BlockAnnounceValidator<Block, DummyRelayChainInterface>,
 	Arc<TestApi>,
 ) {
 	let api = Arc::new(TestApi::new());
 
 	(
 		BlockAnnounceValidator::new(
-			api.clone(),
+			DummyRelayChainInterface { api: api.clone() },
 			ParaId::from(56),
-			Box::new(DummyCollatorNetwork),
-			api.relay_backend.clone(),
-			api.relay_client.clone(),
+			Metrics::default(),
 		),
 		api,
 	)
@@ -169,6 +250,41 @@ fn valid_if_no_data_and_less_than_best_known_number() {
 	);
 }
 
+#[test]
+fn validation_outcomes_are_recorded_in_metrics() {
+	let registry = substrate_prometheus_endpoint::Registry::new();
+	let api = Arc::new(TestApi::new());
+	let mut validator = BlockAnnounceValidator::<Block, _>::new(
+		DummyRelayChainInterface { api },
+		ParaId::from(56),
+		Metrics::register(Some(&registry)).expect("Registers the metrics"),
+	);
+
+	let header = Header {
+		number: 0,
+		..default_header()
+	};
+	block_on(validator.validate(&header, &[])).expect("Header is valid");
+	block_on(validator.validate(&header, &[1, 2, 3])).expect_err("Data can not be decoded");
+
+	let validations = registry
+		.gather()
+		.into_iter()
+		.find(|family| family.get_name() == "cumulus_block_announce_validations_total")
+		.expect("Block announce metrics are registered");
+	let count = |outcome: &str| {
+		validations
+			.get_metric()
+			.iter()
+			.find(|m| m.get_label()[0].get_value() == outcome)
+			.map(|m| m.get_counter().get_value())
+	};
+
+	assert_eq!(count("accepted"), Some(1.0));
+	assert_eq!(count("error"), Some(1.0));
+	assert_eq!(count("new_best"), None);
+}
+
 #[test]
 fn invalid_if_no_data_exceeds_best_known_number() {
 	let mut validator = make_validator_and_api().0;
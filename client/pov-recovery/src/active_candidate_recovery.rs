@@ -20,6 +20,8 @@ use polkadot_node_primitives::AvailableData;
 use polkadot_node_subsystem::messages::AvailabilityRecoveryMessage;
 use polkadot_overseer::OverseerHandler;
 
+use crate::Metrics;
+
 use futures::{channel::oneshot, stream::FuturesUnordered, Future, FutureExt, StreamExt};
 
 use std::{collections::HashSet, pin::Pin};
@@ -35,14 +37,16 @@ pub(crate) struct ActiveCandidateRecovery<Block: BlockT> {
 	/// The block hashes of the candidates currently being recovered.
 	candidates: HashSet<Block::Hash>,
 	overseer_handler: OverseerHandler,
+	metrics: Metrics,
 }
 
 impl<Block: BlockT> ActiveCandidateRecovery<Block> {
-	pub fn new(overseer_handler: OverseerHandler) -> Self {
+	pub fn new(overseer_handler: OverseerHandler, metrics: Metrics) -> Self {
 		Self {
 			recoveries: Default::default(),
 			candidates: Default::default(),
 			overseer_handler,
+			metrics,
 		}
 	}
 
@@ -50,13 +54,14 @@ impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 	pub async fn recover_candidate(
 		&mut self,
 		block_hash: Block::Hash,
-		pending_candidate: crate::PendingCandidate<Block>,
+		pending_candidate: &crate::PendingCandidate<Block>,
 	) {
 		let (tx, rx) = oneshot::channel();
+		let timer = self.metrics.time_recovery();
 
 		self.overseer_handler
 			.send_msg(AvailabilityRecoveryMessage::RecoverAvailableData(
-				pending_candidate.receipt,
+				pending_candidate.receipt.clone(),
 				pending_candidate.session_index,
 				None,
 				tx,
@@ -67,7 +72,10 @@ impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 
 		self.recoveries.push(
 			async move {
-				match rx.await {
+				let result = rx.await;
+				drop(timer);
+
+				match result {
 					Ok(Ok(res)) => (block_hash, Some(res)),
 					Ok(Err(error)) => {
 						tracing::debug!(
@@ -91,6 +99,11 @@ impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 		);
 	}
 
+	/// Returns the number of recoveries that are currently being executed.
+	pub fn active_recoveries(&self) -> usize {
+		self.candidates.len()
+	}
+
 	/// Returns if the given `candidate` is being recovered.
 	pub fn is_being_recovered(&self, candidate: &Block::Hash) -> bool {
 		self.candidates.contains(candidate)
@@ -30,20 +30,26 @@
 //!
 //! 1. For every included relay chain block we note the backed candidate of our parachain. If the
 //!    block belonging to the PoV is already known, we do nothing. Otherwise we start
-//!    a timer that waits a random time between 0..relay_chain_slot_length before starting to recover
-//!    the PoV.
+//!    a timer that waits a random time between [`RecoveryConfig::min_delay`] and
+//!    [`RecoveryConfig::max_delay`] before starting to recover the PoV.
 //!
 //! 2. If between starting and firing the timer the block is imported, we skip the recovery of the
 //!    PoV.
 //!
-//! 3. If the timer fired we recover the PoV using the relay chain PoV recovery protocol. After it
-//!    is recovered, we restore the block and import it.
+//! 3. If the timer fired and a [`ParachainBlockRequester`] is given, we first ask the parachain
+//!    peers for the block and wait [`RecoveryConfig::peer_request_timeout`] for it to be imported.
+//!
+//! 4. Otherwise we recover the PoV using the relay chain PoV recovery protocol. After it
+//!    is recovered, we restore the block and import it. A failed recovery is retried with an
+//!    exponential backoff up to [`RecoveryConfig::max_retries`] times. At most
+//!    [`RecoveryConfig::max_concurrent_recoveries`] recoveries run at the same time, the others
+//!    wait for a free slot.
 //!
 //! If we need to recover multiple PoV blocks (which should hopefully not happen in real life), we
 //! make sure that the blocks are imported in the correct order.
 
 use sc_client_api::{BlockBackend, BlockchainEvents, UsageProvider};
-use sp_api::ProvideRuntimeApi;
+use sc_network::{ExHashT, NetworkService};
 use sp_consensus::{
 	import_queue::{ImportQueue, IncomingBlock},
 	BlockOrigin, BlockStatus,
@@ -56,11 +62,11 @@ use sp_runtime::{
 use polkadot_node_primitives::{AvailableData, POV_BOMB_LIMIT};
 use polkadot_overseer::OverseerHandler;
 use polkadot_primitives::v1::{
-	Block as PBlock, CandidateReceipt, CommittedCandidateReceipt, Id as ParaId, ParachainHost,
-	SessionIndex,
+	CandidateReceipt, CommittedCandidateReceipt, Id as ParaId, SessionIndex,
 };
 
 use cumulus_primitives_core::ParachainBlockData;
+use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult};
 
 use codec::Decode;
 use futures::{select, stream::FuturesUnordered, Future, FutureExt, Stream, StreamExt};
@@ -77,13 +83,95 @@ use std::{
 mod active_candidate_recovery;
 use active_candidate_recovery::ActiveCandidateRecovery;
 
+mod metrics;
+pub use metrics::Metrics;
+
+#[cfg(test)]
+mod tests;
+
 const LOG_TARGET: &str = "cumulus-pov-recovery";
 
+/// Configuration of the [`PoVRecovery`].
+#[derive(Clone, Debug)]
+pub struct RecoveryConfig {
+	/// The minimum time to wait before starting to recover a candidate.
+	pub min_delay: Duration,
+	/// The maximum time to wait before starting to recover a candidate.
+	///
+	/// The actual delay is chosen randomly between `min_delay` and `max_delay`, so that not all
+	/// collators start to recover the same candidate at once.
+	pub max_delay: Duration,
+	/// How often a failed recovery is retried before the candidate is given up.
+	pub max_retries: u32,
+	/// The time to wait before the first retry. It is doubled for every following retry.
+	pub retry_backoff: Duration,
+	/// The maximum number of recoveries that are running at the same time.
+	pub max_concurrent_recoveries: usize,
+	/// The time to wait for a block that was requested from the parachain peers, before falling
+	/// back to availability recovery.
+	///
+	/// Only used when the [`PoVRecovery`] is given a [`ParachainBlockRequester`].
+	pub peer_request_timeout: Duration,
+}
+
+impl RecoveryConfig {
+	/// Returns the default configuration for a relay chain with the given slot duration.
+	pub fn for_relay_chain_slot_duration(slot_duration: Duration) -> Self {
+		Self {
+			min_delay: Duration::from_secs(0),
+			max_delay: slot_duration,
+			max_retries: 2,
+			retry_backoff: slot_duration,
+			max_concurrent_recoveries: 4,
+			peer_request_timeout: slot_duration,
+		}
+	}
+
+	/// Returns a random delay between `min_delay` and `max_delay`.
+	fn recovery_delay(&self) -> Duration {
+		if self.max_delay <= self.min_delay {
+			return self.min_delay;
+		}
+
+		self.min_delay + (self.max_delay - self.min_delay).mul_f64(thread_rng().gen())
+	}
+
+	/// Returns the delay before retrying a recovery that failed `failed_attempts` times.
+	fn retry_delay(&self, failed_attempts: u32) -> Duration {
+		self.retry_backoff * 2u32.pow(failed_attempts.saturating_sub(1).min(16))
+	}
+}
+
+impl Default for RecoveryConfig {
+	fn default() -> Self {
+		Self::for_relay_chain_slot_duration(Duration::from_secs(6))
+	}
+}
+
+/// Something that can request a block from the parachain peers.
+pub trait ParachainBlockRequester<Block: BlockT>: Send + Sync {
+	/// Request the block `hash` with the given `number` from the parachain peers.
+	///
+	/// When a peer provides the block, it is imported the usual way.
+	fn request_block(&self, hash: Block::Hash, number: NumberFor<Block>);
+}
+
+impl<Block: BlockT, H: ExHashT> ParachainBlockRequester<Block> for NetworkService<Block, H> {
+	fn request_block(&self, hash: Block::Hash, number: NumberFor<Block>) {
+		// Without any peers given, the sync asks all the peers it is connected to.
+		self.set_sync_fork_request(Vec::new(), hash, number);
+	}
+}
+
 /// Represents a pending candidate.
 struct PendingCandidate<Block: BlockT> {
 	receipt: CandidateReceipt,
 	session_index: SessionIndex,
 	block_number: NumberFor<Block>,
+	/// The number of recoveries of this candidate that failed.
+	failed_attempts: u32,
+	/// Whether the block was already requested from the parachain peers.
+	requested_from_peers: bool,
 }
 
 /// Encapsulates the logic of the pov recovery.
@@ -97,43 +185,57 @@ pub struct PoVRecovery<Block: BlockT, PC, IQ, RC> {
 	/// available anymore in this map, it means that it was already imported.
 	next_candidate_to_recover: FuturesUnordered<Pin<Box<dyn Future<Output = Block::Hash> + Send>>>,
 	active_candidate_recovery: ActiveCandidateRecovery<Block>,
+	/// Candidates that wait for a free slot, because `max_concurrent_recoveries` are running.
+	waiting_for_recovery_slot: VecDeque<Block::Hash>,
 	/// Blocks that wait that the parent is imported.
 	///
 	/// Uses parent -> blocks mapping.
 	waiting_for_parent: HashMap<Block::Hash, Vec<Block>>,
-	relay_chain_slot_duration: Duration,
+	config: RecoveryConfig,
 	parachain_client: Arc<PC>,
 	parachain_import_queue: IQ,
-	relay_chain_client: Arc<RC>,
+	relay_chain_interface: RC,
 	para_id: ParaId,
+	block_requester: Option<Arc<dyn ParachainBlockRequester<Block>>>,
+	metrics: Metrics,
 }
 
 impl<Block: BlockT, PC, IQ, RC> PoVRecovery<Block, PC, IQ, RC>
 where
 	PC: BlockBackend<Block> + BlockchainEvents<Block> + UsageProvider<Block>,
-	RC: ProvideRuntimeApi<PBlock> + BlockchainEvents<PBlock>,
-	RC::Api: ParachainHost<PBlock>,
+	RC: RelayChainInterface + Clone,
 	IQ: ImportQueue<Block>,
 {
 	/// Create a new instance.
+	///
+	/// If a `block_requester` is given, the blocks of pending candidates are requested from the
+	/// parachain peers before falling back to availability recovery.
 	pub fn new(
 		overseer_handler: OverseerHandler,
-		relay_chain_slot_duration: Duration,
+		config: RecoveryConfig,
 		parachain_client: Arc<PC>,
 		parachain_import_queue: IQ,
-		relay_chain_client: Arc<RC>,
+		relay_chain_interface: RC,
 		para_id: ParaId,
+		block_requester: Option<Arc<dyn ParachainBlockRequester<Block>>>,
+		metrics: Metrics,
 	) -> Self {
 		Self {
 			pending_candidates: HashMap::new(),
 			next_candidate_to_recover: Default::default(),
-			active_candidate_recovery: ActiveCandidateRecovery::new(overseer_handler),
-			relay_chain_slot_duration,
+			active_candidate_recovery: ActiveCandidateRecovery::new(
+				overseer_handler,
+				metrics.clone(),
+			),
+			waiting_for_recovery_slot: VecDeque::new(),
 			waiting_for_parent: HashMap::new(),
+			config,
 			parachain_client,
 			parachain_import_queue,
-			relay_chain_client,
+			relay_chain_interface,
 			para_id,
+			block_requester,
+			metrics,
 		}
 	}
 
@@ -175,24 +277,30 @@ where
 			}
 		}
 
-		if self
-			.pending_candidates
-			.insert(
-				hash,
-				PendingCandidate {
-					block_number: *header.number(),
-					receipt: receipt.to_plain(),
-					session_index,
-				},
-			)
-			.is_some()
-		{
+		if self.pending_candidates.contains_key(&hash) {
 			return;
 		}
 
-		// Wait some random time, with the maximum being the slot duration of the relay chain
-		// before we start to recover the candidate.
-		let delay = Delay::new(self.relay_chain_slot_duration.mul_f64(thread_rng().gen()));
+		self.pending_candidates.insert(
+			hash,
+			PendingCandidate {
+				block_number: *header.number(),
+				receipt: receipt.to_plain(),
+				session_index,
+				failed_attempts: 0,
+				requested_from_peers: false,
+			},
+		);
+		self.metrics.set_pending_candidates(self.pending_candidates.len());
+
+		// Wait some random time before we start to recover the candidate.
+		let delay = self.config.recovery_delay();
+		self.schedule_recovery(hash, delay);
+	}
+
+	/// Schedule the recovery of the candidate for the given `hash` after `delay`.
+	fn schedule_recovery(&mut self, hash: Block::Hash, delay: Duration) {
+		let delay = Delay::new(delay);
 		self.next_candidate_to_recover.push(
 			async move {
 				delay.await;
@@ -203,28 +311,121 @@ where
 	}
 
 	/// Handle an imported block.
-	fn handle_block_imported(&mut self, hash: &Block::Hash) {
+	///
+	/// Recovered blocks that were waiting for it are imported as well.
+	async fn handle_block_imported(&mut self, hash: &Block::Hash) {
 		self.pending_candidates.remove(&hash);
+		self.metrics.set_pending_candidates(self.pending_candidates.len());
+
+		if let Some(waiting) = self.waiting_for_parent.remove(hash) {
+			for block in waiting {
+				self.import_block(block).await;
+			}
+		}
 	}
 
 	/// Handle a finalized block with the given `block_number`.
 	fn handle_block_finalized(&mut self, block_number: NumberFor<Block>) {
 		self.pending_candidates
 			.retain(|_, pc| pc.block_number > block_number);
+		self.metrics.set_pending_candidates(self.pending_candidates.len());
 	}
 
 	/// Recover the candidate for the given `block_hash`.
+	///
+	/// With a block requester, the block is requested from the parachain peers first. The
+	/// recovery is then retried after `peer_request_timeout`, unless the block was imported.
 	async fn recover_candidate(&mut self, block_hash: Block::Hash) {
-		let pending_candidate = match self.pending_candidates.remove(&block_hash) {
+		if self.active_candidate_recovery.is_being_recovered(&block_hash) {
+			return;
+		}
+
+		let pending_candidate = match self.pending_candidates.get_mut(&block_hash) {
 			Some(pending_candidate) => pending_candidate,
 			None => return,
 		};
 
+		if let Some(block_requester) = &self.block_requester {
+			if !pending_candidate.requested_from_peers {
+				pending_candidate.requested_from_peers = true;
+				block_requester.request_block(block_hash, pending_candidate.block_number);
+
+				tracing::debug!(
+					target: LOG_TARGET,
+					?block_hash,
+					"Requested block of pending candidate from parachain peers.",
+				);
+
+				let timeout = self.config.peer_request_timeout;
+				self.schedule_recovery(block_hash, timeout);
+				return;
+			}
+		}
+
+		if self.active_candidate_recovery.active_recoveries()
+			>= self.config.max_concurrent_recoveries
+		{
+			self.waiting_for_recovery_slot.push_back(block_hash);
+			return;
+		}
+
 		self.active_candidate_recovery
 			.recover_candidate(block_hash, pending_candidate)
 			.await;
 	}
 
+	/// Start the recoveries that wait for a free recovery slot.
+	async fn start_waiting_recoveries(&mut self) {
+		while self.active_candidate_recovery.active_recoveries()
+			< self.config.max_concurrent_recoveries
+		{
+			match self.waiting_for_recovery_slot.pop_front() {
+				Some(block_hash) => self.recover_candidate(block_hash).await,
+				None => return,
+			}
+		}
+	}
+
+	/// Handle a failed recovery of the candidate for the given `block_hash`.
+	///
+	/// Schedules a retry, unless the candidate is not pending anymore or ran out of retries.
+	fn handle_recovery_failed(&mut self, block_hash: Block::Hash) {
+		let pending_candidate = match self.pending_candidates.get_mut(&block_hash) {
+			Some(pending_candidate) => pending_candidate,
+			None => {
+				self.clear_waiting_for_parent(block_hash);
+				return;
+			}
+		};
+
+		if pending_candidate.failed_attempts >= self.config.max_retries {
+			tracing::debug!(
+				target: LOG_TARGET,
+				?block_hash,
+				"Giving up to recover candidate.",
+			);
+
+			self.pending_candidates.remove(&block_hash);
+			self.metrics.set_pending_candidates(self.pending_candidates.len());
+			self.clear_waiting_for_parent(block_hash);
+			return;
+		}
+
+		pending_candidate.failed_attempts += 1;
+		let failed_attempts = pending_candidate.failed_attempts;
+		let delay = self.config.retry_delay(failed_attempts);
+
+		tracing::debug!(
+			target: LOG_TARGET,
+			?block_hash,
+			failed_attempts,
+			?delay,
+			"Retrying to recover candidate.",
+		);
+
+		self.schedule_recovery(block_hash, delay);
+	}
+
 	/// Clear `waiting_for_parent` from the given `hash` and do this recursively for all child
 	/// blocks.
 	fn clear_waiting_for_parent(&mut self, hash: Block::Hash) {
@@ -237,6 +438,21 @@ where
 		}
 	}
 
+	/// Returns `true` if the block for the given `hash` is still to be recovered or imported by us.
+	///
+	/// This covers candidates that are being recovered, that wait for a recovery slot, for a retry
+	/// or for the parachain peers, and recovered blocks that wait for their own parent.
+	fn is_recovering(&self, hash: &Block::Hash) -> bool {
+		self.active_candidate_recovery.is_being_recovered(hash)
+			|| self.pending_candidates.contains_key(hash)
+			|| self.waiting_for_recovery_slot.contains(hash)
+			|| self
+				.waiting_for_parent
+				.values()
+				.flatten()
+				.any(|block| block.hash() == *hash)
+	}
+
 	/// Handle a recovered candidate.
 	async fn handle_candidate_recovered(
 		&mut self,
@@ -246,11 +462,16 @@ where
 		let available_data = match available_data {
 			Some(data) => data,
 			None => {
-				self.clear_waiting_for_parent(block_hash);
+				self.metrics.on_recovery_finished(false);
+				self.handle_recovery_failed(block_hash);
 				return;
 			}
 		};
 
+		// Retrying would recover the same data, so the candidate isn't pending anymore.
+		self.pending_candidates.remove(&block_hash);
+		self.metrics.set_pending_candidates(self.pending_candidates.len());
+
 		let raw_block_data = match sp_maybe_compressed_blob::decompress(
 			&available_data.pov.block_data.0,
 			POV_BOMB_LIMIT,
@@ -259,6 +480,7 @@ where
 			Err(error) => {
 				tracing::debug!(target: LOG_TARGET, ?error, "Failed to decompress PoV");
 
+				self.metrics.on_recovery_finished(false);
 				self.clear_waiting_for_parent(block_hash);
 
 				return;
@@ -274,19 +496,22 @@ where
 					"Failed to decode parachain block data from recovered PoV",
 				);
 
+				self.metrics.on_recovery_finished(false);
 				self.clear_waiting_for_parent(block_hash);
 
 				return;
 			}
 		};
 
+		self.metrics.on_recovery_finished(true);
+
 		let block = block_data.into_block();
 
 		let parent = *block.header().parent_hash();
 
 		match self.parachain_client.block_status(&BlockId::hash(parent)) {
 			Ok(BlockStatus::Unknown) => {
-				if self.active_candidate_recovery.is_being_recovered(&parent) {
+				if self.is_recovering(&parent) {
 					tracing::debug!(
 						target: "cumulus-consensus",
 						?block_hash,
@@ -368,7 +593,17 @@ where
 		let mut imported_blocks = self.parachain_client.import_notification_stream().fuse();
 		let mut finalized_blocks = self.parachain_client.finality_notification_stream().fuse();
 		let pending_candidates =
-			pending_candidates(self.relay_chain_client.clone(), self.para_id).fuse();
+			match pending_candidates(self.relay_chain_interface.clone(), self.para_id).await {
+				Ok(pending_candidates) => pending_candidates.fuse(),
+				Err(error) => {
+					tracing::error!(
+						target: LOG_TARGET,
+						error = ?error,
+						"Failed to subscribe to pending candidates.",
+					);
+					return;
+				}
+			};
 		futures::pin_mut!(pending_candidates);
 
 		loop {
@@ -386,7 +621,7 @@ where
 				},
 				imported = imported_blocks.next() => {
 					if let Some(imported) = imported {
-						self.handle_block_imported(&imported.hash);
+						self.handle_block_imported(&imported.hash).await;
 					} else {
 						tracing::debug!(
 							target: LOG_TARGET,
@@ -415,6 +650,7 @@ where
 					self.active_candidate_recovery.wait_for_recovery().fuse() =>
 				{
 					self.handle_candidate_recovered(block_hash, available_data).await;
+					self.start_waiting_recoveries().await;
 				},
 			}
 		}
@@ -422,35 +658,41 @@ where
 }
 
 /// Returns a stream over pending candidates for the parachain corresponding to `para_id`.
-fn pending_candidates<RC>(
-	relay_chain_client: Arc<RC>,
+async fn pending_candidates<RC>(
+	relay_chain_interface: RC,
 	para_id: ParaId,
-) -> impl Stream<Item = (CommittedCandidateReceipt, SessionIndex)>
+) -> RelayChainResult<impl Stream<Item = (CommittedCandidateReceipt, SessionIndex)>>
 where
-	RC: ProvideRuntimeApi<PBlock> + BlockchainEvents<PBlock>,
-	RC::Api: ParachainHost<PBlock>,
+	RC: RelayChainInterface + Clone,
 {
-	relay_chain_client
-		.import_notification_stream()
-		.filter_map(move |n| {
-			let runtime_api = relay_chain_client.runtime_api();
-			let res = runtime_api
-				.candidate_pending_availability(&BlockId::hash(n.hash), para_id)
-				.and_then(|pa| {
-					runtime_api
-						.session_index_for_child(&BlockId::hash(n.hash))
-						.map(|v| pa.map(|pa| (pa, v)))
-				})
-				.map_err(|e| {
-					tracing::error!(
-						target: LOG_TARGET,
-						error = ?e,
-						"Failed fetch pending candidates.",
-					)
-				})
-				.ok()
-				.flatten();
-
-			async move { res }
-		})
+	let import_notification_stream = relay_chain_interface.import_notification_stream().await?;
+
+	Ok(import_notification_stream.filter_map(move |header| {
+		let relay_chain_interface = relay_chain_interface.clone();
+
+		async move {
+			let hash = header.hash();
+			let res = match relay_chain_interface
+				.candidate_pending_availability(hash, para_id)
+				.await
+			{
+				Ok(Some(pa)) => relay_chain_interface
+					.session_index_for_child(hash)
+					.await
+					.map(|v| Some((pa, v))),
+				Ok(None) => Ok(None),
+				Err(e) => Err(e),
+			};
+
+			res.map_err(|e| {
+				tracing::error!(
+					target: LOG_TARGET,
+					error = ?e,
+					"Failed fetch pending candidates.",
+				)
+			})
+			.ok()
+			.flatten()
+		}
+	}))
 }
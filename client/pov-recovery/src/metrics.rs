@@ -0,0 +1,99 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the PoV recovery.
+
+use substrate_prometheus_endpoint::{
+	prometheus::HistogramTimer, register, CounterVec, Gauge, Histogram, HistogramOpts, Opts,
+	PrometheusError, Registry, U64,
+};
+
+#[derive(Clone)]
+struct MetricsInner {
+	pending_candidates: Gauge<U64>,
+	recoveries: CounterVec<U64>,
+	recovery_duration: Histogram,
+}
+
+/// PoV recovery metrics.
+///
+/// Doesn't record anything if it wasn't registered on a prometheus registry.
+#[derive(Clone, Default)]
+pub struct Metrics(Option<MetricsInner>);
+
+impl Metrics {
+	/// Register the metrics on the given `registry`.
+	///
+	/// Returns metrics that don't record anything if `registry` is `None`.
+	pub fn register(registry: Option<&Registry>) -> Result<Self, PrometheusError> {
+		let registry = match registry {
+			Some(registry) => registry,
+			None => return Ok(Self(None)),
+		};
+
+		Ok(Self(Some(MetricsInner {
+			pending_candidates: register(
+				Gauge::new(
+					"cumulus_pov_recovery_pending_candidates",
+					"Number of candidates that wait to be imported or to be recovered.",
+				)?,
+				registry,
+			)?,
+			recoveries: register(
+				CounterVec::new(
+					Opts::new(
+						"cumulus_pov_recovery_recoveries_total",
+						"Number of finished PoV recoveries by outcome.",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+			recovery_duration: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_pov_recovery_duration_seconds",
+						"Time it took to recover the available data of a candidate.",
+					)
+					.buckets(vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]),
+				)?,
+				registry,
+			)?,
+		})))
+	}
+
+	/// Set the number of pending candidates.
+	pub(crate) fn set_pending_candidates(&self, count: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.pending_candidates.set(count as u64);
+		}
+	}
+
+	/// Note a finished recovery, `recovered` is `false` if no block could be restored.
+	pub(crate) fn on_recovery_finished(&self, recovered: bool) {
+		if let Some(metrics) = &self.0 {
+			let outcome = if recovered { "recovered" } else { "failed" };
+			metrics.recoveries.with_label_values(&[outcome]).inc();
+		}
+	}
+
+	/// Start timing a recovery, the duration is recorded when the timer is dropped.
+	pub(crate) fn time_recovery(&self) -> Option<HistogramTimer> {
+		self.0
+			.as_ref()
+			.map(|metrics| metrics.recovery_duration.start_timer())
+	}
+}
@@ -0,0 +1,527 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use async_trait::async_trait;
+use codec::Encode;
+use cumulus_relay_chain_interface::{RelayChainError, RelayHeaderStream};
+use cumulus_test_client::{
+	runtime::{Block, Hash, SLOT_DURATION},
+	ClientBlockImportExt, DefaultTestClientBuilderExt, InitBlockBuilder, TestClientBuilder,
+	TestClientBuilderExt,
+};
+use futures::{
+	channel::{mpsc, oneshot},
+	executor::block_on,
+};
+use polkadot_node_primitives::{BlockData, PoV};
+use polkadot_node_subsystem::{errors::RecoveryError, messages::AvailabilityRecoveryMessage};
+use polkadot_node_subsystem_test_helpers::ForwardSubsystem;
+use polkadot_overseer::{AllSubsystems, HeadSupportsParachains, Overseer};
+use polkadot_primitives::v1::{
+	CandidateCommitments, Hash as PHash, HeadData, Header as PHeader, OccupiedCoreAssumption,
+	PersistedValidationData, ValidatorId,
+};
+use sp_consensus::import_queue::{Link, Origin};
+use sp_core::{testing::TaskExecutor, traits::SpawnNamed};
+use sp_runtime::Justifications;
+use std::sync::Mutex;
+
+struct AlwaysSupportsParachains;
+impl HeadSupportsParachains for AlwaysSupportsParachains {
+	fn head_supports_parachains(&self, _head: &PHash) -> bool {
+		true
+	}
+}
+
+/// A relay chain that has one pending candidate per imported block.
+#[derive(Clone)]
+struct TestRelayChain {
+	candidates: Arc<HashMap<PHash, CommittedCandidateReceipt>>,
+	imported_blocks: Arc<Mutex<Option<mpsc::UnboundedReceiver<PHeader>>>>,
+}
+
+#[async_trait]
+impl RelayChainInterface for TestRelayChain {
+	async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn header(&self, _: PHash) -> RelayChainResult<Option<PHeader>> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn persisted_validation_data(
+		&self,
+		_: PHash,
+		_: ParaId,
+		_: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn candidate_pending_availability(
+		&self,
+		block: PHash,
+		_: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+		Ok(self.candidates.get(&block).cloned())
+	}
+
+	async fn session_index_for_child(&self, _: PHash) -> RelayChainResult<SessionIndex> {
+		Ok(0)
+	}
+
+	async fn validators(&self, _: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+		Ok(false)
+	}
+
+	async fn wait_for_block(&self, _: PHash) -> RelayChainResult<()> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn import_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		self.imported_blocks
+			.lock()
+			.unwrap()
+			.take()
+			.map(|stream| stream.boxed())
+			.ok_or(RelayChainError::NotSupported("second import notification stream"))
+	}
+
+	async fn new_best_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		unimplemented!("Not required in tests")
+	}
+
+	async fn finality_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		unimplemented!("Not required in tests")
+	}
+
+	fn overseer_handler(&self) -> RelayChainResult<OverseerHandler> {
+		unimplemented!("Not required in tests")
+	}
+}
+
+/// An import queue that only remembers the hashes of the blocks to import.
+#[derive(Clone, Default)]
+struct TestImportQueue {
+	imported: Arc<Mutex<Vec<Hash>>>,
+}
+
+impl ImportQueue<Block> for TestImportQueue {
+	fn import_blocks(&mut self, _: BlockOrigin, blocks: Vec<IncomingBlock<Block>>) {
+		self.imported
+			.lock()
+			.unwrap()
+			.extend(blocks.iter().map(|b| b.hash));
+	}
+
+	fn import_justifications(
+		&mut self,
+		_: Origin,
+		_: Hash,
+		_: NumberFor<Block>,
+		_: Justifications,
+	) {
+	}
+
+	fn poll_actions(&mut self, _: &mut std::task::Context, _: &mut dyn Link<Block>) {}
+}
+
+/// A block requester that remembers the requested blocks, but never provides them.
+#[derive(Default)]
+struct TestBlockRequester {
+	requested: Mutex<Vec<Hash>>,
+}
+
+impl ParachainBlockRequester<Block> for TestBlockRequester {
+	fn request_block(&self, hash: Hash, _: NumberFor<Block>) {
+		self.requested.lock().unwrap().push(hash);
+	}
+}
+
+type RecoveryResponse = oneshot::Sender<Result<AvailableData, RecoveryError>>;
+
+struct TestSetup {
+	/// The recovery requests that are received by the mock overseer.
+	recovery_requests: mpsc::Receiver<AvailabilityRecoveryMessage>,
+	/// The blocks that were passed to the import queue.
+	imported: Arc<Mutex<Vec<Hash>>>,
+	/// The hashes of the blocks of the pending candidates, in the order they are pending.
+	block_hashes: Vec<Hash>,
+	/// The block hashes of the candidates by their commitments hash.
+	candidate_blocks: HashMap<PHash, Hash>,
+	/// The data that is recovered for each of the pending candidates.
+	available_data: HashMap<Hash, AvailableData>,
+	/// Keeps the relay chain block import stream open.
+	_relay_chain_blocks: mpsc::UnboundedSender<PHeader>,
+}
+
+impl TestSetup {
+	/// Start a [`PoVRecovery`] that gets `candidates` pending candidates whose blocks are unknown.
+	///
+	/// The blocks are siblings on top of genesis.
+	fn start(
+		config: RecoveryConfig,
+		block_requester: Option<Arc<dyn ParachainBlockRequester<Block>>>,
+		candidates: u64,
+	) -> Self {
+		let client = TestClientBuilder::new().build();
+
+		let blocks = (0..candidates)
+			.map(|i| {
+				// Use different timestamps to get different blocks at the same height.
+				client
+					.init_block_builder_with_timestamp(
+						&BlockId::Number(0),
+						None,
+						Default::default(),
+						SLOT_DURATION * (i + 1),
+					)
+					.build()
+					.expect("Builds the block")
+					.block
+			})
+			.collect();
+
+		Self::start_with_blocks(config, block_requester, blocks)
+	}
+
+	/// Start a [`PoVRecovery`] that gets `candidates` pending candidates whose blocks are unknown.
+	///
+	/// The blocks build a chain on top of genesis, every block is the parent of the next one.
+	fn start_chain(config: RecoveryConfig, candidates: u64) -> Self {
+		// The blocks are only imported into a separate client, to build on top of them.
+		let mut client = TestClientBuilder::new().build();
+		let mut parent = client.chain_info().genesis_hash;
+
+		let blocks = (0..candidates)
+			.map(|i| {
+				let block = client
+					.init_block_builder_with_timestamp(
+						&BlockId::Hash(parent),
+						None,
+						Default::default(),
+						SLOT_DURATION * (i + 1),
+					)
+					.build()
+					.expect("Builds the block")
+					.block;
+				block_on(client.import(BlockOrigin::Own, block.clone()))
+					.expect("Imports the block");
+				parent = block.hash();
+
+				block
+			})
+			.collect();
+
+		Self::start_with_blocks(config, None, blocks)
+	}
+
+	/// Start a [`PoVRecovery`] that gets a pending candidate for each of the given `blocks`.
+	fn start_with_blocks(
+		config: RecoveryConfig,
+		block_requester: Option<Arc<dyn ParachainBlockRequester<Block>>>,
+		blocks: Vec<Block>,
+	) -> Self {
+		let spawner = TaskExecutor::new();
+		let client = Arc::new(TestClientBuilder::new().build());
+
+		let mut block_hashes = Vec::new();
+		let mut candidate_blocks = HashMap::new();
+		let mut available_data = HashMap::new();
+		let mut pending_candidates = HashMap::new();
+		let mut relay_headers = Vec::new();
+
+		for (i, block) in blocks.into_iter().enumerate() {
+			let (header, extrinsics) = block.deconstruct();
+			let block_hash = header.hash();
+
+			let receipt = CommittedCandidateReceipt {
+				descriptor: Default::default(),
+				commitments: CandidateCommitments {
+					head_data: HeadData(header.encode()),
+					..Default::default()
+				},
+			};
+
+			let block_data = ParachainBlockData::<Block>::new(
+				header,
+				extrinsics,
+				sp_trie::CompactProof {
+					encoded_nodes: Vec::new(),
+				},
+			);
+			let pov = PoV {
+				block_data: BlockData(
+					sp_maybe_compressed_blob::compress(&block_data.encode(), POV_BOMB_LIMIT)
+						.expect("Compresses the block"),
+				),
+			};
+
+			let relay_header = PHeader {
+				parent_hash: Default::default(),
+				number: i as u32 + 1,
+				state_root: Default::default(),
+				extrinsics_root: Default::default(),
+				digest: Default::default(),
+			};
+
+			candidate_blocks.insert(receipt.to_plain().commitments_hash, block_hash);
+			pending_candidates.insert(relay_header.hash(), receipt);
+			relay_headers.push(relay_header);
+			available_data.insert(
+				block_hash,
+				AvailableData {
+					pov: pov.into(),
+					validation_data: Default::default(),
+				},
+			);
+			block_hashes.push(block_hash);
+		}
+
+		let (relay_chain_blocks, imported_blocks_rx) = mpsc::unbounded();
+		relay_headers
+			.into_iter()
+			.for_each(|h| relay_chain_blocks.unbounded_send(h).unwrap());
+
+		let relay_chain = TestRelayChain {
+			candidates: Arc::new(pending_candidates),
+			imported_blocks: Arc::new(Mutex::new(Some(imported_blocks_rx))),
+		};
+
+		let (recovery_requests_tx, recovery_requests) = mpsc::channel(64);
+		let all_subsystems = AllSubsystems::<()>::dummy()
+			.replace_availability_recovery(ForwardSubsystem(recovery_requests_tx));
+		let (overseer, overseer_handler) = Overseer::new(
+			Vec::new(),
+			all_subsystems,
+			None,
+			AlwaysSupportsParachains,
+			spawner.clone(),
+		)
+		.expect("Creates overseer");
+		spawner.spawn("overseer", overseer.run().then(|_| async { () }).boxed());
+
+		let import_queue = TestImportQueue::default();
+		let imported = import_queue.imported.clone();
+
+		let pov_recovery = PoVRecovery::new(
+			overseer_handler,
+			config,
+			client,
+			import_queue,
+			relay_chain,
+			ParaId::from(100),
+			block_requester,
+			Metrics::default(),
+		);
+		spawner.spawn("pov-recovery", pov_recovery.run().boxed());
+
+		Self {
+			recovery_requests,
+			imported,
+			block_hashes,
+			candidate_blocks,
+			available_data,
+			_relay_chain_blocks: relay_chain_blocks,
+		}
+	}
+
+	/// Wait for the next recovery request.
+	///
+	/// Returns the hash of the block whose candidate should be recovered.
+	async fn next_recovery_request(&mut self) -> (Hash, RecoveryResponse) {
+		match self.recovery_requests.next().await {
+			Some(AvailabilityRecoveryMessage::RecoverAvailableData(receipt, _, _, response)) => {
+				(self.candidate_blocks[&receipt.commitments_hash], response)
+			}
+			#[allow(unreachable_patterns)]
+			_ => panic!("Expected a recovery request"),
+		}
+	}
+
+	/// Answer a recovery request, successfully if `success` is `true`.
+	fn respond(&self, block_hash: Hash, response: RecoveryResponse, success: bool) {
+		let result = if success {
+			Ok(self.available_data[&block_hash].clone())
+		} else {
+			Err(RecoveryError::Unavailable)
+		};
+
+		let _ = response.send(result);
+	}
+
+	/// Answer the next recovery request, successfully if `success` is `true`.
+	async fn answer_recovery_request(&mut self, success: bool) -> Hash {
+		let (block_hash, response) = self.next_recovery_request().await;
+		self.respond(block_hash, response, success);
+
+		block_hash
+	}
+
+	/// Returns `true` if no recovery is requested within `duration`.
+	async fn no_recovery_request_within(&mut self, duration: Duration) -> bool {
+		select! {
+			_ = self.recovery_requests.next().fuse() => false,
+			_ = Delay::new(duration).fuse() => true,
+		}
+	}
+
+	/// Wait until `count` blocks were passed to the import queue.
+	async fn wait_for_imports(&self, count: usize) -> Vec<Hash> {
+		loop {
+			{
+				let imported = self.imported.lock().unwrap();
+				if imported.len() >= count {
+					return imported.clone();
+				}
+			}
+
+			Delay::new(Duration::from_millis(10)).await;
+		}
+	}
+}
+
+fn test_config() -> RecoveryConfig {
+	RecoveryConfig {
+		min_delay: Duration::from_millis(0),
+		max_delay: Duration::from_millis(0),
+		max_retries: 2,
+		retry_backoff: Duration::from_millis(10),
+		max_concurrent_recoveries: 4,
+		peer_request_timeout: Duration::from_millis(50),
+	}
+}
+
+#[test]
+fn recovers_and_imports_candidate() {
+	let mut setup = TestSetup::start(test_config(), None, 1);
+
+	block_on(async move {
+		setup.answer_recovery_request(true).await;
+
+		assert_eq!(setup.wait_for_imports(1).await, setup.block_hashes);
+	});
+}
+
+#[test]
+fn failed_recovery_is_retried() {
+	let mut setup = TestSetup::start(test_config(), None, 1);
+
+	block_on(async move {
+		setup.answer_recovery_request(false).await;
+		setup.answer_recovery_request(false).await;
+		setup.answer_recovery_request(true).await;
+
+		assert_eq!(setup.wait_for_imports(1).await, setup.block_hashes);
+	});
+}
+
+#[test]
+fn recovery_is_given_up_after_max_retries() {
+	let config = RecoveryConfig {
+		max_retries: 1,
+		..test_config()
+	};
+	let mut setup = TestSetup::start(config, None, 1);
+
+	block_on(async move {
+		setup.answer_recovery_request(false).await;
+		setup.answer_recovery_request(false).await;
+
+		assert!(
+			setup
+				.no_recovery_request_within(Duration::from_millis(200))
+				.await
+		);
+		assert!(setup.imported.lock().unwrap().is_empty());
+	});
+}
+
+#[test]
+fn concurrent_recoveries_are_limited() {
+	let config = RecoveryConfig {
+		max_concurrent_recoveries: 1,
+		..test_config()
+	};
+	let mut setup = TestSetup::start(config, None, 2);
+
+	block_on(async move {
+		let (first, response) = setup.next_recovery_request().await;
+
+		// The second recovery waits until the first one is finished.
+		assert!(
+			setup
+				.no_recovery_request_within(Duration::from_millis(200))
+				.await
+		);
+
+		setup.respond(first, response, true);
+		let second = setup.answer_recovery_request(true).await;
+		assert_ne!(first, second);
+
+		assert_eq!(setup.wait_for_imports(2).await, vec![first, second]);
+	});
+}
+
+#[test]
+fn block_is_requested_from_peers_first() {
+	let block_requester = Arc::new(TestBlockRequester::default());
+	let mut setup = TestSetup::start(test_config(), Some(block_requester.clone()), 1);
+
+	block_on(async move {
+		setup.answer_recovery_request(true).await;
+
+		// Availability recovery only started after the peers didn't provide the block.
+		assert_eq!(*block_requester.requested.lock().unwrap(), setup.block_hashes);
+		assert_eq!(setup.wait_for_imports(1).await, setup.block_hashes);
+	});
+}
+
+#[test]
+fn recovers_more_missing_blocks_in_a_row_than_concurrent_recoveries() {
+	let config = RecoveryConfig {
+		max_concurrent_recoveries: 2,
+		..test_config()
+	};
+	let mut setup = TestSetup::start_chain(config, 6);
+
+	block_on(async move {
+		let first = setup.block_hashes[0];
+		let mut first_response = None;
+
+		// Hold back the first block, so that all its descendants are recovered before it and
+		// need to wait for their parents, which are still queued or waiting themselves.
+		for _ in 0..setup.block_hashes.len() {
+			let (block_hash, response) = setup.next_recovery_request().await;
+			if block_hash == first {
+				first_response = Some(response);
+			} else {
+				setup.respond(block_hash, response, true);
+			}
+		}
+
+		assert!(setup.imported.lock().unwrap().is_empty());
+		setup.respond(first, first_response.expect("First block is recovered"), true);
+
+		assert_eq!(setup.wait_for_imports(6).await, setup.block_hashes);
+	});
+}
@@ -0,0 +1,186 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Relay chain interface
+//!
+//! Provides the [`RelayChainInterface`] trait, the single point through which the parachain
+//! client components (block announcement, consensus, PoV recovery and the collator) talk to the
+//! relay chain. This makes it possible to back a parachain node either by an embedded relay
+//! chain full node or by a remote relay chain node that is reached over RPC.
+
+use std::{pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+use futures::Stream;
+use polkadot_overseer::OverseerHandler;
+use polkadot_primitives::v1::{
+	CommittedCandidateReceipt, Hash as PHash, Header as PHeader, Id as ParaId,
+	OccupiedCoreAssumption, PersistedValidationData, SessionIndex, ValidatorId,
+};
+
+/// A stream of relay chain block headers.
+pub type RelayHeaderStream = Pin<Box<dyn Stream<Item = PHeader> + Send>>;
+
+/// Errors that can occur while talking to the relay chain.
+#[derive(Debug, derive_more::Display)]
+pub enum RelayChainError {
+	#[display(fmt = "Error occurred while calling relay chain runtime: {}", _0)]
+	RuntimeApi(String),
+	#[display(fmt = "Relay chain blockchain returned an error: {}", _0)]
+	Blockchain(String),
+	#[display(
+		fmt = "Timeout while waiting for relay-chain block `{}` to be imported.",
+		_0
+	)]
+	WaitTimeout(PHash),
+	#[display(
+		fmt = "Import listener closed while waiting for relay-chain block `{}` to be imported.",
+		_0
+	)]
+	ImportListenerClosed(PHash),
+	#[display(fmt = "Relay chain RPC request failed: {}", _0)]
+	Rpc(String),
+	#[display(fmt = "Failed to decode relay chain response: {}", _0)]
+	Decode(String),
+	#[display(fmt = "`{}` is not supported by this relay chain interface", _0)]
+	NotSupported(&'static str),
+}
+
+impl std::error::Error for RelayChainError {}
+
+/// The result type used by [`RelayChainInterface`].
+pub type RelayChainResult<T> = Result<T, RelayChainError>;
+
+/// Everything a parachain node needs to know about the relay chain.
+///
+/// All block dependent calls are executed at the relay chain block identified by the given hash.
+#[async_trait]
+pub trait RelayChainInterface: Send + Sync {
+	/// Returns the hash of the current best relay chain block.
+	async fn best_block_hash(&self) -> RelayChainResult<PHash>;
+
+	/// Returns the header of the relay chain block `hash`, if it is known.
+	async fn header(&self, hash: PHash) -> RelayChainResult<Option<PHeader>>;
+
+	/// Returns the [`PersistedValidationData`] of `para_id` at the relay chain block `block`.
+	async fn persisted_validation_data(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+		assumption: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>>;
+
+	/// Returns the candidate of `para_id` that is pending availability at the relay chain block
+	/// `block`.
+	async fn candidate_pending_availability(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>>;
+
+	/// Returns the session index that a child of the relay chain block `block` will have.
+	async fn session_index_for_child(&self, block: PHash) -> RelayChainResult<SessionIndex>;
+
+	/// Returns the validator set at the relay chain block `block`.
+	async fn validators(&self, block: PHash) -> RelayChainResult<Vec<ValidatorId>>;
+
+	/// Returns `true` while the relay chain node is doing a major sync.
+	async fn is_major_syncing(&self) -> RelayChainResult<bool>;
+
+	/// Waits until the relay chain block `hash` is imported.
+	///
+	/// Returns an error if the block wasn't imported in a reasonable time.
+	async fn wait_for_block(&self, hash: PHash) -> RelayChainResult<()>;
+
+	/// Returns a stream of all imported relay chain block headers.
+	async fn import_notification_stream(&self) -> RelayChainResult<RelayHeaderStream>;
+
+	/// Returns a stream of relay chain block headers that became the new best block.
+	async fn new_best_notification_stream(&self) -> RelayChainResult<RelayHeaderStream>;
+
+	/// Returns a stream of finalized relay chain block headers.
+	async fn finality_notification_stream(&self) -> RelayChainResult<RelayHeaderStream>;
+
+	/// Returns the handle to the overseer of the relay chain node.
+	///
+	/// Only an embedded relay chain node runs an overseer, so this is what collators need.
+	fn overseer_handler(&self) -> RelayChainResult<OverseerHandler>;
+}
+
+#[async_trait]
+impl<T> RelayChainInterface for Arc<T>
+where
+	T: RelayChainInterface + ?Sized,
+{
+	async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+		(**self).best_block_hash().await
+	}
+
+	async fn header(&self, hash: PHash) -> RelayChainResult<Option<PHeader>> {
+		(**self).header(hash).await
+	}
+
+	async fn persisted_validation_data(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+		assumption: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>> {
+		(**self)
+			.persisted_validation_data(block, para_id, assumption)
+			.await
+	}
+
+	async fn candidate_pending_availability(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+		(**self).candidate_pending_availability(block, para_id).await
+	}
+
+	async fn session_index_for_child(&self, block: PHash) -> RelayChainResult<SessionIndex> {
+		(**self).session_index_for_child(block).await
+	}
+
+	async fn validators(&self, block: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+		(**self).validators(block).await
+	}
+
+	async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+		(**self).is_major_syncing().await
+	}
+
+	async fn wait_for_block(&self, hash: PHash) -> RelayChainResult<()> {
+		(**self).wait_for_block(hash).await
+	}
+
+	async fn import_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		(**self).import_notification_stream().await
+	}
+
+	async fn new_best_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		(**self).new_best_notification_stream().await
+	}
+
+	async fn finality_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		(**self).finality_notification_stream().await
+	}
+
+	fn overseer_handler(&self) -> RelayChainResult<OverseerHandler> {
+		(**self).overseer_handler()
+	}
+}
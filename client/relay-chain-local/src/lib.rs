@@ -0,0 +1,222 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Embedded relay chain interface
+//!
+//! Provides [`RelayChainLocal`], a [`RelayChainInterface`] that is backed by a relay chain full
+//! node running inside the parachain node.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cumulus_relay_chain_interface::{
+	RelayChainError, RelayChainInterface, RelayChainResult, RelayHeaderStream,
+};
+use futures::{future::ready, StreamExt};
+use parking_lot::Mutex;
+use polkadot_overseer::OverseerHandler;
+use polkadot_primitives::v1::{
+	Block as PBlock, CommittedCandidateReceipt, Hash as PHash, Header as PHeader, Id as ParaId,
+	OccupiedCoreAssumption, ParachainHost, PersistedValidationData, SessionIndex, ValidatorId,
+};
+use polkadot_service::{
+	AbstractClient, Client as PClient, ClientHandle, ExecuteWithClient, FullBackend,
+	RuntimeApiCollection,
+};
+use sc_client_api::{blockchain::HeaderBackend, Backend, BlockchainEvents};
+use sp_api::ProvideRuntimeApi;
+use sp_consensus::SyncOracle;
+use sp_runtime::{generic::BlockId, traits::BlakeTwo256};
+
+pub use wait_on_relay_chain_block::{Error as WaitError, WaitOnRelayChainBlock};
+
+mod wait_on_relay_chain_block;
+
+/// A [`RelayChainInterface`] implementation that uses an embedded relay chain full node.
+pub struct RelayChainLocal<Client> {
+	full_client: Arc<Client>,
+	backend: Arc<FullBackend>,
+	sync_oracle: Arc<Mutex<Box<dyn SyncOracle + Send>>>,
+	overseer_handler: Option<OverseerHandler>,
+}
+
+impl<Client> RelayChainLocal<Client> {
+	/// Create a new instance of [`RelayChainLocal`].
+	pub fn new(
+		full_client: Arc<Client>,
+		backend: Arc<FullBackend>,
+		sync_oracle: Box<dyn SyncOracle + Send>,
+		overseer_handler: Option<OverseerHandler>,
+	) -> Self {
+		Self {
+			full_client,
+			backend,
+			sync_oracle: Arc::new(Mutex::new(sync_oracle)),
+			overseer_handler,
+		}
+	}
+}
+
+impl<Client> Clone for RelayChainLocal<Client> {
+	fn clone(&self) -> Self {
+		Self {
+			full_client: self.full_client.clone(),
+			backend: self.backend.clone(),
+			sync_oracle: self.sync_oracle.clone(),
+			overseer_handler: self.overseer_handler.clone(),
+		}
+	}
+}
+
+fn runtime_api_error(error: impl std::fmt::Debug) -> RelayChainError {
+	RelayChainError::RuntimeApi(format!("{:?}", error))
+}
+
+#[async_trait]
+impl<Client> RelayChainInterface for RelayChainLocal<Client>
+where
+	Client: ProvideRuntimeApi<PBlock> + BlockchainEvents<PBlock> + Send + Sync + 'static,
+	Client::Api: ParachainHost<PBlock>,
+{
+	async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+		Ok(self.backend.blockchain().info().best_hash)
+	}
+
+	async fn header(&self, hash: PHash) -> RelayChainResult<Option<PHeader>> {
+		self.backend
+			.blockchain()
+			.header(BlockId::Hash(hash))
+			.map_err(|e| RelayChainError::Blockchain(e.to_string()))
+	}
+
+	async fn persisted_validation_data(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+		assumption: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>> {
+		self.full_client
+			.runtime_api()
+			.persisted_validation_data(&BlockId::Hash(block), para_id, assumption)
+			.map_err(runtime_api_error)
+	}
+
+	async fn candidate_pending_availability(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+		self.full_client
+			.runtime_api()
+			.candidate_pending_availability(&BlockId::Hash(block), para_id)
+			.map_err(runtime_api_error)
+	}
+
+	async fn session_index_for_child(&self, block: PHash) -> RelayChainResult<SessionIndex> {
+		self.full_client
+			.runtime_api()
+			.session_index_for_child(&BlockId::Hash(block))
+			.map_err(runtime_api_error)
+	}
+
+	async fn validators(&self, block: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+		self.full_client
+			.runtime_api()
+			.validators(&BlockId::Hash(block))
+			.map_err(runtime_api_error)
+	}
+
+	async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+		Ok(self.sync_oracle.lock().is_major_syncing())
+	}
+
+	async fn wait_for_block(&self, hash: PHash) -> RelayChainResult<()> {
+		let wait = WaitOnRelayChainBlock::new(self.backend.clone(), self.full_client.clone())
+			.wait_on_relay_chain_block(hash);
+
+		wait.await.map_err(Into::into)
+	}
+
+	async fn import_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		Ok(self
+			.full_client
+			.import_notification_stream()
+			.map(|n| n.header)
+			.boxed())
+	}
+
+	async fn new_best_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		Ok(self
+			.full_client
+			.import_notification_stream()
+			.filter_map(|n| ready(if n.is_new_best { Some(n.header) } else { None }))
+			.boxed())
+	}
+
+	async fn finality_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		Ok(self
+			.full_client
+			.finality_notification_stream()
+			.map(|n| n.header)
+			.boxed())
+	}
+
+	fn overseer_handler(&self) -> RelayChainResult<OverseerHandler> {
+		self.overseer_handler
+			.clone()
+			.ok_or(RelayChainError::NotSupported("overseer_handler"))
+	}
+}
+
+/// Builds a [`RelayChainLocal`] for the given relay chain full node.
+///
+/// As the full node only provides a [`PClient`], the concrete client instance is accessed
+/// through [`ExecuteWithClient`].
+pub fn build_relay_chain_interface(
+	full_node: &polkadot_service::NewFull<PClient>,
+) -> Arc<dyn RelayChainInterface> {
+	full_node.client.execute_with(RelayChainLocalBuilder {
+		backend: full_node.backend.clone(),
+		sync_oracle: Box::new(full_node.network.clone()),
+		overseer_handler: full_node.overseer_handler.clone(),
+	})
+}
+
+struct RelayChainLocalBuilder {
+	backend: Arc<FullBackend>,
+	sync_oracle: Box<dyn SyncOracle + Send>,
+	overseer_handler: Option<OverseerHandler>,
+}
+
+impl ExecuteWithClient for RelayChainLocalBuilder {
+	type Output = Arc<dyn RelayChainInterface>;
+
+	fn execute_with_client<Client, Api, PBackend>(self, client: Arc<Client>) -> Self::Output
+	where
+		<Api as sp_api::ApiExt<PBlock>>::StateBackend: sp_api::StateBackend<BlakeTwo256>,
+		PBackend: Backend<PBlock>,
+		PBackend::State: sp_api::StateBackend<BlakeTwo256>,
+		Api: RuntimeApiCollection<StateBackend = PBackend::State>,
+		Client: AbstractClient<PBlock, PBackend, Api = Api> + 'static,
+	{
+		Arc::new(RelayChainLocal::new(
+			client,
+			self.backend,
+			self.sync_oracle,
+			self.overseer_handler,
+		))
+	}
+}
@@ -16,6 +16,7 @@
 
 //! Provides the [`WaitOnRelayChainBlock`] type.
 
+use cumulus_relay_chain_interface::RelayChainError;
 use futures::{future::ready, Future, FutureExt, StreamExt};
 use polkadot_primitives::v1::{Block as PBlock, Hash as PHash};
 use sc_client_api::{
@@ -49,6 +50,16 @@ pub enum Error {
 	BlockchainError(PHash, blockchain::Error),
 }
 
+impl From<Error> for RelayChainError {
+	fn from(error: Error) -> Self {
+		match error {
+			Error::Timeout(hash) => RelayChainError::WaitTimeout(hash),
+			Error::ImportListenerClosed(hash) => RelayChainError::ImportListenerClosed(hash),
+			e @ Error::BlockchainError(..) => RelayChainError::Blockchain(e.to_string()),
+		}
+	}
+}
+
 /// A helper to wait for a given relay chain block in an async way.
 ///
 /// The caller needs to pass the hash of a block it waits for and the function will return when the
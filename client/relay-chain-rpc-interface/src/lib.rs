@@ -0,0 +1,244 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Remote relay chain interface
+//!
+//! Provides [`RelayChainRpcInterface`], a [`RelayChainInterface`] that talks to a relay chain
+//! node over its JSON-RPC websocket endpoint instead of embedding a full relay chain node.
+//!
+//! Runtime API calls are forwarded through `state_call`, so the remote node needs to expose the
+//! `chain`, `state` and `system` RPC methods. There is no overseer on the remote side, so this
+//! interface can not be used to run a collator.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use codec::{Decode, Encode};
+use cumulus_relay_chain_interface::{
+	RelayChainError, RelayChainInterface, RelayChainResult, RelayHeaderStream,
+};
+use futures::{FutureExt, StreamExt};
+use jsonrpsee_ws_client::{
+	traits::{Client, SubscriptionClient},
+	v2::params::JsonRpcParams,
+	WsClient, WsClientBuilder,
+};
+use polkadot_overseer::OverseerHandler;
+use polkadot_primitives::v1::{
+	CommittedCandidateReceipt, Hash as PHash, Header as PHeader, Id as ParaId,
+	OccupiedCoreAssumption, PersistedValidationData, SessionIndex, ValidatorId,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use sp_core::Bytes;
+use sp_runtime::traits::Header as HeaderT;
+
+#[cfg(test)]
+mod tests;
+
+const LOG_TARGET: &str = "relay-chain-rpc-interface";
+
+/// The timeout in seconds after that the waiting for a block should be aborted.
+const TIMEOUT_IN_SECONDS: u64 = 6;
+
+/// The subset of the `system_health` response we are interested in.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Health {
+	is_syncing: bool,
+}
+
+fn rpc_error(error: impl std::fmt::Display) -> RelayChainError {
+	RelayChainError::Rpc(error.to_string())
+}
+
+fn to_json(value: impl serde::Serialize) -> RelayChainResult<JsonValue> {
+	serde_json::to_value(value).map_err(rpc_error)
+}
+
+/// A [`RelayChainInterface`] implementation that uses the JSON-RPC interface of a remote relay
+/// chain node.
+#[derive(Clone)]
+pub struct RelayChainRpcInterface {
+	client: Arc<WsClient>,
+}
+
+impl RelayChainRpcInterface {
+	/// Connect to the relay chain node listening at the websocket `url`.
+	pub async fn new(url: &str) -> RelayChainResult<Self> {
+		let client = WsClientBuilder::default()
+			.max_request_body_size(u32::MAX)
+			.build(url)
+			.await
+			.map_err(rpc_error)?;
+
+		tracing::debug!(target: LOG_TARGET, url, "Connected to relay chain node.");
+
+		Ok(Self {
+			client: Arc::new(client),
+		})
+	}
+
+	async fn request<R: DeserializeOwned>(
+		&self,
+		method: &str,
+		params: Vec<JsonValue>,
+	) -> RelayChainResult<R> {
+		self.client
+			.request(method, JsonRpcParams::Array(params))
+			.await
+			.map_err(|e| {
+				tracing::debug!(target: LOG_TARGET, method, error = %e, "Relay chain RPC request failed.");
+				rpc_error(e)
+			})
+	}
+
+	/// Call the runtime function `method` with the SCALE encoded `payload` at the relay chain
+	/// block `block` and decode the result.
+	async fn call_runtime_api<R: Decode>(
+		&self,
+		method: &str,
+		block: PHash,
+		payload: impl Encode,
+	) -> RelayChainResult<R> {
+		let params = vec![
+			to_json(method)?,
+			to_json(Bytes(payload.encode()))?,
+			to_json(block)?,
+		];
+		let response: Bytes = self.request("state_call", params).await?;
+
+		R::decode(&mut &response.0[..]).map_err(|e| RelayChainError::Decode(e.to_string()))
+	}
+
+	async fn subscribe_headers(
+		&self,
+		subscribe_method: &str,
+		unsubscribe_method: &str,
+	) -> RelayChainResult<RelayHeaderStream> {
+		let subscription = self
+			.client
+			.subscribe::<PHeader>(subscribe_method, JsonRpcParams::NoParams, unsubscribe_method)
+			.await
+			.map_err(rpc_error)?;
+
+		Ok(futures::stream::unfold(subscription, |mut subscription| async move {
+			subscription.next().await.map(|header| (header, subscription))
+		})
+		.boxed())
+	}
+}
+
+#[async_trait]
+impl RelayChainInterface for RelayChainRpcInterface {
+	async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+		self.request::<Option<PHash>>("chain_getBlockHash", Vec::new())
+			.await?
+			.ok_or_else(|| RelayChainError::Rpc("Relay chain node returned no best block".into()))
+	}
+
+	async fn header(&self, hash: PHash) -> RelayChainResult<Option<PHeader>> {
+		self.request("chain_getHeader", vec![to_json(hash)?]).await
+	}
+
+	async fn persisted_validation_data(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+		assumption: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>> {
+		self.call_runtime_api(
+			"ParachainHost_persisted_validation_data",
+			block,
+			(para_id, assumption),
+		)
+		.await
+	}
+
+	async fn candidate_pending_availability(
+		&self,
+		block: PHash,
+		para_id: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+		self.call_runtime_api("ParachainHost_candidate_pending_availability", block, para_id)
+			.await
+	}
+
+	async fn session_index_for_child(&self, block: PHash) -> RelayChainResult<SessionIndex> {
+		self.call_runtime_api("ParachainHost_session_index_for_child", block, ())
+			.await
+	}
+
+	async fn validators(&self, block: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+		self.call_runtime_api("ParachainHost_validators", block, ())
+			.await
+	}
+
+	async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+		let health: Health = self.request("system_health", Vec::new()).await?;
+
+		Ok(health.is_syncing)
+	}
+
+	async fn wait_for_block(&self, hash: PHash) -> RelayChainResult<()> {
+		if self.header(hash).await?.is_some() {
+			return Ok(());
+		}
+
+		let mut imported = self.import_notification_stream().await?.fuse();
+
+		// The block could have been imported before the subscription was established.
+		if self.header(hash).await?.is_some() {
+			return Ok(());
+		}
+
+		let mut timeout = futures_timer::Delay::new(Duration::from_secs(TIMEOUT_IN_SECONDS)).fuse();
+
+		loop {
+			futures::select! {
+				_ = timeout => return Err(RelayChainError::WaitTimeout(hash)),
+				header = imported.next() => match header {
+					Some(header) if header.hash() == hash => return Ok(()),
+					// Not the block we waited on.
+					Some(_) => continue,
+					None => return Err(RelayChainError::ImportListenerClosed(hash)),
+				}
+			}
+		}
+	}
+
+	async fn import_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		self.subscribe_headers("chain_subscribeAllHeads", "chain_unsubscribeAllHeads")
+			.await
+	}
+
+	async fn new_best_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		self.subscribe_headers("chain_subscribeNewHeads", "chain_unsubscribeNewHeads")
+			.await
+	}
+
+	async fn finality_notification_stream(&self) -> RelayChainResult<RelayHeaderStream> {
+		self.subscribe_headers(
+			"chain_subscribeFinalizedHeads",
+			"chain_unsubscribeFinalizedHeads",
+		)
+		.await
+	}
+
+	fn overseer_handler(&self) -> RelayChainResult<OverseerHandler> {
+		Err(RelayChainError::NotSupported("overseer_handler"))
+	}
+}
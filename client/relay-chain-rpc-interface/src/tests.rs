@@ -0,0 +1,177 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use jsonrpc_core::{Error as JsonRpcError, IoHandler, Params, Value};
+use jsonrpc_ws_server::{Server, ServerBuilder};
+use polkadot_primitives::v1::HeadData;
+
+const PARA_ID: u32 = 100;
+
+fn relay_header(number: u32) -> PHeader {
+	PHeader {
+		parent_hash: Default::default(),
+		number,
+		state_root: Default::default(),
+		extrinsics_root: Default::default(),
+		digest: Default::default(),
+	}
+}
+
+fn parent_head() -> HeadData {
+	HeadData(vec![1, 2, 3])
+}
+
+fn encoded(value: impl Encode) -> Value {
+	serde_json::to_value(Bytes(value.encode())).unwrap()
+}
+
+/// Handles `state_call` like a relay chain node running a runtime that only knows `PARA_ID`.
+fn state_call(params: Params) -> Result<Value, JsonRpcError> {
+	let (method, data, _at): (String, Bytes, Option<PHash>) = params.parse()?;
+
+	let response = match method.as_str() {
+		"ParachainHost_persisted_validation_data" => {
+			let (para_id, _) = <(ParaId, OccupiedCoreAssumption)>::decode(&mut &data[..])
+				.map_err(|_| JsonRpcError::invalid_params("Invalid payload"))?;
+			let data = if para_id == ParaId::from(PARA_ID) {
+				Some(PersistedValidationData {
+					parent_head: parent_head(),
+					..Default::default()
+				})
+			} else {
+				None
+			};
+			encoded(data)
+		}
+		"ParachainHost_candidate_pending_availability" => {
+			encoded(Option::<CommittedCandidateReceipt>::None)
+		}
+		"ParachainHost_session_index_for_child" => encoded(5u32),
+		"ParachainHost_validators" => encoded(vec![ValidatorId::default()]),
+		_ => return Err(JsonRpcError::method_not_found()),
+	};
+
+	Ok(response)
+}
+
+/// Start a mock relay chain node that knows about the blocks with the given `headers`.
+fn start_mock_relay_node(headers: Vec<PHeader>, is_syncing: bool) -> (Server, String) {
+	let mut io = IoHandler::new();
+
+	let best_hash = headers.last().map(|h| h.hash());
+	io.add_method("chain_getBlockHash", move |_| Ok(serde_json::to_value(best_hash).unwrap()));
+	io.add_method("chain_getHeader", move |params: Params| {
+		let (hash,): (PHash,) = params.parse()?;
+		let header = headers.iter().find(|h| h.hash() == hash).cloned();
+		Ok(serde_json::to_value(header).unwrap())
+	});
+	io.add_method("state_call", state_call);
+	io.add_method("system_health", move |_| {
+		Ok(serde_json::json!({
+			"peers": 1,
+			"isSyncing": is_syncing,
+			"shouldHavePeers": true,
+		}))
+	});
+
+	let server = ServerBuilder::new(io)
+		.start(&"127.0.0.1:0".parse().unwrap())
+		.expect("Starts the mock relay chain node");
+	let url = format!("ws://{}", server.addr());
+
+	(server, url)
+}
+
+#[tokio::test]
+async fn fetches_best_block_and_headers() {
+	let headers = vec![relay_header(1), relay_header(2)];
+	let (_server, url) = start_mock_relay_node(headers.clone(), false);
+	let interface = RelayChainRpcInterface::new(&url).await.unwrap();
+
+	assert_eq!(interface.best_block_hash().await.unwrap(), headers[1].hash());
+	assert_eq!(
+		interface.header(headers[0].hash()).await.unwrap(),
+		Some(headers[0].clone())
+	);
+	assert_eq!(interface.header(PHash::repeat_byte(1)).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn forwards_runtime_api_calls() {
+	let (_server, url) = start_mock_relay_node(vec![relay_header(1)], false);
+	let interface = RelayChainRpcInterface::new(&url).await.unwrap();
+	let at = PHash::repeat_byte(2);
+
+	let data = interface
+		.persisted_validation_data(at, PARA_ID.into(), OccupiedCoreAssumption::TimedOut)
+		.await
+		.unwrap();
+	assert_eq!(data.map(|d| d.parent_head), Some(parent_head()));
+
+	assert!(interface
+		.persisted_validation_data(at, 200.into(), OccupiedCoreAssumption::TimedOut)
+		.await
+		.unwrap()
+		.is_none());
+	assert!(interface
+		.candidate_pending_availability(at, PARA_ID.into())
+		.await
+		.unwrap()
+		.is_none());
+	assert_eq!(interface.session_index_for_child(at).await.unwrap(), 5);
+	assert_eq!(
+		interface.validators(at).await.unwrap(),
+		vec![ValidatorId::default()]
+	);
+}
+
+#[tokio::test]
+async fn reports_sync_state() {
+	let (_server, url) = start_mock_relay_node(Vec::new(), true);
+	let interface = RelayChainRpcInterface::new(&url).await.unwrap();
+
+	assert!(interface.is_major_syncing().await.unwrap());
+}
+
+#[tokio::test]
+async fn wait_for_block_returns_for_known_block() {
+	let header = relay_header(1);
+	let (_server, url) = start_mock_relay_node(vec![header.clone()], false);
+	let interface = RelayChainRpcInterface::new(&url).await.unwrap();
+
+	assert!(interface.wait_for_block(header.hash()).await.is_ok());
+}
+
+#[tokio::test]
+async fn rpc_errors_are_reported() {
+	let (_server, url) = start_mock_relay_node(Vec::new(), false);
+	let interface = RelayChainRpcInterface::new(&url).await.unwrap();
+
+	assert!(matches!(
+		interface.best_block_hash().await,
+		Err(RelayChainError::Rpc(_))
+	));
+	// The mock node doesn't support subscriptions.
+	assert!(matches!(
+		interface.wait_for_block(PHash::repeat_byte(3)).await,
+		Err(RelayChainError::Rpc(_))
+	));
+	assert!(matches!(
+		interface.overseer_handler(),
+		Err(RelayChainError::NotSupported(_))
+	));
+}
@@ -28,6 +28,7 @@ use frame_support::{
 };
 use pallet_authorship::EventHandler;
 use pallet_session::SessionManager;
+use sp_runtime::Perbill;
 
 pub type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -60,6 +61,33 @@ fn register_candidates<T: Config>(count: u32) {
 	}
 }
 
+fn delegate_to<T: Config>(candidate: &T::AccountId, name: &'static str, count: u32) {
+	let amount = T::MinDelegation::get().max(T::Currency::minimum_balance());
+	for d in 0..count {
+		let who: T::AccountId = account(name, d, SEED);
+		T::Currency::make_free_balance_be(&who, amount * 2u32.into());
+		<CollatorSelection<T>>::delegate(
+			RawOrigin::Signed(who).into(),
+			candidate.clone(),
+			amount,
+		).unwrap();
+	}
+}
+
+/// Spread `count` delegations round robin among `candidates`, each from a new delegator.
+fn delegate_among<T: Config>(candidates: &[T::AccountId], name: &'static str, count: u32) {
+	let amount = T::MinDelegation::get().max(T::Currency::minimum_balance());
+	for d in 0..count {
+		let who: T::AccountId = account(name, d, SEED);
+		T::Currency::make_free_balance_be(&who, amount * 2u32.into());
+		<CollatorSelection<T>>::delegate(
+			RawOrigin::Signed(who).into(),
+			candidates[d as usize % candidates.len()].clone(),
+			amount,
+		).unwrap();
+	}
+}
+
 benchmarks! {
 	where_clause { where T: pallet_authorship::Config }
 
@@ -103,7 +131,7 @@ benchmarks! {
 	// worse case is when we have all the max-candidate slots filled except one, and we fill that
 	// one.
 	register_as_candidate {
-		let c in 1 .. T::MaxCandidates::get();
+		let c in 1 .. T::MaxCandidates::get() - 1;
 
 		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
 		<DesiredCandidates<T>>::put(c + 1);
@@ -118,44 +146,165 @@ benchmarks! {
 		assert_last_event::<T>(Event::CandidateAdded(caller, bond / 2u32.into()).into());
 	}
 
-	// worse case is the last candidate leaving.
+	// worse case is the last candidate leaving with all delegator slots filled.
 	leave_intent {
 		let c in 1 .. T::MaxCandidates::get();
+		let d in 0 .. T::MaxDelegatorsPerCandidate::get();
 		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
 		<DesiredCandidates<T>>::put(c);
 		register_candidates::<T>(c);
 
 		let leaving = <Candidates<T>>::get().last().unwrap().who.clone();
+		delegate_to::<T>(&leaving, "delegator", d);
 		whitelist!(leaving);
 	}: _(RawOrigin::Signed(leaving.clone()))
 	verify {
-		assert_last_event::<T>(Event::CandidateRemoved(leaving).into());
+		assert_last_event::<T>(Event::CandidateLeaving(leaving.clone()).into());
+		assert!(<LeavingCandidates<T>>::contains_key(&leaving));
+		assert_eq!(<DelegatorCount<T>>::get(&leaving), 0);
+	}
+
+	// worse case is the last candidate lowering its deposit, which needs a new unbonding chunk.
+	update_bond {
+		let c in 1 .. T::MaxCandidates::get();
+		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
+		<DesiredCandidates<T>>::put(c);
+		register_candidates::<T>(c);
+
+		let candidate = <Candidates<T>>::get().last().unwrap().who.clone();
+		let bond = <CandidacyBond<T>>::get();
+		assert_ok!(<CollatorSelection<T>>::update_bond(
+			RawOrigin::Signed(candidate.clone()).into(),
+			bond * 2u32.into(),
+		));
+		whitelist!(candidate);
+	}: _(RawOrigin::Signed(candidate.clone()), bond)
+	verify {
+		assert_last_event::<T>(Event::CandidateBondUpdated(candidate.clone(), bond).into());
+		assert_eq!(<Unbonding<T>>::get(&candidate).len(), 1);
+	}
+
+	// worse case is adding a new delegator next to an existing delegation.
+	delegate {
+		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
+		register_candidates::<T>(1);
+		let candidate = <Candidates<T>>::get()[0].who.clone();
+		delegate_to::<T>(&candidate, "delegator", 1);
+
+		let caller: T::AccountId = whitelisted_caller();
+		let amount = T::MinDelegation::get().max(T::Currency::minimum_balance());
+		T::Currency::make_free_balance_be(&caller, amount * 2u32.into());
+	}: _(RawOrigin::Signed(caller.clone()), candidate.clone(), amount)
+	verify {
+		assert_last_event::<T>(Event::Delegated(caller, candidate, amount).into());
 	}
 
-	// worse case is paying a non-existing candidate account.
+	// worse case is removing the delegation entirely.
+	undelegate {
+		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
+		register_candidates::<T>(1);
+		let candidate = <Candidates<T>>::get()[0].who.clone();
+
+		let caller: T::AccountId = whitelisted_caller();
+		let amount = T::MinDelegation::get().max(T::Currency::minimum_balance());
+		T::Currency::make_free_balance_be(&caller, amount * 2u32.into());
+		assert_ok!(<CollatorSelection<T>>::delegate(
+			RawOrigin::Signed(caller.clone()).into(),
+			candidate.clone(),
+			amount,
+		));
+	}: _(RawOrigin::Signed(caller.clone()), candidate.clone(), amount)
+	verify {
+		assert_last_event::<T>(Event::Undelegated(caller, candidate, amount).into());
+	}
+
+	// worse case is withdrawing a full list of unbonding chunks.
+	withdraw_unbonded {
+		let caller: T::AccountId = whitelisted_caller();
+		let value = T::Currency::minimum_balance();
+		let total = value * (MAX_UNBONDING_CHUNKS as u32).into();
+		T::Currency::make_free_balance_be(&caller, total + value);
+		T::Currency::reserve(&caller, total)?;
+		let chunks = (0..MAX_UNBONDING_CHUNKS)
+			.map(|_| UnbondingChunk { value, unlock_at: 0u32.into() })
+			.collect::<Vec<_>>();
+		<Unbonding<T>>::insert(&caller, chunks);
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert_last_event::<T>(Event::Withdrawn(caller, total).into());
+	}
+
+	set_commission {
+		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
+		register_candidates::<T>(1);
+		let candidate = <Candidates<T>>::get()[0].who.clone();
+		let commission = Perbill::from_percent(10);
+	}: _(RawOrigin::Signed(candidate.clone()), commission)
+	verify {
+		assert_last_event::<T>(Event::CommissionSet(candidate, commission).into());
+	}
+
+	// worse case is a collator backed by the maximum number of delegators.
+	payout {
+		let d in 0 .. T::MaxDelegatorsPerCandidate::get();
+		let caller: T::AccountId = whitelisted_caller();
+		let collator: T::AccountId = account("collator", 0, SEED);
+		let stake = T::Currency::minimum_balance();
+		let others = (0..d).map(|i| (account("delegator", i, SEED), stake)).collect::<Vec<_>>();
+		let exposure = Exposure {
+			total: stake * (d + 1).into(),
+			own: stake,
+			others,
+			commission: Perbill::from_percent(10),
+		};
+		<ErasStakers<T>>::insert(0, &collator, exposure);
+
+		let reward = T::Currency::minimum_balance() * 100u32.into() * (d + 1).into();
+		T::Currency::make_free_balance_be(
+			&<CollatorSelection<T>>::account_id(),
+			reward + T::Currency::minimum_balance(),
+		);
+		<ErasRewards<T>>::insert(0, &collator, reward);
+		<TotalUnclaimed<T>>::put(reward);
+		<ActiveEra<T>>::put(1);
+	}: _(RawOrigin::Signed(caller), 0, collator.clone())
+	verify {
+		assert_eq!(<ErasRewards<T>>::get(0, &collator), 0u32.into());
+		assert!(T::Currency::free_balance(&collator) > 0u32.into());
+	}
+
+	// worse case is accruing the reward of an author who already has rewards in the era, with
+	// other rewards still unclaimed.
 	note_author {
 		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
+		let unclaimed = T::Currency::minimum_balance() * 4u32.into();
 		T::Currency::make_free_balance_be(
 			&<CollatorSelection<T>>::account_id(),
-			T::Currency::minimum_balance() * 4u32.into(),
+			T::Currency::minimum_balance() * 4u32.into() + unclaimed,
 		);
-		let author = account("author", 0, SEED);
+		let author: T::AccountId = account("author", 0, SEED);
+		let era = 1;
+		<ActiveEra<T>>::put(era);
+		<ErasRewards<T>>::insert(era, &author, unclaimed);
+		<TotalUnclaimed<T>>::put(unclaimed);
 		let new_block: T::BlockNumber = 10u32.into();
 
 		frame_system::Pallet::<T>::set_block_number(new_block);
-		assert!(T::Currency::free_balance(&author) == 0u32.into());
 	}: {
 		<CollatorSelection<T> as EventHandler<_, _>>::note_author(author.clone())
 	} verify {
-		assert!(T::Currency::free_balance(&author) > 0u32.into());
-		assert_eq!(frame_system::Pallet::<T>::block_number(), new_block);
+		assert!(<ErasRewards<T>>::get(era, &author) > unclaimed);
+		assert!(<TotalUnclaimed<T>>::get() > unclaimed);
+		assert_eq!(<LastAuthoredBlock<T>>::get(&author), new_block);
 	}
 
-	// worse case is on new session.
-	// TODO review this benchmark
+	// worse case is on new session, with the delegations of the kicked candidates to release and
+	// those of the elected candidates to snapshot.
 	new_session {
 		let r in 1 .. T::MaxCandidates::get();
-		let c in 1 .. T::MaxCandidates::get();
+		let c in 2 .. T::MaxCandidates::get();
+		let d in 0 .. T::MaxDelegatorsPerCandidate::get();
+		let e in 0 .. T::MaxDelegatorsPerCandidate::get();
 
 		<CandidacyBond<T>>::put(T::Currency::minimum_balance());
 		<DesiredCandidates<T>>::put(c);
@@ -166,7 +315,8 @@ benchmarks! {
 		let zero_block: T::BlockNumber = 0u32.into();
 		let candidates = <Candidates<T>>::get();
 
-		let non_removals = c.saturating_sub(r);
+		// the first candidate is always elected.
+		let non_removals = c.saturating_sub(r).max(1);
 
 		for i in 0..c {
 			<LastAuthoredBlock<T>>::insert(candidates[i as usize].who.clone(), zero_block);
@@ -174,6 +324,10 @@ benchmarks! {
 		for i in 0..non_removals {
 			<LastAuthoredBlock<T>>::insert(candidates[i as usize].who.clone(), new_block);
 		}
+		let accounts = candidates.iter().map(|c| c.who.clone()).collect::<Vec<_>>();
+		let (elected, kicked) = accounts.split_at(non_removals as usize);
+		delegate_among::<T>(kicked, "kicked-delegator", d);
+		delegate_among::<T>(elected, "elected-delegator", e);
 
 		let pre_length = <Candidates<T>>::get().len();
 		frame_system::Pallet::<T>::set_block_number(new_block);
@@ -184,6 +338,12 @@ benchmarks! {
 		<CollatorSelection<T> as SessionManager<_>>::new_session(0)
 	} verify {
 		assert!(<Candidates<T>>::get().len() < pre_length);
+		for who in kicked {
+			assert_eq!(<DelegatorCount<T>>::get(who), 0);
+		}
+		for who in elected {
+			assert!(<ErasStakers<T>>::contains_key(0, who));
+		}
 	}
 }
 
@@ -28,6 +28,8 @@
 //! - Collator: A parachain block producer.
 //! - Bond: An amount of `Balance` _reserved_ for candidate registration.
 //! - Invulnerable: An account guaranteed to be in the collator set.
+//! - Delegator: A token holder backing a candidate with reserved funds.
+//! - Backing: A candidate's bond plus all delegations to it that passed the bond delay.
 //!
 //! ## Implementation
 //!
@@ -38,22 +40,42 @@
 //! 2. [`Candidates`]: these are *candidates to the collation task* and may or may not be elected as
 //!    a final collator.
 //!
-//! The current implementation resolves congestion of [`Candidates`] in a first-come-first-serve
-//! manner.
+//! Candidates can change their deposit with [`Pallet::update_bond`], as long as it stays at least
+//! [`CandidacyBond`]. Leaving happens in two phases: [`Pallet::leave_intent`] marks the candidate
+//! as leaving and starts unbonding its delegations, and at the next session change it is removed
+//! from [`Candidates`] and its deposit starts unbonding like undelegated funds.
+//!
+//! Up to [`Config::MaxCandidates`] accounts may register as candidates, but only the
+//! [`DesiredCandidates`] with the highest backing are elected at each session change. Candidates
+//! with equal backing keep their registration order.
+//!
+//! ### Delegation
+//!
+//! Any account can [`Pallet::delegate`] funds to a candidate. Delegated funds are reserved and only
+//! count towards the candidate's backing after [`Config::BondDelay`] blocks. Funds released through
+//! [`Pallet::undelegate`], or because the candidate left, stay reserved for [`Config::UnbondDelay`]
+//! blocks before they can be unreserved with [`Pallet::withdraw_unbonded`].
 //!
 //! ### Rewards
 //!
-//! The Collator Selection pallet maintains an on-chain account (the "Pot"). In each block, the
-//! collator who authored it receives:
+//! The Collator Selection pallet maintains an on-chain account (the "Pot"), which receives the
+//! runtime's fee share and [`Config::RewardPerBlock`] newly minted funds per authored block. In each
+//! block, half of the Pot's unallocated value is accrued to the author for the current era (one
+//! era per session).
 //!
-//! - Half the value of the Pot.
-//! - Half the value of the transaction fees within the block. The other half of the transaction
-//!   fees are deposited into the Pot.
+//! When an elected candidate's era starts, its own deposit, its matured delegations and its
+//! commission are snapshotted. After the era has ended, anyone can call [`Pallet::payout`] to
+//! distribute the accrued reward: the collator first takes its commission, and the remainder is
+//! split among the collator and its delegators pro rata to their snapshotted stake. Invulnerables
+//! have no stake and receive the whole reward. Rewards that are not paid out within
+//! [`Config::HistoryDepth`] eras are returned to the Pot.
 //!
 //! To initiate rewards an ED needs to be transferred to the pot address.
 //!
-//! Note: Eventually the Pot distribution may be modified as discussed in
-//! [this issue](https://github.com/paritytech/statemint/issues/21#issuecomment-810481073).
+//! ### Slashing
+//!
+//! Candidates kicked for not producing blocks lose [`Config::KickSlash`] of their deposit, which
+//! is handed to [`Config::Slash`].
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -77,6 +99,7 @@ pub mod pallet {
 		inherent::Vec,
 		traits::{
 			Currency, ReservableCurrency, EnsureOrigin, ExistenceRequirement::KeepAlive,
+			Imbalance, OnUnbalanced,
 		},
 		PalletId,
 	};
@@ -84,18 +107,22 @@ pub mod pallet {
 	use frame_system::Config as SystemConfig;
 	use frame_support::{
 		sp_runtime::{
-			RuntimeDebug,
-			traits::{AccountIdConversion, CheckedSub, Zero, Saturating},
+			Perbill, RuntimeDebug,
+			helpers_128bit::multiply_by_rational,
+			traits::{AccountIdConversion, CheckedSub, Zero, Saturating, SaturatedConversion},
 		},
 		weights::DispatchClass,
 	};
 	use core::ops::Div;
+	use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
 	use pallet_session::SessionManager;
 	use sp_staking::SessionIndex;
 	pub use crate::weights::WeightInfo;
 
 	type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as SystemConfig>::AccountId>>::Balance;
+	type NegativeImbalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as SystemConfig>::AccountId>>::NegativeImbalance;
 
 	/// A convertor from collators id. Since this pallet does not have stash/controller, this is
 	/// just identity.
@@ -121,8 +148,8 @@ pub mod pallet {
 		/// Account Identifier from which the internal Pot is generated.
 		type PotId: Get<PalletId>;
 
-		/// Maximum number of candidates that we should have. This is used for benchmarking and is not
-		/// enforced.
+		/// Maximum number of candidates that can register. Only the [`DesiredCandidates`] best
+		/// backed ones are elected.
 		///
 		/// This does not take into account the invulnerables.
 		type MaxCandidates: Get<u32>;
@@ -135,6 +162,33 @@ pub mod pallet {
 		// Will be kicked if block is not produced in threshold.
 		type KickThreshold: Get<Self::BlockNumber>;
 
+		/// Minimum amount a delegator must keep delegated to a candidate.
+		type MinDelegation: Get<BalanceOf<Self>>;
+
+		/// Maximum number of delegators a single candidate can have.
+		///
+		/// This bounds the delegations released by [`Pallet::leave_intent`] or for every kicked
+		/// candidate, and snapshotted for every elected candidate at a session change.
+		type MaxDelegatorsPerCandidate: Get<u32>;
+
+		/// Number of blocks before a new delegation counts towards the candidate's backing.
+		type BondDelay: Get<Self::BlockNumber>;
+
+		/// Number of blocks undelegated funds stay reserved before they can be withdrawn.
+		type UnbondDelay: Get<Self::BlockNumber>;
+
+		/// Amount minted into the pot for every authored block.
+		type RewardPerBlock: Get<BalanceOf<Self>>;
+
+		/// Number of eras for which rewards can still be paid out.
+		type HistoryDepth: Get<SessionIndex>;
+
+		/// Portion of the deposit slashed from candidates kicked for not producing blocks.
+		type KickSlash: Get<Perbill>;
+
+		/// Handler for the slashed deposits of kicked candidates.
+		type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
 		/// The weight information of this pallet.
 		type WeightInfo: WeightInfo;
 	}
@@ -148,6 +202,112 @@ pub mod pallet {
 		pub deposit: Balance,
 	}
 
+	/// Maximum number of unbonding chunks a delegator can have at once.
+	pub const MAX_UNBONDING_CHUNKS: usize = 32;
+
+	/// Maximum number of pending chunks a single delegation can have at once.
+	pub const MAX_PENDING_CHUNKS: usize = 32;
+
+	/// Delegated funds that count towards the candidate's backing from `matures_at` on.
+	#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+	pub struct PendingChunk<Balance, BlockNumber> {
+		/// Amount of delegated funds.
+		pub value: Balance,
+		/// Block from which the funds count towards the candidate's backing.
+		pub matures_at: BlockNumber,
+	}
+
+	/// Funds delegated by a single delegator to a single candidate.
+	#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug)]
+	pub struct Delegation<Balance, BlockNumber> {
+		/// Amount counting towards the candidate's backing.
+		pub active: Balance,
+		/// Amounts still waiting for the bond delay, oldest first. Every top-up gets its own chunk,
+		/// so it does not delay the funds delegated before it.
+		pub pending: Vec<PendingChunk<Balance, BlockNumber>>,
+	}
+
+	impl<Balance, BlockNumber> Delegation<Balance, BlockNumber> where
+		Balance: Saturating + Zero + Copy + Ord,
+		BlockNumber: PartialOrd,
+	{
+		/// Total amount reserved for this delegation.
+		pub fn total(&self) -> Balance {
+			self.pending.iter().fold(self.active, |acc, chunk| acc.saturating_add(chunk.value))
+		}
+
+		/// Amount counting towards the candidate's backing at block `now`.
+		pub fn matured(&self, now: &BlockNumber) -> Balance {
+			self.pending.iter()
+				.filter(|chunk| chunk.matures_at <= *now)
+				.fold(self.active, |acc, chunk| acc.saturating_add(chunk.value))
+		}
+
+		/// Move the pending chunks whose bond delay has passed at block `now` into `active`.
+		fn settle(&mut self, now: &BlockNumber) {
+			let active = &mut self.active;
+			self.pending.retain(|chunk| if chunk.matures_at <= *now {
+				*active = active.saturating_add(chunk.value);
+				false
+			} else {
+				true
+			});
+		}
+
+		/// Add `value` to the funds counting towards the candidate's backing from `matures_at` on.
+		///
+		/// Returns `false` if the delegation already has [`MAX_PENDING_CHUNKS`] pending chunks.
+		fn add_pending(&mut self, value: Balance, matures_at: BlockNumber) -> bool {
+			if let Some(last) = self.pending.last_mut().filter(|last| last.matures_at == matures_at) {
+				last.value = last.value.saturating_add(value);
+			} else if self.pending.len() < MAX_PENDING_CHUNKS {
+				self.pending.push(PendingChunk { value, matures_at });
+			} else {
+				return false
+			}
+			true
+		}
+
+		/// Remove `amount` from the delegation. Funds that are not backing the candidate yet are
+		/// released first, latest first.
+		fn release(&mut self, mut amount: Balance) {
+			while let Some(last) = self.pending.last_mut() {
+				if amount.is_zero() {
+					break
+				}
+				let taken = amount.min(last.value);
+				last.value = last.value.saturating_sub(taken);
+				amount = amount.saturating_sub(taken);
+				if last.value.is_zero() {
+					self.pending.pop();
+				}
+			}
+			self.active = self.active.saturating_sub(amount);
+		}
+	}
+
+	/// The stake backing a collator during an era.
+	#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug)]
+	pub struct Exposure<AccountId, Balance> {
+		/// Total stake, own deposit included.
+		pub total: Balance,
+		/// The collator's own deposit.
+		pub own: Balance,
+		/// Matured delegations to the collator.
+		pub others: Vec<(AccountId, Balance)>,
+		/// The collator's commission.
+		pub commission: Perbill,
+	}
+
+	/// Undelegated funds that can be withdrawn from `unlock_at` on.
+	#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+	pub struct UnbondingChunk<Balance, BlockNumber> {
+		/// Amount of funds to be unreserved.
+		pub value: Balance,
+		/// Block from which the funds can be withdrawn.
+		pub unlock_at: BlockNumber,
+	}
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
@@ -183,6 +343,79 @@ pub mod pallet {
 	#[pallet::getter(fn candidacy_bond)]
 	pub type CandidacyBond<T> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+	/// Delegations to candidates, keyed by candidate and then by delegator.
+	#[pallet::storage]
+	#[pallet::getter(fn delegations)]
+	pub type Delegations<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::AccountId,
+		Delegation<BalanceOf<T>, T::BlockNumber>,
+	>;
+
+	/// Number of delegators of each candidate.
+	#[pallet::storage]
+	#[pallet::getter(fn delegator_count)]
+	pub type DelegatorCount<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Candidates that will be removed at the next session change, with the block they asked to
+	/// leave at.
+	#[pallet::storage]
+	#[pallet::getter(fn leaving_candidates)]
+	pub type LeavingCandidates<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::BlockNumber>;
+
+	/// Commission each candidate takes from its rewards before sharing them with delegators.
+	#[pallet::storage]
+	#[pallet::getter(fn commission)]
+	pub type Commission<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, Perbill, ValueQuery>;
+
+	/// The era (session) whose collators are currently authoring.
+	#[pallet::storage]
+	#[pallet::getter(fn active_era)]
+	pub type ActiveEra<T> = StorageValue<_, SessionIndex, ValueQuery>;
+
+	/// Stake of the elected candidates, keyed by era and collator.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_stakers)]
+	pub type ErasStakers<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Twox64Concat,
+		T::AccountId,
+		Exposure<T::AccountId, BalanceOf<T>>,
+	>;
+
+	/// Rewards accrued by collators and not yet paid out, keyed by era and collator.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_rewards)]
+	pub type ErasRewards<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Sum of all [`ErasRewards`]. This part of the pot is not available for new rewards.
+	#[pallet::storage]
+	#[pallet::getter(fn total_unclaimed)]
+	pub type TotalUnclaimed<T> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// Undelegated funds of each delegator, waiting for the unbond delay.
+	#[pallet::storage]
+	#[pallet::getter(fn unbonding)]
+	pub type Unbonding<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Vec<UnbondingChunk<BalanceOf<T>, T::BlockNumber>>,
+		ValueQuery,
+	>;
 
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
@@ -233,6 +466,22 @@ pub mod pallet {
 		NewCandidacyBond(BalanceOf<T>),
 		CandidateAdded(T::AccountId, BalanceOf<T>),
 		CandidateRemoved(T::AccountId),
+		/// A candidate changed its deposit. \[candidate, deposit\]
+		CandidateBondUpdated(T::AccountId, BalanceOf<T>),
+		/// A candidate will be removed at the next session change. \[candidate\]
+		CandidateLeaving(T::AccountId),
+		/// A delegator delegated funds to a candidate. \[delegator, candidate, amount\]
+		Delegated(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// A delegator started unbonding funds from a candidate. \[delegator, candidate, amount\]
+		Undelegated(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// A delegator withdrew unbonded funds. \[delegator, amount\]
+		Withdrawn(T::AccountId, BalanceOf<T>),
+		/// A candidate set its commission. \[candidate, commission\]
+		CommissionSet(T::AccountId, Perbill),
+		/// A collator or delegator was paid a reward. \[era, who, amount\]
+		Rewarded(SessionIndex, T::AccountId, BalanceOf<T>),
+		/// A kicked candidate was slashed. \[candidate, amount\]
+		CandidateSlashed(T::AccountId, BalanceOf<T>),
 	}
 
 	// Errors inform users that something went wrong.
@@ -245,6 +494,30 @@ pub mod pallet {
 		NotCandidate,
 		AlreadyInvulnerable,
 		InvalidProof,
+		/// The candidate already has the maximum number of delegators.
+		TooManyDelegators,
+		/// The delegation would be below [`Config::MinDelegation`].
+		DelegationBelowMinimum,
+		/// The account does not delegate to the candidate.
+		NotDelegator,
+		/// The delegation is smaller than the requested amount.
+		InsufficientDelegation,
+		/// The delegator has too many unbonding chunks; withdraw some first.
+		TooManyUnbondingChunks,
+		/// The delegation has too many amounts waiting for the bond delay; wait for some to mature.
+		TooManyPendingChunks,
+		/// There are no unbonded funds to withdraw.
+		NothingToWithdraw,
+		/// The era has not ended yet.
+		EraNotFinished,
+		/// The era is older than [`Config::HistoryDepth`].
+		EraTooOld,
+		/// The collator has no unpaid rewards for the era.
+		NothingToPayout,
+		/// The deposit would be below [`CandidacyBond`].
+		DepositTooLow,
+		/// The candidate is already leaving.
+		AlreadyLeaving,
 	}
 
 	#[pallet::hooks]
@@ -297,7 +570,7 @@ pub mod pallet {
 
 			// ensure we are below limit.
 			let length = <Candidates<T>>::decode_len().unwrap_or_default();
-			ensure!((length as u32) < Self::desired_candidates(), Error::<T>::TooManyCandidates);
+			ensure!((length as u32) < T::MaxCandidates::get(), Error::<T>::TooManyCandidates);
 			ensure!(!Self::invulnerables().contains(&who), Error::<T>::AlreadyInvulnerable);
 
 			let deposit = Self::candidacy_bond();
@@ -320,13 +593,215 @@ pub mod pallet {
 			Ok(Some(T::WeightInfo::register_as_candidate(current_count as u32)).into())
 		}
 
-		#[pallet::weight(T::WeightInfo::leave_intent(T::MaxCandidates::get()))]
+		/// Announce that the caller stops being a candidate.
+		///
+		/// All delegations to the caller start unbonding for [`Config::UnbondDelay`] blocks right
+		/// away. The caller is removed at the next session change, after which its deposit unbonds
+		/// as well.
+		#[pallet::weight(T::WeightInfo::leave_intent(
+			T::MaxCandidates::get(),
+			T::MaxDelegatorsPerCandidate::get(),
+		))]
 		pub fn leave_intent(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 
-			let current_count = Self::try_remove_candidate(&who)?;
+			let candidates = Self::candidates();
+			ensure!(candidates.iter().any(|c| c.who == who), Error::<T>::NotCandidate);
+			ensure!(!<LeavingCandidates<T>>::contains_key(&who), Error::<T>::AlreadyLeaving);
+
+			<LeavingCandidates<T>>::insert(&who, frame_system::Pallet::<T>::block_number());
+			// the candidate is not elected again, so its delegations are released now and paid for
+			// by the caller instead of at the session change. The current era's exposure was
+			// already snapshotted, so the delegators still get their rewards.
+			let released = Self::release_delegations(&who);
+			Self::deposit_event(Event::CandidateLeaving(who));
+			Ok(Some(T::WeightInfo::leave_intent(candidates.len() as u32, released)).into())
+		}
+
+		/// Change the caller's candidacy deposit to `deposit`.
+		///
+		/// An increase is reserved immediately. A decrease stops counting towards the caller's
+		/// backing immediately and can be withdrawn after [`Config::UnbondDelay`] blocks.
+		#[pallet::weight(T::WeightInfo::update_bond(T::MaxCandidates::get()))]
+		pub fn update_bond(origin: OriginFor<T>, deposit: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(deposit >= Self::candidacy_bond(), Error::<T>::DepositTooLow);
+			ensure!(!<LeavingCandidates<T>>::contains_key(&who), Error::<T>::AlreadyLeaving);
+
+			let current_count = <Candidates<T>>::try_mutate(|candidates| -> Result<usize, DispatchError> {
+				let candidate = candidates.iter_mut()
+					.find(|candidate| candidate.who == who)
+					.ok_or(Error::<T>::NotCandidate)?;
+				if deposit > candidate.deposit {
+					T::Currency::reserve(&who, deposit - candidate.deposit)?;
+				} else if deposit < candidate.deposit {
+					Self::schedule_unbond(&who, candidate.deposit - deposit, false)?;
+				}
+				candidate.deposit = deposit;
+				Ok(candidates.len())
+			})?;
 
-			Ok(Some(T::WeightInfo::leave_intent(current_count as u32)).into())
+			Self::deposit_event(Event::CandidateBondUpdated(who, deposit));
+			Ok(Some(T::WeightInfo::update_bond(current_count as u32)).into())
+		}
+
+		/// Delegate `amount` to `candidate`.
+		///
+		/// The funds are reserved immediately but only count towards the candidate's backing after
+		/// [`Config::BondDelay`] blocks.
+		#[pallet::weight(T::WeightInfo::delegate())]
+		pub fn delegate(
+			origin: OriginFor<T>,
+			candidate: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				Self::candidates().iter().any(|c| c.who == candidate),
+				Error::<T>::NotCandidate,
+			);
+			ensure!(!<LeavingCandidates<T>>::contains_key(&candidate), Error::<T>::AlreadyLeaving);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let existing = <Delegations<T>>::get(&candidate, &who);
+			if existing.is_none() {
+				ensure!(
+					Self::delegator_count(&candidate) < T::MaxDelegatorsPerCandidate::get(),
+					Error::<T>::TooManyDelegators,
+				);
+			}
+			let mut delegation = existing.clone().unwrap_or_default();
+			delegation.settle(&now);
+			ensure!(
+				delegation.total().saturating_add(amount) >= T::MinDelegation::get(),
+				Error::<T>::DelegationBelowMinimum,
+			);
+
+			ensure!(
+				delegation.add_pending(amount, now + T::BondDelay::get()),
+				Error::<T>::TooManyPendingChunks,
+			);
+
+			T::Currency::reserve(&who, amount)?;
+			<Delegations<T>>::insert(&candidate, &who, delegation);
+			if existing.is_none() {
+				<DelegatorCount<T>>::mutate(&candidate, |count| *count += 1);
+			}
+
+			Self::deposit_event(Event::Delegated(who, candidate, amount));
+			Ok(().into())
+		}
+
+		/// Stop delegating `amount` to `candidate`.
+		///
+		/// The funds stop counting towards the candidate's backing immediately and can be withdrawn
+		/// after [`Config::UnbondDelay`] blocks.
+		#[pallet::weight(T::WeightInfo::undelegate())]
+		pub fn undelegate(
+			origin: OriginFor<T>,
+			candidate: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let mut delegation = <Delegations<T>>::get(&candidate, &who).ok_or(Error::<T>::NotDelegator)?;
+			delegation.settle(&now);
+			let remaining = delegation.total()
+				.checked_sub(&amount)
+				.ok_or(Error::<T>::InsufficientDelegation)?;
+			ensure!(
+				remaining.is_zero() || remaining >= T::MinDelegation::get(),
+				Error::<T>::DelegationBelowMinimum,
+			);
+
+			Self::schedule_unbond(&who, amount, false)?;
+			delegation.release(amount);
+			if remaining.is_zero() {
+				<Delegations<T>>::remove(&candidate, &who);
+				<DelegatorCount<T>>::mutate(&candidate, |count| *count = count.saturating_sub(1));
+			} else {
+				<Delegations<T>>::insert(&candidate, &who, delegation);
+			}
+
+			Self::deposit_event(Event::Undelegated(who, candidate, amount));
+			Ok(().into())
+		}
+
+		/// Unreserve all undelegated funds of the caller whose unbond delay has passed.
+		#[pallet::weight(T::WeightInfo::withdraw_unbonded())]
+		pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let mut chunks = Self::unbonding(&who);
+			let mut withdrawn: BalanceOf<T> = Zero::zero();
+			chunks.retain(|chunk| if chunk.unlock_at <= now {
+				withdrawn = withdrawn.saturating_add(chunk.value);
+				false
+			} else {
+				true
+			});
+			ensure!(!withdrawn.is_zero(), Error::<T>::NothingToWithdraw);
+
+			T::Currency::unreserve(&who, withdrawn);
+			if chunks.is_empty() {
+				<Unbonding<T>>::remove(&who);
+			} else {
+				<Unbonding<T>>::insert(&who, chunks);
+			}
+
+			Self::deposit_event(Event::Withdrawn(who, withdrawn));
+			Ok(().into())
+		}
+
+		/// Set the commission the caller takes from its rewards before sharing them with its
+		/// delegators.
+		///
+		/// Takes effect from the next era the caller is elected in.
+		#[pallet::weight(T::WeightInfo::set_commission())]
+		pub fn set_commission(origin: OriginFor<T>, commission: Perbill) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::candidates().iter().any(|c| c.who == who), Error::<T>::NotCandidate);
+
+			<Commission<T>>::insert(&who, commission);
+			Self::deposit_event(Event::CommissionSet(who, commission));
+			Ok(().into())
+		}
+
+		/// Pay out the rewards `collator` accrued in `era` to itself and its delegators.
+		///
+		/// Any account can call this for any finished era within [`Config::HistoryDepth`].
+		#[pallet::weight(T::WeightInfo::payout(T::MaxDelegatorsPerCandidate::get()))]
+		pub fn payout(
+			origin: OriginFor<T>,
+			era: SessionIndex,
+			collator: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+
+			let active_era = Self::active_era();
+			ensure!(era < active_era, Error::<T>::EraNotFinished);
+			ensure!(era.saturating_add(T::HistoryDepth::get()) >= active_era, Error::<T>::EraTooOld);
+			let reward = <ErasRewards<T>>::take(era, &collator);
+			ensure!(!reward.is_zero(), Error::<T>::NothingToPayout);
+			<TotalUnclaimed<T>>::mutate(|total| *total = total.saturating_sub(reward));
+
+			let exposure = Self::eras_stakers(era, &collator).unwrap_or_default();
+			let delegators = exposure.others.len() as u32;
+			if exposure.total.is_zero() {
+				Self::pay_reward(era, &collator, reward);
+			} else {
+				let commission = exposure.commission * reward;
+				let shared = reward.saturating_sub(commission);
+				let own_share = Self::share_of(shared, exposure.own, exposure.total);
+				Self::pay_reward(era, &collator, commission.saturating_add(own_share));
+				for (delegator, stake) in exposure.others {
+					Self::pay_reward(era, &delegator, Self::share_of(shared, stake, exposure.total));
+				}
+			}
+
+			Ok(Some(T::WeightInfo::payout(delegators)).into())
 		}
 	}
 
@@ -335,48 +810,227 @@ pub mod pallet {
 		pub fn account_id() -> T::AccountId {
 			T::PotId::get().into_account()
 		}
-		/// Removes a candidate if they exist and sends them back their deposit
-		fn try_remove_candidate(who: &T::AccountId) -> Result<usize, DispatchError> {
-			let current_count = <Candidates<T>>::try_mutate(|candidates| -> Result<usize, DispatchError> {
+		/// Removes a candidate if they exist and sends them back their deposit, either immediately
+		/// or, if `unbond` is set, after the unbond delay.
+		///
+		/// Returns the number of released delegations.
+		fn try_remove_candidate(who: &T::AccountId, unbond: bool) -> Result<u32, DispatchError> {
+			let released = <Candidates<T>>::try_mutate(|candidates| -> Result<u32, DispatchError> {
 				let index = candidates.iter().position(|candidate| candidate.who == *who).ok_or(Error::<T>::NotCandidate)?;
-				T::Currency::unreserve(&who, candidates[index].deposit);
+				if unbond {
+					let _success = Self::schedule_unbond(who, candidates[index].deposit, true);
+					debug_assert!(_success.is_ok());
+				} else {
+					T::Currency::unreserve(&who, candidates[index].deposit);
+				}
 				candidates.remove(index);
+				<LeavingCandidates<T>>::remove(who);
 				<LastAuthoredBlock<T>>::remove(who.clone());
-				Ok(candidates.len())
+				<Commission<T>>::remove(who);
+				Ok(Self::release_delegations(who))
 			});
 			Self::deposit_event(Event::CandidateRemoved(who.clone()));
-			current_count
+			released
+		}
+
+		/// `part / total` of `amount`, rounded down.
+		fn share_of(amount: BalanceOf<T>, part: BalanceOf<T>, total: BalanceOf<T>) -> BalanceOf<T> {
+			multiply_by_rational(
+				amount.saturated_into(),
+				part.saturated_into(),
+				total.saturated_into(),
+			).map(|share| share.saturated_into()).unwrap_or_else(|_| Zero::zero())
+		}
+
+		/// Transfer `amount` out of the pot to `who`.
+		fn pay_reward(era: SessionIndex, who: &T::AccountId, amount: BalanceOf<T>) {
+			if amount.is_zero() {
+				return
+			}
+			match T::Currency::transfer(&Self::account_id(), who, amount, KeepAlive) {
+				Ok(()) => Self::deposit_event(Event::Rewarded(era, who.clone(), amount)),
+				Err(why) => log::warn!("Failed to pay reward to {:?}: {:?}", who, why),
+			}
+		}
+
+		/// Snapshot the stake of the elected candidates among `collators` for `era`.
+		///
+		/// Returns the number of read delegations, which is at most
+		/// [`Config::MaxDelegatorsPerCandidate`] per elected candidate.
+		fn store_exposures(era: SessionIndex, collators: &[T::AccountId]) -> u32 {
+			let now = frame_system::Pallet::<T>::block_number();
+			let elected = collators.iter().collect::<BTreeSet<_>>();
+			let mut read = 0;
+			for candidate in Self::candidates().into_iter().filter(|c| elected.contains(&c.who)) {
+				let others = <Delegations<T>>::iter_prefix(&candidate.who)
+					.inspect(|_| read += 1)
+					.map(|(delegator, delegation)| (delegator, delegation.matured(&now)))
+					.filter(|(_, stake)| !stake.is_zero())
+					.collect::<Vec<_>>();
+				let total = others.iter().fold(candidate.deposit, |acc, (_, stake)| acc.saturating_add(*stake));
+				let exposure = Exposure {
+					total,
+					own: candidate.deposit,
+					others,
+					commission: Self::commission(&candidate.who),
+				};
+				<ErasStakers<T>>::insert(era, &candidate.who, exposure);
+			}
+			read
+		}
+
+		/// Remove the stakers of `era` and return its unclaimed rewards to the pot.
+		fn prune_era(era: SessionIndex) {
+			<ErasStakers<T>>::drain_prefix(era).for_each(drop);
+			let forfeited = <ErasRewards<T>>::drain_prefix(era)
+				.fold(Zero::zero(), |acc: BalanceOf<T>, (_, reward)| acc.saturating_add(reward));
+			<TotalUnclaimed<T>>::mutate(|total| *total = total.saturating_sub(forfeited));
+		}
+
+		/// Slash [`Config::KickSlash`] of a candidate's deposit.
+		fn slash_candidate(who: &T::AccountId) {
+			<Candidates<T>>::mutate(|candidates| {
+				if let Some(candidate) = candidates.iter_mut().find(|c| c.who == *who) {
+					let (imbalance, _) = T::Currency::slash_reserved(who, T::KickSlash::get() * candidate.deposit);
+					let slashed = imbalance.peek();
+					T::Slash::on_unbalanced(imbalance);
+					// only the remainder is unreserved when the candidate is removed.
+					candidate.deposit = candidate.deposit.saturating_sub(slashed);
+					if !slashed.is_zero() {
+						Self::deposit_event(Event::CandidateSlashed(who.clone(), slashed));
+					}
+				}
+			});
+		}
+
+		/// Start unbonding all delegations to a candidate that is no longer registered.
+		///
+		/// Returns the number of released delegations, which is at most
+		/// [`Config::MaxDelegatorsPerCandidate`].
+		fn release_delegations(candidate: &T::AccountId) -> u32 {
+			let mut released = 0;
+			for (delegator, delegation) in <Delegations<T>>::drain_prefix(candidate) {
+				let _success = Self::schedule_unbond(&delegator, delegation.total(), true);
+				debug_assert!(_success.is_ok());
+				released += 1;
+			}
+			<DelegatorCount<T>>::remove(candidate);
+			released
+		}
+
+		/// Schedule `value` of `who`'s reserved funds to be withdrawable after the unbond delay.
+		///
+		/// If `who` already has [`MAX_UNBONDING_CHUNKS`] chunks this fails, unless `force` is set, in
+		/// which case the funds are merged into the latest chunk.
+		fn schedule_unbond(who: &T::AccountId, value: BalanceOf<T>, force: bool) -> DispatchResult {
+			let unlock_at = frame_system::Pallet::<T>::block_number() + T::UnbondDelay::get();
+			<Unbonding<T>>::try_mutate(who, |chunks| {
+				if chunks.len() < MAX_UNBONDING_CHUNKS {
+					chunks.push(UnbondingChunk { value, unlock_at });
+				} else if let (true, Some(last)) = (force, chunks.last_mut()) {
+					// no chunk unlocks later than `unlock_at`, so nothing is released earlier.
+					last.value = last.value.saturating_add(value);
+					last.unlock_at = unlock_at;
+				} else {
+					Err(Error::<T>::TooManyUnbondingChunks)?
+				}
+				Ok(())
+			})
+		}
+
+		/// The total backing of a candidate: its deposit plus all matured delegations.
+		pub fn total_backing(who: &T::AccountId) -> BalanceOf<T> {
+			let deposit = Self::candidates()
+				.into_iter()
+				.find(|candidate| candidate.who == *who)
+				.map(|candidate| candidate.deposit)
+				.unwrap_or_else(Zero::zero);
+			deposit.saturating_add(Self::delegated_backing(who))
+		}
+
+		/// Sum of all delegations to `who` that passed the bond delay.
+		fn delegated_backing(who: &T::AccountId) -> BalanceOf<T> {
+			let now = frame_system::Pallet::<T>::block_number();
+			<Delegations<T>>::iter_prefix_values(who)
+				.fold(Zero::zero(), |acc: BalanceOf<T>, delegation| acc.saturating_add(delegation.matured(&now)))
 		}
 
 		/// Assemble the current set of candidates and invulnerables into the next collator set.
 		///
-		/// This is done on the fly, as frequent as we are told to do so, as the session manager.
+		/// Candidates are ordered by their total backing and only the first [`DesiredCandidates`]
+		/// are elected. This is done on the fly, as frequent as we are told to do so, as the session
+		/// manager.
 		pub fn assemble_collators(candidates: Vec<T::AccountId>) -> Vec<T::AccountId> {
+			let deposits = Self::candidates()
+				.into_iter()
+				.map(|candidate| (candidate.who, candidate.deposit))
+				.collect::<BTreeMap<_, _>>();
+			let mut backed = candidates.into_iter().map(|who| {
+				let deposit = deposits.get(&who).cloned().unwrap_or_else(Zero::zero);
+				let backing = deposit.saturating_add(Self::delegated_backing(&who));
+				(who, backing)
+			}).collect::<Vec<_>>();
+			// stable sort, ties are resolved by registration order.
+			backed.sort_by(|a, b| b.1.cmp(&a.1));
+
+			let desired = Self::desired_candidates() as usize;
+			let grace = frame_system::Pallet::<T>::block_number() + T::KickThreshold::get();
 			let mut collators = Self::invulnerables();
-			collators.extend(
-				candidates.into_iter().collect::<Vec<_>>(),
-			);
+			for (index, (who, _)) in backed.into_iter().enumerate() {
+				if index < desired {
+					collators.push(who);
+				} else {
+					// candidates that are not elected cannot author, so they must not be kicked for it.
+					<LastAuthoredBlock<T>>::insert(who, grace);
+				}
+			}
 			collators
 		}
+		/// Removes all candidates that announced to leave, their deposits start unbonding.
+		///
+		/// Returns the number of released delegations.
+		pub fn remove_leaving_candidates() -> u32 {
+			let leaving = <LeavingCandidates<T>>::iter().map(|(who, _)| who).collect::<Vec<_>>();
+			let mut released = 0;
+			for who in leaving {
+				match Self::try_remove_candidate(&who, true) {
+					Ok(count) => released += count,
+					Err(why) => {
+						log::warn!("Failed to remove leaving candidate {:?}", why);
+						debug_assert!(false, "failed to remove leaving candidate {:?}", why);
+					}
+				}
+			}
+			released
+		}
+
 		/// Kicks out and candidates that did not produce a block in the kick threshold.
-		pub fn kick_stale_candidates(candidates: Vec<CandidateInfo<T::AccountId, BalanceOf<T>>>) -> Vec<T::AccountId> {
+		///
+		/// Returns the remaining candidates and the number of released delegations.
+		pub fn kick_stale_candidates(
+			candidates: Vec<CandidateInfo<T::AccountId, BalanceOf<T>>>,
+		) -> (Vec<T::AccountId>, u32) {
 			let now = frame_system::Pallet::<T>::block_number();
 			let kick_threshold = T::KickThreshold::get();
+			let mut released = 0;
 			let new_candidates = candidates.into_iter().filter_map(|c| {
 				let last_block = <LastAuthoredBlock<T>>::get(c.who.clone());
 				let since_last = now.saturating_sub(last_block);
 				if since_last < kick_threshold {
 					Some(c.who)
 				} else {
-					let outcome = Self::try_remove_candidate(&c.who);
-					if let Err(why) = outcome {
-						log::warn!("Failed to remove candidate {:?}", why);
-						debug_assert!(false, "failed to remove candidate {:?}", why);
+					Self::slash_candidate(&c.who);
+					match Self::try_remove_candidate(&c.who, false) {
+						Ok(count) => released += count,
+						Err(why) => {
+							log::warn!("Failed to remove candidate {:?}", why);
+							debug_assert!(false, "failed to remove candidate {:?}", why);
+						}
 					}
 					None
 				}
 			}).collect::<Vec<_>>();
-			new_candidates
+			(new_candidates, released)
 		}
 	}
 
@@ -387,11 +1041,17 @@ pub mod pallet {
 	{
 		fn note_author(author: T::AccountId) {
 			let pot = Self::account_id();
-			// assumes an ED will be sent to pot.
-			let reward = T::Currency::free_balance(&pot).checked_sub(&T::Currency::minimum_balance()).unwrap_or_else(Zero::zero).div(2u32.into());
-			// `reward` is half of pot account minus ED, this should never fail.
-			let _success = T::Currency::transfer(&pot, &author, reward, KeepAlive);
-			debug_assert!(_success.is_ok());
+			let minted = T::RewardPerBlock::get();
+			if !minted.is_zero() {
+				drop(T::Currency::deposit_creating(&pot, minted));
+			}
+			// assumes an ED will be sent to pot. Rewards accrued but not paid out yet are kept, too.
+			let allocated = T::Currency::minimum_balance().saturating_add(Self::total_unclaimed());
+			let reward = T::Currency::free_balance(&pot).checked_sub(&allocated).unwrap_or_else(Zero::zero).div(2u32.into());
+			if !reward.is_zero() {
+				<ErasRewards<T>>::mutate(Self::active_era(), &author, |accrued| *accrued = accrued.saturating_add(reward));
+				<TotalUnclaimed<T>>::mutate(|total| *total = total.saturating_add(reward));
+			}
 			<LastAuthoredBlock<T>>::insert(author, frame_system::Pallet::<T>::block_number());
 
 			frame_system::Pallet::<T>::register_extra_weight_unchecked(
@@ -414,21 +1074,33 @@ pub mod pallet {
 				<frame_system::Pallet<T>>::block_number(),
 			);
 
-			let candidates = Self::candidates();
-			let candidates_len_before = candidates.len();
-			let active_candidates = Self::kick_stale_candidates(candidates);
+			let candidates_len_before = <Candidates<T>>::decode_len().unwrap_or_default();
+			let released_leaving = Self::remove_leaving_candidates();
+			let (active_candidates, released_kicked) = Self::kick_stale_candidates(Self::candidates());
 			let active_candidates_len = active_candidates.len();
 			let result = Self::assemble_collators(active_candidates);
 			let removed = candidates_len_before - active_candidates_len;
+			let snapshotted = Self::store_exposures(index, &result);
 
+			// releasing the delegations of removed candidates and snapshotting those of elected
+			// candidates is bounded by `MaxDelegatorsPerCandidate` per candidate and charged per
+			// delegation.
 			frame_system::Pallet::<T>::register_extra_weight_unchecked(
-				T::WeightInfo::new_session(candidates_len_before as u32, removed as u32),
+				T::WeightInfo::new_session(
+					removed as u32,
+					candidates_len_before as u32,
+					released_leaving + released_kicked,
+					snapshotted,
+				),
 				DispatchClass::Mandatory,
 			);
 			Some(result)
 		}
-		fn start_session(_: SessionIndex) {
-			// we don't care.
+		fn start_session(index: SessionIndex) {
+			<ActiveEra<T>>::put(index);
+			if let Some(expired) = index.checked_sub(T::HistoryDepth::get() + 1) {
+				Self::prune_era(expired);
+			}
 		}
 		fn end_session(_: SessionIndex) {
 			// we don't care.
@@ -22,7 +22,7 @@ use frame_support::{
 	PalletId
 };
 use sp_runtime::{
-	RuntimeAppPublic,
+	Perbill, RuntimeAppPublic,
 	traits::{BlakeTwo256, IdentityLookup, OpaqueKeys},
 	testing::{Header, UintAuthorityId},
 };
@@ -186,8 +186,15 @@ ord_parameter_types! {
 
 parameter_types! {
 	pub const PotId: PalletId = PalletId(*b"PotStake");
-	pub const MaxCandidates: u32 = 20;
+	pub static MaxCandidates: u32 = 20;
 	pub const MaxInvulnerables: u32 = 20;
+	pub const MinDelegation: u64 = 5;
+	pub const MaxDelegatorsPerCandidate: u32 = 3;
+	pub const BondDelay: u64 = 5;
+	pub const UnbondDelay: u64 = 20;
+	pub static RewardPerBlock: u64 = 0;
+	pub const HistoryDepth: u32 = 2;
+	pub static KickSlash: Perbill = Perbill::zero();
 }
 
 impl Config for Test {
@@ -198,6 +205,14 @@ impl Config for Test {
 	type MaxCandidates = MaxCandidates;
 	type MaxInvulnerables = MaxInvulnerables;
 	type KickThreshold = Period;
+	type MinDelegation = MinDelegation;
+	type MaxDelegatorsPerCandidate = MaxDelegatorsPerCandidate;
+	type BondDelay = BondDelay;
+	type UnbondDelay = UnbondDelay;
+	type RewardPerBlock = RewardPerBlock;
+	type HistoryDepth = HistoryDepth;
+	type KickSlash = KickSlash;
+	type Slash = ();
 	type WeightInfo = ();
 }
 
@@ -15,13 +15,15 @@
 
 
 use crate as collator_selection;
-use crate::{mock::*, Error, CandidateInfo};
+use crate::{mock::*, Error, CandidateInfo, Delegation, Exposure, PendingChunk, UnbondingChunk};
 use frame_support::{
 	assert_noop, assert_ok,
 	traits::{OnInitialize, Currency, GenesisBuild},
 };
-use sp_runtime::traits::BadOrigin;
+use sp_runtime::{Perbill, traits::BadOrigin};
 use pallet_balances::Error as BalancesError;
+use pallet_authorship::EventHandler;
+use pallet_session::SessionManager;
 
 #[test]
 fn basic_setup_works() {
@@ -85,8 +87,8 @@ fn set_candidacy_bond() {
 #[test]
 fn cannot_register_candidate_if_too_many() {
 	new_test_ext().execute_with(|| {
-		// reset desired candidates:
-		<crate::DesiredCandidates<Test>>::put(0);
+		// reset max candidates:
+		MaxCandidates::set(0);
 
 		// can't accept anyone anymore.
 		assert_noop!(
@@ -94,8 +96,8 @@ fn cannot_register_candidate_if_too_many() {
 			Error::<Test>::TooManyCandidates,
 		);
 
-		// reset desired candidates:
-		<crate::DesiredCandidates<Test>>::put(1);
+		// reset max candidates:
+		MaxCandidates::set(1);
 		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(4)));
 
 		// but no more
@@ -190,10 +192,91 @@ fn leave_intent() {
 			Error::<Test>::NotCandidate
 		);
 
-		// bond is returned
+		// still a candidate until the session changes.
 		assert_ok!(CollatorSelection::leave_intent(Origin::signed(3)));
-		assert_eq!(Balances::free_balance(3), 100);
+		assert_eq!(CollatorSelection::leaving_candidates(3), Some(0));
+		assert_eq!(CollatorSelection::candidates().len(), 1);
+		assert_eq!(Balances::free_balance(3), 90);
+
+		// cannot leave twice.
+		assert_noop!(
+			CollatorSelection::leave_intent(Origin::signed(3)),
+			Error::<Test>::AlreadyLeaving
+		);
+
+		initialize_to_block(10);
+		assert!(CollatorSelection::candidates().is_empty());
+		assert_eq!(CollatorSelection::leaving_candidates(3), None);
 		assert_eq!(CollatorSelection::last_authored_block(3), 0);
+		// 3 is not part of the next set.
+		assert_eq!(Session::queued_keys().len(), 2);
+
+		// bond unbonds.
+		assert_eq!(CollatorSelection::unbonding(3), vec![UnbondingChunk { value: 10, unlock_at: 30 }]);
+		assert_eq!(Balances::free_balance(3), 90);
+
+		// and is returned afterwards.
+		initialize_to_block(30);
+		assert_ok!(CollatorSelection::withdraw_unbonded(Origin::signed(3)));
+		assert_eq!(Balances::free_balance(3), 100);
+	});
+}
+
+#[test]
+fn update_bond_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			CollatorSelection::update_bond(Origin::signed(3), 20),
+			Error::<Test>::NotCandidate
+		);
+
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+
+		// cannot go below the candidacy bond.
+		assert_noop!(
+			CollatorSelection::update_bond(Origin::signed(3), 9),
+			Error::<Test>::DepositTooLow
+		);
+
+		// increasing reserves immediately.
+		assert_ok!(CollatorSelection::update_bond(Origin::signed(3), 30));
+		assert_eq!(CollatorSelection::candidates(), vec![CandidateInfo { who: 3, deposit: 30 }]);
+		assert_eq!(Balances::free_balance(3), 70);
+		assert_eq!(CollatorSelection::total_backing(&3), 30);
+
+		// poor.
+		assert_noop!(
+			CollatorSelection::update_bond(Origin::signed(3), 200),
+			BalancesError::<Test>::InsufficientBalance
+		);
+
+		// decreasing unbonds.
+		assert_ok!(CollatorSelection::update_bond(Origin::signed(3), 15));
+		assert_eq!(CollatorSelection::candidates(), vec![CandidateInfo { who: 3, deposit: 15 }]);
+		assert_eq!(CollatorSelection::total_backing(&3), 15);
+		assert_eq!(Balances::reserved_balance(3), 30);
+		assert_eq!(CollatorSelection::unbonding(3), vec![UnbondingChunk { value: 15, unlock_at: 20 }]);
+
+		System::set_block_number(20);
+		assert_ok!(CollatorSelection::withdraw_unbonded(Origin::signed(3)));
+		assert_eq!(Balances::free_balance(3), 85);
+	});
+}
+
+#[test]
+fn leaving_candidate_cannot_update_bond_or_be_delegated_to() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::leave_intent(Origin::signed(3)));
+
+		assert_noop!(
+			CollatorSelection::update_bond(Origin::signed(3), 20),
+			Error::<Test>::AlreadyLeaving
+		);
+		assert_noop!(
+			CollatorSelection::delegate(Origin::signed(5), 3, 20),
+			Error::<Test>::AlreadyLeaving
+		);
 	});
 }
 
@@ -218,10 +301,18 @@ fn authorship_event_handler() {
 		assert_eq!(CollatorSelection::candidates(), vec![collator]);
 		assert_eq!(CollatorSelection::last_authored_block(4), 0);
 
-		// half of the pot goes to the collator who's the author (4 in tests).
+		// half of the pot is accrued to the collator who's the author (4 in tests).
+		assert_eq!(CollatorSelection::eras_rewards(0, 4), 50);
+		assert_eq!(CollatorSelection::total_unclaimed(), 50);
+		assert_eq!(Balances::free_balance(4), 90);
+
+		// and paid out once the era is over.
+		<crate::ActiveEra<Test>>::put(1);
+		assert_ok!(CollatorSelection::payout(Origin::signed(1), 0, 4));
 		assert_eq!(Balances::free_balance(4), 140);
 		// half + ED stays.
 		assert_eq!(Balances::free_balance(CollatorSelection::account_id()), 55);
+		assert_eq!(CollatorSelection::total_unclaimed(), 0);
 	});
 }
 
@@ -318,6 +409,371 @@ fn kick_mechanism() {
 	});
 }
 
+#[test]
+fn can_register_more_candidates_than_desired() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(CollatorSelection::desired_candidates(), 2);
+
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(4)));
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(5)));
+
+		assert_eq!(CollatorSelection::candidates().len(), 3);
+	});
+}
+
+#[test]
+fn delegate_works() {
+	new_test_ext().execute_with(|| {
+		initialize_to_block(1);
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_eq!(CollatorSelection::total_backing(&3), 10);
+
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 20));
+		assert_eq!(Balances::free_balance(5), 80);
+		assert_eq!(Balances::reserved_balance(5), 20);
+		assert_eq!(
+			CollatorSelection::delegations(3, 5),
+			Some(Delegation { active: 0, pending: vec![PendingChunk { value: 20, matures_at: 6 }] }),
+		);
+		assert_eq!(CollatorSelection::delegator_count(3), 1);
+
+		// does not count before the bond delay.
+		assert_eq!(CollatorSelection::total_backing(&3), 10);
+		initialize_to_block(6);
+		assert_eq!(CollatorSelection::total_backing(&3), 30);
+
+		// topping up settles the matured amount and delays only the new one.
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 10));
+		assert_eq!(
+			CollatorSelection::delegations(3, 5),
+			Some(Delegation { active: 20, pending: vec![PendingChunk { value: 10, matures_at: 11 }] }),
+		);
+		assert_eq!(CollatorSelection::total_backing(&3), 30);
+		assert_eq!(CollatorSelection::delegator_count(3), 1);
+	});
+}
+
+#[test]
+fn top_up_does_not_delay_pending_funds() {
+	new_test_ext().execute_with(|| {
+		initialize_to_block(1);
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 20));
+
+		initialize_to_block(3);
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 10));
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 10));
+		assert_eq!(
+			CollatorSelection::delegations(3, 5),
+			Some(Delegation {
+				active: 0,
+				pending: vec![
+					PendingChunk { value: 20, matures_at: 6 },
+					PendingChunk { value: 20, matures_at: 8 },
+				],
+			}),
+		);
+
+		// the latest funds are released first.
+		assert_ok!(CollatorSelection::undelegate(Origin::signed(5), 3, 5));
+		assert_eq!(CollatorSelection::delegations(3, 5).unwrap().total(), 35);
+
+		// the first delegation still matures at block 6.
+		initialize_to_block(6);
+		assert_eq!(CollatorSelection::total_backing(&3), 30);
+		initialize_to_block(8);
+		assert_eq!(CollatorSelection::total_backing(&3), 45);
+	});
+}
+
+#[test]
+fn cannot_delegate_badly() {
+	new_test_ext().execute_with(|| {
+		// not a candidate, and invulnerables cannot be delegated to.
+		assert_noop!(
+			CollatorSelection::delegate(Origin::signed(5), 3, 20),
+			Error::<Test>::NotCandidate,
+		);
+		assert_noop!(
+			CollatorSelection::delegate(Origin::signed(5), 1, 20),
+			Error::<Test>::NotCandidate,
+		);
+
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+
+		// below minimum.
+		assert_noop!(
+			CollatorSelection::delegate(Origin::signed(5), 3, 4),
+			Error::<Test>::DelegationBelowMinimum,
+		);
+
+		// poor.
+		assert_noop!(
+			CollatorSelection::delegate(Origin::signed(33), 3, 5),
+			BalancesError::<Test>::InsufficientBalance,
+		);
+
+		// too many delegators.
+		for who in 10..13 {
+			Balances::make_free_balance_be(&who, 100);
+			assert_ok!(CollatorSelection::delegate(Origin::signed(who), 3, 5));
+		}
+		assert_noop!(
+			CollatorSelection::delegate(Origin::signed(5), 3, 5),
+			Error::<Test>::TooManyDelegators,
+		);
+		// but existing delegators can top up.
+		assert_ok!(CollatorSelection::delegate(Origin::signed(10), 3, 5));
+	});
+}
+
+#[test]
+fn undelegate_and_withdraw_works() {
+	new_test_ext().execute_with(|| {
+		initialize_to_block(1);
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 20));
+
+		assert_noop!(
+			CollatorSelection::undelegate(Origin::signed(4), 3, 10),
+			Error::<Test>::NotDelegator,
+		);
+		assert_noop!(
+			CollatorSelection::undelegate(Origin::signed(5), 3, 21),
+			Error::<Test>::InsufficientDelegation,
+		);
+		assert_noop!(
+			CollatorSelection::undelegate(Origin::signed(5), 3, 16),
+			Error::<Test>::DelegationBelowMinimum,
+		);
+
+		initialize_to_block(6);
+		assert_ok!(CollatorSelection::undelegate(Origin::signed(5), 3, 10));
+		assert_eq!(
+			CollatorSelection::delegations(3, 5),
+			Some(Delegation { active: 10, pending: vec![] }),
+		);
+		assert_eq!(CollatorSelection::total_backing(&3), 20);
+		assert_eq!(CollatorSelection::unbonding(5), vec![UnbondingChunk { value: 10, unlock_at: 26 }]);
+		// still reserved.
+		assert_eq!(Balances::reserved_balance(5), 20);
+
+		// nothing to withdraw before the unbond delay.
+		assert_noop!(
+			CollatorSelection::withdraw_unbonded(Origin::signed(5)),
+			Error::<Test>::NothingToWithdraw,
+		);
+
+		// remove the rest.
+		assert_ok!(CollatorSelection::undelegate(Origin::signed(5), 3, 10));
+		assert_eq!(CollatorSelection::delegations(3, 5), None);
+		assert_eq!(CollatorSelection::delegator_count(3), 0);
+		assert_eq!(CollatorSelection::total_backing(&3), 10);
+
+		initialize_to_block(26);
+		assert_ok!(CollatorSelection::withdraw_unbonded(Origin::signed(5)));
+		assert_eq!(Balances::free_balance(5), 100);
+		assert_eq!(Balances::reserved_balance(5), 0);
+		assert!(CollatorSelection::unbonding(5).is_empty());
+	});
+}
+
+#[test]
+fn too_many_unbonding_chunks() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		Balances::make_free_balance_be(&5, 1_000);
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 500));
+
+		for _ in 0..crate::MAX_UNBONDING_CHUNKS {
+			assert_ok!(CollatorSelection::undelegate(Origin::signed(5), 3, 1));
+		}
+		assert_noop!(
+			CollatorSelection::undelegate(Origin::signed(5), 3, 1),
+			Error::<Test>::TooManyUnbondingChunks,
+		);
+	});
+}
+
+#[test]
+fn leaving_candidate_releases_delegations() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 3, 20));
+
+		initialize_to_block(2);
+		assert_ok!(CollatorSelection::leave_intent(Origin::signed(3)));
+		assert_eq!(CollatorSelection::delegations(3, 5), None);
+		assert_eq!(CollatorSelection::delegator_count(3), 0);
+		assert_eq!(CollatorSelection::unbonding(5), vec![UnbondingChunk { value: 20, unlock_at: 22 }]);
+		// funds stay reserved until withdrawn.
+		assert_eq!(Balances::reserved_balance(5), 20);
+
+		// nothing is left to release when the candidate is removed.
+		initialize_to_block(10);
+		assert!(CollatorSelection::candidates().is_empty());
+		assert_eq!(CollatorSelection::unbonding(5), vec![UnbondingChunk { value: 20, unlock_at: 22 }]);
+	});
+}
+
+#[test]
+fn election_is_stake_weighted() {
+	new_test_ext().execute_with(|| {
+		initialize_to_block(1);
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(4)));
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(5)));
+
+		// equal backing keeps registration order.
+		assert_eq!(CollatorSelection::assemble_collators(vec![3, 4, 5]), vec![1, 2, 3, 4]);
+
+		Balances::make_free_balance_be(&10, 100);
+		assert_ok!(CollatorSelection::delegate(Origin::signed(10), 5, 50));
+		Balances::make_free_balance_be(&11, 100);
+		assert_ok!(CollatorSelection::delegate(Origin::signed(11), 4, 5));
+
+		// delegations are not yet active.
+		assert_eq!(CollatorSelection::assemble_collators(vec![3, 4, 5]), vec![1, 2, 3, 4]);
+
+		initialize_to_block(6);
+		assert_eq!(CollatorSelection::assemble_collators(vec![3, 4, 5]), vec![1, 2, 5, 4]);
+		// 3 was not elected, so it gets a fresh grace period.
+		assert_eq!(CollatorSelection::last_authored_block(3), 16);
+
+		initialize_to_block(20);
+		assert_eq!(SessionHandlerCollators::get(), vec![1, 2, 5, 4]);
+		// 3 was not kicked even though it never authored.
+		assert_eq!(CollatorSelection::candidates().len(), 3);
+	});
+}
+#[test]
+fn set_commission_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			CollatorSelection::set_commission(Origin::signed(3), Perbill::from_percent(10)),
+			Error::<Test>::NotCandidate,
+		);
+
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::set_commission(Origin::signed(3), Perbill::from_percent(10)));
+		assert_eq!(CollatorSelection::commission(3), Perbill::from_percent(10));
+
+		// cleared when leaving.
+		assert_ok!(CollatorSelection::leave_intent(Origin::signed(3)));
+		initialize_to_block(10);
+		assert_eq!(CollatorSelection::commission(3), Perbill::zero());
+	});
+}
+
+#[test]
+fn rewards_are_shared_with_delegators() {
+	new_test_ext().execute_with(|| {
+		initialize_to_block(1);
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(4)));
+		assert_ok!(CollatorSelection::set_commission(Origin::signed(4), Perbill::from_percent(20)));
+		assert_ok!(CollatorSelection::delegate(Origin::signed(5), 4, 30));
+
+		// the stake is snapshotted when the era is planned.
+		initialize_to_block(6);
+		CollatorSelection::new_session(5);
+		assert_eq!(
+			CollatorSelection::eras_stakers(5, 4),
+			Some(Exposure {
+				total: 40,
+				own: 10,
+				others: vec![(5, 30)],
+				commission: Perbill::from_percent(20),
+			}),
+		);
+		CollatorSelection::start_session(5);
+		assert_eq!(CollatorSelection::active_era(), 5);
+
+		// changing the commission does not affect the snapshot.
+		assert_ok!(CollatorSelection::set_commission(Origin::signed(4), Perbill::from_percent(100)));
+
+		Balances::make_free_balance_be(&CollatorSelection::account_id(), 105);
+		CollatorSelection::note_author(4);
+		assert_eq!(CollatorSelection::eras_rewards(5, 4), 50);
+
+		assert_noop!(
+			CollatorSelection::payout(Origin::signed(1), 5, 4),
+			Error::<Test>::EraNotFinished,
+		);
+
+		CollatorSelection::start_session(6);
+		assert_ok!(CollatorSelection::payout(Origin::signed(1), 5, 4));
+		// 10 commission, and a quarter of the remaining 40.
+		assert_eq!(Balances::free_balance(4), 90 + 20);
+		// three quarters of the remaining 40.
+		assert_eq!(Balances::free_balance(5), 70 + 30);
+		assert_eq!(Balances::free_balance(CollatorSelection::account_id()), 55);
+		assert_eq!(CollatorSelection::total_unclaimed(), 0);
+
+		// cannot be paid twice.
+		assert_noop!(
+			CollatorSelection::payout(Origin::signed(1), 5, 4),
+			Error::<Test>::NothingToPayout,
+		);
+	});
+}
+
+#[test]
+fn old_rewards_are_forfeited() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&CollatorSelection::account_id(), 105);
+		CollatorSelection::note_author(1);
+		assert_eq!(CollatorSelection::eras_rewards(0, 1), 50);
+
+		// still within the history depth.
+		CollatorSelection::start_session(2);
+
+		// now it is not.
+		CollatorSelection::start_session(3);
+		assert_eq!(CollatorSelection::eras_rewards(0, 1), 0);
+		assert_eq!(CollatorSelection::total_unclaimed(), 0);
+		assert_noop!(
+			CollatorSelection::payout(Origin::signed(1), 0, 1),
+			Error::<Test>::EraTooOld,
+		);
+
+		// the forfeited reward is available for new rewards.
+		CollatorSelection::note_author(1);
+		assert_eq!(CollatorSelection::eras_rewards(3, 1), 50);
+	});
+}
+
+#[test]
+fn rewards_can_be_minted() {
+	new_test_ext().execute_with(|| {
+		RewardPerBlock::set(20);
+		Balances::make_free_balance_be(&CollatorSelection::account_id(), 5);
+
+		CollatorSelection::note_author(1);
+		assert_eq!(Balances::free_balance(CollatorSelection::account_id()), 25);
+		assert_eq!(CollatorSelection::eras_rewards(0, 1), 10);
+	});
+}
+
+#[test]
+fn kicked_candidate_is_slashed() {
+	new_test_ext().execute_with(|| {
+		KickSlash::set(Perbill::from_percent(50));
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(3)));
+		assert_ok!(CollatorSelection::register_as_candidate(Origin::signed(4)));
+		assert_ok!(CollatorSelection::delegate(Origin::signed(3), 4, 20));
+
+		initialize_to_block(20);
+		// 3 never authored and was kicked.
+		assert_eq!(CollatorSelection::candidates().len(), 1);
+		assert!(System::events().iter().any(|record|
+			record.event == Event::CollatorSelection(crate::Event::CandidateSlashed(3, 5))
+		));
+		// half of the deposit is gone, the delegation is still reserved.
+		assert_eq!(Balances::free_balance(3), 75);
+		assert_eq!(Balances::reserved_balance(3), 20);
+	});
+}
 
 #[test]
 #[should_panic = "duplicate invulnerables in genesis."]
@@ -15,6 +15,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+// TODO: rerun the benchmarks. The weights of `update_bond`, `delegate`, `undelegate`,
+// `withdraw_unbonded`, `set_commission` and `payout` and the delegation components of
+// `leave_intent` and `new_session` are estimates.
+
 #![allow(unused_parens)]
 #![allow(unused_imports)]
 
@@ -27,9 +31,15 @@ pub trait WeightInfo {
 	fn set_desired_candidates() -> Weight;
 	fn set_candidacy_bond() -> Weight;
 	fn register_as_candidate(_c: u32) -> Weight;
-	fn leave_intent(_c: u32) -> Weight;
+	fn leave_intent(_c: u32, _d: u32) -> Weight;
+	fn update_bond(_c: u32) -> Weight;
+	fn delegate() -> Weight;
+	fn undelegate() -> Weight;
+	fn withdraw_unbonded() -> Weight;
+	fn set_commission() -> Weight;
+	fn payout(_d: u32) -> Weight;
 	fn note_author() -> Weight;
-	fn new_session(_c: u32, _r: u32) -> Weight;
+	fn new_session(_r: u32, _c: u32, _d: u32, _e: u32) -> Weight;
 }
 
 /// Weights for pallet_collator_selection using the Substrate node and recommended hardware.
@@ -56,28 +66,70 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
-	fn leave_intent(c: u32, ) -> Weight {
+	fn leave_intent(c: u32, d: u32, ) -> Weight {
 		(55_336_000 as Weight)
 			// Standard Error: 0
 			.saturating_add((151_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add((38_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(d as Weight)))
+	}
+	fn update_bond(c: u32, ) -> Weight {
+		(49_000_000 as Weight)
+			.saturating_add((150_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn delegate() -> Weight {
+		(58_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn undelegate() -> Weight {
+		(52_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn withdraw_unbonded() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
+	fn set_commission() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn payout(d: u32, ) -> Weight {
+		(62_000_000 as Weight)
+			.saturating_add((48_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(d as Weight)))
+	}
 	fn note_author() -> Weight {
 		(71_461_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
-	fn new_session(r: u32, c: u32, ) -> Weight {
+	fn new_session(r: u32, c: u32, d: u32, e: u32, ) -> Weight {
 		(0 as Weight)
 			// Standard Error: 1_010_000
 			.saturating_add((109_961_000 as Weight).saturating_mul(r as Weight))
 			// Standard Error: 1_010_000
 			.saturating_add((151_952_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((38_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((9_000_000 as Weight).saturating_mul(e as Weight))
 			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(e as Weight)))
 			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(d as Weight)))
 	}
 }
 
@@ -105,27 +157,69 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
-	fn leave_intent(c: u32, ) -> Weight {
+	fn leave_intent(c: u32, d: u32, ) -> Weight {
 		(55_336_000 as Weight)
 			// Standard Error: 0
 			.saturating_add((151_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add((38_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(d as Weight)))
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(d as Weight)))
+	}
+	fn update_bond(c: u32, ) -> Weight {
+		(49_000_000 as Weight)
+			.saturating_add((150_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn delegate() -> Weight {
+		(58_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn undelegate() -> Weight {
+		(52_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn withdraw_unbonded() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn set_commission() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn payout(d: u32, ) -> Weight {
+		(62_000_000 as Weight)
+			.saturating_add((48_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(d as Weight)))
 	}
 	fn note_author() -> Weight {
 		(71_461_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
-	fn new_session(r: u32, c: u32, ) -> Weight {
+	fn new_session(r: u32, c: u32, d: u32, e: u32, ) -> Weight {
 		(0 as Weight)
 			// Standard Error: 1_010_000
 			.saturating_add((109_961_000 as Weight).saturating_mul(r as Weight))
 			// Standard Error: 1_010_000
 			.saturating_add((151_952_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((38_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((9_000_000 as Weight).saturating_mul(e as Weight))
 			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(e as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(d as Weight)))
 	}
 }
@@ -31,7 +31,8 @@ use cumulus_primitives_core::{
 	relay_chain, AbridgedHostConfiguration, ChannelStatus, CollationInfo, DmpMessageHandler,
 	GetChannelInfo, InboundDownwardMessage, InboundHrmpMessage, MessageSendError, OnValidationData,
 	OutboundHrmpMessage, ParaId, UpwardMessage, UpwardMessageSender, XcmpMessageHandler,
-	XcmpMessageSource, PersistedValidationData,
+	XcmpMessageSource, PersistedValidationData, UpgradeStatus, AuthorizedUpgradeInfo,
+	MAX_ADDITIONAL_RELAY_STATE_KEYS,
 };
 use cumulus_primitives_parachain_inherent::ParachainInherentData;
 use frame_support::{
@@ -46,13 +47,14 @@ use frame_system::{ensure_none, ensure_root};
 use polkadot_parachain::primitives::RelayChainBlockNumber;
 use relay_state_snapshot::MessagingStateSnapshot;
 use sp_runtime::{
-	traits::{BlakeTwo256, Block as BlockT, Hash},
+	traits::{BlakeTwo256, Block as BlockT, Hash, Saturating},
 	transaction_validity::{
 		InvalidTransaction, TransactionLongevity, TransactionSource, TransactionValidity,
 		ValidTransaction,
 	},
 };
 use sp_std::{cmp, collections::btree_map::BTreeMap, prelude::*};
+use sp_version::RuntimeVersion;
 
 mod relay_state_snapshot;
 #[macro_use]
@@ -83,7 +85,7 @@ mod tests;
 /// # fn main() {}
 /// ```
 pub use cumulus_pallet_parachain_system_proc_macro::register_validate_block;
-pub use relay_state_snapshot::RelayChainStateProof;
+pub use relay_state_snapshot::{ReadEntryErr, RelayChainStateProof};
 
 pub use pallet::*;
 
@@ -125,6 +127,12 @@ pub mod pallet {
 
 		/// The weight we reserve at the beginning of the block for processing XCMP messages.
 		type ReservedXcmpWeight: Get<Weight>;
+
+		/// Something which reads relay chain state on top of the one read by this pallet.
+		type RelayStateReader: RelayStateReader;
+
+		/// The number of blocks between authorizing an upgrade and being able to enact it.
+		type UpgradeCooldown: Get<Self::BlockNumber>;
 	}
 
 	#[pallet::hooks]
@@ -355,7 +363,7 @@ pub mod pallet {
 			<T::OnValidationData as OnValidationData>::on_validation_data(&vfp);
 
 			// TODO: This is more than zero, but will need benchmarking to figure out what.
-			let mut total_weight = 0;
+			let mut total_weight = T::RelayStateReader::on_relay_state(&relay_state_proof);
 			total_weight += Self::process_inbound_downward_messages(
 				relevant_messaging_state.dmq_mqc_head,
 				downward_messages,
@@ -382,36 +390,65 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// Authorize an upgrade to the code with the hash `code_hash`, replacing any previous
+		/// authorization. Anyone may enact it with `enact_authorized_upgrade` once
+		/// `UpgradeCooldown` blocks have passed.
 		#[pallet::weight((1_000_000, DispatchClass::Operational))]
 		pub fn authorize_upgrade(origin: OriginFor<T>, code_hash: T::Hash) -> DispatchResult {
 			ensure_root(origin)?;
 
+			let enactable_at = frame_system::Pallet::<T>::block_number()
+				.saturating_add(T::UpgradeCooldown::get());
 			AuthorizedUpgrade::<T>::put(&code_hash);
+			AuthorizedUpgradeEnactableAt::<T>::put(enactable_at);
+
+			Self::deposit_event(Event::UpgradeAuthorized(code_hash, enactable_at));
+			Ok(())
+		}
 
-			Self::deposit_event(Event::UpgradeAuthorized(code_hash));
+		/// Revoke the authorization made with `authorize_upgrade`.
+		#[pallet::weight((1_000_000, DispatchClass::Operational))]
+		pub fn cancel_authorized_upgrade(origin: OriginFor<T>) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let code_hash = AuthorizedUpgrade::<T>::take().ok_or(Error::<T>::NothingAuthorized)?;
+			AuthorizedUpgradeEnactableAt::<T>::kill();
+
+			Self::deposit_event(Event::UpgradeAuthorizationCancelled(code_hash));
 			Ok(())
 		}
 
 		#[pallet::weight(1_000_000)]
 		pub fn enact_authorized_upgrade(_: OriginFor<T>, code: Vec<u8>) -> DispatchResultWithPostInfo {
-			Self::validate_authorized_upgrade(&code[..])?;
+			let code_hash = Self::validate_authorized_upgrade(&code[..])?;
+			let spec_version = sp_io::misc::runtime_version(&code)
+				.and_then(|v| <RuntimeVersion as codec::Decode>::decode(&mut &v[..]).ok())
+				.ok_or(Error::<T>::FailedToExtractRuntimeVersion)?
+				.spec_version;
 			Self::set_code_impl(code)?;
 			AuthorizedUpgrade::<T>::kill();
+			AuthorizedUpgradeEnactableAt::<T>::kill();
+
+			Self::deposit_event(Event::UpgradeEnacted(code_hash, spec_version));
 			Ok(Pays::No.into())
 		}
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	#[pallet::metadata(T::Hash = "Hash")]
+	#[pallet::metadata(T::Hash = "Hash", T::BlockNumber = "BlockNumber")]
 	pub enum Event<T: Config> {
 		/// The validation function has been scheduled to apply as of the contained relay chain
 		/// block number.
 		ValidationFunctionStored(RelayChainBlockNumber),
 		/// The validation function was applied as of the contained relay chain block number.
 		ValidationFunctionApplied(RelayChainBlockNumber),
-		/// An upgrade has been authorized.
-		UpgradeAuthorized(T::Hash),
+		/// An upgrade has been authorized. \[ code_hash, enactable_at \]
+		UpgradeAuthorized(T::Hash, T::BlockNumber),
+		/// An upgrade authorization has been cancelled. \[ code_hash \]
+		UpgradeAuthorizationCancelled(T::Hash),
+		/// An authorized upgrade has been enacted. \[ code_hash, spec_version \]
+		UpgradeEnacted(T::Hash, u32),
 		/// Some downward messages have been received and will be processed.
 		/// \[ count \]
 		DownwardMessagesReceived(u32),
@@ -439,6 +476,10 @@ pub mod pallet {
 		NothingAuthorized,
 		/// The given code upgrade has not been authorized.
 		Unauthorized,
+		/// The cooldown of the authorized code upgrade has not passed yet.
+		TooEarly,
+		/// The runtime version could not be read from the given code.
+		FailedToExtractRuntimeVersion,
 	}
 
 	/// We need to store the new validation function for the span between
@@ -562,6 +603,13 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type AuthorizedUpgrade<T: Config> = StorageValue<_, T::Hash>;
 
+	/// The block from which [`AuthorizedUpgrade`] may be enacted.
+	///
+	/// NOTE: Absent for authorizations made before the cooldown existed, which may be enacted
+	/// right away.
+	#[pallet::storage]
+	pub(super) type AuthorizedUpgradeEnactableAt<T: Config> = StorageValue<_, T::BlockNumber>;
+
 	#[pallet::inherent]
 	impl<T: Config> ProvideInherent for Pallet<T> {
 		type Call = Call<T>;
@@ -602,8 +650,31 @@ impl<T: Config> Pallet<T> {
 		let required_hash = AuthorizedUpgrade::<T>::get().ok_or(Error::<T>::NothingAuthorized)?;
 		let actual_hash = T::Hashing::hash(&code[..]);
 		ensure!(actual_hash == required_hash, Error::<T>::Unauthorized);
+		if let Some(enactable_at) = AuthorizedUpgradeEnactableAt::<T>::get() {
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= enactable_at,
+				Error::<T>::TooEarly
+			);
+		}
 		Ok(actual_hash)
 	}
+
+	/// Returns the state of the runtime upgrades of this parachain.
+	///
+	/// This is expected to be used by the
+	/// [`UpgradeStatusApi`](cumulus_primitives_core::UpgradeStatusApi) runtime api.
+	pub fn upgrade_status() -> UpgradeStatus<T::Hash, T::BlockNumber> {
+		let authorized = AuthorizedUpgrade::<T>::get().map(|code_hash| AuthorizedUpgradeInfo {
+			code_hash,
+			enactable_at: AuthorizedUpgradeEnactableAt::<T>::get()
+				.unwrap_or_else(frame_system::Pallet::<T>::block_number),
+		});
+
+		UpgradeStatus {
+			authorized,
+			scheduled_at: <PendingRelayChainBlockNumber<T>>::get(),
+		}
+	}
 }
 
 impl<T: Config> sp_runtime::traits::ValidateUnsigned for Pallet<T> {
@@ -611,14 +682,17 @@ impl<T: Config> sp_runtime::traits::ValidateUnsigned for Pallet<T> {
 
 	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
 		if let Call::enact_authorized_upgrade(ref code) = call {
-			if let Ok(hash) = Self::validate_authorized_upgrade(code) {
-				return Ok(ValidTransaction {
+			match Self::validate_authorized_upgrade(code) {
+				Ok(hash) => return Ok(ValidTransaction {
 					priority: 100,
 					requires: vec![],
 					provides: vec![hash.as_ref().to_vec()],
 					longevity: TransactionLongevity::max_value(),
 					propagate: true,
-				});
+				}),
+				Err(e) if e == Error::<T>::TooEarly.into() =>
+					return Err(InvalidTransaction::Future.into()),
+				Err(_) => {}
 			}
 		}
 		if let Call::set_validation_data(..) = call {
@@ -924,6 +998,27 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Returns the relay chain storage keys read by [`Config::RelayStateReader`], which should be
+	/// included in the relay chain state proof of the next block.
+	///
+	/// At most [`MAX_ADDITIONAL_RELAY_STATE_KEYS`] keys are returned, the reads of the other keys
+	/// will fail.
+	///
+	/// This is expected to be used by the
+	/// [`CollectRelayStateKeys`](cumulus_primitives_core::CollectRelayStateKeys) runtime api.
+	pub fn relay_state_keys() -> Vec<Vec<u8>> {
+		let mut keys = T::RelayStateReader::relay_state_keys();
+		if keys.len() > MAX_ADDITIONAL_RELAY_STATE_KEYS {
+			log::warn!(
+				target: "parachain-system",
+				"Too many relay chain state keys requested, only the first {} are proven.",
+				MAX_ADDITIONAL_RELAY_STATE_KEYS,
+			);
+			keys.truncate(MAX_ADDITIONAL_RELAY_STATE_KEYS);
+		}
+		keys
+	}
+
 	/// Returns the [`CollationInfo`] of the current active block.
 	///
 	/// This is expected to be used by the
@@ -1027,6 +1122,37 @@ impl<T: Config> UpwardMessageSender for Pallet<T> {
 	}
 }
 
+/// Something which reads some relay chain state on top of the well known keys read by this pallet,
+/// e.g. the balance of an account or the head of another parachain.
+///
+/// The keys returned by `relay_state_keys` are collected by the collator through the
+/// [`CollectRelayStateKeys`](cumulus_primitives_core::CollectRelayStateKeys) runtime api and
+/// included in the relay chain state proof. Their values can then be read with
+/// [`RelayChainStateProof::read_entry`] in `on_relay_state`.
+pub trait RelayStateReader {
+	/// The relay chain storage keys to include in the relay chain state proof of the next block.
+	fn relay_state_keys() -> Vec<Vec<u8>>;
+
+	/// Called with the relay chain state proof when the validation data is set, returning the
+	/// weight used.
+	fn on_relay_state(proof: &RelayChainStateProof) -> Weight;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl RelayStateReader for Tuple {
+	fn relay_state_keys() -> Vec<Vec<u8>> {
+		let mut keys = Vec::new();
+		for_tuples!( #( keys.extend(Tuple::relay_state_keys()); )* );
+		keys
+	}
+
+	fn on_relay_state(proof: &RelayChainStateProof) -> Weight {
+		let mut weight: Weight = 0;
+		for_tuples!( #( weight = weight.saturating_add(Tuple::on_relay_state(proof)); )* );
+		weight
+	}
+}
+
 /// Something that can check the inherents of a block.
 pub trait CheckInherents<Block: BlockT> {
 	/// Check all inherents of the block.
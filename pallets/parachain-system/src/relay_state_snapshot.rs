@@ -227,4 +227,28 @@ impl RelayChainStateProof {
 	pub fn read_slot(&self) -> Result<relay_chain::v1::Slot, Error> {
 		read_entry(&self.trie_backend, relay_chain::well_known_keys::CURRENT_SLOT, None).map_err(Error::Slot)
 	}
+
+	/// Read the entry given by `key` from the relay chain state proof and decode it.
+	///
+	/// If the value is absent in the relay chain state, `fallback` is returned instead. The key
+	/// must have been included in the proof by the collator, see
+	/// [`RelayStateReader`](crate::RelayStateReader).
+	///
+	/// Returns an error if anything failed at reading or decoding, or if the value is absent and
+	/// no `fallback` was given.
+	pub fn read_entry<T: Decode>(&self, key: &[u8], fallback: Option<T>) -> Result<T, ReadEntryErr> {
+		read_entry(&self.trie_backend, key, fallback)
+	}
+
+	/// Read the optional entry given by `key` from the relay chain state proof and decode it.
+	///
+	/// Returns `None` if the value is absent in the relay chain state, or an error if anything
+	/// failed at reading or decoding.
+	pub fn read_optional_entry<T: Decode>(&self, key: &[u8]) -> Result<Option<T>, ReadEntryErr> {
+		match read_entry(&self.trie_backend, key, None) {
+			Ok(v) => Ok(Some(v)),
+			Err(ReadEntryErr::Absent) => Ok(None),
+			Err(err) => Err(err),
+		}
+	}
 }
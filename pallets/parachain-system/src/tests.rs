@@ -33,7 +33,7 @@ use frame_system::{InitKind, RawOrigin};
 use hex_literal::hex;
 use relay_chain::v1::HrmpChannelId;
 use sp_core::H256;
-use sp_runtime::{testing::Header, traits::IdentityLookup};
+use sp_runtime::{testing::Header, traits::{IdentityLookup, ValidateUnsigned}};
 use sp_version::RuntimeVersion;
 use std::cell::RefCell;
 
@@ -67,6 +67,7 @@ parameter_types! {
 	pub const ParachainId: ParaId = ParaId::new(200);
 	pub const ReservedXcmpWeight: Weight = 0;
 	pub const ReservedDmpWeight: Weight = 0;
+	pub const UpgradeCooldown: u64 = 5;
 }
 impl frame_system::Config for Test {
 	type Origin = Origin;
@@ -102,15 +103,20 @@ impl Config for Test {
 	type ReservedDmpWeight = ReservedDmpWeight;
 	type XcmpMessageHandler = SaveIntoThreadLocal;
 	type ReservedXcmpWeight = ReservedXcmpWeight;
+	type RelayStateReader = SaveIntoThreadLocal;
+	type UpgradeCooldown = UpgradeCooldown;
 }
 
 pub struct FromThreadLocal;
 pub struct SaveIntoThreadLocal;
 
+const RELAY_STATE_KEY: &[u8] = b"relay_state_key";
+
 std::thread_local! {
 	static HANDLED_DMP_MESSAGES: RefCell<Vec<(relay_chain::BlockNumber, Vec<u8>)>> = RefCell::new(Vec::new());
 	static HANDLED_XCMP_MESSAGES: RefCell<Vec<(ParaId, relay_chain::BlockNumber, Vec<u8>)>> = RefCell::new(Vec::new());
 	static SENT_MESSAGES: RefCell<Vec<(ParaId, Vec<u8>)>> = RefCell::new(Vec::new());
+	static READ_RELAY_STATE: RefCell<Vec<Option<u32>>> = RefCell::new(Vec::new());
 }
 
 fn send_message(
@@ -171,11 +177,25 @@ impl XcmpMessageHandler for SaveIntoThreadLocal {
 	}
 }
 
+impl RelayStateReader for SaveIntoThreadLocal {
+	fn relay_state_keys() -> Vec<Vec<u8>> {
+		vec![RELAY_STATE_KEY.to_vec()]
+	}
+
+	fn on_relay_state(proof: &RelayChainStateProof) -> Weight {
+		// The key is only in the proof when the sproof builder was given a value for it.
+		let read = proof.read_optional_entry(RELAY_STATE_KEY).ok().flatten();
+		READ_RELAY_STATE.with(|r| r.borrow_mut().push(read));
+		0
+	}
+}
+
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
 fn new_test_ext() -> sp_io::TestExternalities {
 	HANDLED_DMP_MESSAGES.with(|m| m.borrow_mut().clear());
 	HANDLED_XCMP_MESSAGES.with(|m| m.borrow_mut().clear());
+	READ_RELAY_STATE.with(|r| r.borrow_mut().clear());
 
 	frame_system::GenesisConfig::default()
 		.build_storage::<Test>()
@@ -923,3 +943,109 @@ fn receive_hrmp_after_pause() {
 			});
 		});
 }
+
+#[test]
+fn reads_additional_relay_state() {
+	assert_eq!(
+		ParachainSystem::relay_state_keys(),
+		vec![RELAY_STATE_KEY.to_vec()],
+	);
+
+	BlockTests::new()
+		.with_relay_sproof_builder(|_, relay_block_num, sproof| {
+			if relay_block_num == 2 {
+				sproof.additional_key_values.push((RELAY_STATE_KEY.to_vec(), 42u32.encode()));
+			}
+		})
+		.add(1, || {})
+		.add_with_post_test(2, || {}, || {
+			READ_RELAY_STATE.with(|r| {
+				assert_eq!(*r.borrow(), vec![None, Some(42)]);
+			});
+		});
+}
+
+fn authorized_code() -> Vec<u8> {
+	vec![1, 2, 3]
+}
+
+fn authorized_hash() -> H256 {
+	BlakeTwo256::hash(&authorized_code())
+}
+
+#[test]
+fn authorized_upgrade_waits_for_cooldown() {
+	BlockTests::new()
+		.add(1, || {
+			assert_ok!(ParachainSystem::authorize_upgrade(RawOrigin::Root.into(), authorized_hash()));
+			assert_eq!(
+				System::events().last().unwrap().event,
+				Event::ParachainSystem(crate::Event::UpgradeAuthorized(authorized_hash(), 6)),
+			);
+		})
+		.add(5, || {
+			assert_eq!(
+				ParachainSystem::enact_authorized_upgrade(RawOrigin::None.into(), authorized_code())
+					.map_err(|e| e.error),
+				Err(Error::<Test>::TooEarly.into()),
+			);
+			assert_eq!(
+				ParachainSystem::validate_unsigned(
+					TransactionSource::External,
+					&crate::Call::enact_authorized_upgrade(authorized_code()),
+				),
+				Err(InvalidTransaction::Future.into()),
+			);
+		})
+		.add_with_post_test(
+			6,
+			|| {
+				assert_ok!(ParachainSystem::enact_authorized_upgrade(
+					RawOrigin::None.into(),
+					authorized_code(),
+				));
+			},
+			|| {
+				assert!(System::events().iter().any(|r| r.event ==
+					Event::ParachainSystem(crate::Event::UpgradeEnacted(authorized_hash(), 2))));
+				assert!(<PendingValidationCode<Test>>::exists());
+				assert_eq!(ParachainSystem::upgrade_status().authorized, None);
+			},
+		);
+}
+
+#[test]
+fn authorized_upgrade_can_be_cancelled() {
+	BlockTests::new()
+		.add(1, || {
+			assert_ok!(ParachainSystem::authorize_upgrade(RawOrigin::Root.into(), authorized_hash()));
+			assert_eq!(
+				ParachainSystem::upgrade_status(),
+				UpgradeStatus {
+					authorized: Some(AuthorizedUpgradeInfo {
+						code_hash: authorized_hash(),
+						enactable_at: 6,
+					}),
+					scheduled_at: None,
+				},
+			);
+
+			assert_ok!(ParachainSystem::cancel_authorized_upgrade(RawOrigin::Root.into()));
+			assert_eq!(
+				System::events().last().unwrap().event,
+				Event::ParachainSystem(crate::Event::UpgradeAuthorizationCancelled(authorized_hash())),
+			);
+			assert_eq!(ParachainSystem::upgrade_status().authorized, None);
+			assert_eq!(
+				ParachainSystem::cancel_authorized_upgrade(RawOrigin::Root.into()),
+				Err(Error::<Test>::NothingAuthorized.into()),
+			);
+		})
+		.add(6, || {
+			assert_eq!(
+				ParachainSystem::enact_authorized_upgrade(RawOrigin::None.into(), authorized_code())
+					.map_err(|e| e.error),
+				Err(Error::<Test>::NothingAuthorized.into()),
+			);
+		});
+}
@@ -0,0 +1,170 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Randomness source backed by the relay chain.
+//!
+//! Unlike `pallet_randomness_collective_flip`, whose entropy comes from the parachain block hashes
+//! and can thus be biased by the collators, this pallet uses the BABE randomness of the current
+//! relay chain epoch. That value is fixed before the epoch starts, so the collators can't choose it.
+//!
+//! It is not unbiasable though. Relay chain block authors can bias BABE randomness by withholding
+//! their blocks, and a collator can pick the relay parent it builds on and thus delay the switch
+//! to the randomness of a new epoch. Only use it where this level of influence is acceptable.
+//!
+//! The randomness is read from the relay chain state proof of every block. For this the pallet has
+//! to be registered as the (or part of the) `RelayStateReader` of the parachain system pallet:
+//!
+//! ```ignore
+//! impl cumulus_pallet_parachain_system::Config for Runtime {
+//!     type RelayStateReader = RelayRandomness;
+//!     // ...
+//! }
+//! ```
+//!
+//! Other pallets can then use the pallet as their
+//! [`Randomness`](frame_support::traits::Randomness) source. Note that the value only changes once
+//! per relay chain epoch, so callers must use distinct subjects to get distinct values. Until the
+//! first epoch randomness is received, the returned block number is zero and the value must not be
+//! used.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Encode;
+use cumulus_pallet_parachain_system::{RelayChainStateProof, RelayStateReader};
+use frame_support::{traits::Randomness, weights::Weight};
+use sp_runtime::traits::{Hash, Zero};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// The BABE randomness of an epoch.
+pub type EpochRandomness = [u8; 32];
+
+/// The index of a relay chain epoch.
+pub type EpochIndex = u64;
+
+/// Storage key of the randomness of the current epoch, i.e. `Babe::Randomness`, on the relay chain.
+pub fn relay_epoch_randomness_key() -> Vec<u8> {
+	relay_storage_value_key(b"Babe", b"Randomness")
+}
+
+/// Storage key of the index of the current epoch, i.e. `Babe::EpochIndex`, on the relay chain.
+pub fn relay_epoch_index_key() -> Vec<u8> {
+	relay_storage_value_key(b"Babe", b"EpochIndex")
+}
+
+fn relay_storage_value_key(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+	[sp_io::hashing::twox_128(pallet), sp_io::hashing::twox_128(item)].concat()
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The randomness of a new relay chain epoch was received. \[ epoch_index \]
+		NewEpochRandomness(EpochIndex),
+	}
+
+	/// The index of the relay chain epoch whose randomness is used.
+	#[pallet::storage]
+	#[pallet::getter(fn epoch_index)]
+	pub(super) type RelayEpochIndex<T: Config> = StorageValue<_, EpochIndex, OptionQuery>;
+
+	/// The randomness of the relay chain epoch, and the parachain block in which it was received.
+	#[pallet::storage]
+	#[pallet::getter(fn epoch_randomness)]
+	pub(super) type RelayEpochRandomness<T: Config> =
+		StorageValue<_, (EpochRandomness, T::BlockNumber), OptionQuery>;
+}
+
+impl<T: Config> Pallet<T> {
+	/// Read the epoch randomness from the relay chain state proof and store it if the epoch
+	/// changed.
+	fn update_epoch_randomness(proof: &RelayChainStateProof) -> Result<bool, &'static str> {
+		let epoch_index: EpochIndex = proof
+			.read_entry(&relay_epoch_index_key(), Some(0))
+			.map_err(|_| "cannot read the relay chain epoch index")?;
+		if <RelayEpochIndex<T>>::get() == Some(epoch_index) {
+			return Ok(false);
+		}
+
+		let randomness: EpochRandomness = proof
+			.read_entry(&relay_epoch_randomness_key(), None)
+			.map_err(|_| "cannot read the relay chain epoch randomness")?;
+		<RelayEpochIndex<T>>::put(epoch_index);
+		<RelayEpochRandomness<T>>::put((randomness, frame_system::Pallet::<T>::block_number()));
+		Self::deposit_event(Event::NewEpochRandomness(epoch_index));
+		Ok(true)
+	}
+}
+
+impl<T: Config> RelayStateReader for Pallet<T> {
+	fn relay_state_keys() -> Vec<Vec<u8>> {
+		vec![relay_epoch_index_key(), relay_epoch_randomness_key()]
+	}
+
+	fn on_relay_state(proof: &RelayChainStateProof) -> Weight {
+		match Self::update_epoch_randomness(proof) {
+			Ok(true) => T::DbWeight::get().reads_writes(1, 2),
+			Ok(false) => T::DbWeight::get().reads(1),
+			Err(e) => {
+				// Keep using the randomness of the previous epoch, this is only expected when the
+				// collator doesn't include the keys in the proof.
+				log::warn!(target: "relay-randomness", "Epoch randomness not updated: {}", e);
+				T::DbWeight::get().reads(1)
+			}
+		}
+	}
+}
+
+impl<T: Config> Randomness<T::Hash, T::BlockNumber> for Pallet<T> {
+	/// Returns a value derived from the relay chain epoch randomness and `subject`, together with
+	/// the parachain block in which the epoch randomness was received.
+	///
+	/// Before the first epoch randomness is received the default hash and block number zero are
+	/// returned. The randomness is never received in the genesis block, so callers can detect this.
+	fn random(subject: &[u8]) -> (T::Hash, T::BlockNumber) {
+		match <RelayEpochRandomness<T>>::get() {
+			Some((randomness, known_since)) => {
+				let output = (randomness, subject).using_encoded(T::Hashing::hash);
+				(output, known_since)
+			}
+			None => (T::Hash::default(), Zero::zero()),
+		}
+	}
+}
@@ -0,0 +1,170 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use codec::Encode;
+use cumulus_test_relay_sproof_builder::RelayStateSproofBuilder;
+use frame_support::parameter_types;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+use crate as relay_randomness;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		RelayRandomness: relay_randomness::{Pallet, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type BlockLength = ();
+	type BlockWeights = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+impl Config for Test {
+	type Event = Event;
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// A relay chain state proof with the given epoch, if any.
+fn relay_state_proof(epoch: Option<(EpochIndex, EpochRandomness)>) -> RelayChainStateProof {
+	let mut builder = RelayStateSproofBuilder::default();
+	if let Some((index, randomness)) = epoch {
+		builder.additional_key_values = vec![
+			(relay_epoch_index_key(), index.encode()),
+			(relay_epoch_randomness_key(), randomness.encode()),
+		];
+	}
+	let para_id = builder.para_id;
+	let (root, proof) = builder.into_state_root_and_proof();
+	RelayChainStateProof::new(para_id, root, proof).expect("the proof matches the root")
+}
+
+#[test]
+fn reads_the_babe_keys() {
+	assert_eq!(
+		RelayRandomness::relay_state_keys(),
+		vec![relay_epoch_index_key(), relay_epoch_randomness_key()],
+	);
+}
+
+#[test]
+fn stores_the_randomness_of_a_new_epoch() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(RelayRandomness::random(b"subject").1, 0);
+
+		RelayRandomness::on_relay_state(&relay_state_proof(Some((1, [1; 32]))));
+		assert_eq!(RelayRandomness::epoch_index(), Some(1));
+		assert_eq!(RelayRandomness::epoch_randomness(), Some(([1; 32], 1)));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::RelayRandomness(crate::Event::NewEpochRandomness(1)),
+		);
+
+		// the randomness of an epoch is only read once
+		System::set_block_number(2);
+		RelayRandomness::on_relay_state(&relay_state_proof(Some((1, [2; 32]))));
+		assert_eq!(RelayRandomness::epoch_randomness(), Some(([1; 32], 1)));
+
+		System::set_block_number(3);
+		RelayRandomness::on_relay_state(&relay_state_proof(Some((2, [2; 32]))));
+		assert_eq!(RelayRandomness::epoch_index(), Some(2));
+		assert_eq!(RelayRandomness::epoch_randomness(), Some(([2; 32], 3)));
+	});
+}
+
+#[test]
+fn keeps_the_previous_randomness_without_the_keys() {
+	new_test_ext().execute_with(|| {
+		RelayRandomness::on_relay_state(&relay_state_proof(Some((1, [1; 32]))));
+
+		System::set_block_number(2);
+		RelayRandomness::on_relay_state(&relay_state_proof(None));
+		assert_eq!(RelayRandomness::epoch_index(), Some(1));
+		assert_eq!(RelayRandomness::epoch_randomness(), Some(([1; 32], 1)));
+	});
+}
+
+#[test]
+fn random_is_unusable_before_the_first_epoch() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(RelayRandomness::random(b"first"), (Default::default(), 0));
+		assert_eq!(RelayRandomness::random(b"second"), (Default::default(), 0));
+
+		// still unusable if the keys are missing from the proof
+		RelayRandomness::on_relay_state(&relay_state_proof(None));
+		assert_eq!(RelayRandomness::random(b"first"), (Default::default(), 0));
+	});
+}
+
+#[test]
+fn random_is_deterministic() {
+	new_test_ext().execute_with(|| {
+		RelayRandomness::on_relay_state(&relay_state_proof(Some((1, [1; 32]))));
+		let (first, known_since) = RelayRandomness::random(b"first");
+		assert_eq!(known_since, 1);
+		assert_eq!(first, BlakeTwo256::hash_of(&([1u8; 32], &b"first"[..])));
+		assert_eq!(RelayRandomness::random(b"first").0, first);
+		assert_ne!(RelayRandomness::random(b"second").0, first);
+
+		System::set_block_number(2);
+		RelayRandomness::on_relay_state(&relay_state_proof(Some((2, [2; 32]))));
+		assert_ne!(RelayRandomness::random(b"first").0, first);
+	});
+}
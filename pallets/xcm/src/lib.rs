@@ -14,21 +14,33 @@
 // You should have received a copy of the GNU General Public License
 // along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Pallet for stuff specific to parachains' usage of XCM. Right now that's just the origin
-//! used by parachains when receiving `Transact` messages from other parachains or the Relay chain
-//! which must be natively represented.
+//! Pallet for stuff specific to parachains' usage of XCM. That's the origin used by parachains
+//! when receiving `Transact` messages from other parachains or the Relay chain which must be
+//! natively represented, and the allow-list of the calls such messages may dispatch.
+//!
+//! The allow-list is enforced by wrapping the XCM barrier in [`AllowedTransacts`]. Wrapping the
+//! XCM executor in [`AuditedXcmExecutor`] records every `Transact` along with the outcome of its
+//! message. Runtimes which don't wrap their barrier should leave the `Call` and `Storage` of the
+//! pallet out of `construct_runtime`, as the allow-list would have no effect there.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use sp_std::{prelude::*, convert::TryFrom};
+use sp_std::{prelude::*, convert::TryFrom, marker::PhantomData};
 use cumulus_primitives_core::{ParaId, DmpMessageHandler};
 use cumulus_primitives_core::relay_chain::BlockNumber as RelayBlockNumber;
 use codec::{Encode, Decode};
-use sp_runtime::traits::BadOrigin;
-use xcm::{VersionedXcm, v0::{Xcm, Junction, Outcome, ExecuteXcm}};
-use frame_support::dispatch::Weight;
+use sp_runtime::{RuntimeDebug, traits::BadOrigin};
+use xcm::{VersionedXcm, v0::{Xcm, Junction, MultiLocation, Order, OriginKind, Outcome, ExecuteXcm}};
+use xcm_executor::traits::ShouldExecute;
+use frame_support::{dispatch::Weight, traits::Contains};
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -46,17 +58,84 @@ pub mod pallet {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
 		type XcmExecutor: ExecuteXcm<Self::Call>;
+
+		/// The `(origin_type, pallet_index, call_index)` of the calls that `Transact` may always
+		/// dispatch, in addition to those in `AllowedCalls`.
+		type BaseAllowedCalls: Contains<(OriginKind, u8, u8)>;
+
+		/// The origin which may change `AllowedCalls`.
+		type ControllerOrigin: EnsureOrigin<Self::Origin>;
 	}
 
 	#[pallet::error]
 	pub enum Error<T> {
+		/// The call is already allowed for the origin kind.
+		AlreadyAllowed,
+		/// The call is not allowed for the origin kind.
+		NotAllowed,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
 
 	#[pallet::call]
-	impl<T: Config> Pallet<T> {}
+	impl<T: Config> Pallet<T> {
+		/// Allow `Transact` with `origin_type` to dispatch the call `call_index` of the pallet
+		/// `pallet_index`, or every call of that pallet if `call_index` is `None`.
+		///
+		/// - `origin`: Must pass `ControllerOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn allow_call(
+			origin: OriginFor<T>,
+			origin_type: OriginKind,
+			pallet_index: u8,
+			call_index: Option<u8>,
+		) -> DispatchResultWithPostInfo {
+			T::ControllerOrigin::ensure_origin(origin)?;
+			ensure!(
+				!<AllowedCalls<T>>::contains_key(origin_type, (pallet_index, call_index)),
+				Error::<T>::AlreadyAllowed,
+			);
+
+			<AllowedCalls<T>>::insert(origin_type, (pallet_index, call_index), ());
+			Self::deposit_event(Event::CallAllowed(origin_type, pallet_index, call_index));
+			Ok(().into())
+		}
+
+		/// Revoke an entry previously added with `allow_call`.
+		///
+		/// - `origin`: Must pass `ControllerOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn disallow_call(
+			origin: OriginFor<T>,
+			origin_type: OriginKind,
+			pallet_index: u8,
+			call_index: Option<u8>,
+		) -> DispatchResultWithPostInfo {
+			T::ControllerOrigin::ensure_origin(origin)?;
+			ensure!(
+				<AllowedCalls<T>>::contains_key(origin_type, (pallet_index, call_index)),
+				Error::<T>::NotAllowed,
+			);
+
+			<AllowedCalls<T>>::remove(origin_type, (pallet_index, call_index));
+			Self::deposit_event(Event::CallDisallowed(origin_type, pallet_index, call_index));
+			Ok(().into())
+		}
+	}
+
+	/// The calls that `Transact` may dispatch per origin kind, keyed by pallet index and call
+	/// index. A `None` call index allows every call of the pallet.
+	#[pallet::storage]
+	pub(super) type AllowedCalls<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		OriginKind,
+		Twox64Concat,
+		(u8, Option<u8>),
+		(),
+		OptionQuery,
+	>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -71,6 +150,146 @@ pub mod pallet {
 		/// Downward message executed with the given outcome.
 		/// \[ id, outcome \]
 		ExecutedDownward([u8; 8], Outcome),
+		/// A call was allowed for `Transact`.
+		/// \[ origin_type, pallet_index, call_index \]
+		CallAllowed(OriginKind, u8, Option<u8>),
+		/// A call is no longer allowed for `Transact`.
+		/// \[ origin_type, pallet_index, call_index \]
+		CallDisallowed(OriginKind, u8, Option<u8>),
+		/// A message containing a `Transact` was executed with the given outcome. The outcome is
+		/// that of the whole message, so it is an error as well if the call was not allowed.
+		/// \[ origin, origin_type, pallet_index, call_index, outcome \]
+		Transacted(MultiLocation, OriginKind, u8, u8, Outcome),
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Whether `Transact` may dispatch `call`.
+	pub fn is_allowed(call: &TransactCall) -> bool {
+		let TransactCall { origin_type, pallet_index, call_index, .. } = *call;
+		T::BaseAllowedCalls::contains(&(origin_type, pallet_index, call_index)) ||
+			<AllowedCalls<T>>::contains_key(origin_type, (pallet_index, None)) ||
+			<AllowedCalls<T>>::contains_key(origin_type, (pallet_index, Some(call_index)))
+	}
+}
+
+/// How deep `transact_calls` follows `RelayedFrom` and `BuyExecution` before giving up.
+const MAX_NESTING: u32 = 8;
+
+/// A `Transact` instruction of an XCM message.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct TransactCall {
+	/// The location the call is dispatched from.
+	pub origin: MultiLocation,
+	/// The kind of origin the call is dispatched with.
+	pub origin_type: OriginKind,
+	/// The index of the pallet of the call in the runtime.
+	pub pallet_index: u8,
+	/// The index of the call in its pallet.
+	pub call_index: u8,
+}
+
+/// The `Transact` instructions that executing `message` from `origin` would dispatch, including
+/// those of nested messages.
+///
+/// Returns `Err` if a call is too short to hold its indices or the nesting is deeper than
+/// `MAX_NESTING`, since then the message can't be vetted.
+pub fn transact_calls<Call>(
+	origin: &MultiLocation,
+	message: &Xcm<Call>,
+) -> Result<Vec<TransactCall>, ()> {
+	let mut calls = Vec::new();
+	collect_transact_calls(origin, message, 0, &mut calls)?;
+	Ok(calls)
+}
+
+fn collect_transact_calls<Call>(
+	origin: &MultiLocation,
+	message: &Xcm<Call>,
+	depth: u32,
+	calls: &mut Vec<TransactCall>,
+) -> Result<(), ()> {
+	if depth > MAX_NESTING {
+		return Err(())
+	}
+
+	match message {
+		Xcm::Transact { origin_type, call, .. } => {
+			// The call is not decoded yet, but its first two bytes are the pallet and call index.
+			let encoded = Vec::<u8>::decode(&mut &call.encode()[..]).map_err(|_| ())?;
+			match encoded[..] {
+				[pallet_index, call_index, ..] => calls.push(TransactCall {
+					origin: origin.clone(),
+					origin_type: *origin_type,
+					pallet_index,
+					call_index,
+				}),
+				_ => return Err(()),
+			}
+		}
+		Xcm::RelayedFrom { who, message } => {
+			let mut origin = origin.clone();
+			origin.append_with(who.clone()).map_err(|_| ())?;
+			collect_transact_calls(&origin, message, depth + 1, calls)?;
+		}
+		Xcm::WithdrawAsset { effects, .. } |
+		Xcm::ReserveAssetDeposit { effects, .. } |
+		Xcm::TeleportAsset { effects, .. } => {
+			for effect in effects {
+				if let Order::BuyExecution { xcm, .. } = effect {
+					for message in xcm {
+						collect_transact_calls(origin, message, depth + 1, calls)?;
+					}
+				}
+			}
+		}
+		_ => {}
+	}
+
+	Ok(())
+}
+
+/// Barrier which rejects any message with a `Transact` that is not allowed by `Pallet::is_allowed`
+/// and otherwise defers to `Inner`.
+pub struct AllowedTransacts<T, Inner>(PhantomData<(T, Inner)>);
+impl<T: Config, Inner: ShouldExecute> ShouldExecute for AllowedTransacts<T, Inner> {
+	fn should_execute<Call>(
+		origin: &MultiLocation,
+		top_level: bool,
+		message: &Xcm<Call>,
+		shallow_weight: Weight,
+		weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		if !transact_calls(origin, message)?.iter().all(Pallet::<T>::is_allowed) {
+			return Err(())
+		}
+		Inner::should_execute(origin, top_level, message, shallow_weight, weight_credit)
+	}
+}
+
+/// Adapts the XCM executor `Inner` to deposit a `Transacted` event for every `Transact` of an
+/// executed message.
+pub struct AuditedXcmExecutor<T, Inner>(PhantomData<(T, Inner)>);
+impl<T: Config, Inner: ExecuteXcm<T::Call>> ExecuteXcm<T::Call> for AuditedXcmExecutor<T, Inner> {
+	fn execute_xcm_in_credit(
+		origin: MultiLocation,
+		message: Xcm<T::Call>,
+		weight_limit: Weight,
+		weight_credit: Weight,
+	) -> Outcome {
+		// A message whose calls can't be listed is rejected by `AllowedTransacts` anyway.
+		let calls = transact_calls(&origin, &message).unwrap_or_default();
+		let outcome = Inner::execute_xcm_in_credit(origin, message, weight_limit, weight_credit);
+		for call in calls {
+			Pallet::<T>::deposit_event(Event::Transacted(
+				call.origin,
+				call.origin_type,
+				call.pallet_index,
+				call.call_index,
+				outcome.clone(),
+			));
+		}
+		outcome
 	}
 }
 
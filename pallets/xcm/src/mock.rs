@@ -0,0 +1,124 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate as cumulus_pallet_xcm;
+use frame_support::{parameter_types, traits::Contains, weights::Weight};
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
+use sp_runtime::testing::Header;
+use xcm::v0::{MultiLocation, OriginKind, Outcome, Xcm, ExecuteXcm};
+use xcm_executor::traits::ShouldExecute;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		CumulusXcm: cumulus_pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type BlockLength = ();
+	type BlockWeights = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+/// The pallet index of [`BASE_CALL`].
+pub const BASE_PALLET: u8 = 99;
+/// The call `Transact` may always dispatch with `OriginKind::Native`.
+pub const BASE_CALL: u8 = 1;
+
+pub struct BaseAllowedCalls;
+impl Contains<(OriginKind, u8, u8)> for BaseAllowedCalls {
+	fn contains(call: &(OriginKind, u8, u8)) -> bool {
+		*call == (OriginKind::Native, BASE_PALLET, BASE_CALL)
+	}
+}
+
+/// Completes every message, using all of its weight limit.
+pub struct TestExecutor;
+impl ExecuteXcm<Call> for TestExecutor {
+	fn execute_xcm_in_credit(
+		_origin: MultiLocation,
+		_message: Xcm<Call>,
+		weight_limit: Weight,
+		_credit: Weight,
+	) -> Outcome {
+		Outcome::Complete(weight_limit)
+	}
+}
+
+/// Only lets top level messages through.
+pub struct AllowTopLevel;
+impl ShouldExecute for AllowTopLevel {
+	fn should_execute<Call>(
+		_origin: &MultiLocation,
+		top_level: bool,
+		_message: &Xcm<Call>,
+		_shallow_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		if top_level { Ok(()) } else { Err(()) }
+	}
+}
+
+pub type Barrier = cumulus_pallet_xcm::AllowedTransacts<Test, AllowTopLevel>;
+
+pub type AuditedExecutor = cumulus_pallet_xcm::AuditedXcmExecutor<Test, TestExecutor>;
+
+impl cumulus_pallet_xcm::Config for Test {
+	type Event = Event;
+	type XcmExecutor = TestExecutor;
+	type BaseAllowedCalls = BaseAllowedCalls;
+	type ControllerOrigin = frame_system::EnsureRoot<u64>;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
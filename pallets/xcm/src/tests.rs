@@ -0,0 +1,292 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::mock::{
+	self, new_test_ext, AuditedExecutor, Barrier, CumulusXcm, System, Test, BASE_CALL, BASE_PALLET,
+};
+use frame_support::{assert_noop, assert_ok};
+use xcm::v0::{Junction::*, MultiAsset, MultiLocation::*};
+
+fn transact(origin_type: OriginKind, call: Vec<u8>) -> Xcm<()> {
+	Xcm::Transact { origin_type, require_weight_at_most: 1_000, call: call.into() }
+}
+
+fn buy_execution(xcm: Vec<Xcm<()>>) -> Order<()> {
+	Order::BuyExecution {
+		fees: MultiAsset::All,
+		weight: 0,
+		debt: 1_000,
+		halt_on_error: true,
+		xcm,
+	}
+}
+
+fn sibling() -> MultiLocation {
+	X2(Parent, Parachain(2000))
+}
+
+fn call(origin_type: OriginKind, pallet_index: u8, call_index: u8) -> TransactCall {
+	TransactCall { origin: sibling(), origin_type, pallet_index, call_index }
+}
+
+fn xcm_events() -> Vec<Event<Test>> {
+	System::events()
+		.into_iter()
+		.filter_map(|r| if let mock::Event::CumulusXcm(e) = r.event { Some(e) } else { None })
+		.collect()
+}
+
+fn should_execute(message: &Xcm<()>, top_level: bool) -> Result<(), ()> {
+	Barrier::should_execute(&sibling(), top_level, message, 0, &mut 0)
+}
+
+#[test]
+fn transact_calls_reads_indices_of_top_level_transact() {
+	let message = transact(OriginKind::SovereignAccount, vec![15, 3, 42, 42]);
+
+	assert_eq!(
+		transact_calls(&sibling(), &message),
+		Ok(vec![TransactCall {
+			origin: sibling(),
+			origin_type: OriginKind::SovereignAccount,
+			pallet_index: 15,
+			call_index: 3,
+		}]),
+	);
+}
+
+#[test]
+fn transact_calls_follows_nested_messages() {
+	let account = AccountId32 { network: xcm::v0::NetworkId::Any, id: [1; 32] };
+	let message = Xcm::WithdrawAsset {
+		assets: vec![MultiAsset::All],
+		effects: vec![buy_execution(vec![
+			transact(OriginKind::Native, vec![99, 1]),
+			Xcm::RelayedFrom {
+				who: X1(account.clone()),
+				message: Box::new(transact(OriginKind::SovereignAccount, vec![15, 0])),
+			},
+		])],
+	};
+
+	assert_eq!(
+		transact_calls(&sibling(), &message),
+		Ok(vec![
+			TransactCall {
+				origin: sibling(),
+				origin_type: OriginKind::Native,
+				pallet_index: 99,
+				call_index: 1,
+			},
+			TransactCall {
+				origin: X3(Parent, Parachain(2000), account),
+				origin_type: OriginKind::SovereignAccount,
+				pallet_index: 15,
+				call_index: 0,
+			},
+		]),
+	);
+}
+
+#[test]
+fn transact_calls_ignores_messages_without_transact() {
+	let message = Xcm::<()>::WithdrawAsset { assets: vec![MultiAsset::All], effects: vec![] };
+
+	assert_eq!(transact_calls(&sibling(), &message), Ok(vec![]));
+}
+
+#[test]
+fn transact_calls_rejects_truncated_call() {
+	let message = transact(OriginKind::Superuser, vec![15]);
+
+	assert_eq!(transact_calls(&X1(Parent), &message), Err(()));
+}
+
+#[test]
+fn transact_calls_rejects_deep_nesting() {
+	let mut message = transact(OriginKind::Native, vec![99, 1]);
+	for _ in 0..=MAX_NESTING {
+		message = Xcm::RelayedFrom { who: Null, message: Box::new(message) };
+	}
+
+	assert_eq!(transact_calls(&Null, &message), Err(()));
+}
+
+#[test]
+fn is_allowed_checks_base_and_allowed_calls() {
+	new_test_ext().execute_with(|| {
+		assert!(CumulusXcm::is_allowed(&call(OriginKind::Native, BASE_PALLET, BASE_CALL)));
+		assert!(!CumulusXcm::is_allowed(&call(OriginKind::Superuser, BASE_PALLET, BASE_CALL)));
+		assert!(!CumulusXcm::is_allowed(&call(OriginKind::Native, BASE_PALLET, 2)));
+
+		// a single call.
+		assert_ok!(CumulusXcm::allow_call(mock::Origin::root(), OriginKind::SovereignAccount, 15, Some(3)));
+		assert!(CumulusXcm::is_allowed(&call(OriginKind::SovereignAccount, 15, 3)));
+		assert!(!CumulusXcm::is_allowed(&call(OriginKind::SovereignAccount, 15, 4)));
+		assert!(!CumulusXcm::is_allowed(&call(OriginKind::Native, 15, 3)));
+
+		// every call of a pallet.
+		assert_ok!(CumulusXcm::allow_call(mock::Origin::root(), OriginKind::Native, 16, None));
+		assert!(CumulusXcm::is_allowed(&call(OriginKind::Native, 16, 0)));
+		assert!(CumulusXcm::is_allowed(&call(OriginKind::Native, 16, 255)));
+		assert!(!CumulusXcm::is_allowed(&call(OriginKind::Xcm, 16, 0)));
+	});
+}
+
+#[test]
+fn only_the_controller_can_change_allowed_calls() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			CumulusXcm::allow_call(mock::Origin::signed(1), OriginKind::Native, 15, None),
+			BadOrigin,
+		);
+		assert_noop!(
+			CumulusXcm::disallow_call(mock::Origin::signed(1), OriginKind::Native, 15, None),
+			BadOrigin,
+		);
+		assert_noop!(
+			CumulusXcm::allow_call(Origin::SiblingParachain(2000.into()).into(), OriginKind::Native, 15, None),
+			BadOrigin,
+		);
+	});
+}
+
+#[test]
+fn allow_and_disallow_call_work() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			CumulusXcm::disallow_call(mock::Origin::root(), OriginKind::Native, 15, None),
+			Error::<Test>::NotAllowed,
+		);
+
+		assert_ok!(CumulusXcm::allow_call(mock::Origin::root(), OriginKind::Native, 15, None));
+		assert_eq!(xcm_events().last(), Some(&Event::CallAllowed(OriginKind::Native, 15, None)));
+		assert_noop!(
+			CumulusXcm::allow_call(mock::Origin::root(), OriginKind::Native, 15, None),
+			Error::<Test>::AlreadyAllowed,
+		);
+		// the pallet-wide entry is independent of the entries of its calls.
+		assert_noop!(
+			CumulusXcm::disallow_call(mock::Origin::root(), OriginKind::Native, 15, Some(0)),
+			Error::<Test>::NotAllowed,
+		);
+
+		assert_ok!(CumulusXcm::disallow_call(mock::Origin::root(), OriginKind::Native, 15, None));
+		assert_eq!(xcm_events().last(), Some(&Event::CallDisallowed(OriginKind::Native, 15, None)));
+		assert!(!CumulusXcm::is_allowed(&call(OriginKind::Native, 15, 0)));
+	});
+}
+
+#[test]
+fn barrier_lets_allowed_transact_through() {
+	new_test_ext().execute_with(|| {
+		let message = transact(OriginKind::Native, vec![BASE_PALLET, BASE_CALL]);
+		assert_eq!(should_execute(&message, true), Ok(()));
+
+		assert_ok!(CumulusXcm::allow_call(mock::Origin::root(), OriginKind::SovereignAccount, 15, Some(3)));
+		let message = Xcm::WithdrawAsset {
+			assets: vec![MultiAsset::All],
+			effects: vec![buy_execution(vec![transact(OriginKind::SovereignAccount, vec![15, 3])])],
+		};
+		assert_eq!(should_execute(&message, true), Ok(()));
+	});
+}
+
+#[test]
+fn barrier_rejects_transact_that_is_not_allowed() {
+	new_test_ext().execute_with(|| {
+		let message = transact(OriginKind::Superuser, vec![BASE_PALLET, BASE_CALL]);
+		assert_eq!(should_execute(&message, true), Err(()));
+
+		// a single call that is not allowed rejects the whole message.
+		let message = Xcm::WithdrawAsset {
+			assets: vec![MultiAsset::All],
+			effects: vec![buy_execution(vec![
+				transact(OriginKind::Native, vec![BASE_PALLET, BASE_CALL]),
+				transact(OriginKind::Native, vec![15, 0]),
+			])],
+		};
+		assert_eq!(should_execute(&message, true), Err(()));
+
+		// as does a call that can't be vetted.
+		assert_eq!(should_execute(&transact(OriginKind::Native, vec![BASE_PALLET]), true), Err(()));
+
+		// until it is allowed.
+		assert_ok!(CumulusXcm::allow_call(mock::Origin::root(), OriginKind::Native, 15, None));
+		assert_eq!(should_execute(&message, true), Ok(()));
+	});
+}
+
+#[test]
+fn barrier_defers_to_inner_barrier() {
+	new_test_ext().execute_with(|| {
+		let message = Xcm::<()>::WithdrawAsset { assets: vec![MultiAsset::All], effects: vec![] };
+		assert_eq!(should_execute(&message, true), Ok(()));
+		assert_eq!(should_execute(&message, false), Err(()));
+
+		let message = transact(OriginKind::Native, vec![BASE_PALLET, BASE_CALL]);
+		assert_eq!(should_execute(&message, false), Err(()));
+	});
+}
+
+#[test]
+fn audited_executor_records_every_transact() {
+	new_test_ext().execute_with(|| {
+		let transact = |origin_type, call: Vec<u8>| Xcm::<mock::Call>::Transact {
+			origin_type,
+			require_weight_at_most: 1_000,
+			call: call.into(),
+		};
+		let message = Xcm::RelayedFrom {
+			who: X1(Parachain(2001)),
+			message: Box::new(Xcm::WithdrawAsset {
+				assets: vec![MultiAsset::All],
+				effects: vec![Order::BuyExecution {
+					fees: MultiAsset::All,
+					weight: 0,
+					debt: 1_000,
+					halt_on_error: true,
+					xcm: vec![
+						transact(OriginKind::Native, vec![BASE_PALLET, BASE_CALL]),
+						transact(OriginKind::SovereignAccount, vec![15, 3]),
+					],
+				}],
+			}),
+		};
+
+		let outcome = AuditedExecutor::execute_xcm(X1(Parent), message, 100);
+		assert_eq!(outcome, Outcome::Complete(100));
+		let origin = X2(Parent, Parachain(2001));
+		assert_eq!(
+			xcm_events(),
+			vec![
+				Event::Transacted(origin.clone(), OriginKind::Native, BASE_PALLET, BASE_CALL, outcome.clone()),
+				Event::Transacted(origin, OriginKind::SovereignAccount, 15, 3, outcome),
+			],
+		);
+	});
+}
+
+#[test]
+fn audited_executor_ignores_messages_without_transact() {
+	new_test_ext().execute_with(|| {
+		let message = Xcm::<mock::Call>::WithdrawAsset { assets: vec![MultiAsset::All], effects: vec![] };
+
+		assert_eq!(AuditedExecutor::execute_xcm(X1(Parent), message, 100), Outcome::Complete(100));
+		assert!(xcm_events().is_empty());
+	});
+}
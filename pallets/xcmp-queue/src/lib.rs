@@ -30,7 +30,7 @@ use cumulus_primitives_core::{
 	relay_chain::BlockNumber as RelayBlockNumber, ChannelStatus, GetChannelInfo, MessageSendError,
 	ParaId, XcmpMessageHandler, XcmpMessageSource,
 };
-use frame_support::weights::Weight;
+use frame_support::{traits::EnsureOrigin, weights::{constants::WEIGHT_PER_MILLIS, Weight}};
 use rand_chacha::{
 	rand_core::{RngCore, SeedableRng},
 	ChaChaRng,
@@ -42,12 +42,20 @@ use xcm::{
 	VersionedXcm,
 };
 
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 pub use pallet::*;
 
+/// Index used to identify overweight XCMs.
+pub type OverweightIndex = u64;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
-	use frame_support::pallet_prelude::*;
+	use frame_support::{pallet_prelude::*, transactional};
 	use frame_system::pallet_prelude::*;
 
 	#[pallet::pallet]
@@ -63,6 +71,13 @@ pub mod pallet {
 
 		/// Information on the avaialble XCMP channels.
 		type ChannelInfo: GetChannelInfo;
+
+		/// Origin which is allowed to execute overweight messages.
+		type ExecuteOverweightOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Origin which is allowed to suspend and resume individual channels and to set the
+		/// inbound quotas of the siblings.
+		type ControllerOrigin: EnsureOrigin<Self::Origin>;
 	}
 
 	impl Default for QueueConfigData {
@@ -73,6 +88,7 @@ pub mod pallet {
 				resume_threshold: 1,
 				threshold_weight: 100_000,
 				weight_restrict_decay: 2,
+				xcmp_max_individual_weight: 20 * WEIGHT_PER_MILLIS,
 			}
 		}
 	}
@@ -86,7 +102,226 @@ pub mod pallet {
 	}
 
 	#[pallet::call]
-	impl<T: Config> Pallet<T> {}
+	impl<T: Config> Pallet<T> {
+		/// Service a single overweight XCM.
+		///
+		/// - `origin`: Must pass `ExecuteOverweightOrigin`.
+		/// - `index`: The index of the overweight XCM to service
+		/// - `weight_limit`: The amount of weight that XCM execution may take.
+		///
+		/// Errors:
+		/// - `BadOverweightIndex`: XCM under `index` is not found in the `Overweight` storage map.
+		/// - `BadXcm`: XCM under `index` cannot be properly decoded into a valid XCM format.
+		/// - `WeightOverLimit`: XCM execution may use greater `weight_limit`.
+		///
+		/// Events:
+		/// - `OverweightServiced`: On success.
+		#[pallet::weight(weight_limit.saturating_add(1_000_000))]
+		#[transactional]
+		pub fn service_overweight(
+			origin: OriginFor<T>,
+			index: OverweightIndex,
+			weight_limit: Weight,
+		) -> DispatchResultWithPostInfo {
+			T::ExecuteOverweightOrigin::ensure_origin(origin)?;
+
+			let (sender, sent_at, data) =
+				Overweight::<T>::get(index).ok_or(Error::<T>::BadOverweightIndex)?;
+			let xcm = VersionedXcm::<T::Call>::decode(&mut &data[..])
+				.map_err(|_| Error::<T>::BadXcm)?;
+			let used = match Self::handle_xcm_message(sender, sent_at, xcm, weight_limit) {
+				Ok(used) => used,
+				Err(XcmError::WeightLimitReached(_)) | Err(XcmError::TooMuchWeightRequired) => {
+					return Err(Error::<T>::WeightOverLimit.into())
+				}
+				// The message was executed and failed, there is no point in keeping it around.
+				Err(_) => 0,
+			};
+			Overweight::<T>::remove(index);
+			Self::deposit_event(Event::OverweightServiced(index, used));
+			Ok(Some(used.saturating_add(1_000_000)).into())
+		}
+
+		/// Overwrites the number of pages of messages which must be in the queue for the other
+		/// side to be told to suspend their sending.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `new`: Desired value for `QueueConfigData.suspend_threshold`
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn update_suspend_threshold(origin: OriginFor<T>, new: u32) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::update_queue_config(|data| data.suspend_threshold = new)?;
+			Ok(().into())
+		}
+
+		/// Overwrites the number of pages of messages which must be in the queue after which we
+		/// drop any further messages from the channel.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `new`: Desired value for `QueueConfigData.drop_threshold`
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn update_drop_threshold(origin: OriginFor<T>, new: u32) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::update_queue_config(|data| data.drop_threshold = new)?;
+			Ok(().into())
+		}
+
+		/// Overwrites the number of pages of messages which the queue must be reduced to before
+		/// it signals that message sending may recommence after it has been suspended.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `new`: Desired value for `QueueConfigData.resume_threshold`
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn update_resume_threshold(origin: OriginFor<T>, new: u32) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::update_queue_config(|data| data.resume_threshold = new)?;
+			Ok(().into())
+		}
+
+		/// Overwrites the amount of remaining weight under which we stop processing messages.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `new`: Desired value for `QueueConfigData.threshold_weight`
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn update_threshold_weight(origin: OriginFor<T>, new: Weight) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::update_queue_config(|data| data.threshold_weight = new)?;
+			Ok(().into())
+		}
+
+		/// Overwrites the speed to which the available weight approaches the maximum weight.
+		/// A lower number results in a faster progression. A value of 1 makes the entire weight
+		/// available initially.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `new`: Desired value for `QueueConfigData.weight_restrict_decay`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn update_weight_restrict_decay(origin: OriginFor<T>, new: Weight) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::update_queue_config(|data| data.weight_restrict_decay = new)?;
+			Ok(().into())
+		}
+
+		/// Overwrite the maximum amount of weight any individual message may consume.
+		/// Messages above this weight go into the overweight queue and may only be serviced
+		/// explicitly.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `new`: Desired value for `QueueConfigData.xcmp_max_individual_weight`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn update_xcmp_max_individual_weight(origin: OriginFor<T>, new: Weight) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::update_queue_config(|data| data.xcmp_max_individual_weight = new)?;
+			Ok(().into())
+		}
+
+		/// Stop executing the messages received from `para`.
+		///
+		/// Incoming messages are still queued, up to `drop_threshold` pages, and `para` is signalled
+		/// to suspend its sending. None of them are executed until `resume_inbound` is called.
+		///
+		/// - `origin`: Must pass `ControllerOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 4))]
+		pub fn suspend_inbound(origin: OriginFor<T>, para: ParaId) -> DispatchResultWithPostInfo {
+			T::ControllerOrigin::ensure_origin(origin)?;
+			ensure!(!<InboundSuspended<T>>::get(para), Error::<T>::AlreadySuspended);
+
+			<InboundSuspended<T>>::insert(para, true);
+			let signalled = <InboundXcmpStatus<T>>::mutate(|status| {
+				match status.binary_search_by_key(&para, |item| item.0) {
+					Ok(i) if status[i].1 == InboundStatus::Suspended => true,
+					Ok(i) => {
+						status[i].1 = InboundStatus::Suspended;
+						false
+					}
+					Err(_) => false,
+				}
+			});
+			if !signalled && Self::send_signal(para, ChannelSignal::Suspend).is_err() {
+				log::warn!("Attempt to suspend channel failed. Messages may be dropped.");
+			}
+
+			Self::deposit_event(Event::InboundChannelSuspended(para));
+			Ok(().into())
+		}
+
+		/// Resume executing the messages received from `para` after `suspend_inbound`.
+		///
+		/// If messages from `para` are still queued, `para` is signalled to resume its sending once
+		/// the queue is reduced to `resume_threshold`, like for any other suspended channel.
+		///
+		/// - `origin`: Must pass `ControllerOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn resume_inbound(origin: OriginFor<T>, para: ParaId) -> DispatchResultWithPostInfo {
+			T::ControllerOrigin::ensure_origin(origin)?;
+			ensure!(<InboundSuspended<T>>::get(para), Error::<T>::NotSuspended);
+
+			<InboundSuspended<T>>::remove(para);
+			let status = <InboundXcmpStatus<T>>::get();
+			let queued = match status.binary_search_by_key(&para, |item| item.0) {
+				Ok(i) => status[i].1 == InboundStatus::Suspended,
+				Err(_) => false,
+			};
+			if !queued && Self::send_signal(para, ChannelSignal::Resume).is_err() {
+				log::warn!("Attempt to resume channel failed.");
+			}
+
+			Self::deposit_event(Event::InboundChannelResumed(para));
+			Ok(().into())
+		}
+
+		/// Stop sending the messages queued for `para`, regardless of the signals received from it.
+		/// Channel signals are still sent.
+		///
+		/// - `origin`: Must pass `ControllerOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn suspend_outbound(origin: OriginFor<T>, para: ParaId) -> DispatchResultWithPostInfo {
+			T::ControllerOrigin::ensure_origin(origin)?;
+			ensure!(!<OutboundSuspended<T>>::get(para), Error::<T>::AlreadySuspended);
+
+			<OutboundSuspended<T>>::insert(para, true);
+			Self::deposit_event(Event::OutboundChannelSuspended(para));
+			Ok(().into())
+		}
+
+		/// Resume sending the messages queued for `para` after `suspend_outbound`.
+		///
+		/// - `origin`: Must pass `ControllerOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn resume_outbound(origin: OriginFor<T>, para: ParaId) -> DispatchResultWithPostInfo {
+			T::ControllerOrigin::ensure_origin(origin)?;
+			ensure!(<OutboundSuspended<T>>::get(para), Error::<T>::NotSuspended);
+
+			<OutboundSuspended<T>>::remove(para);
+			Self::deposit_event(Event::OutboundChannelResumed(para));
+			Ok(().into())
+		}
+
+		/// Set the number of messages and the amount of weight of the messages from `para` which
+		/// may be executed per block, or remove the quota with `None`.
+		///
+		/// Messages above the quota stay queued for the following blocks.
+		///
+		/// - `origin`: Must pass `ControllerOrigin`.
+		#[pallet::weight(T::DbWeight::get().writes(2))]
+		pub fn set_inbound_quota(
+			origin: OriginFor<T>,
+			para: ParaId,
+			quota: Option<InboundQuota>,
+		) -> DispatchResultWithPostInfo {
+			T::ControllerOrigin::ensure_origin(origin)?;
+
+			match quota {
+				Some(quota) => <InboundQuotas<T>>::insert(para, quota),
+				None => {
+					<InboundQuotas<T>>::remove(para);
+					<InboundUsage<T>>::remove(para);
+				}
+			}
+			Self::deposit_event(Event::InboundQuotaSet(para, quota));
+			Ok(().into())
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -104,6 +339,24 @@ pub mod pallet {
 		UpwardMessageSent(Option<T::Hash>),
 		/// An HRMP message was sent to a sibling parachain.
 		XcmpMessageSent(Option<T::Hash>),
+		/// An XCM exceeded the individual message weight budget.
+		/// \[ sender, sent_at, index, required \]
+		OverweightEnqueued(ParaId, RelayBlockNumber, OverweightIndex, Weight),
+		/// An XCM from the overweight queue was executed with the given actual weight used.
+		/// \[ index, used \]
+		OverweightServiced(OverweightIndex, Weight),
+		/// The queue configuration was updated.
+		QueueConfigUpdated(QueueConfigData),
+		/// The messages from a sibling are no longer executed. \[ para \]
+		InboundChannelSuspended(ParaId),
+		/// The messages from a sibling are executed again. \[ para \]
+		InboundChannelResumed(ParaId),
+		/// The messages to a sibling are no longer sent. \[ para \]
+		OutboundChannelSuspended(ParaId),
+		/// The messages to a sibling are sent again. \[ para \]
+		OutboundChannelResumed(ParaId),
+		/// The inbound quota of a sibling was set or removed. \[ para, quota \]
+		InboundQuotaSet(ParaId, Option<InboundQuota>),
 	}
 
 	#[pallet::error]
@@ -114,6 +367,16 @@ pub mod pallet {
 		BadXcmOrigin,
 		/// Bad XCM data.
 		BadXcm,
+		/// Bad overweight index.
+		BadOverweightIndex,
+		/// Provided weight is possibly not enough to execute the message.
+		WeightOverLimit,
+		/// The thresholds must satisfy `resume_threshold < suspend_threshold <= drop_threshold`.
+		BadQueueConfig,
+		/// The channel is already suspended by the controller.
+		AlreadySuspended,
+		/// The channel is not suspended by the controller.
+		NotSuspended,
 	}
 
 	/// Status of the inbound XCMP channels.
@@ -162,8 +425,52 @@ pub mod pallet {
 		StorageMap<_, Blake2_128Concat, ParaId, Vec<u8>, ValueQuery>;
 
 	/// The configuration which controls the dynamics of the outbound queue.
+	///
+	/// NOTE: `QueueConfigData` gained `xcmp_max_individual_weight` along with the setters. Before
+	/// that this value was never written, so there is nothing to migrate.
 	#[pallet::storage]
 	pub(super) type QueueConfig<T: Config> = StorageValue<_, QueueConfigData, ValueQuery>;
+
+	/// The messages that exceeded max individual message weight budget.
+	///
+	/// These message stay in this storage map until they are manually dispatched via
+	/// `service_overweight`.
+	#[pallet::storage]
+	pub(super) type Overweight<T: Config> =
+		StorageMap<_, Twox64Concat, OverweightIndex, (ParaId, RelayBlockNumber, Vec<u8>), OptionQuery>;
+
+	/// The number of overweight messages ever recorded in `Overweight`. Also doubles as the next
+	/// available free overweight index.
+	#[pallet::storage]
+	pub(super) type OverweightCount<T: Config> = StorageValue<_, OverweightIndex, ValueQuery>;
+
+	/// The siblings whose inbound messages are not executed, as decided by the controller. This is
+	/// independent of the automatic suspension in `InboundXcmpStatus`.
+	#[pallet::storage]
+	pub(super) type InboundSuspended<T: Config> =
+		StorageMap<_, Blake2_128Concat, ParaId, bool, ValueQuery>;
+
+	/// The siblings whose outbound messages are not sent, as decided by the controller. This is
+	/// independent of the signals received from them.
+	#[pallet::storage]
+	pub(super) type OutboundSuspended<T: Config> =
+		StorageMap<_, Blake2_128Concat, ParaId, bool, ValueQuery>;
+
+	/// The per block quotas of the messages executed from a sibling.
+	#[pallet::storage]
+	pub(super) type InboundQuotas<T: Config> =
+		StorageMap<_, Blake2_128Concat, ParaId, InboundQuota, OptionQuery>;
+
+	/// The block in which the messages of a sibling with a quota were last executed, and the number
+	/// of messages and the weight used in that block.
+	#[pallet::storage]
+	pub(super) type InboundUsage<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		ParaId,
+		(T::BlockNumber, u32, Weight),
+		OptionQuery,
+	>;
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
@@ -194,6 +501,18 @@ pub struct QueueConfigData {
 	/// The speed to which the available weight approaches the maximum weight. A lower number
 	/// results in a faster progression. A value of 1 makes the entire weight available initially.
 	weight_restrict_decay: Weight,
+	/// The maximum amount of weight any individual message may consume. Messages above this weight
+	/// go into the overweight queue and may only be serviced explicitly.
+	xcmp_max_individual_weight: Weight,
+}
+
+/// The limits on the messages of a sibling executed per block.
+#[derive(Copy, Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct InboundQuota {
+	/// The maximum number of messages executed per block.
+	pub max_messages: u32,
+	/// The maximum amount of weight used by the messages per block.
+	pub max_weight: Weight,
 }
 
 #[derive(PartialEq, Eq, Copy, Clone, Encode, Decode)]
@@ -307,6 +626,32 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Apply `f` to the queue configuration, checking that the thresholds stay consistent.
+	fn update_queue_config(f: impl FnOnce(&mut QueueConfigData)) -> Result<(), Error<T>> {
+		let mut data = <QueueConfig<T>>::get();
+		f(&mut data);
+		if data.resume_threshold >= data.suspend_threshold
+			|| data.suspend_threshold > data.drop_threshold
+		{
+			return Err(Error::<T>::BadQueueConfig);
+		}
+		<QueueConfig<T>>::put(data);
+		Self::deposit_event(Event::QueueConfigUpdated(data));
+		Ok(())
+	}
+
+	/// Place an XCM that is too heavy to be executed automatically into the overweight store,
+	/// returning its index.
+	fn stash_overweight(sender: ParaId, sent_at: RelayBlockNumber, xcm: Vec<u8>) -> OverweightIndex {
+		let index = <OverweightCount<T>>::mutate(|count| {
+			let index = *count;
+			*count += 1;
+			index
+		});
+		<Overweight<T>>::insert(index, (sender, sent_at, xcm));
+		index
+	}
+
 	pub fn send_blob_message(recipient: ParaId, blob: Vec<u8>) -> Result<u32, MessageSendError> {
 		Self::send_fragment(recipient, XcmpMessageFormat::ConcatenatedEncodedBlob, blob)
 	}
@@ -381,18 +726,54 @@ impl<T: Config> Pallet<T> {
 		max_weight: Weight,
 	) -> (Weight, bool) {
 		let data = <InboundXcmpMessages<T>>::get(sender, sent_at);
+		let now = frame_system::Pallet::<T>::block_number();
+		let quota = <InboundQuotas<T>>::get(sender);
+		// The number of messages and the weight of the sender already used in this block.
+		let mut usage = match <InboundUsage<T>>::get(sender) {
+			Some((at, messages, weight)) if at == now => (messages, weight),
+			_ => (0, 0),
+		};
+		let quota_exhausted = |usage: &(u32, Weight)| {
+			quota.map_or(false, |q| usage.0 >= q.max_messages || usage.1 >= q.max_weight)
+		};
+		// A message which does not fit in the quota of a single block could never be executed.
+		let max_individual_weight = <QueueConfig<T>>::get().xcmp_max_individual_weight;
+		let max_individual_weight = quota.map_or(max_individual_weight, |q| {
+			q.max_weight.min(max_individual_weight)
+		});
 		let mut last_remaining_fragments;
 		let mut remaining_fragments = &data[..];
 		let mut weight_used = 0;
 		match format {
 			XcmpMessageFormat::ConcatenatedVersionedXcm => {
-				while !remaining_fragments.is_empty() {
+				while !remaining_fragments.is_empty() && !quota_exhausted(&usage) {
 					last_remaining_fragments = remaining_fragments;
 					if let Ok(xcm) = VersionedXcm::<T::Call>::decode(&mut remaining_fragments) {
-						let weight = max_weight - weight_used;
+						let mut weight = max_weight - weight_used;
+						if let Some(quota) = quota {
+							weight = weight.min(quota.max_weight.saturating_sub(usage.1));
+						}
 						match Self::handle_xcm_message(sender, sent_at, xcm, weight) {
-							Ok(used) => weight_used = weight_used.saturating_add(used),
-							Err(XcmError::TooMuchWeightRequired) => {
+							Ok(used) => {
+								weight_used = weight_used.saturating_add(used);
+								usage = (usage.0 + 1, usage.1.saturating_add(used));
+							}
+							Err(XcmError::WeightLimitReached(required))
+								if required > max_individual_weight =>
+							{
+								// Overweight: it would never fit in the weight we are willing to
+								// spend automatically, so move it to the overweight store and go
+								// on with the following messages.
+								let len = last_remaining_fragments.len() - remaining_fragments.len();
+								let xcm = last_remaining_fragments[..len].to_vec();
+								let index = Self::stash_overweight(sender, sent_at, xcm);
+								Self::deposit_event(Event::OverweightEnqueued(
+									sender, sent_at, index, required,
+								));
+								usage.0 += 1;
+							}
+							Err(XcmError::WeightLimitReached(_))
+							| Err(XcmError::TooMuchWeightRequired) => {
 								// That message didn't get processed this time because of being
 								// too heavy. We leave it around for next time and bail.
 								remaining_fragments = last_remaining_fragments;
@@ -400,6 +781,7 @@ impl<T: Config> Pallet<T> {
 							}
 							Err(_) => {
 								// Message looks invalid; don't attempt to retry
+								usage.0 += 1;
 							}
 						}
 					} else {
@@ -409,12 +791,18 @@ impl<T: Config> Pallet<T> {
 				}
 			}
 			XcmpMessageFormat::ConcatenatedEncodedBlob => {
-				while !remaining_fragments.is_empty() {
+				while !remaining_fragments.is_empty() && !quota_exhausted(&usage) {
 					last_remaining_fragments = remaining_fragments;
 					if let Ok(blob) = <Vec<u8>>::decode(&mut remaining_fragments) {
-						let weight = max_weight - weight_used;
+						let mut weight = max_weight - weight_used;
+						if let Some(quota) = quota {
+							weight = weight.min(quota.max_weight.saturating_sub(usage.1));
+						}
 						match Self::handle_blob_message(sender, sent_at, blob, weight) {
-							Ok(used) => weight_used = weight_used.saturating_add(used),
+							Ok(used) => {
+								weight_used = weight_used.saturating_add(used);
+								usage = (usage.0 + 1, usage.1.saturating_add(used));
+							}
 							Err(true) => {
 								// That message didn't get processed this time because of being
 								// too heavy. We leave it around for next time and bail.
@@ -423,6 +811,7 @@ impl<T: Config> Pallet<T> {
 							}
 							Err(false) => {
 								// Message invalid; don't attempt to retry
+								usage.0 += 1;
 							}
 						}
 					} else {
@@ -436,6 +825,9 @@ impl<T: Config> Pallet<T> {
 				remaining_fragments = &b""[..];
 			}
 		}
+		if quota.is_some() {
+			<InboundUsage<T>>::insert(sender, (now, usage.0, usage.1));
+		}
 		let is_empty = remaining_fragments.is_empty();
 		if is_empty {
 			<InboundXcmpMessages<T>>::remove(sender, sent_at);
@@ -504,6 +896,12 @@ impl<T: Config> Pallet<T> {
 			let index = shuffled[shuffle_index];
 			let sender = status[index].0;
 
+			if <InboundSuspended<T>>::get(sender) {
+				// Suspended by the controller; the messages stay queued until it is resumed.
+				shuffle_index += 1;
+				continue;
+			}
+
 			if weight_available != max_weight {
 				// Get incrementally closer to freeing up max_weight for message execution over the
 				// first round. For the second round we unlock all weight. If we come close enough
@@ -697,6 +1095,10 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 			if outbound_status == OutboundStatus::Suspended {
 				continue;
 			}
+			if !signalling && <OutboundSuspended<T>>::get(para_id) {
+				// Suspended by the controller; only the signals go through.
+				continue;
+			}
 			let (max_size_now, max_size_ever) = match T::ChannelInfo::get_channel_status(para_id) {
 				ChannelStatus::Closed => {
 					// This means that there is no such channel anymore. Nothing to be done but
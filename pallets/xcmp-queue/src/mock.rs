@@ -0,0 +1,141 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use super::*;
+use crate as xcmp_queue;
+use cumulus_primitives_core::{ChannelStatus, GetChannelInfo, ParaId};
+use frame_support::parameter_types;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use xcm::v0::OriginKind;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+pub type MockXcm = Xcm<Call>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		XcmpQueue: xcmp_queue::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+type AccountId = u64;
+
+impl frame_system::Config for Test {
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type BlockLength = ();
+	type BlockWeights = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+/// Executes `Transact` messages if they fit in the weight limit and fails everything else.
+pub struct MockExec;
+impl ExecuteXcm<Call> for MockExec {
+	fn execute_xcm_in_credit(
+		_origin: MultiLocation,
+		message: MockXcm,
+		weight_limit: Weight,
+		_credit: Weight,
+	) -> Outcome {
+		match message {
+			Xcm::Transact { require_weight_at_most, .. } => {
+				if require_weight_at_most <= weight_limit {
+					Outcome::Complete(require_weight_at_most)
+				} else {
+					Outcome::Error(XcmError::WeightLimitReached(require_weight_at_most))
+				}
+			}
+			_ => Outcome::Error(XcmError::Unimplemented),
+		}
+	}
+}
+
+/// Every channel is open and empty.
+pub struct ChannelInfo;
+impl GetChannelInfo for ChannelInfo {
+	fn get_channel_status(_id: ParaId) -> ChannelStatus {
+		ChannelStatus::Ready(10, 10)
+	}
+
+	fn get_channel_max(_id: ParaId) -> Option<usize> {
+		Some(usize::max_value())
+	}
+}
+
+impl Config for Test {
+	type Event = Event;
+	type XcmExecutor = MockExec;
+	type ChannelInfo = ChannelInfo;
+	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type ControllerOrigin = frame_system::EnsureRoot<AccountId>;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// A `Transact` requiring the given weight.
+pub fn transact(weight: Weight) -> MockXcm {
+	Xcm::Transact {
+		origin_type: OriginKind::Native,
+		require_weight_at_most: weight,
+		call: Vec::new().into(),
+	}
+}
+
+/// Queue a page of the given messages from `sender`, as `handle_xcmp_messages` would do.
+pub fn enqueue_page(sender: ParaId, sent_at: RelayBlockNumber, messages: &[MockXcm]) {
+	let data = messages
+		.iter()
+		.flat_map(|m| VersionedXcm::<Call>::from(m.clone()).encode())
+		.collect::<Vec<_>>();
+	InboundXcmpMessages::<Test>::insert(sender, sent_at, data);
+}
@@ -0,0 +1,256 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use super::*;
+use mock::*;
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::traits::BadOrigin;
+
+const SENDER: ParaId = ParaId::new(1000);
+
+fn last_event() -> Event<Test> {
+	System::events()
+		.into_iter()
+		.filter_map(|r| if let mock::Event::XcmpQueue(e) = r.event { Some(e) } else { None })
+		.last()
+		.expect("an event was deposited")
+}
+
+#[test]
+fn queue_config_setters_require_root() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(XcmpQueue::update_suspend_threshold(Origin::signed(1), 3), BadOrigin);
+		assert_noop!(XcmpQueue::update_xcmp_max_individual_weight(Origin::signed(1), 1), BadOrigin);
+
+		assert_ok!(XcmpQueue::update_suspend_threshold(Origin::root(), 3));
+		assert_ok!(XcmpQueue::update_threshold_weight(Origin::root(), 5_000));
+		assert_ok!(XcmpQueue::update_weight_restrict_decay(Origin::root(), 4));
+		assert_ok!(XcmpQueue::update_xcmp_max_individual_weight(Origin::root(), 10_000));
+
+		let config = QueueConfig::<Test>::get();
+		assert_eq!(config.suspend_threshold, 3);
+		assert_eq!(config.threshold_weight, 5_000);
+		assert_eq!(config.weight_restrict_decay, 4);
+		assert_eq!(config.xcmp_max_individual_weight, 10_000);
+		assert_eq!(last_event(), Event::QueueConfigUpdated(config));
+	});
+}
+
+#[test]
+fn queue_config_thresholds_are_validated() {
+	new_test_ext().execute_with(|| {
+		// default: resume 1, suspend 2, drop 5
+		assert_noop!(
+			XcmpQueue::update_resume_threshold(Origin::root(), 2),
+			Error::<Test>::BadQueueConfig
+		);
+		assert_noop!(
+			XcmpQueue::update_suspend_threshold(Origin::root(), 6),
+			Error::<Test>::BadQueueConfig
+		);
+		assert_noop!(
+			XcmpQueue::update_drop_threshold(Origin::root(), 1),
+			Error::<Test>::BadQueueConfig
+		);
+
+		assert_ok!(XcmpQueue::update_suspend_threshold(Origin::root(), 5));
+		assert_ok!(XcmpQueue::update_drop_threshold(Origin::root(), 5));
+		assert_ok!(XcmpQueue::update_resume_threshold(Origin::root(), 4));
+	});
+}
+
+#[test]
+fn overweight_messages_are_stashed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(XcmpQueue::update_xcmp_max_individual_weight(Origin::root(), 1_000));
+		enqueue_page(SENDER, 1, &[transact(100), transact(5_000), transact(200)]);
+
+		let (used, is_empty) = XcmpQueue::process_xcmp_message(
+			SENDER,
+			(1, XcmpMessageFormat::ConcatenatedVersionedXcm),
+			2_000,
+		);
+
+		// the overweight message does not block the ones behind it
+		assert_eq!(used, 300);
+		assert!(is_empty);
+		assert_eq!(OverweightCount::<Test>::get(), 1);
+		assert_eq!(
+			Overweight::<Test>::get(0),
+			Some((SENDER, 1, VersionedXcm::<Call>::from(transact(5_000)).encode()))
+		);
+		assert!(System::events().iter().any(|r| r.event
+			== mock::Event::XcmpQueue(Event::OverweightEnqueued(SENDER, 1, 0, 5_000))));
+	});
+}
+
+#[test]
+fn heavy_messages_under_the_individual_limit_are_kept_in_the_queue() {
+	new_test_ext().execute_with(|| {
+		enqueue_page(SENDER, 1, &[transact(100), transact(5_000)]);
+
+		let (used, is_empty) = XcmpQueue::process_xcmp_message(
+			SENDER,
+			(1, XcmpMessageFormat::ConcatenatedVersionedXcm),
+			2_000,
+		);
+
+		assert_eq!(used, 100);
+		assert!(!is_empty);
+		assert_eq!(OverweightCount::<Test>::get(), 0);
+		assert_eq!(
+			InboundXcmpMessages::<Test>::get(SENDER, 1),
+			VersionedXcm::<Call>::from(transact(5_000)).encode()
+		);
+	});
+}
+
+#[test]
+fn service_overweight_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(XcmpQueue::update_xcmp_max_individual_weight(Origin::root(), 1_000));
+		enqueue_page(SENDER, 1, &[transact(5_000)]);
+		XcmpQueue::process_xcmp_message(
+			SENDER,
+			(1, XcmpMessageFormat::ConcatenatedVersionedXcm),
+			2_000,
+		);
+
+		assert_noop!(XcmpQueue::service_overweight(Origin::signed(1), 0, 10_000), BadOrigin);
+		assert_noop!(
+			XcmpQueue::service_overweight(Origin::root(), 1, 10_000),
+			Error::<Test>::BadOverweightIndex
+		);
+		assert_noop!(
+			XcmpQueue::service_overweight(Origin::root(), 0, 4_999),
+			Error::<Test>::WeightOverLimit
+		);
+
+		let info = XcmpQueue::service_overweight(Origin::root(), 0, 10_000).unwrap();
+		assert_eq!(info.actual_weight, Some(5_000 + 1_000_000));
+		assert_eq!(last_event(), Event::OverweightServiced(0, 5_000));
+		assert_eq!(Overweight::<Test>::get(0), None);
+
+		assert_noop!(
+			XcmpQueue::service_overweight(Origin::root(), 0, 10_000),
+			Error::<Test>::BadOverweightIndex
+		);
+	});
+}
+
+#[test]
+fn suspend_inbound_keeps_messages_queued() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(XcmpQueue::suspend_inbound(Origin::signed(1), SENDER), BadOrigin);
+		assert_noop!(
+			XcmpQueue::resume_inbound(Origin::root(), SENDER),
+			Error::<Test>::NotSuspended
+		);
+
+		assert_ok!(XcmpQueue::suspend_inbound(Origin::root(), SENDER));
+		assert_noop!(
+			XcmpQueue::suspend_inbound(Origin::root(), SENDER),
+			Error::<Test>::AlreadySuspended
+		);
+		assert_eq!(last_event(), Event::InboundChannelSuspended(SENDER));
+		assert_eq!(
+			SignalMessages::<Test>::get(SENDER),
+			(XcmpMessageFormat::Signals, ChannelSignal::Suspend).encode()
+		);
+
+		let page = (XcmpMessageFormat::ConcatenatedVersionedXcm, VersionedXcm::<Call>::from(transact(100)))
+			.encode();
+		let used = XcmpQueue::handle_xcmp_messages(vec![(SENDER, 1, &page[..])].into_iter(), 1_000_000);
+		assert_eq!(used, 0);
+		assert!(InboundXcmpMessages::<Test>::contains_key(SENDER, 1));
+
+		assert_ok!(XcmpQueue::resume_inbound(Origin::root(), SENDER));
+		assert_eq!(last_event(), Event::InboundChannelResumed(SENDER));
+		assert_eq!(
+			SignalMessages::<Test>::get(SENDER),
+			(XcmpMessageFormat::Signals, ChannelSignal::Suspend, ChannelSignal::Resume).encode()
+		);
+		assert_eq!(XcmpQueue::service_xcmp_queue(1_000_000), 100);
+		assert!(!InboundXcmpMessages::<Test>::contains_key(SENDER, 1));
+	});
+}
+
+#[test]
+fn suspend_outbound_holds_messages() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(XcmpQueue::send_blob_message(SENDER, vec![1, 2, 3]));
+
+		assert_noop!(XcmpQueue::suspend_outbound(Origin::signed(1), SENDER), BadOrigin);
+		assert_ok!(XcmpQueue::suspend_outbound(Origin::root(), SENDER));
+		assert_noop!(
+			XcmpQueue::suspend_outbound(Origin::root(), SENDER),
+			Error::<Test>::AlreadySuspended
+		);
+		assert!(XcmpQueue::take_outbound_messages(10).is_empty());
+
+		assert_ok!(XcmpQueue::resume_outbound(Origin::root(), SENDER));
+		assert_noop!(
+			XcmpQueue::resume_outbound(Origin::root(), SENDER),
+			Error::<Test>::NotSuspended
+		);
+		let messages = XcmpQueue::take_outbound_messages(10);
+		assert_eq!(messages.len(), 1);
+		assert_eq!(messages[0].0, SENDER);
+	});
+}
+
+#[test]
+fn inbound_message_quota_is_per_block() {
+	new_test_ext().execute_with(|| {
+		let quota = InboundQuota { max_messages: 2, max_weight: 1_000 };
+		assert_noop!(XcmpQueue::set_inbound_quota(Origin::signed(1), SENDER, Some(quota)), BadOrigin);
+		assert_ok!(XcmpQueue::set_inbound_quota(Origin::root(), SENDER, Some(quota)));
+		assert_eq!(last_event(), Event::InboundQuotaSet(SENDER, Some(quota)));
+		enqueue_page(SENDER, 1, &[transact(100), transact(100), transact(100)]);
+
+		let format = (1, XcmpMessageFormat::ConcatenatedVersionedXcm);
+		assert_eq!(XcmpQueue::process_xcmp_message(SENDER, format, 1_000_000), (200, false));
+		assert_eq!(XcmpQueue::process_xcmp_message(SENDER, format, 1_000_000), (0, false));
+
+		System::set_block_number(2);
+		assert_eq!(XcmpQueue::process_xcmp_message(SENDER, format, 1_000_000), (100, true));
+
+		assert_ok!(XcmpQueue::set_inbound_quota(Origin::root(), SENDER, None));
+		assert!(InboundQuotas::<Test>::get(SENDER).is_none());
+		assert!(InboundUsage::<Test>::get(SENDER).is_none());
+	});
+}
+
+#[test]
+fn inbound_weight_quota_is_per_block() {
+	new_test_ext().execute_with(|| {
+		let quota = InboundQuota { max_messages: 10, max_weight: 250 };
+		assert_ok!(XcmpQueue::set_inbound_quota(Origin::root(), SENDER, Some(quota)));
+		enqueue_page(SENDER, 1, &[transact(100), transact(100), transact(100), transact(5_000)]);
+
+		let format = (1, XcmpMessageFormat::ConcatenatedVersionedXcm);
+		assert_eq!(XcmpQueue::process_xcmp_message(SENDER, format, 1_000_000), (200, false));
+
+		// a message which never fits in the quota goes to the overweight store
+		System::set_block_number(2);
+		assert_eq!(XcmpQueue::process_xcmp_message(SENDER, format, 1_000_000), (100, true));
+		assert_eq!(
+			Overweight::<Test>::get(0),
+			Some((SENDER, 1, VersionedXcm::<Call>::from(transact(5_000)).encode()))
+		);
+	});
+}
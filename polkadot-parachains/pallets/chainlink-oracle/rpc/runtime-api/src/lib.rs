@@ -0,0 +1,34 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+mod types;
+
+pub use pallet_chainlink_feed::RoundId;
+pub use types::{FeedConfigInfo, OracleStatusInfo, RoundInfo, RoundRange};
+
+/// The maximum number of rounds returned by a single `rounds` call
+pub const MAX_ROUNDS_PER_QUERY: RoundId = 1_000;
+
+sp_api::decl_runtime_apis! {
+    pub trait ChainlinkFeedApi<FeedId, AccountId, Balance, BlockNumber, Value> where
+        FeedId: Codec,
+        AccountId: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+        Value: Codec,
+    {
+        fn feed_ids() -> Vec<FeedId>;
+
+        fn feed_config(feed_id: FeedId) -> Option<FeedConfigInfo<AccountId, Balance, BlockNumber, Value>>;
+
+        fn oracle_statuses(feed_id: FeedId) -> Vec<OracleStatusInfo<AccountId, Value>>;
+
+        // the rounds of the feed that have not been pruned yet
+        fn round_range(feed_id: FeedId) -> Option<RoundRange>;
+
+        // at most `MAX_ROUNDS_PER_QUERY` rounds starting from `from`
+        fn rounds(feed_id: FeedId, from: RoundId, to: RoundId) -> Vec<RoundInfo<BlockNumber, Value>>;
+    }
+}
@@ -0,0 +1,178 @@
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sp_std::prelude::*;
+
+use pallet_chainlink_feed::{FeedConfig, OracleStatus, Round, RoundId};
+use frame_support::Parameter;
+
+#[derive(Eq, PartialEq, Encode, Decode, Default, Clone)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct FeedConfigInfo<AccountId, Balance, BlockNumber, Value> {
+    pub owner: AccountId,
+    pub pending_owner: Option<AccountId>,
+    #[cfg_attr(feature = "std", serde(bound(serialize = "Value: std::fmt::Display")))]
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_as_string"))]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "Value: std::str::FromStr")))]
+    #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_from_string"))]
+    pub min_submission_value: Value,
+    #[cfg_attr(feature = "std", serde(bound(serialize = "Value: std::fmt::Display")))]
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_as_string"))]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "Value: std::str::FromStr")))]
+    #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_from_string"))]
+    pub max_submission_value: Value,
+    pub min_submissions: u32,
+    pub max_submissions: u32,
+    #[cfg_attr(feature = "std", serde(bound(serialize = "Balance: std::fmt::Display")))]
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_as_string"))]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "Balance: std::str::FromStr")))]
+    #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_from_string"))]
+    pub payment: Balance,
+    pub timeout: BlockNumber,
+    pub decimals: u8,
+    pub description: Vec<u8>,
+    pub restart_delay: RoundId,
+    pub reporting_round: RoundId,
+    pub latest_round: RoundId,
+    pub first_valid_round: Option<RoundId>,
+    pub oracle_count: u32,
+    pub pruning_window: RoundId,
+    pub next_round_to_prune: RoundId,
+    #[cfg_attr(feature = "std", serde(bound(serialize = "Balance: std::fmt::Display")))]
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_as_string"))]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "Balance: std::str::FromStr")))]
+    #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_from_string"))]
+    pub debt: Balance,
+    #[cfg_attr(feature = "std", serde(bound(serialize = "Balance: std::fmt::Display")))]
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_option_as_string"))]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "Balance: std::str::FromStr")))]
+    #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_option_from_string"))]
+    pub max_debt: Option<Balance>,
+}
+
+#[derive(Eq, PartialEq, Encode, Decode, Default, Clone)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct OracleStatusInfo<AccountId, Value> {
+    pub oracle: AccountId,
+    pub starting_round: RoundId,
+    pub ending_round: Option<RoundId>,
+    pub last_reported_round: Option<RoundId>,
+    pub last_started_round: Option<RoundId>,
+    #[cfg_attr(feature = "std", serde(bound(serialize = "Value: std::fmt::Display")))]
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_option_as_string"))]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "Value: std::str::FromStr")))]
+    #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_option_from_string"))]
+    pub latest_submission: Option<Value>,
+}
+
+#[derive(Eq, PartialEq, Encode, Decode, Default, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct RoundRange {
+    /// The oldest round that is not pruned
+    pub first: RoundId,
+    /// The round oracles are reporting for
+    pub last: RoundId,
+}
+
+#[derive(Eq, PartialEq, Encode, Decode, Default, Clone)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct RoundInfo<BlockNumber, Value> {
+    pub round_id: RoundId,
+    pub started_at: BlockNumber,
+    #[cfg_attr(feature = "std", serde(bound(serialize = "Value: std::fmt::Display")))]
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_option_as_string"))]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "Value: std::str::FromStr")))]
+    #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_option_from_string"))]
+    pub answer: Option<Value>,
+    pub updated_at: Option<BlockNumber>,
+    pub answered_in_round: Option<RoundId>,
+}
+
+impl<AccountId, Balance, BlockNumber, Value> From<FeedConfig<AccountId, Balance, BlockNumber, Value>>
+    for FeedConfigInfo<AccountId, Balance, BlockNumber, Value>
+where
+    AccountId: Parameter,
+    Balance: Parameter,
+    BlockNumber: Parameter,
+    Value: Parameter,
+{
+    fn from(c: FeedConfig<AccountId, Balance, BlockNumber, Value>) -> Self {
+        Self {
+            owner: c.owner,
+            pending_owner: c.pending_owner,
+            min_submission_value: c.submission_value_bounds.0,
+            max_submission_value: c.submission_value_bounds.1,
+            min_submissions: c.submission_count_bounds.0,
+            max_submissions: c.submission_count_bounds.1,
+            payment: c.payment,
+            timeout: c.timeout,
+            decimals: c.decimals,
+            description: c.description,
+            restart_delay: c.restart_delay,
+            reporting_round: c.reporting_round,
+            latest_round: c.latest_round,
+            first_valid_round: c.first_valid_round,
+            oracle_count: c.oracle_count,
+            pruning_window: c.pruning_window,
+            next_round_to_prune: c.next_round_to_prune,
+            debt: c.debt,
+            max_debt: c.max_debt,
+        }
+    }
+}
+
+impl<AccountId, Value> From<(AccountId, OracleStatus<Value>)> for OracleStatusInfo<AccountId, Value> {
+    fn from((oracle, s): (AccountId, OracleStatus<Value>)) -> Self {
+        Self {
+            oracle,
+            starting_round: s.starting_round,
+            ending_round: s.ending_round,
+            last_reported_round: s.last_reported_round,
+            last_started_round: s.last_started_round,
+            latest_submission: s.latest_submission,
+        }
+    }
+}
+
+impl<BlockNumber, Value> From<(RoundId, Round<BlockNumber, Value>)> for RoundInfo<BlockNumber, Value> {
+    fn from((round_id, r): (RoundId, Round<BlockNumber, Value>)) -> Self {
+        Self {
+            round_id,
+            started_at: r.started_at,
+            answer: r.answer,
+            updated_at: r.updated_at,
+            answered_in_round: r.answered_in_round,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn serialize_as_string<S: Serializer, T: std::fmt::Display>(t: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&t.to_string())
+}
+
+#[cfg(feature = "std")]
+fn deserialize_from_string<'de, D: Deserializer<'de>, T: std::str::FromStr>(deserializer: D) -> Result<T, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    s.parse::<T>()
+        .map_err(|_| serde::de::Error::custom("Parse from string failed"))
+}
+
+#[cfg(feature = "std")]
+fn serialize_option_as_string<S: Serializer, T: std::fmt::Display>(t: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+    match t {
+        Some(t) => serializer.serialize_some(&t.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(feature = "std")]
+fn deserialize_option_from_string<'de, D: Deserializer<'de>, T: std::str::FromStr>(deserializer: D) -> Result<Option<T>, D::Error> {
+    let s = Option::<String>::deserialize(deserializer)?;
+    s.map(|s| s.parse::<T>().map_err(|_| serde::de::Error::custom("Parse from string failed")))
+        .transpose()
+}
@@ -0,0 +1,158 @@
+//! RPC interface for querying the chainlink feeds, their oracles and the rounds that
+//! have not been pruned yet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_chainlink_oracle_rpc_runtime_api::ChainlinkFeedApi as ChainlinkFeedRuntimeApi;
+pub use pallet_chainlink_oracle_rpc_runtime_api::{
+    FeedConfigInfo, OracleStatusInfo, RoundId, RoundInfo, RoundRange, MAX_ROUNDS_PER_QUERY,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Error code of a failed runtime api call
+const RUNTIME_ERROR: i64 = 1;
+/// Error code of a request for too many rounds
+const INVALID_RANGE: i64 = 2;
+
+#[rpc]
+pub trait ChainlinkFeedApi<BlockHash, FeedId, AccountId, Balance, BlockNumber, Value> {
+    #[rpc(name = "chainlinkFeed_feedIds")]
+    fn feed_ids(&self, at: Option<BlockHash>) -> Result<Vec<FeedId>>;
+
+    #[rpc(name = "chainlinkFeed_feedConfig")]
+    fn feed_config(
+        &self,
+        feed_id: FeedId,
+        at: Option<BlockHash>,
+    ) -> Result<Option<FeedConfigInfo<AccountId, Balance, BlockNumber, Value>>>;
+
+    #[rpc(name = "chainlinkFeed_oracleStatuses")]
+    fn oracle_statuses(
+        &self,
+        feed_id: FeedId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<OracleStatusInfo<AccountId, Value>>>;
+
+    #[rpc(name = "chainlinkFeed_roundRange")]
+    fn round_range(&self, feed_id: FeedId, at: Option<BlockHash>) -> Result<Option<RoundRange>>;
+
+    #[rpc(name = "chainlinkFeed_rounds")]
+    fn rounds(
+        &self,
+        feed_id: FeedId,
+        from: RoundId,
+        to: RoundId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<RoundInfo<BlockNumber, Value>>>;
+}
+
+/// A struct that implements the `ChainlinkFeedApi`.
+pub struct ChainlinkFeed<C, M> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<M>,
+}
+
+impl<C, M> ChainlinkFeed<C, M> {
+    /// Create new `ChainlinkFeed` instance with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client, _marker: Default::default() }
+    }
+}
+
+impl<C, Block> ChainlinkFeed<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+{
+    /// Run a runtime api call at the given block, or at the best block if none is given.
+    fn query<R>(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        call: impl FnOnce(&C::Api, &BlockId<Block>) -> std::result::Result<R, sp_api::ApiError>,
+    ) -> Result<R> {
+        let api = self.client.runtime_api();
+        // If the block hash is not supplied assume the best block.
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        call(&*api, &at).map_err(runtime_error)
+    }
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Unable to query the chainlink feed.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, Block, FeedId, AccountId, Balance, BlockNumber, Value>
+    ChainlinkFeedApi<<Block as BlockT>::Hash, FeedId, AccountId, Balance, BlockNumber, Value>
+    for ChainlinkFeed<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C::Api: ChainlinkFeedRuntimeApi<Block, FeedId, AccountId, Balance, BlockNumber, Value>,
+    FeedId: Codec,
+    AccountId: Codec,
+    Balance: Codec,
+    BlockNumber: Codec,
+    Value: Codec,
+{
+    fn feed_ids(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<FeedId>> {
+        self.query(at, |api, at| api.feed_ids(at))
+    }
+
+    fn feed_config(
+        &self,
+        feed_id: FeedId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<FeedConfigInfo<AccountId, Balance, BlockNumber, Value>>> {
+        self.query(at, |api, at| api.feed_config(at, feed_id))
+    }
+
+    fn oracle_statuses(
+        &self,
+        feed_id: FeedId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<OracleStatusInfo<AccountId, Value>>> {
+        self.query(at, |api, at| api.oracle_statuses(at, feed_id))
+    }
+
+    fn round_range(
+        &self,
+        feed_id: FeedId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<RoundRange>> {
+        self.query(at, |api, at| api.round_range(at, feed_id))
+    }
+
+    fn rounds(
+        &self,
+        feed_id: FeedId,
+        from: RoundId,
+        to: RoundId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<RoundInfo<BlockNumber, Value>>> {
+        if to < from || to - from >= MAX_ROUNDS_PER_QUERY {
+            return Err(RpcError {
+                code: ErrorCode::ServerError(INVALID_RANGE),
+                message: format!("At most {} rounds can be queried at once.", MAX_ROUNDS_PER_QUERY),
+                data: None,
+            });
+        }
+
+        self.query(at, |api, at| api.rounds(at, feed_id, from, to))
+    }
+}
@@ -0,0 +1,160 @@
+use super::*;
+
+use jsonrpc_core::IoHandler;
+use sp_api::{ApiRef, ProvideRuntimeApi};
+use sp_blockchain::{BlockStatus, Info};
+use sp_runtime::traits::NumberFor;
+use substrate_test_runtime_client::runtime::{Block, Header};
+
+type FeedId = u32;
+type AccountId = u64;
+type Balance = u128;
+type BlockNumber = u64;
+type Value = u128;
+
+/// The rounds of feed 0, feed 1 has no rounds
+const ROUNDS: RoundRange = RoundRange { first: 1, last: 3 };
+
+/// A client with the chain of the test runtime and a mocked chainlink feed runtime api.
+struct TestClient(substrate_test_runtime_client::Client<substrate_test_runtime_client::Backend>);
+
+struct RuntimeApi;
+
+sp_api::mock_impl_runtime_apis! {
+    impl ChainlinkFeedRuntimeApi<Block, FeedId, AccountId, Balance, BlockNumber, Value> for RuntimeApi {
+        fn feed_ids() -> Vec<FeedId> {
+            vec![0, 1]
+        }
+
+        fn feed_config(feed_id: FeedId) -> Option<FeedConfigInfo<AccountId, Balance, BlockNumber, Value>> {
+            (feed_id == 0).then(|| FeedConfigInfo { latest_round: ROUNDS.last, ..Default::default() })
+        }
+
+        fn oracle_statuses(feed_id: FeedId) -> Vec<OracleStatusInfo<AccountId, Value>> {
+            if feed_id == 0 {
+                vec![OracleStatusInfo { oracle: 7, ..Default::default() }]
+            } else {
+                vec![]
+            }
+        }
+
+        fn round_range(feed_id: FeedId) -> Option<RoundRange> {
+            (feed_id == 0).then(|| ROUNDS)
+        }
+
+        fn rounds(feed_id: FeedId, from: RoundId, to: RoundId) -> Vec<RoundInfo<BlockNumber, Value>> {
+            if feed_id != 0 {
+                return vec![];
+            }
+            (from.max(ROUNDS.first)..=to.min(ROUNDS.last))
+                .map(|round_id| RoundInfo {
+                    round_id,
+                    started_at: round_id.into(),
+                    answer: Some(round_id as Value * 100),
+                    ..Default::default()
+                })
+                .collect()
+        }
+    }
+}
+
+impl ProvideRuntimeApi<Block> for TestClient {
+    type Api = RuntimeApi;
+
+    fn runtime_api<'a>(&'a self) -> ApiRef<'a, Self::Api> {
+        RuntimeApi.into()
+    }
+}
+
+impl HeaderBackend<Block> for TestClient {
+    fn header(&self, id: BlockId<Block>) -> sp_blockchain::Result<Option<Header>> {
+        HeaderBackend::header(&self.0, id)
+    }
+
+    fn info(&self) -> Info<Block> {
+        HeaderBackend::info(&self.0)
+    }
+
+    fn status(&self, id: BlockId<Block>) -> sp_blockchain::Result<BlockStatus> {
+        HeaderBackend::status(&self.0, id)
+    }
+
+    fn number(&self, hash: <Block as BlockT>::Hash) -> sp_blockchain::Result<Option<NumberFor<Block>>> {
+        HeaderBackend::number(&self.0, hash)
+    }
+
+    fn hash(&self, number: NumberFor<Block>) -> sp_blockchain::Result<Option<<Block as BlockT>::Hash>> {
+        HeaderBackend::hash(&self.0, number)
+    }
+}
+
+fn rpc() -> ChainlinkFeed<TestClient, Block> {
+    ChainlinkFeed::new(Arc::new(TestClient(substrate_test_runtime_client::new())))
+}
+
+fn api() -> impl ChainlinkFeedApi<<Block as BlockT>::Hash, FeedId, AccountId, Balance, BlockNumber, Value> {
+    rpc()
+}
+
+#[test]
+fn queries_the_best_block_or_the_given_block() {
+    let rpc = api();
+    let genesis = HeaderBackend::info(&substrate_test_runtime_client::new()).genesis_hash;
+
+    assert_eq!(rpc.feed_ids(None).unwrap(), vec![0, 1]);
+    assert_eq!(rpc.feed_ids(Some(genesis)).unwrap(), vec![0, 1]);
+}
+
+#[test]
+fn feed_queries_work() {
+    let rpc = api();
+
+    assert_eq!(rpc.feed_config(0, None).unwrap().map(|c| c.latest_round), Some(ROUNDS.last));
+    assert_eq!(rpc.feed_config(1, None).unwrap(), None);
+    assert_eq!(rpc.oracle_statuses(0, None).unwrap().iter().map(|s| s.oracle).collect::<Vec<_>>(), vec![7]);
+    assert!(rpc.oracle_statuses(1, None).unwrap().is_empty());
+    assert_eq!(rpc.round_range(0, None).unwrap(), Some(ROUNDS));
+    assert_eq!(rpc.round_range(1, None).unwrap(), None);
+}
+
+#[test]
+fn rounds_are_returned_within_the_range() {
+    let rpc = api();
+
+    let rounds = rpc.rounds(0, 0, 10, None).unwrap();
+    assert_eq!(rounds.iter().map(|r| r.round_id).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(rounds[1].answer, Some(200));
+    assert!(rpc.rounds(1, 0, 10, None).unwrap().is_empty());
+}
+
+#[test]
+fn too_many_rounds_are_rejected() {
+    let rpc = api();
+    let invalid_range = |r: Result<Vec<RoundInfo<BlockNumber, Value>>>| {
+        r.unwrap_err().code == ErrorCode::ServerError(INVALID_RANGE)
+    };
+
+    assert!(rpc.rounds(0, 0, MAX_ROUNDS_PER_QUERY - 1, None).is_ok());
+    assert!(invalid_range(rpc.rounds(0, 0, MAX_ROUNDS_PER_QUERY, None)));
+    assert!(invalid_range(rpc.rounds(0, 2, 1, None)));
+}
+
+#[test]
+fn rpc_methods_are_registered() {
+    let mut io = IoHandler::new();
+    io.extend_with(ChainlinkFeedApi::<_, FeedId, AccountId, Balance, BlockNumber, Value>::to_delegate(rpc()));
+
+    let request = r#"{"jsonrpc":"2.0","method":"chainlinkFeed_rounds","params":[0,2,3],"id":1}"#;
+    let response: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+    assert_eq!(
+        response["result"],
+        serde_json::json!([
+            {"roundId": 2, "startedAt": 2, "answer": "200", "updatedAt": null, "answeredInRound": null},
+            {"roundId": 3, "startedAt": 3, "answer": "300", "updatedAt": null, "answeredInRound": null},
+        ])
+    );
+
+    let request = r#"{"jsonrpc":"2.0","method":"chainlinkFeed_roundRange","params":[1],"id":2}"#;
+    let response: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+    assert_eq!(response["result"], serde_json::Value::Null);
+}
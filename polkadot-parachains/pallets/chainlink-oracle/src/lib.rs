@@ -41,7 +41,7 @@ pub mod pallet {
 
     /* ------- Local Libs -------- */
     use polkadot_parachain_primitives::{CurrencyId, Price};
-    use pallet_traits::{PriceProvider};
+    use pallet_traits::{PriceProvider, Releases, StorageVersioned};
 
     type FeedIdOf<T> = <<T as Config>::Oracle as FeedOracle<T>>::FeedId;
     #[pallet::config]
@@ -50,8 +50,45 @@ pub mod pallet {
         type CurrencyFeedConvertor: Convert<CurrencyId, Option<FeedIdOf<Self>>>;
     }
 
+    /// The storage version, used to decide which migrations to run on a runtime upgrade.
+    /// `None` on chains that predate the stored version.
+    #[pallet::storage]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, OptionQuery>;
+
+    #[pallet::genesis_config]
+    #[derive(Default)]
+    pub struct GenesisConfig {}
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig {
+        fn build(&self) {
+            StorageVersion::<T>::put(<Pallet<T> as StorageVersioned<T>>::LATEST);
+        }
+    }
+
     #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_runtime_upgrade() -> Weight {
+            Self::upgrade_storage()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            Self::ensure_latest_storage_version()
+        }
+    }
+
+    impl<T: Config> StorageVersioned<T> for Pallet<T> {
+        const LATEST: Releases = Releases::V1_0_0;
+
+        fn storage_version() -> Option<Releases> {
+            StorageVersion::<T>::get()
+        }
+
+        fn put_storage_version(version: Releases) {
+            StorageVersion::<T>::put(version);
+        }
+    }
 
     #[pallet::error]
     pub enum Error<T> {
@@ -132,7 +132,7 @@ construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Storage, Config, Event<T>},
-		Currencies: currencies::{Pallet, Call, Storage, Event<T>},
+		Currencies: currencies::{Pallet, Call, Storage, Config, Event<T>},
 		Tokens: orml_tokens::{Pallet, Storage, Event<T>, Config<T>},
 		PalletBalances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
@@ -195,6 +195,9 @@ impl ExtBuilder {
             .assimilate_storage(&mut t)
             .unwrap();
 
+        GenesisBuild::<Runtime>::assimilate_storage(&currencies::GenesisConfig::default(), &mut t)
+            .unwrap();
+
         t.into()
     }
 }
@@ -3,7 +3,8 @@
 #![cfg(test)]
 
 use super::*;
-use frame_support::{assert_ok};
+use pallet_traits::{Releases, StorageVersioned};
+use frame_support::{assert_ok, traits::OnRuntimeUpgrade};
 use mock::{*};
 
 #[test]
@@ -15,3 +16,34 @@ fn currencies_transfer_should_work() {
             assert_ok!(Currencies::transfer(Origin::signed(ALICE), ALICE, NATIVE_CURRENCY_ID, 50));
         });
 }
+
+#[test]
+fn genesis_writes_the_storage_version() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_eq!(StorageVersion::<Runtime>::get(), Some(Releases::V1_0_0));
+
+            // nothing to migrate at the latest version
+            Currencies::on_runtime_upgrade();
+            assert_eq!(StorageVersion::<Runtime>::get(), Some(Releases::V1_0_0));
+        });
+}
+
+#[test]
+fn runtime_upgrade_writes_the_storage_version() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            // a chain that predates the stored version
+            StorageVersion::<Runtime>::kill();
+            assert_eq!(
+                Currencies::ensure_latest_storage_version(),
+                Err("storage version is not the latest")
+            );
+
+            Currencies::on_runtime_upgrade();
+            assert_eq!(StorageVersion::<Runtime>::get(), Some(Releases::V1_0_0));
+            assert_eq!(Currencies::ensure_latest_storage_version(), Ok(()));
+        });
+}
@@ -21,9 +21,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+pub use types::LendingAction;
 
 mod types;
 mod pool;
+mod migrations;
 
 #[cfg(test)]
 mod tests;
@@ -33,24 +35,26 @@ mod tests;
 
 #[frame_support::pallet]
 pub mod pallet {
-    use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+    use frame_support::{dispatch::{DispatchErrorWithPostInfo, DispatchResultWithPostInfo}, pallet_prelude::*};
     use frame_support::error::BadOrigin;
     use frame_support::PalletId;
     use frame_support::transactional;
     use frame_system::pallet_prelude::*;
     use sp_runtime::{FixedPointNumber, FixedU128};
-    use sp_runtime::traits::{AccountIdConversion, CheckedDiv, CheckedMul, Convert, One, Zero};
+    use sp_runtime::traits::{AccountIdConversion, Convert, One, Zero};
     use sp_std::{vec::Vec};
     use sp_std::collections::btree_map::BTreeMap;
 
-    use pallet_traits::{MultiCurrency, PriceProvider};
-    use polkadot_parachain_primitives::{CustomError, InvalidParameters, PoolId, PriceValue};
+    use pallet_traits::{MultiCurrency, PriceProvider, Releases, StorageVersioned};
+    use polkadot_parachain_primitives::{CustomError, InvalidParameters, PoolId};
 
     use crate::pool::{Pool, PoolProxy, PoolRepository};
-    use crate::types::{Convertor, UserAccountUtil, UserBalanceStats, UserData, UserSupplyDebtData};
+    use crate::types::{Convertor, LendingAction, Rounding, UserAccountUtil, UserBalanceStats, UserData, UserSupplyDebtData};
 
     /* --------- Local Libs --------- */
     const PALLET_ID: PalletId = PalletId(*b"Floating");
+    /// The maximum number of actions in a single `batch_actions` call
+    const MAX_BATCH_ACTIONS: usize = 16;
 
     /// User supply struct
     pub(crate) type BalanceOf<T> =
@@ -65,7 +69,8 @@ pub mod pallet {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
         type Currency: MultiCurrency<Self::AccountId>;
         type PriceProvider: PriceProvider<Self, CurrencyId = CurrencyIdOf<Self>>;
-        type Conversion: Convert<BalanceOf<Self>, FixedU128> + Convert<FixedU128, BalanceOf<Self>>;
+        /// Converts balances into `FixedU128` for valuation against prices only, accounting stays in balances
+        type Conversion: Convert<BalanceOf<Self>, FixedU128>;
     }
 
     #[pallet::pallet]
@@ -76,6 +81,13 @@ pub mod pallet {
     #[pallet::getter(fn get_liquidation_threshold)]
     pub(super) type LiquidationThreshold<T: Config> = StorageValue<_, FixedU128, ValueQuery>;
 
+    /// Storage version of the pallet.
+    ///
+    /// v1.0.0 keeps the user amounts and pool totals as `FixedU128`, v2.0.0 as scaled `Balance`
+    /// shares. This is set to v2.0.0 for new networks.
+    #[pallet::storage]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
     #[pallet::storage]
     #[pallet::getter(fn pool_user_debts)]
     pub(super) type PoolUserDebts<T: Config> = StorageDoubleMap<
@@ -84,7 +96,7 @@ pub mod pallet {
         PoolId,
         Twox64Concat,
         T::AccountId,
-        UserData
+        UserData<BalanceOf<T>>
     >;
 
     #[pallet::storage]
@@ -95,7 +107,7 @@ pub mod pallet {
         PoolId,
         Twox64Concat,
         T::AccountId,
-        UserData
+        UserData<BalanceOf<T>>
     >;
 
     #[pallet::storage]
@@ -140,6 +152,7 @@ pub mod pallet {
         fn build(&self) {
             log::info!("triggered genesis");
             LiquidationThreshold::<T>::put(self.liquidation_threshold);
+            StorageVersion::<T>::put(<Pallet<T> as StorageVersioned<T>>::LATEST);
             for paras in &self.pools {
                 let id = <NextPoolId<T>>::get();
                 let mut pool = Pool::<T>::default_pool(
@@ -173,16 +186,22 @@ pub mod pallet {
         PoolUpdated(PoolId),
 
         /* ----- Operational ----- */
-        /// The balance has been supplied to the pool[pool_id, account_id, fixed_u_128]
-        SupplySuccessful(PoolId, T::AccountId, FixedU128),
-        /// The balance has been withdrawn to the pool[pool_id, account_id, fixed_u_128]
-        WithdrawSuccessful(PoolId, T::AccountId, FixedU128),
-        /// The balance has been borrowed from the pool[pool_id, account_id, fixed_u_128]
-        BorrowSuccessful(PoolId, T::AccountId, FixedU128),
-        /// The balance has been repaid to the pool[pool_id, account_id, fixed_u_128]
-        ReplaySuccessful(PoolId, T::AccountId, FixedU128),
+        /// The balance has been supplied to the pool[pool_id, account_id, balance]
+        SupplySuccessful(PoolId, T::AccountId, BalanceOf<T>),
+        /// The balance has been withdrawn to the pool[pool_id, account_id, balance]
+        WithdrawSuccessful(PoolId, T::AccountId, BalanceOf<T>),
+        /// The balance has been borrowed from the pool[pool_id, account_id, balance]
+        BorrowSuccessful(PoolId, T::AccountId, BalanceOf<T>),
+        /// The balance has been repaid to the pool[pool_id, account_id, balance]
+        ReplaySuccessful(PoolId, T::AccountId, BalanceOf<T>),
+        /// The balance has been supplied to the pool for the beneficiary[pool_id, payer, beneficiary, balance]
+        SupplyOnBehalfSuccessful(PoolId, T::AccountId, T::AccountId, BalanceOf<T>),
+        /// The debt of the beneficiary has been repaid by the payer[pool_id, payer, beneficiary, balance]
+        RepayOnBehalfSuccessful(PoolId, T::AccountId, T::AccountId, BalanceOf<T>),
         /// Liquidation is successful
         LiquidationSuccessful,
+        /// All the actions in the batch are performed[account_id, number of actions]
+        BatchActionsSuccessful(T::AccountId, u32),
     }
 
     // Errors inform users that something went wrong.
@@ -203,6 +222,8 @@ pub mod pallet {
         BalanceTooLow,
         /// User supply is too low.
         UserSupplyTooLow,
+        /// User debt is too low.
+        UserDebtTooLow,
         /// User has not supply in the pool
         UserNoSupplyInPool,
         /// User has not debt in the pool
@@ -215,10 +236,48 @@ pub mod pallet {
         NotEnoughLiquidity,
         /// The requirement of not smaller than liquidation threshold is violated
         BelowLiquidationThreshold,
+        /// The batch contains no actions
+        EmptyBatch,
+        /// The batch contains more actions than allowed
+        TooManyActions,
     }
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_runtime_upgrade() -> Weight {
+            Self::upgrade_storage()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<(), &'static str> {
+            crate::migrations::pre_upgrade::<T>()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            crate::migrations::post_upgrade::<T>()
+        }
+    }
+
+    impl<T: Config> StorageVersioned<T> for Pallet<T> {
+        const LATEST: Releases = Releases::V2_0_0;
+
+        fn storage_version() -> Option<Releases> {
+            Some(StorageVersion::<T>::get())
+        }
+
+        fn put_storage_version(version: Releases) {
+            StorageVersion::<T>::put(version);
+        }
+
+        fn migrate(from: Option<Releases>) -> Weight {
+            if from == Some(Releases::V1_0_0) {
+                log::info!("migrating floating-rate-lend to scaled balances");
+                return crate::migrations::migrate_to_scaled_balances::<T>();
+            }
+            0
+        }
+    }
 
     #[pallet::call]
     impl<T:Config> Pallet<T> {
@@ -376,25 +435,24 @@ pub mod pallet {
             #[pallet::compact] amount: BalanceOf<T>,
         ) -> DispatchResultWithPostInfo {
             let account = ensure_signed(origin)?;
-            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
-
-            // check floating-rate-pool exists and get floating-rate-pool instance
-            let mut pool: PoolProxy<T> = PoolRepository::<T>::find_without_price(pool_id)?;
-            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
-
-            let amount_u128 = T::Conversion::convert(amount);
-            if amount_u128 < pool.minimal_amount() { return Err(Error::<T>::BalanceTooLow.into()) }
-
-            // transfer asset
-            T::Currency::transfer(pool.currency_id(), &account, &Self::account_id(), amount)?;
-
-            pool.accrue_interest()?;
-            pool.increment_supply(&amount_u128);
-            PoolRepository::<T>::save(pool.clone());
-            UserAccountUtil::<T>::accrue_interest_and_increment_supply(&pool, account.clone(), &amount_u128)?;
-
-            Self::deposit_event(Event::SupplySuccessful(pool_id, account, amount_u128));
+            Self::do_supply(&account, &account, pool_id, amount)?;
+            Self::deposit_event(Event::SupplySuccessful(pool_id, account, amount));
+            Ok(().into())
+        }
 
+        /// Supply certain amount to the floating-rate-pool for the beneficiary, paid by the caller.
+        /// The supply belongs to the beneficiary, e.g. to top up collateral of an account close to liquidation.
+        #[pallet::weight(1)]
+        #[transactional]
+        pub fn supply_on_behalf(
+            origin: OriginFor<T>,
+            beneficiary: T::AccountId,
+            pool_id: PoolId,
+            #[pallet::compact] amount: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let payer = ensure_signed(origin)?;
+            Self::do_supply(&payer, &beneficiary, pool_id, amount)?;
+            Self::deposit_event(Event::SupplyOnBehalfSuccessful(pool_id, payer, beneficiary, amount));
             Ok(().into())
         }
 
@@ -406,47 +464,8 @@ pub mod pallet {
             #[pallet::compact] amount: BalanceOf<T>,
         ) -> DispatchResultWithPostInfo {
             let account = ensure_signed(origin)?;
-
-            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
-
-            // Check pool can withdraw
-            let mut pool: PoolProxy<T> = PoolRepository::<T>::find(pool_id)?;
-            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
-            // This ensures the pool's supply will never be lower than 0
-            let mut amount_fu128 = T::Conversion::convert(amount);
-            if !pool.allow_amount_deduction(&amount_fu128) { return Err(Error::<T>::NotEnoughLiquidity.into()); }
-
-            // Check user supply can withdraw
-            let user_supply = PoolUserSupplies::<T>::get(pool.id(), account.clone())
-                .ok_or(Error::<T>::UserNoSupplyInPool)?;
-            let mut transfer_amount = amount;
-            if user_supply.amount() < amount_fu128 {
-                amount_fu128 = user_supply.amount();
-                transfer_amount = T::Conversion::convert(user_supply.amount());
-            }
-
-            // Check if this pool is collateral
-            if pool.can_be_collateral() {
-                let (pool_map, user_supply_debt) = Self::prefetch_for_liquidation_check(account.clone())?;
-                let r = UserAccountUtil::<T>::is_withdraw_trigger_liquidation(
-                    (pool_id, amount_fu128),
-                    user_supply_debt,
-                    pool_map,
-                    LiquidationThreshold::<T>::get(),
-                )?;
-                if r { return Err(Error::<T>::BelowLiquidationThreshold.into()); }
-            }
-
-            // Now the checks are done, we can prepare to transfer
-            UserAccountUtil::<T>::decrement_supply(&pool, account.clone(), &amount_fu128, user_supply)?;
-            pool.decrement_supply(&amount_fu128);
-            PoolRepository::<T>::save(pool.clone());
-
-            // Now perform the writes
-            T::Currency::transfer(pool.currency_id(), &Self::account_id(), &account, transfer_amount)?;
-
-            Self::deposit_event(Event::WithdrawSuccessful(pool_id, account, amount_fu128));
-
+            let amount = Self::do_withdraw(&account, pool_id, amount, true)?;
+            Self::deposit_event(Event::WithdrawSuccessful(pool_id, account, amount));
             Ok(().into())
         }
 
@@ -458,36 +477,8 @@ pub mod pallet {
             #[pallet::compact] amount: BalanceOf<T>,
         ) -> DispatchResultWithPostInfo {
             let account = ensure_signed(origin)?;
-            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
-
-            // Check pool can borrow
-            let mut pool: PoolProxy<T> = PoolRepository::<T>::find(pool_id)?;
-            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
-            // Check sufficient liquidity
-            let amount_u128 = T::Conversion::convert(amount);
-            pool.accrue_interest()?;
-            if !pool.allow_amount_deduction(&amount_u128) { return Err(Error::<T>::NotEnoughLiquidity.into()); }
-
-            let (mut pool_map, user_supply_debt) = Self::prefetch_for_liquidation_check(account.clone())?;
-            if !pool_map.contains_key(&pool_id) { pool_map.insert(pool_id, pool.clone()); }
-            let r = UserAccountUtil::<T>::is_borrow_trigger_liquidation(
-                (pool_id,amount_u128),
-                user_supply_debt,
-                pool_map,
-                LiquidationThreshold::<T>::get(),
-            )?;
-            if r { return Err(Error::<T>::BelowLiquidationThreshold.into()); }
-
-            // Ready to perform the writes
-            // TODO: add check can transfer
-            T::Currency::transfer(pool.currency_id(), &Self::account_id(), &account, amount)?;
-            UserAccountUtil::<T>::accrue_interest_and_increment_debt(&pool, account.clone(), &amount_u128)?;
-
-            pool.increment_debt(&amount_u128);
-            PoolRepository::<T>::save(pool);
-
-            Self::deposit_event(Event::BorrowSuccessful(pool_id, account, amount_u128));
-
+            Self::do_borrow(&account, pool_id, amount, true)?;
+            Self::deposit_event(Event::BorrowSuccessful(pool_id, account, amount));
             Ok(().into())
         }
 
@@ -499,34 +490,74 @@ pub mod pallet {
             #[pallet::compact] amount: BalanceOf<T>,
         ) -> DispatchResultWithPostInfo {
             let account = ensure_signed(origin)?;
-            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
-
-            // Check pool can borrow
-            let mut pool = PoolRepository::<T>::find_without_price(pool_id)?;
-            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
+            let amount = Self::do_repay(&account, &account, pool_id, amount)?;
+            Self::deposit_event(Event::ReplaySuccessful(pool_id, account, amount));
+            Ok(().into())
+        }
 
-            // Check user supply can withdraw
-            let mut user_debt = PoolUserDebts::<T>::get(pool.id(), account.clone())
-                .ok_or(Error::<T>::UserNoDebtInPool)?;
+        /// Repay the debt of the beneficiary, paid by the caller.
+        /// At most the outstanding debt of the beneficiary, including interest, is repaid.
+        #[pallet::weight(1)]
+        #[transactional]
+        pub fn repay_on_behalf(
+            origin: OriginFor<T>,
+            beneficiary: T::AccountId,
+            pool_id: PoolId,
+            #[pallet::compact] amount: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let payer = ensure_signed(origin)?;
+            let amount = Self::do_repay(&payer, &beneficiary, pool_id, amount)?;
+            Self::deposit_event(Event::RepayOnBehalfSuccessful(pool_id, payer, beneficiary, amount));
+            Ok(().into())
+        }
 
-            let mut amount_fu128 = T::Conversion::convert(amount);
-            let mut transfer_amount = amount;
-            if user_debt.amount() < amount_fu128 {
-                amount_fu128 = user_debt.amount();
-                transfer_amount = T::Conversion::convert(amount_fu128);
+        /// Perform a list of lending actions atomically.
+        /// The actions are executed in order and the liquidation threshold is only checked once all of
+        /// them are done, so positions can be rebalanced without passing through unhealthy states.
+        #[pallet::weight(1)]
+        #[transactional]
+        pub fn batch_actions(
+            origin: OriginFor<T>,
+            actions: Vec<LendingAction<BalanceOf<T>>>,
+        ) -> DispatchResultWithPostInfo {
+            let account = ensure_signed(origin)?;
+            if actions.is_empty() { return Err(Error::<T>::EmptyBatch.into()); }
+            if actions.len() > MAX_BATCH_ACTIONS { return Err(Error::<T>::TooManyActions.into()); }
+
+            let mut check_health = false;
+            for action in actions.iter().cloned() {
+                match action {
+                    LendingAction::Supply(pool_id, amount) => {
+                        Self::do_supply(&account, &account, pool_id, amount)?;
+                        Self::deposit_event(Event::SupplySuccessful(pool_id, account.clone(), amount));
+                    },
+                    LendingAction::Withdraw(pool_id, amount) => {
+                        let amount = Self::do_withdraw(&account, pool_id, amount, false)?;
+                        check_health = true;
+                        Self::deposit_event(Event::WithdrawSuccessful(pool_id, account.clone(), amount));
+                    },
+                    LendingAction::Borrow(pool_id, amount) => {
+                        Self::do_borrow(&account, pool_id, amount, false)?;
+                        check_health = true;
+                        Self::deposit_event(Event::BorrowSuccessful(pool_id, account.clone(), amount));
+                    },
+                    LendingAction::Repay(pool_id, amount) => {
+                        let amount = Self::do_repay(&account, &account, pool_id, amount)?;
+                        Self::deposit_event(Event::ReplaySuccessful(pool_id, account.clone(), amount));
+                    },
+                }
             }
 
-            pool.accrue_interest()?;
-            pool.decrement_debt(&amount_fu128);
-            PoolRepository::<T>::save(pool.clone());
-
-            user_debt.accrue_interest(&pool.total_debt_index())?;
-            UserAccountUtil::<T>::decrement_debt(&pool, account.clone(), &amount_fu128, user_debt)?;
-
-            // Transfer currency
-            T::Currency::transfer(pool.currency_id(), &account, &Self::account_id(), transfer_amount)?;
+            // supplying and repaying can only improve the position, no need to check for those
+            if check_health {
+                let (pool_map, user_supply_debt) = Self::prefetch_for_liquidation_check(account.clone())?;
+                let balances = UserAccountUtil::<T>::user_balances(&user_supply_debt, &pool_map)?;
+                if balances.is_liquidated(LiquidationThreshold::<T>::get()) {
+                    return Err(Error::<T>::BelowLiquidationThreshold.into());
+                }
+            }
 
-            Self::deposit_event(Event::ReplaySuccessful(pool_id, account, amount_fu128));
+            Self::deposit_event(Event::BatchActionsSuccessful(account, actions.len() as u32));
 
             Ok(().into())
         }
@@ -554,9 +585,9 @@ pub mod pallet {
             if !debt_pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
 
             // Ensure the user has got the collateral and debt
-            let mut user_debt = PoolUserDebts::<T>::get(debt_pool_id, target_user.clone())
+            let user_debt = PoolUserDebts::<T>::get(debt_pool_id, target_user.clone())
                 .ok_or(Error::<T>::UserNoDebtInPool)?;
-            let mut user_collateral = PoolUserSupplies::<T>::get(collateral_pool_id, target_user.clone())
+            let user_collateral = PoolUserSupplies::<T>::get(collateral_pool_id, target_user.clone())
                 .ok_or(Error::<T>::UserNoSupplyInPool)?;
 
             // Ensure the user is liquidated
@@ -568,52 +599,54 @@ pub mod pallet {
 
             let mut debt_pool = pool_map.get(&debt_pool_id).ok_or(CustomError::InconsistentState)?.clone();
             let mut collateral_pool = pool_map.get(&collateral_pool_id).ok_or(CustomError::InconsistentState)?.clone();
-            user_debt.accrue_interest(&debt_pool.total_debt_index())?;
-            user_collateral.accrue_interest(&collateral_pool.total_debt_index())?;
+            let user_debt_amount = *user_supply_debt.debt.get(&debt_pool_id).ok_or(CustomError::InconsistentState)?;
+            let user_collateral_amount = *user_supply_debt.supply.get(&collateral_pool_id).ok_or(CustomError::InconsistentState)?;
 
             // price should have been checked in liquidation checks
             let discounted_collateral_price = collateral_pool.discounted_price(&collateral_pool.price());
 
             // Now, we derive the amount for liquidation
-            let arbitrageur_get_limit = collateral_pool.closable_amount(&user_collateral.amount(), &collateral_pool.price());
-            let arbitrageur_pay_limit = Self::convert_amount(
-                &arbitrageur_get_limit,
+            let arbitrageur_get_limit = collateral_pool.closable_amount(&user_collateral_amount, &collateral_pool.price());
+            let arbitrageur_pay_limit = Convertor::convert_by_price(
+                arbitrageur_get_limit,
                 &discounted_collateral_price,
                 &debt_pool.price(),
+                Rounding::Down,
             )?;
 
             // Now we calculate the total amount to transfer to arbitrageur
-            let mut pay_amount = T::Conversion::convert(pay_amount);
+            let mut pay_amount = pay_amount;
             if pay_amount > arbitrageur_pay_limit { pay_amount = arbitrageur_pay_limit; }
-            if pay_amount > user_debt.amount() { pay_amount = user_debt.amount(); }
+            if pay_amount > user_debt_amount { pay_amount = user_debt_amount; }
 
-            // TODO: check rounding errors due to discount_factor
-            let get_amount = Self::convert_amount(
-                &pay_amount,
+            // the collateral given out is rounded down in favour of the protocol
+            let get_amount = Convertor::convert_by_price(
+                pay_amount,
                 &debt_pool.price(),
                 &discounted_collateral_price,
+                Rounding::Down,
             )?;
 
+            let (pay_amount, debt_shares) = UserAccountUtil::<T>::repayable(&debt_pool, &user_debt, pay_amount)?;
+            let (get_amount, collateral_shares) = UserAccountUtil::<T>::withdrawable(&collateral_pool, &user_collateral, get_amount)?;
+
             let debt_currency_id = debt_pool.currency_id();
             let collateral_currency_id = collateral_pool.currency_id();
 
             // Update user accounts
-            UserAccountUtil::<T>::decrement_supply(&collateral_pool, target_user.clone(), &get_amount, user_collateral)?;
-            UserAccountUtil::<T>::decrement_debt(&debt_pool, target_user.clone(), &pay_amount, user_debt)?;
+            UserAccountUtil::<T>::decrement_supply(&collateral_pool, target_user.clone(), &collateral_shares, user_collateral)?;
+            UserAccountUtil::<T>::decrement_debt(&debt_pool, target_user.clone(), &debt_shares, user_debt)?;
 
             // update pools
-            // TODO: do we need to check if the amounts are deductable here again?
-            debt_pool.decrement_debt(&pay_amount);
-            collateral_pool.decrement_supply(&get_amount);
+            debt_pool.decrement_debt(&debt_shares)?;
+            collateral_pool.decrement_supply(&collateral_shares)?;
             PoolRepository::<T>::save(debt_pool);
             PoolRepository::<T>::save(collateral_pool);
 
             // Now we can transfer debt from arbitrageur to pool
-            let pay_amount_transfer = T::Conversion::convert(pay_amount);
-            T::Currency::transfer(debt_currency_id, &account, &Self::account_id(), pay_amount_transfer)?;
+            T::Currency::transfer(debt_currency_id, &account, &Self::account_id(), pay_amount)?;
             // Then the collateral to arbitrageur
-            let get_amount_transfer = T::Conversion::convert(get_amount);
-            T::Currency::transfer(collateral_currency_id, &Self::account_id(), &account, get_amount_transfer)?;
+            T::Currency::transfer(collateral_currency_id, &Self::account_id(), &account, get_amount)?;
 
             Self::deposit_event(Event::LiquidationSuccessful);
 
@@ -638,12 +671,12 @@ pub mod pallet {
         }
 
         /// Get the user supply balance for the user in a pool
-        pub fn user_supply_balance(pool_id: PoolId, user: T::AccountId) -> Result<FixedU128, CustomError> {
+        pub fn user_supply_balance(pool_id: PoolId, user: T::AccountId) -> Result<BalanceOf<T>, CustomError> {
             UserAccountUtil::<T>::supply_balance_with_interest(pool_id, user)
         }
 
         /// Get the user debt balance for the user in a pool
-        pub fn user_debt_balance(pool_id: PoolId, user: T::AccountId) -> Result<FixedU128, CustomError> {
+        pub fn user_debt_balance(pool_id: PoolId, user: T::AccountId) -> Result<BalanceOf<T>, CustomError> {
             UserAccountUtil::<T>::debt_balance_with_interest(pool_id, user)
         }
 
@@ -653,8 +686,164 @@ pub mod pallet {
             UserAccountUtil::<T>::user_balances(&user_supply_debt, &pool_map)
         }
 
+        /// Checks if the user can be liquidated, without writing the accrued interest
+        pub fn is_liquidatable(user: T::AccountId) -> bool {
+            Self::accrued_supply_debt(user)
+                .and_then(|(pool_map, user_supply_debt)| UserAccountUtil::<T>::user_balances(&user_supply_debt, &pool_map))
+                .map(|balances| balances.is_liquidated(LiquidationThreshold::<T>::get()))
+                .unwrap_or(false)
+        }
+
         /* -------- Internal Helper Functions ------------ */
 
+        /// Supplies `amount` from the payer into the pool, the supply shares go to the beneficiary
+        fn do_supply(
+            payer: &T::AccountId,
+            beneficiary: &T::AccountId,
+            pool_id: PoolId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
+
+            // check floating-rate-pool exists and get floating-rate-pool instance
+            let mut pool: PoolProxy<T> = PoolRepository::<T>::find_without_price(pool_id)?;
+            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
+            if amount < pool.minimal_amount() { return Err(Error::<T>::BalanceTooLow.into()) }
+
+            // shares are rounded down, the user never gets more than supplied
+            pool.accrue_interest()?;
+            let shares = pool.supply_shares(&amount, Rounding::Down)?;
+            if shares.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
+
+            // transfer asset
+            T::Currency::transfer(pool.currency_id(), payer, &Self::account_id(), amount)?;
+
+            pool.increment_supply(&shares)?;
+            PoolRepository::<T>::save(pool.clone());
+            UserAccountUtil::<T>::increment_supply(&pool, beneficiary.clone(), &shares)?;
+
+            Ok(().into())
+        }
+
+        /// Withdraws up to `amount` of the account's supply, returns the amount withdrawn.
+        /// The liquidation threshold is only checked if `check_liquidation` is set.
+        fn do_withdraw(
+            account: &T::AccountId,
+            pool_id: PoolId,
+            amount: BalanceOf<T>,
+            check_liquidation: bool,
+        ) -> Result<BalanceOf<T>, DispatchErrorWithPostInfo> {
+            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
+
+            // Check pool can withdraw
+            let mut pool: PoolProxy<T> = PoolRepository::<T>::find(pool_id)?;
+            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
+            pool.accrue_interest()?;
+
+            // Check user supply can withdraw
+            let user_supply = PoolUserSupplies::<T>::get(pool.id(), account.clone())
+                .ok_or(Error::<T>::UserNoSupplyInPool)?;
+            let (amount, shares) = UserAccountUtil::<T>::withdrawable(&pool, &user_supply, amount)?;
+
+            // This ensures the pool's supply will never be lower than 0
+            if !pool.allow_amount_deduction(&amount)? { return Err(Error::<T>::NotEnoughLiquidity.into()); }
+
+            // Check if this pool is collateral
+            if check_liquidation && pool.can_be_collateral() {
+                let (pool_map, user_supply_debt) = Self::prefetch_for_liquidation_check(account.clone())?;
+                let r = UserAccountUtil::<T>::is_withdraw_trigger_liquidation(
+                    (pool_id, amount),
+                    user_supply_debt,
+                    pool_map,
+                    LiquidationThreshold::<T>::get(),
+                )?;
+                if r { return Err(Error::<T>::BelowLiquidationThreshold.into()); }
+            }
+
+            // Now the checks are done, we can prepare to transfer
+            UserAccountUtil::<T>::decrement_supply(&pool, account.clone(), &shares, user_supply)?;
+            pool.decrement_supply(&shares)?;
+            PoolRepository::<T>::save(pool.clone());
+
+            // Now perform the writes
+            T::Currency::transfer(pool.currency_id(), &Self::account_id(), account, amount)?;
+
+            Ok(amount)
+        }
+
+        /// Borrows `amount` from the pool.
+        /// The liquidation threshold is only checked if `check_liquidation` is set.
+        fn do_borrow(
+            account: &T::AccountId,
+            pool_id: PoolId,
+            amount: BalanceOf<T>,
+            check_liquidation: bool,
+        ) -> DispatchResultWithPostInfo {
+            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
+
+            // Check pool can borrow
+            let mut pool: PoolProxy<T> = PoolRepository::<T>::find(pool_id)?;
+            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
+            // Check sufficient liquidity
+            pool.accrue_interest()?;
+            if !pool.allow_amount_deduction(&amount)? { return Err(Error::<T>::NotEnoughLiquidity.into()); }
+
+            if check_liquidation {
+                let (mut pool_map, user_supply_debt) = Self::prefetch_for_liquidation_check(account.clone())?;
+                if !pool_map.contains_key(&pool_id) { pool_map.insert(pool_id, pool.clone()); }
+                let r = UserAccountUtil::<T>::is_borrow_trigger_liquidation(
+                    (pool_id, amount),
+                    user_supply_debt,
+                    pool_map,
+                    LiquidationThreshold::<T>::get(),
+                )?;
+                if r { return Err(Error::<T>::BelowLiquidationThreshold.into()); }
+            }
+
+            // debt shares are rounded up, the user always owes at least the borrowed amount
+            let shares = pool.debt_shares(&amount, Rounding::Up)?;
+
+            // Ready to perform the writes
+            // TODO: add check can transfer
+            T::Currency::transfer(pool.currency_id(), &Self::account_id(), account, amount)?;
+            UserAccountUtil::<T>::increment_debt(&pool, account.clone(), &shares)?;
+
+            pool.increment_debt(&shares)?;
+            PoolRepository::<T>::save(pool);
+
+            Ok(().into())
+        }
+
+        /// Repays up to `amount` of the beneficiary's debt from the payer, returns the amount repaid
+        fn do_repay(
+            payer: &T::AccountId,
+            beneficiary: &T::AccountId,
+            pool_id: PoolId,
+            amount: BalanceOf<T>,
+        ) -> Result<BalanceOf<T>, DispatchErrorWithPostInfo> {
+            if amount.is_zero() { return Err(Error::<T>::BalanceTooLow.into()); }
+
+            // Check pool can borrow
+            let mut pool = PoolRepository::<T>::find_without_price(pool_id)?;
+            if !pool.enabled() { return Err(Error::<T>::PoolNotEnabled.into()); }
+
+            // Check the beneficiary has got the debt
+            let user_debt = PoolUserDebts::<T>::get(pool.id(), beneficiary.clone())
+                .ok_or(Error::<T>::UserNoDebtInPool)?;
+
+            pool.accrue_interest()?;
+            let (amount, shares) = UserAccountUtil::<T>::repayable(&pool, &user_debt, amount)?;
+
+            pool.decrement_debt(&shares)?;
+            PoolRepository::<T>::save(pool.clone());
+            UserAccountUtil::<T>::decrement_debt(&pool, beneficiary.clone(), &shares, user_debt)?;
+
+            // Transfer currency
+            T::Currency::transfer(pool.currency_id(), payer, &Self::account_id(), amount)?;
+
+            Ok(amount)
+        }
+
         fn account_id() -> T::AccountId { PALLET_ID.into_account() }
 
         fn ensure_signed_and_root(origin: OriginFor<T>) -> Result<T::AccountId, BadOrigin> {
@@ -667,18 +856,6 @@ pub mod pallet {
             Ok(origin)
         }
 
-        fn convert_amount(
-            amount_of_source: &FixedU128,
-            price_of_source: &PriceValue,
-            price_of_target: &PriceValue
-        ) -> Result<FixedU128, CustomError> {
-            Ok(price_of_source
-                .checked_div(price_of_target)
-                .ok_or(CustomError::FlownError)?
-                .checked_mul(amount_of_source)
-                .ok_or(CustomError::FlownError)?)
-        }
-
         /// Check the floating-rate-pool's parameters
         /// TODO: we need to tighten some of the checks
         fn check_pool(pool: &Pool<T>) -> Result<(), InvalidParameters> {
@@ -696,20 +873,28 @@ pub mod pallet {
             Err(InvalidParameters{})
         }
 
-        fn prefetch_for_liquidation_check(account: T::AccountId) -> Result<(BTreeMap<PoolId, PoolProxy<T>>, UserSupplyDebtData), CustomError> {
+        fn prefetch_for_liquidation_check(account: T::AccountId) -> Result<(BTreeMap<PoolId, PoolProxy<T>>, UserSupplyDebtData<BalanceOf<T>>), CustomError> {
+            let (pools, supply_debt_map) = Self::accrued_supply_debt(account)?;
+            for p in pools.values() {
+                PoolRepository::<T>::save(p.clone());
+            }
+            Ok((pools, supply_debt_map))
+        }
+
+        /// Fetches the pools of the user with the interest accrued up to now, the pools are not saved
+        fn accrued_supply_debt(account: T::AccountId) -> Result<(BTreeMap<PoolId, PoolProxy<T>>, UserSupplyDebtData<BalanceOf<T>>), CustomError> {
             // Prefetch all the needed data
             let user_debts = UserAccountUtil::<T>::get_debt_pools(account.clone());
             let user_supplies = UserAccountUtil::<T>::get_supply_pools(account.clone());
-            let pools = PoolRepository::<T>::find_pools(&user_supplies, &user_debts)?;
+            let mut pools = PoolRepository::<T>::find_pools(&user_supplies, &user_debts)?;
 
             // Accrue interest for all
-            for mut p in pools.values().cloned() {
+            for p in pools.values_mut() {
                 p.accrue_interest()?;
-                PoolRepository::<T>::save(p);
             }
-            let supply_debt_map = UserAccountUtil::<T>::accrue_interest_for_user(account.clone(), &pools)?;
+            let supply_debt_map = UserAccountUtil::<T>::user_supply_debt(account, &pools)?;
 
             Ok((pools, supply_debt_map))
         }
     }
-}
\ No newline at end of file
+}
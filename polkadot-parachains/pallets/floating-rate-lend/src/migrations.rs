@@ -0,0 +1,219 @@
+//! Storage migrations for the floating rate lending module.
+
+use codec::{Decode, Encode};
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+use sp_runtime::FixedU128;
+use sp_runtime::traits::{Convert, One, Saturating, Zero};
+use sp_std::{vec::Vec};
+use sp_std::collections::btree_map::BTreeMap;
+
+use polkadot_parachain_primitives::{CustomError, PoolId};
+
+use crate::{BalanceOf, Config, CurrencyIdOf, PoolStorage, PoolUserDebts, PoolUserSupplies};
+#[cfg(feature = "try-runtime")]
+use crate::{Pallet, StorageVersion};
+#[cfg(feature = "try-runtime")]
+use pallet_traits::{Releases, StorageVersioned};
+use crate::pool::Pool;
+use crate::types::{Convertor, Rounding, UserAccountUtil, UserData};
+
+/// The pool layout of `Releases::V1_0_0`, supply and debt kept as `FixedU128` with interest applied
+#[derive(Encode, Decode)]
+pub(crate) struct OldPool<T: Config> {
+    pub(crate) id: u64,
+    pub(crate) name: Vec<u8>,
+    pub(crate) currency_id: CurrencyIdOf<T>,
+    pub(crate) can_be_collateral: bool,
+    pub(crate) enabled: bool,
+    pub(crate) supply: FixedU128,
+    pub(crate) total_supply_index: FixedU128,
+    pub(crate) debt: FixedU128,
+    pub(crate) total_debt_index: FixedU128,
+    pub(crate) interest_updated_at: T::BlockNumber,
+    pub(crate) minimal_amount: FixedU128,
+    pub(crate) safe_factor: FixedU128,
+    pub(crate) close_factor: FixedU128,
+    pub(crate) close_minimal_amount: FixedU128,
+    pub(crate) discount_factor: FixedU128,
+    pub(crate) utilization_factor: FixedU128,
+    pub(crate) initial_interest_rate: FixedU128,
+    pub(crate) last_updated: T::BlockNumber,
+    pub(crate) last_updated_by: T::AccountId,
+    pub(crate) created_by: T::AccountId,
+    pub(crate) created_at: T::BlockNumber,
+}
+
+/// The user data layout of `Releases::V1_0_0`, the amount is valid at `index`
+#[derive(Encode, Decode)]
+pub(crate) struct OldUserData {
+    pub(crate) amount: FixedU128,
+    pub(crate) index: FixedU128,
+}
+
+/// Migrates user positions and pools from `FixedU128` amounts to scaled `Balance` shares.
+///
+/// A user amount recorded at the user's index is worth `amount / index` shares of the pool, no
+/// matter how far the pool index has moved since. Supplies round down and debts round up. Pool
+/// totals are rebuilt from the migrated user shares so that they always add up.
+pub fn migrate_to_scaled_balances<T: Config>() -> Weight {
+    // the value of a single balance unit, used to convert the legacy amounts into balances
+    let unit = T::Conversion::convert(BalanceOf::<T>::one());
+
+    let mut reads_writes: Weight = 0;
+    let mut supply_shares: BTreeMap<PoolId, BalanceOf<T>> = BTreeMap::new();
+    let mut debt_shares: BTreeMap<PoolId, BalanceOf<T>> = BTreeMap::new();
+    let mut emptied_supplies = Vec::new();
+    let mut emptied_debts = Vec::new();
+
+    PoolUserSupplies::<T>::translate::<OldUserData, _>(|pool_id, account, old| {
+        reads_writes += 1;
+        match to_shares::<T>(&old, &unit, Rounding::Down) {
+            Ok(shares) if !shares.is_zero() => {
+                let total = supply_shares.entry(pool_id).or_insert_with(Zero::zero);
+                *total = total.saturating_add(shares);
+                Some(UserData::new(shares))
+            },
+            _ => {
+                emptied_supplies.push((pool_id, account));
+                None
+            },
+        }
+    });
+
+    PoolUserDebts::<T>::translate::<OldUserData, _>(|pool_id, account, old| {
+        reads_writes += 1;
+        match to_shares::<T>(&old, &unit, Rounding::Up) {
+            Ok(shares) if !shares.is_zero() => {
+                let total = debt_shares.entry(pool_id).or_insert_with(Zero::zero);
+                *total = total.saturating_add(shares);
+                Some(UserData::new(shares))
+            },
+            _ => {
+                emptied_debts.push((pool_id, account));
+                None
+            },
+        }
+    });
+
+    for (pool_id, account) in emptied_supplies {
+        log::warn!("dropping empty supply of pool {:?} during migration", pool_id);
+        UserAccountUtil::<T>::remove_user_supply(pool_id, account);
+        reads_writes += 1;
+    }
+    for (pool_id, account) in emptied_debts {
+        log::warn!("dropping empty debt of pool {:?} during migration", pool_id);
+        UserAccountUtil::<T>::remove_user_debt(pool_id, account);
+        reads_writes += 1;
+    }
+
+    PoolStorage::<T>::translate::<OldPool<T>, _>(|id, old| {
+        reads_writes += 1;
+        let minimal_amount = Convertor::fixed_to_balance(&old.minimal_amount, &unit, Rounding::Up)
+            .unwrap_or_else(|_| Zero::zero());
+        let mut pool = Pool::<T>::new(
+            old.id,
+            old.name,
+            old.currency_id,
+            old.can_be_collateral,
+            old.safe_factor,
+            old.close_factor,
+            old.discount_factor,
+            old.utilization_factor,
+            old.initial_interest_rate,
+            minimal_amount,
+            old.created_by,
+            old.created_at,
+        )
+            .with_indices(old.total_supply_index, old.total_debt_index)
+            .with_scaled_balances(
+                supply_shares.get(&id).cloned().unwrap_or_else(Zero::zero),
+                debt_shares.get(&id).cloned().unwrap_or_else(Zero::zero),
+            );
+        pool.enabled = old.enabled;
+        pool.close_minimal_amount = old.close_minimal_amount;
+        pool.interest_updated_at = old.interest_updated_at;
+        pool.last_updated = old.last_updated;
+        pool.last_updated_by = old.last_updated_by;
+
+        log::info!(
+            "migrated floating-rate-pool {:?}, legacy supply {:?} and debt {:?}",
+            id,
+            old.supply,
+            old.debt,
+        );
+        Some(pool)
+    });
+
+    T::DbWeight::get().reads_writes(reads_writes, reads_writes)
+}
+
+fn to_shares<T: Config>(old: &OldUserData, unit: &FixedU128, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+    let amount: BalanceOf<T> = Convertor::fixed_to_balance(&old.amount, unit, rounding)?;
+    Convertor::to_shares(amount, &old.index, rounding)
+}
+
+/// The temporary storage key of the pools that exist before the upgrade
+#[cfg(feature = "try-runtime")]
+const POOLS_BEFORE_UPGRADE: &str = "floating-rate-lend:pools";
+
+/// Records the pools that exist before the upgrade, in the layout of the current storage version.
+#[cfg(feature = "try-runtime")]
+pub fn pre_upgrade<T: Config>() -> Result<(), &'static str> {
+    use frame_support::storage::{migration::storage_key_iter, StoragePrefixedMap};
+    use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+    use frame_support::Twox64Concat;
+
+    let pools: Vec<PoolId> = if StorageVersion::<T>::get() == Releases::V1_0_0 {
+        storage_key_iter::<PoolId, OldPool<T>, Twox64Concat>(
+            PoolStorage::<T>::module_prefix(),
+            PoolStorage::<T>::storage_prefix(),
+        )
+            .map(|(id, _)| id)
+            .collect()
+    } else {
+        PoolStorage::<T>::iter().map(|(id, _)| id).collect()
+    };
+
+    log::info!("floating-rate-lend has {} pools before the upgrade", pools.len());
+    Pallet::<T>::set_temp_storage(pools, POOLS_BEFORE_UPGRADE);
+    Ok(())
+}
+
+/// Checks that the storage is at the latest version, that all pools recorded by [`pre_upgrade`]
+/// still decode and that the pool totals add up to the shares of their users.
+#[cfg(feature = "try-runtime")]
+pub fn post_upgrade<T: Config>() -> Result<(), &'static str> {
+    use frame_support::ensure;
+    use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+
+    Pallet::<T>::ensure_latest_storage_version()?;
+
+    let pools: Vec<PoolId> = Pallet::<T>::get_temp_storage(POOLS_BEFORE_UPGRADE)
+        .ok_or("pools before the upgrade were not recorded")?;
+
+    let mut supply_shares: BTreeMap<PoolId, BalanceOf<T>> = BTreeMap::new();
+    for (pool_id, _, supply) in PoolUserSupplies::<T>::iter() {
+        let total = supply_shares.entry(pool_id).or_insert_with(Zero::zero);
+        *total = total.saturating_add(supply.shares());
+    }
+    let mut debt_shares: BTreeMap<PoolId, BalanceOf<T>> = BTreeMap::new();
+    for (pool_id, _, debt) in PoolUserDebts::<T>::iter() {
+        let total = debt_shares.entry(pool_id).or_insert_with(Zero::zero);
+        *total = total.saturating_add(debt.shares());
+    }
+
+    for id in pools {
+        let pool = PoolStorage::<T>::try_get(id).map_err(|_| "pool can not be decoded after the upgrade")?;
+        ensure!(
+            pool.scaled_supply() == supply_shares.get(&id).cloned().unwrap_or_else(Zero::zero),
+            "pool supply does not add up to the supplies of its users"
+        );
+        ensure!(
+            pool.scaled_debt() == debt_shares.get(&id).cloned().unwrap_or_else(Zero::zero),
+            "pool debt does not add up to the debts of its users"
+        );
+    }
+
+    Ok(())
+}
@@ -1,18 +1,18 @@
 use codec::{Decode, Encode};
 use sp_runtime::{FixedPointNumber, FixedU128};
 use sp_runtime::RuntimeDebug;
-use sp_runtime::traits::{CheckedAdd, CheckedDiv, CheckedMul, One, Saturating, Zero};
+use sp_runtime::traits::{CheckedAdd, CheckedMul, CheckedSub, Convert, One, Saturating, UniqueSaturatedInto, Zero};
 use sp_std::{vec::Vec};
 use sp_std::convert::TryInto;
 use sp_std::marker;
-use sp_std::ops::{Add, Mul, Sub};
+use sp_std::ops::Mul;
 
 use pallet_traits::PriceProvider;
 use polkadot_parachain_primitives::{PoolId, PriceValue, CustomError, Price};
 
-use crate::{Config, CurrencyIdOf, PoolStorage};
+use crate::{BalanceOf, Config, CurrencyIdOf, PoolStorage};
 use sp_std::collections::btree_map::BTreeMap;
-use crate::types::Convertor;
+use crate::types::{Convertor, Rounding};
 
 /// The floating-rate-pool for different lending transactions.
 /// Each floating-rate-pool needs to be associated with a currency id.
@@ -29,15 +29,17 @@ pub struct Pool<T: Config> {
     pub enabled: bool,
 
     /* --- Supply And Debt --- */
-    supply: FixedU128,
+    /// The total supply shares. The supplied balance is `scaled_supply * total_supply_index`
+    scaled_supply: BalanceOf<T>,
     total_supply_index: FixedU128,
-    debt: FixedU128,
+    /// The total debt shares. The borrowed balance is `scaled_debt * total_debt_index`
+    scaled_debt: BalanceOf<T>,
     total_debt_index: FixedU128,
     pub interest_updated_at: T::BlockNumber,
 
     /* ----- Parameters ----- */
-    /// Minimum amount required for each transaction and to retain the account in pool
-    pub minimal_amount: FixedU128,
+    /// Minimum amount required for each transaction
+    pub minimal_amount: BalanceOf<T>,
     /// When this is used as collateral, the value is multiplied by safe_factor
     pub safe_factor: FixedU128,
     /// Only a close_factor of the collateral can be liquidated at a time
@@ -81,7 +83,7 @@ impl <T: Config> Pool<T> {
             Convertor::convert_percentage(95),
             Convertor::convert_percentage_annum_to_per_block(20),
             Convertor::convert_percentage_annum_to_per_block(2),
-            FixedU128::from(0),
+            Zero::zero(),
             owner,
             block_number,
         )
@@ -97,7 +99,7 @@ impl <T: Config> Pool<T> {
                discount_factor: FixedU128,
                utilization_factor: FixedU128,
                initial_interest_rate: FixedU128,
-               minimal_amount: FixedU128,
+               minimal_amount: BalanceOf<T>,
                owner: T::AccountId,
                block_number: T::BlockNumber,
     ) -> Pool<T>{
@@ -107,9 +109,9 @@ impl <T: Config> Pool<T> {
             currency_id,
             can_be_collateral,
             enabled: false,
-            supply: FixedU128::zero(),
+            scaled_supply: Zero::zero(),
             total_supply_index: FixedU128::one(),
-            debt: FixedU128::zero(),
+            scaled_debt: Zero::zero(),
             total_debt_index: FixedU128::one(),
             interest_updated_at: block_number,
             minimal_amount,
@@ -127,17 +129,41 @@ impl <T: Config> Pool<T> {
         }
     }
 
+    /// Rebuilds a pool from an already scaled supply and debt, used by the storage migration
+    pub(crate) fn with_scaled_balances(mut self, scaled_supply: BalanceOf<T>, scaled_debt: BalanceOf<T>) -> Pool<T> {
+        self.scaled_supply = scaled_supply;
+        self.scaled_debt = scaled_debt;
+        self
+    }
+
+    /// Restores the interest indices, used by the storage migration
+    pub(crate) fn with_indices(mut self, total_supply_index: FixedU128, total_debt_index: FixedU128) -> Pool<T> {
+        self.total_supply_index = total_supply_index;
+        self.total_debt_index = total_debt_index;
+        self
+    }
+
     /* ------- Getters and Setters ------- */
-    pub fn supply(&self) -> FixedU128 { self.supply }
+    /// The total supply of the pool including interest, rounded down
+    pub fn supply(&self) -> Result<BalanceOf<T>, CustomError> {
+        self.supply_amount(&self.scaled_supply, Rounding::Down)
+    }
+
+    /// The total debt of the pool including interest, rounded up
+    pub fn debt(&self) -> Result<BalanceOf<T>, CustomError> {
+        self.debt_amount(&self.scaled_debt, Rounding::Up)
+    }
+
+    pub fn scaled_supply(&self) -> BalanceOf<T> { self.scaled_supply }
 
-    pub fn debt(&self) -> FixedU128 { self.debt }
+    pub fn scaled_debt(&self) -> BalanceOf<T> { self.scaled_debt }
 
     pub fn total_supply_index(&self) -> FixedU128 { self.total_supply_index }
 
     pub fn total_debt_index(&self) -> FixedU128 { self.total_debt_index }
 
-    /// Accrue interest for the floating-rate-pool. The block_number is the block number when the floating-rate-pool is updated
-    /// TODO: update and check all the overflow here
+    /// Accrue interest for the floating-rate-pool. The block_number is the block number when the floating-rate-pool is updated.
+    /// Only the indices move, the scaled supply and debt are left untouched.
     pub fn accrue_interest(&mut self, block_number: T::BlockNumber) -> Result<bool, CustomError>{
         // Not updating if the time is the same or lagging
         if self.interest_updated_at >= block_number {
@@ -156,43 +182,74 @@ impl <T: Config> Pool<T> {
         let supply_multiplier = FixedU128::one() + s_rate * FixedU128::saturating_from_integer(elapsed_time_u32);
         let debt_multiplier = FixedU128::one() + d_rate * FixedU128::saturating_from_integer(elapsed_time_u32);
 
-        self.supply = supply_multiplier * self.supply;
-        self.total_supply_index = self.total_supply_index * supply_multiplier;
-
-        self.debt = debt_multiplier * self.debt;
-        self.total_debt_index = self.total_debt_index * debt_multiplier;
+        self.total_supply_index = self.total_supply_index
+            .checked_mul(&supply_multiplier)
+            .ok_or(CustomError::FlownError)?;
+        self.total_debt_index = self.total_debt_index
+            .checked_mul(&debt_multiplier)
+            .ok_or(CustomError::FlownError)?;
 
         self.interest_updated_at = block_number;
 
         Ok(true)
     }
 
-    /// Increment the supply of the pool
-    pub fn increment_supply(&mut self, amount: &FixedU128) { self.supply = self.supply.add(*amount); }
+    /// Increment the supply shares of the pool
+    pub fn increment_supply(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.scaled_supply = self.scaled_supply.checked_add(shares).ok_or(CustomError::FlownError)?;
+        Ok(())
+    }
+
+    /// Decrement the supply shares of the pool
+    pub fn decrement_supply(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.scaled_supply = self.scaled_supply.checked_sub(shares).ok_or(CustomError::FlownError)?;
+        Ok(())
+    }
+
+    /// Increment the debt shares of the pool
+    pub fn increment_debt(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.scaled_debt = self.scaled_debt.checked_add(shares).ok_or(CustomError::FlownError)?;
+        Ok(())
+    }
+
+    /// Decrement the debt shares of the pool
+    pub fn decrement_debt(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.scaled_debt = self.scaled_debt.checked_sub(shares).ok_or(CustomError::FlownError)?;
+        Ok(())
+    }
+
+    /// The supply shares worth `amount` at the current supply index
+    pub fn supply_shares(&self, amount: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        Convertor::to_shares(*amount, &self.total_supply_index, rounding)
+    }
 
-    /// Decrement the supply of the pool
-    /// TODO: handles if amount is more than supply
-    pub fn decrement_supply(&mut self, amount: &FixedU128) { self.supply = self.supply.sub(*amount); }
+    /// The balance `shares` of supply are worth at the current supply index
+    pub fn supply_amount(&self, shares: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        Convertor::to_amount(*shares, &self.total_supply_index, rounding)
+    }
 
-    /// Increment the debt of the pool
-    pub fn increment_debt(&mut self, amount: &FixedU128) { self.debt = self.debt.add(*amount); }
+    /// The debt shares worth `amount` at the current debt index
+    pub fn debt_shares(&self, amount: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        Convertor::to_shares(*amount, &self.total_debt_index, rounding)
+    }
 
-    /// Decrement the debt of the pool
-    /// TODO: handles if amount is more than debt
-    pub fn decrement_debt(&mut self, amount: &FixedU128) { self.debt = self.debt.sub(*amount); }
+    /// The balance `shares` of debt are worth at the current debt index
+    pub fn debt_amount(&self, shares: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        Convertor::to_amount(*shares, &self.total_debt_index, rounding)
+    }
 
     /// The amount that can be close given the input
-    pub fn closable_amount(&self, amount: &FixedU128, price: &PriceValue) -> FixedU128 {
-        let evaluation = amount.saturating_mul(*price);
+    pub fn closable_amount(&self, amount: &BalanceOf<T>, price: &PriceValue) -> BalanceOf<T> {
+        let evaluation = T::Conversion::convert(*amount).saturating_mul(*price);
         if evaluation <= self.close_minimal_amount { return *amount; }
-        self.close_factor.mul(*amount)
+        Convertor::to_amount(*amount, &self.close_factor, Rounding::Down).unwrap_or_else(|_| *amount)
     }
 
     /// The discounted price of the pool given the current price of the currency
     pub fn discounted_price(&self, price: &PriceValue) -> PriceValue { self.discount_factor.mul(*price) }
 
     pub fn supply_interest_rate(&self) -> Result<FixedU128, CustomError> {
-        if self.supply == FixedU128::zero() {
+        if self.scaled_supply.is_zero() {
             return Ok(FixedU128::zero());
         }
 
@@ -201,7 +258,7 @@ impl <T: Config> Pool<T> {
     }
 
     pub fn debt_interest_rate(&self) -> Result<FixedU128, CustomError> {
-        if self.supply == FixedU128::zero() {
+        if self.scaled_supply.is_zero() {
             return Ok(self.initial_interest_rate);
         }
 
@@ -211,7 +268,9 @@ impl <T: Config> Pool<T> {
     }
 
     fn utilization_ratio(&self) -> Result<FixedU128, CustomError> {
-        self.debt.checked_div(&self.supply).ok_or(CustomError::FlownError)
+        let debt: u128 = self.debt()?.unique_saturated_into();
+        let supply: u128 = self.supply()?.unique_saturated_into();
+        FixedU128::checked_from_rational(debt, supply).ok_or(CustomError::FlownError)
     }
 }
 
@@ -230,15 +289,34 @@ impl <T: Config> PoolProxy<T> {
     pub fn enabled(&self) -> bool { self.pool.enabled }
     pub fn can_be_collateral(&self) -> bool { self.pool.can_be_collateral }
     pub fn currency_id(&self) -> CurrencyIdOf<T> { self.pool.currency_id }
-    pub fn minimal_amount(&self) -> FixedU128 { self.pool.minimal_amount }
+    pub fn minimal_amount(&self) -> BalanceOf<T> { self.pool.minimal_amount }
     pub fn safe_factor(&self) -> FixedU128 { self.pool.safe_factor }
     pub fn discounted_price(&self, price: &PriceValue) -> FixedU128 { self.pool.discounted_price(price) }
-    pub fn closable_amount(&self, amount: &FixedU128, price: &PriceValue) -> FixedU128 { self.pool.closable_amount(amount, price) }
+    pub fn closable_amount(&self, amount: &BalanceOf<T>, price: &PriceValue) -> BalanceOf<T> { self.pool.closable_amount(amount, price) }
     pub fn total_debt_index(&self) -> FixedU128 { self.pool.total_debt_index }
     pub fn total_supply_index(&self) -> FixedU128 { self.pool.total_supply_index }
+    pub fn supply(&self) -> Result<BalanceOf<T>, CustomError> { self.pool.supply() }
+    pub fn debt(&self) -> Result<BalanceOf<T>, CustomError> { self.pool.debt() }
+
+    pub fn supply_shares(&self, amount: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        self.pool.supply_shares(amount, rounding)
+    }
 
-    pub fn allow_amount_deduction(&self, amount: &FixedU128) -> bool {
-        self.pool.debt().add(*amount) <= self.pool.supply()
+    pub fn supply_amount(&self, shares: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        self.pool.supply_amount(shares, rounding)
+    }
+
+    pub fn debt_shares(&self, amount: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        self.pool.debt_shares(amount, rounding)
+    }
+
+    pub fn debt_amount(&self, shares: &BalanceOf<T>, rounding: Rounding) -> Result<BalanceOf<T>, CustomError> {
+        self.pool.debt_amount(shares, rounding)
+    }
+
+    pub fn allow_amount_deduction(&self, amount: &BalanceOf<T>) -> Result<bool, CustomError> {
+        let debt = self.pool.debt()?.checked_add(amount).ok_or(CustomError::FlownError)?;
+        Ok(debt <= self.pool.supply()?)
     }
 
     /// Accrue interest and persists in the storage
@@ -248,20 +326,20 @@ impl <T: Config> PoolProxy<T> {
         Ok(updated)
     }
 
-    pub fn increment_debt(&mut self, amount: &FixedU128) {
-        self.pool.increment_debt(amount);
+    pub fn increment_debt(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.pool.increment_debt(shares)
     }
 
-    pub fn decrement_debt(&mut self, amount: &FixedU128) {
-        self.pool.decrement_debt(amount);
+    pub fn decrement_debt(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.pool.decrement_debt(shares)
     }
 
-    pub fn decrement_supply(&mut self, amount: &FixedU128) {
-        self.pool.decrement_supply(amount);
+    pub fn decrement_supply(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.pool.decrement_supply(shares)
     }
 
-    pub fn increment_supply(&mut self, amount: &FixedU128) {
-        self.pool.increment_supply(amount);
+    pub fn increment_supply(&mut self, shares: &BalanceOf<T>) -> Result<(), CustomError> {
+        self.pool.increment_supply(shares)
     }
 
     pub fn price_ready(&self) -> bool {
@@ -302,4 +380,4 @@ impl <T: Config> PoolRepository<T> {
     pub fn save(pool: PoolProxy<T>) {
         PoolStorage::<T>::insert(pool.pool.id, pool.pool);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,139 @@
+use crate::{Error, LendingAction, PoolStorage, PoolUserDebts, PoolUserSupplies};
+use crate::tests::mock::{*};
+
+use frame_support::{assert_noop, assert_ok};
+use crate::pool::PoolRepository;
+use polkadot_parachain_primitives::BALANCE_ONE;
+use crate::types::UserAccountUtil;
+
+/// Two collateral pools and a debt pool, ACCOUNT_1 supplied 1000 to pool 0 and borrowed 800 from pool 1
+fn setup_position() {
+    System::set_block_number(1);
+    for (id, can_be_collateral) in vec![(0, true), (1, false), (2, true)] {
+        let mut pool = pool_proxy(id, can_be_collateral);
+        pool.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+        PoolRepository::save(pool);
+        FloatingRateLend::enable_pool(Origin::signed(ROOT), id).ok();
+    }
+    FloatingRateLend::update_liquidation_threshold(Origin::signed(ROOT), 100).ok();
+
+    let pool_0 = PoolRepository::<Runtime>::find_without_price(0).unwrap();
+    UserAccountUtil::<Runtime>::increment_supply(&pool_0, ACCOUNT_1, &(1000 * BALANCE_ONE)).unwrap();
+
+    let mut pool_1 = PoolRepository::<Runtime>::find_without_price(1).unwrap();
+    pool_1.increment_debt(&(800 * BALANCE_ONE)).unwrap();
+    PoolRepository::save(pool_1.clone());
+    UserAccountUtil::<Runtime>::increment_debt(&pool_1, ACCOUNT_1, &(800 * BALANCE_ONE)).unwrap();
+}
+
+#[test]
+fn floating_lend_batch_actions_empty() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_noop!(
+                FloatingRateLend::batch_actions(Origin::signed(ACCOUNT_1), vec![]),
+                Error::<Runtime>::EmptyBatch
+            );
+        });
+}
+
+#[test]
+fn floating_lend_batch_actions_too_many() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let actions = vec![LendingAction::Supply(0, BALANCE_ONE); 17];
+            assert_noop!(
+                FloatingRateLend::batch_actions(Origin::signed(ACCOUNT_1), actions),
+                Error::<Runtime>::TooManyActions
+            );
+        });
+}
+
+#[test]
+fn floating_lend_batch_actions_move_collateral() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            setup_position();
+
+            // withdrawing alone would breach the liquidation threshold
+            assert_noop!(
+                FloatingRateLend::withdraw(Origin::signed(ACCOUNT_1), 0, 1000 * BALANCE_ONE),
+                Error::<Runtime>::BelowLiquidationThreshold
+            );
+
+            let actions = vec![
+                LendingAction::Withdraw(0, 1000 * BALANCE_ONE),
+                LendingAction::Supply(2, 1000 * BALANCE_ONE),
+            ];
+            assert_ok!(FloatingRateLend::batch_actions(Origin::signed(ACCOUNT_1), actions));
+            assert_eq!(
+                System::events().pop().unwrap().event,
+                Event::FloatingRateLend(crate::Event::BatchActionsSuccessful(ACCOUNT_1, 2))
+            );
+
+            assert_eq!(PoolUserSupplies::<Runtime>::get(0, ACCOUNT_1).is_none(), true);
+            assert_eq!(PoolUserSupplies::<Runtime>::get(2, ACCOUNT_1).unwrap().shares(), 1000 * BALANCE_ONE);
+            assert_eq!(PoolStorage::<Runtime>::get(0).unwrap().supply().unwrap(), 2000 * BALANCE_ONE);
+            assert_eq!(PoolStorage::<Runtime>::get(2).unwrap().supply().unwrap(), 4000 * BALANCE_ONE);
+        });
+}
+
+#[test]
+fn floating_lend_batch_actions_unhealthy_reverted() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            setup_position();
+
+            // the supply does not make up for the extra debt, nothing is applied
+            let actions = vec![
+                LendingAction::Supply(2, 100 * BALANCE_ONE),
+                LendingAction::Borrow(1, 500 * BALANCE_ONE),
+            ];
+            assert_noop!(
+                FloatingRateLend::batch_actions(Origin::signed(ACCOUNT_1), actions),
+                Error::<Runtime>::BelowLiquidationThreshold
+            );
+        });
+}
+
+#[test]
+fn floating_lend_batch_actions_unwind() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            setup_position();
+
+            let actions = vec![
+                LendingAction::Repay(1, 800 * BALANCE_ONE),
+                LendingAction::Withdraw(0, 1000 * BALANCE_ONE),
+            ];
+            assert_ok!(FloatingRateLend::batch_actions(Origin::signed(ACCOUNT_1), actions));
+
+            assert_eq!(PoolUserDebts::<Runtime>::get(1, ACCOUNT_1).is_none(), true);
+            assert_eq!(PoolUserSupplies::<Runtime>::get(0, ACCOUNT_1).is_none(), true);
+            assert_eq!(PoolStorage::<Runtime>::get(1).unwrap().debt().unwrap(), 0);
+        });
+}
+
+#[test]
+fn floating_lend_batch_actions_failed_action_reverts_all() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            setup_position();
+
+            // the second action fails, the first one is reverted as well
+            let actions = vec![
+                LendingAction::Supply(2, 100 * BALANCE_ONE),
+                LendingAction::Repay(2, 100 * BALANCE_ONE),
+            ];
+            assert_noop!(
+                FloatingRateLend::batch_actions(Origin::signed(ACCOUNT_1), actions),
+                Error::<Runtime>::UserNoDebtInPool
+            );
+        });
+}
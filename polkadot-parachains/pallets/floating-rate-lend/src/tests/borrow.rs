@@ -3,9 +3,7 @@ use crate::tests::mock::{*};
 
 use frame_support::{assert_noop, assert_ok};
 use crate::pool::PoolRepository;
-use sp_runtime::FixedU128;
 use polkadot_parachain_primitives::BALANCE_ONE;
-use sp_runtime::traits::{One};
 use crate::types::UserAccountUtil;
 
 #[test]
@@ -37,7 +35,7 @@ fn floating_lend_borrow_not_enough_liquidity() {
         .build()
         .execute_with(|| {
             let mut pool = default_pool_proxy();
-            pool.increment_supply(&FixedU128::one());
+            pool.increment_supply(&BALANCE_ONE).unwrap();
             PoolRepository::save(pool.clone());
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
@@ -57,23 +55,23 @@ fn floating_lend_borrow_success() {
             let mut pool_1 = pool_proxy(1, false);
             let mut pool_2 = pool_proxy(2, false);
 
-            pool_0.increment_supply(&FixedU128::from(3000));
-            pool_0.increment_debt(&FixedU128::from(1000));
+            pool_0.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_0.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
 
-            pool_1.increment_supply(&FixedU128::from(3000));
-            pool_1.increment_debt(&FixedU128::from(2000));
+            pool_1.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_1.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_2.increment_supply(&FixedU128::from(4000));
-            pool_2.increment_debt(&FixedU128::from(2000));
+            pool_2.increment_supply(&(4000 * BALANCE_ONE)).unwrap();
+            pool_2.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
             PoolRepository::save(pool_0.clone());
             PoolRepository::save(pool_1.clone());
             PoolRepository::save(pool_2.clone());
             // this is to simulate the user supply. User supplied 1000 pool has 3000
-            let amount = FixedU128::from(1000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_0, ROOT.clone(), &FixedU128::from(2000)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_1, ROOT.clone(), &amount).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_2, ROOT.clone(), &FixedU128::from(800)).unwrap();
+            let amount = 1000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool_0, ROOT.clone(), &(2000 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_1, ROOT.clone(), &amount).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_2, ROOT.clone(), &(800 * BALANCE_ONE)).unwrap();
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 2).ok();
@@ -84,10 +82,10 @@ fn floating_lend_borrow_success() {
 
             // check supplies
             let user = PoolUserDebts::<Runtime>::get(pool_2.id(), ROOT.clone()).unwrap();
-            assert_eq!(user.amount(), FixedU128::from(1800));
+            assert_eq!(user.shares(), 1800 * BALANCE_ONE);
 
             let pool = PoolStorage::<Runtime>::get(pool_2.id()).unwrap();
-            assert_eq!(pool.debt(), FixedU128::from(3000));
+            assert_eq!(pool.debt().unwrap(), 3000 * BALANCE_ONE);
         });
 }
 
@@ -100,23 +98,23 @@ fn floating_lend_borrow_failed() {
             let mut pool_1 = pool_proxy(1, false);
             let mut pool_2 = pool_proxy(2, false);
 
-            pool_0.increment_supply(&FixedU128::from(3000));
-            pool_0.increment_debt(&FixedU128::from(1000));
+            pool_0.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_0.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
 
-            pool_1.increment_supply(&FixedU128::from(3000));
-            pool_1.increment_debt(&FixedU128::from(2000));
+            pool_1.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_1.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_2.increment_supply(&FixedU128::from(4000));
-            pool_2.increment_debt(&FixedU128::from(2000));
+            pool_2.increment_supply(&(4000 * BALANCE_ONE)).unwrap();
+            pool_2.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
             PoolRepository::save(pool_0.clone());
             PoolRepository::save(pool_1.clone());
             PoolRepository::save(pool_2.clone());
             // this is to simulate the user supply. User supplied 1000 pool has 3000
-            let amount = FixedU128::from(1000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_0, ROOT.clone(), &FixedU128::from(2000)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_1, ROOT.clone(), &amount).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_2, ROOT.clone(), &FixedU128::from(800)).unwrap();
+            let amount = 1000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool_0, ROOT.clone(), &(2000 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_1, ROOT.clone(), &amount).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_2, ROOT.clone(), &(800 * BALANCE_ONE)).unwrap();
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 2).ok();
@@ -130,9 +128,9 @@ fn floating_lend_borrow_failed() {
 
             // check supplies
             let user = PoolUserDebts::<Runtime>::get(pool_2.id(), ROOT.clone()).unwrap();
-            assert_eq!(user.amount(), FixedU128::from(800));
+            assert_eq!(user.shares(), 800 * BALANCE_ONE);
 
             let pool = PoolStorage::<Runtime>::get(pool_2.id()).unwrap();
-            assert_eq!(pool.debt(), FixedU128::from(2000));
+            assert_eq!(pool.debt().unwrap(), 2000 * BALANCE_ONE);
         });
 }
\ No newline at end of file
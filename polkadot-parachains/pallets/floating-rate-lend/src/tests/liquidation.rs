@@ -118,8 +118,8 @@ fn floating_lend_liquidate_no_supply() {
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 1).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
 
-            let amount = FixedU128::from(1000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&debt_pool_0, ACCOUNT_1.clone(), &amount).unwrap();
+            let amount = 1000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_debt(&debt_pool_0, ACCOUNT_1.clone(), &amount).unwrap();
 
             let debt_pool_id = 0;
             let collateral_pool_id = 1;
@@ -136,28 +136,29 @@ fn floating_lend_liquidate_user_not_liquidated() {
         .build()
         .execute_with(|| {
             let mut debt_pool = pool_proxy(0, false);
-            debt_pool.increment_debt(&FixedU128::from(1000));
-            debt_pool.increment_supply(&FixedU128::from(2000));
+            debt_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            debt_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(debt_pool.clone());
 
             let mut collateral_pool = pool_proxy(1, true);
-            collateral_pool.increment_debt(&FixedU128::from(1000));
-            collateral_pool.increment_supply(&FixedU128::from(2000));
+            collateral_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            collateral_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(collateral_pool.clone());
 
             let mut supply_pool = pool_proxy(2, false);
-            supply_pool.increment_debt(&FixedU128::from(1000));
-            supply_pool.increment_supply(&FixedU128::from(2000));
+            supply_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            supply_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(supply_pool.clone());
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 1).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 2).ok();
 
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&debt_pool, ACCOUNT_1.clone(), &FixedU128::from(1800)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&supply_pool, ACCOUNT_1.clone(), &FixedU128::from(1800)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&collateral_pool, ACCOUNT_1.clone(), &FixedU128::from(2000)).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&debt_pool, ACCOUNT_1.clone(), &(1800 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&supply_pool, ACCOUNT_1.clone(), &(1800 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&collateral_pool, ACCOUNT_1.clone(), &(2000 * BALANCE_ONE)).unwrap();
 
+            assert!(!FloatingRateLend::is_liquidatable(ACCOUNT_1));
             assert_noop!(
                 FloatingRateLend::liquidate(Origin::signed(ROOT), ACCOUNT_1, debt_pool.id(), 100, collateral_pool.id()),
                 Error::<Runtime>::UserNotUnderLiquidation
@@ -171,27 +172,27 @@ fn floating_lend_liquidate_user_liquidated_not_fully_closable() {
         .build()
         .execute_with(|| {
             let mut debt_pool = pool_proxy_with_price(10, false, FixedU128::from(2));
-            debt_pool.increment_debt(&FixedU128::from(1000));
-            debt_pool.increment_supply(&FixedU128::from(2000));
+            debt_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            debt_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(debt_pool.clone());
 
             let mut collateral_pool = pool_proxy_with_price(11, true, FixedU128::saturating_from_rational(5, 10));
-            collateral_pool.increment_debt(&FixedU128::from(1000));
-            collateral_pool.increment_supply(&FixedU128::from(3000));
+            collateral_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            collateral_pool.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(collateral_pool.clone());
 
             let mut supply_pool = pool_proxy(12, false);
-            supply_pool.increment_debt(&FixedU128::from(1000));
-            supply_pool.increment_supply(&FixedU128::from(2000));
+            supply_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            supply_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(supply_pool.clone());
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 10).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 11).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 12).ok();
             FloatingRateLend::update_liquidation_threshold(Origin::signed(ROOT), 100).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&debt_pool, ACCOUNT_1.clone(), &FixedU128::from(1000)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&supply_pool, ACCOUNT_1.clone(), &FixedU128::from(1800)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&collateral_pool, ACCOUNT_1.clone(), &FixedU128::from(2000)).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&debt_pool, ACCOUNT_1.clone(), &(1000 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&supply_pool, ACCOUNT_1.clone(), &(1800 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&collateral_pool, ACCOUNT_1.clone(), &(2000 * BALANCE_ONE)).unwrap();
 
             // Debt pool price: $2, collateral pool price: $0.5, discount factor: 0.9
             // Account_1 debt worth: 1000 * $2 = 2000
@@ -204,17 +205,18 @@ fn floating_lend_liquidate_user_liquidated_not_fully_closable() {
             // User collateral to deduct: 1000, left 1000
             // Transfer 225 of debt pool currency from the arbitrager to the pool
             // Transfer 1000 of collateral pool currency from the pool to the arbitrager
+            assert!(FloatingRateLend::is_liquidatable(ACCOUNT_1));
             FloatingRateLend::liquidate(Origin::signed(ROOT), ACCOUNT_1, debt_pool.id(), 1000 * BALANCE_ONE, collateral_pool.id()).ok();
 
             let pool = PoolStorage::<Runtime>::get(debt_pool.id()).unwrap();
-            assert_eq!(pool.debt(), FixedU128::from(775));
+            assert_eq!(pool.debt().unwrap(), 775 * BALANCE_ONE);
             let pool = PoolStorage::<Runtime>::get(collateral_pool.id()).unwrap();
-            assert_eq!(pool.supply(), FixedU128::from_inner(2000000000000000000100));
+            assert_eq!(pool.supply().unwrap(), 2000 * BALANCE_ONE);
 
             let account_1_collateral = PoolUserSupplies::<Runtime>::get(collateral_pool.id(), ACCOUNT_1.clone()).unwrap();
-            assert_eq!(account_1_collateral.amount(), FixedU128::from_inner(1000000000000000000100));
+            assert_eq!(account_1_collateral.shares(), 1000 * BALANCE_ONE);
             let account_1_debt = PoolUserDebts::<Runtime>::get(debt_pool.id(), ACCOUNT_1.clone()).unwrap();
-            assert_eq!(account_1_debt.amount(), FixedU128::from(775));
+            assert_eq!(account_1_debt.shares(), 775 * BALANCE_ONE);
         });
 }
 
@@ -225,33 +227,33 @@ fn floating_lend_liquidate_user_liquidated_fully_closable() {
         .build()
         .execute_with(|| {
             let mut debt_pool = pool_proxy_with_price(10, false, FixedU128::from(2));
-            debt_pool.increment_debt(&FixedU128::from(1000));
-            debt_pool.increment_supply(&FixedU128::from(2000));
+            debt_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            debt_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(debt_pool.clone());
 
             let mut debt_pool_2 = pool_proxy_with_price(9, false, FixedU128::from(2));
-            debt_pool_2.increment_debt(&FixedU128::from(1000));
-            debt_pool_2.increment_supply(&FixedU128::from(2000));
+            debt_pool_2.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            debt_pool_2.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(debt_pool_2.clone());
 
             let mut collateral_pool = pool_proxy_with_price(11, true, FixedU128::saturating_from_rational(5, 10));
-            collateral_pool.increment_debt(&FixedU128::from(1000));
-            collateral_pool.increment_supply(&FixedU128::from(3000));
+            collateral_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            collateral_pool.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(collateral_pool.clone());
 
             let mut supply_pool = pool_proxy(12, false);
-            supply_pool.increment_debt(&FixedU128::from(1000));
-            supply_pool.increment_supply(&FixedU128::from(2000));
+            supply_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+            supply_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(supply_pool.clone());
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 10).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 11).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 12).ok();
             FloatingRateLend::update_liquidation_threshold(Origin::signed(ROOT), 100).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&debt_pool, ACCOUNT_1.clone(), &FixedU128::from(1000)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&debt_pool_2, ACCOUNT_1.clone(), &FixedU128::from(3000)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&supply_pool, ACCOUNT_1.clone(), &FixedU128::from(1800)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&collateral_pool, ACCOUNT_1.clone(), &FixedU128::from(180)).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&debt_pool, ACCOUNT_1.clone(), &(1000 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&debt_pool_2, ACCOUNT_1.clone(), &(3000 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&supply_pool, ACCOUNT_1.clone(), &(1800 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&collateral_pool, ACCOUNT_1.clone(), &(180 * BALANCE_ONE)).unwrap();
 
             // Debt pool price: $2, collateral pool price: $0.5, discount factor: 0.9
             // Account_1 debt worth: 1000 * $2 = 2000
@@ -267,14 +269,14 @@ fn floating_lend_liquidate_user_liquidated_fully_closable() {
             FloatingRateLend::liquidate(Origin::signed(ROOT), ACCOUNT_1, debt_pool.id(), 1000 * BALANCE_ONE, collateral_pool.id()).ok();
 
             let pool = PoolStorage::<Runtime>::get(debt_pool.id()).unwrap();
-            assert_eq!(pool.debt(), FixedU128::from_inner(959500000000000000000));
+            assert_eq!(pool.debt().unwrap(), 9595 * BALANCE_ONE / 10);
             let pool = PoolStorage::<Runtime>::get(collateral_pool.id()).unwrap();
-            assert_eq!(pool.supply(), FixedU128::from_inner(2820000000000000000018));
+            assert_eq!(pool.supply().unwrap(), 2820 * BALANCE_ONE);
 
             let account_1_collateral = PoolUserSupplies::<Runtime>::get(collateral_pool.id(), ACCOUNT_1.clone());
             assert_eq!(account_1_collateral.is_none(), true);
             let account_1_debt = PoolUserDebts::<Runtime>::get(debt_pool.id(), ACCOUNT_1.clone()).unwrap();
-            assert_eq!(account_1_debt.amount(), FixedU128::from_inner(959500000000000000000));
+            assert_eq!(account_1_debt.shares(), 9595 * BALANCE_ONE / 10);
         });
 }
 
@@ -284,33 +286,33 @@ fn floating_lend_liquidate_user_liquidated_fully_closable_not_enough_debt() {
             .build()
             .execute_with(|| {
                     let mut debt_pool = pool_proxy_with_price(10, false, FixedU128::from(2));
-                    debt_pool.increment_debt(&FixedU128::from(1000));
-                    debt_pool.increment_supply(&FixedU128::from(2000));
+                    debt_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+                    debt_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
                     PoolRepository::save(debt_pool.clone());
 
                     let mut debt_pool_2 = pool_proxy_with_price(9, false, FixedU128::from(2));
-                    debt_pool_2.increment_debt(&FixedU128::from(1000));
-                    debt_pool_2.increment_supply(&FixedU128::from(2000));
+                    debt_pool_2.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+                    debt_pool_2.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
                     PoolRepository::save(debt_pool_2.clone());
 
                     let mut collateral_pool = pool_proxy_with_price(11, true, FixedU128::saturating_from_rational(5, 10));
-                    collateral_pool.increment_debt(&FixedU128::from(1000));
-                    collateral_pool.increment_supply(&FixedU128::from(3000));
+                    collateral_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+                    collateral_pool.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
                     PoolRepository::save(collateral_pool.clone());
 
                     let mut supply_pool = pool_proxy(12, false);
-                    supply_pool.increment_debt(&FixedU128::from(1000));
-                    supply_pool.increment_supply(&FixedU128::from(2000));
+                    supply_pool.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
+                    supply_pool.increment_supply(&(2000 * BALANCE_ONE)).unwrap();
                     PoolRepository::save(supply_pool.clone());
 
                     FloatingRateLend::enable_pool(Origin::signed(ROOT), 10).ok();
                     FloatingRateLend::enable_pool(Origin::signed(ROOT), 11).ok();
                     FloatingRateLend::enable_pool(Origin::signed(ROOT), 12).ok();
                     FloatingRateLend::update_liquidation_threshold(Origin::signed(ROOT), 100).ok();
-                    UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&debt_pool, ACCOUNT_1.clone(), &FixedU128::from(30)).unwrap();
-                    UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&debt_pool_2, ACCOUNT_1.clone(), &FixedU128::from(3000)).unwrap();
-                    UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&supply_pool, ACCOUNT_1.clone(), &FixedU128::from(1800)).unwrap();
-                    UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&collateral_pool, ACCOUNT_1.clone(), &FixedU128::from(180)).unwrap();
+                    UserAccountUtil::<Runtime>::increment_debt(&debt_pool, ACCOUNT_1.clone(), &(30 * BALANCE_ONE)).unwrap();
+                    UserAccountUtil::<Runtime>::increment_debt(&debt_pool_2, ACCOUNT_1.clone(), &(3000 * BALANCE_ONE)).unwrap();
+                    UserAccountUtil::<Runtime>::increment_supply(&supply_pool, ACCOUNT_1.clone(), &(1800 * BALANCE_ONE)).unwrap();
+                    UserAccountUtil::<Runtime>::increment_supply(&collateral_pool, ACCOUNT_1.clone(), &(180 * BALANCE_ONE)).unwrap();
 
                     // Debt pool price: $2, collateral pool price: $0.5, discount factor: 0.9
                     // Account_1 debt worth: 30 * $2 = 60
@@ -319,19 +321,20 @@ fn floating_lend_liquidate_user_liquidated_fully_closable_not_enough_debt() {
                     // Closable collateral worth: 180 * ($0.5 * 0.9) = 81
                     // Debt pool to deduct: 81 / $2 = 40.5 > 30 = 30, left 970
                     // User debt to deduct 30, left 970
-                    // Collateral pool to deduct: 30 * 2 / 0.45 = 133.33, left 3000 - 133.33 = 2866.67
+                    // Collateral pool to deduct: 30 * 2 / 0.45 = 133.33 rounded down, left 3000 - 133.33 = 2866.67
                     // User collateral to deduct: 180 - 133.33, left 46.67
                     // Transfer 30 of debt pool currency from the arbitrager to the pool
                     // Transfer 66.67 of collateral pool currency from the pool to the arbitrager
                     FloatingRateLend::liquidate(Origin::signed(ROOT), ACCOUNT_1, debt_pool.id(), 1000 * BALANCE_ONE, collateral_pool.id()).ok();
 
                     let pool = PoolStorage::<Runtime>::get(debt_pool.id()).unwrap();
-                    assert_eq!(pool.debt(), FixedU128::from_inner(970000000000000000000));
+                    assert_eq!(pool.debt().unwrap(), 970 * BALANCE_ONE);
                     let pool = PoolStorage::<Runtime>::get(collateral_pool.id()).unwrap();
-                    assert_eq!(pool.supply(), FixedU128::from_inner(2866666666666666666680));
+                    // the collateral given out is rounded down, the pool keeps the remainder
+                    assert_eq!(pool.supply().unwrap(), 2_866_666_666_666_667);
 
                     let account_1_collateral = PoolUserSupplies::<Runtime>::get(collateral_pool.id(), ACCOUNT_1.clone()).unwrap();
-                    assert_eq!(account_1_collateral.amount(), FixedU128::from_inner(46666666666666666680));
+                    assert_eq!(account_1_collateral.shares(), 46_666_666_666_667);
                     let account_1_debt = PoolUserDebts::<Runtime>::get(debt_pool.id(), ACCOUNT_1.clone());
                     assert_eq!(account_1_debt.is_none(), true);
             });
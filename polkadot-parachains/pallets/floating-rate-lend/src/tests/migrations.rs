@@ -0,0 +1,128 @@
+use frame_support::storage::unhashed;
+use frame_support::traits::OnRuntimeUpgrade;
+use sp_runtime::{FixedU128, FixedPointNumber};
+use sp_runtime::traits::{One, Zero};
+
+use polkadot_parachain_primitives::BALANCE_ONE;
+
+use crate::{PoolStorage, PoolUserDebts, PoolUserSupplies, StorageVersion, UserSupplySet};
+use crate::migrations::{OldPool, OldUserData};
+use crate::tests::mock::{*};
+use pallet_traits::Releases;
+
+fn old_pool(supply_index: FixedU128, debt_index: FixedU128) -> OldPool<Runtime> {
+    OldPool {
+        id: 0,
+        name: vec![],
+        currency_id: 0,
+        can_be_collateral: true,
+        enabled: true,
+        supply: FixedU128::from(150),
+        total_supply_index: supply_index,
+        debt: FixedU128::from(10),
+        total_debt_index: debt_index,
+        interest_updated_at: 1,
+        minimal_amount: FixedU128::from(2),
+        safe_factor: FixedU128::saturating_from_rational(9, 10),
+        close_factor: FixedU128::saturating_from_rational(5, 10),
+        close_minimal_amount: FixedU128::from(100),
+        discount_factor: FixedU128::saturating_from_rational(9, 10),
+        utilization_factor: Zero::zero(),
+        initial_interest_rate: Zero::zero(),
+        last_updated: 1,
+        last_updated_by: ROOT,
+        created_by: ROOT,
+        created_at: 1,
+    }
+}
+
+fn put_old_supply(account: AccountId, amount: FixedU128, index: FixedU128) {
+    let key = PoolUserSupplies::<Runtime>::hashed_key_for(0, account.clone());
+    unhashed::put(&key, &OldUserData { amount, index });
+    UserSupplySet::<Runtime>::insert(account, vec![(0, 0)]);
+}
+
+/// Puts a pool with a supply and a debt of `ACCOUNT_1` and a dust supply of `ACCOUNT_2` in the
+/// `Releases::V1_0_0` layout
+fn put_old_state(supply_index: FixedU128, debt_index: FixedU128) {
+    unhashed::put(&PoolStorage::<Runtime>::hashed_key_for(0), &old_pool(supply_index, debt_index));
+
+    // supplied at the current index, 150 / 1.5 = 100 shares
+    put_old_supply(ACCOUNT_1, FixedU128::from(150), supply_index);
+    // worth less than a balance unit, dropped
+    put_old_supply(ACCOUNT_2, FixedU128::from_inner(1), FixedU128::one());
+    // borrowed at the current index, 10 / 3 shares rounded up
+    let key = PoolUserDebts::<Runtime>::hashed_key_for(0, ACCOUNT_1);
+    unhashed::put(&key, &OldUserData { amount: FixedU128::from(10), index: debt_index });
+}
+
+#[test]
+fn floating_lend_migrate_to_scaled_balances() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let supply_index = FixedU128::saturating_from_rational(3, 2);
+            let debt_index = FixedU128::from(3);
+            put_old_state(supply_index, debt_index);
+
+            assert_eq!(StorageVersion::<Runtime>::get(), Releases::V1_0_0);
+            FloatingRateLend::on_runtime_upgrade();
+            assert_eq!(StorageVersion::<Runtime>::get(), Releases::V2_0_0);
+
+            let supply = PoolUserSupplies::<Runtime>::get(0, ACCOUNT_1).unwrap();
+            assert_eq!(supply.shares(), 100 * BALANCE_ONE);
+            let debt = PoolUserDebts::<Runtime>::get(0, ACCOUNT_1).unwrap();
+            assert_eq!(debt.shares(), 3_333_333_333_334);
+
+            assert_eq!(PoolUserSupplies::<Runtime>::get(0, ACCOUNT_2).is_none(), true);
+            assert_eq!(UserSupplySet::<Runtime>::get(ACCOUNT_2).is_empty(), true);
+
+            let pool = PoolStorage::<Runtime>::get(0).unwrap();
+            assert_eq!(pool.enabled, true);
+            assert_eq!(pool.minimal_amount, 2 * BALANCE_ONE);
+            assert_eq!(pool.total_supply_index(), supply_index);
+            assert_eq!(pool.total_debt_index(), debt_index);
+            assert_eq!(pool.scaled_supply(), 100 * BALANCE_ONE);
+            assert_eq!(pool.scaled_debt(), 3_333_333_333_334);
+            assert_eq!(pool.supply().unwrap(), 150 * BALANCE_ONE);
+            assert_eq!(pool.debt().unwrap(), 10 * BALANCE_ONE + 2);
+
+            // running the upgrade again does not touch the migrated data
+            FloatingRateLend::on_runtime_upgrade();
+            assert_eq!(PoolStorage::<Runtime>::get(0).unwrap(), pool);
+        });
+}
+
+#[cfg(feature = "try-runtime")]
+#[test]
+fn floating_lend_migration_passes_the_upgrade_checks() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            put_old_state(FixedU128::saturating_from_rational(3, 2), FixedU128::from(3));
+
+            FloatingRateLend::pre_upgrade().unwrap();
+            FloatingRateLend::on_runtime_upgrade();
+            FloatingRateLend::post_upgrade().unwrap();
+
+            // the pool totals must add up to the user shares
+            PoolUserDebts::<Runtime>::remove(0, ACCOUNT_1);
+            FloatingRateLend::pre_upgrade().unwrap();
+            assert_eq!(
+                FloatingRateLend::post_upgrade(),
+                Err("pool debt does not add up to the debts of its users")
+            );
+        });
+}
+
+#[cfg(feature = "try-runtime")]
+#[test]
+fn floating_lend_post_upgrade_requires_the_latest_version() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            StorageVersion::<Runtime>::put(Releases::V1_0_0);
+            FloatingRateLend::pre_upgrade().unwrap();
+            assert_eq!(FloatingRateLend::post_upgrade(), Err("storage version is not the latest"));
+        });
+}
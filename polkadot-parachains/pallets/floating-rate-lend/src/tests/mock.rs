@@ -9,7 +9,6 @@ use frame_support::traits::GenesisBuild;
 use sp_core::H256;
 use sp_runtime::{AccountId32, FixedPointNumber, FixedU128, testing::Header, traits::IdentityLookup};
 use sp_runtime::traits::{Convert, Zero};
-use sp_std::convert::TryInto;
 
 use pallet_traits::{MultiCurrency, PriceProvider};
 use polkadot_parachain_primitives::{BALANCE_ONE, Price, PoolId, PriceValue};
@@ -126,13 +125,6 @@ impl Convert<Balance, FixedU128> for Conversion {
     }
 }
 
-impl Convert<FixedU128, Balance> for Conversion {
-    fn convert(a: FixedU128) -> Balance {
-        let accuracy = FixedU128::accuracy();
-        (a.into_inner() / (accuracy / BALANCE_ONE)).try_into().unwrap()
-    }
-}
-
 impl Config for Runtime {
     type Event = Event;
     type Currency = MockMultiCurrency;
@@ -204,7 +196,7 @@ pub fn default_test_pool() -> Pool<Runtime> {
         FixedU128::from_float(DEFAULT_DISCOUNT_FACTOR),
         utilization_factor,
         initial_interest_rate,
-        FixedU128::from(2),
+        2 * BALANCE_ONE,
         ROOT,
         1
     )
@@ -223,7 +215,7 @@ pub fn test_pool(id: PoolId, can_be_collateral: bool) -> Pool<Runtime> {
         FixedU128::from_float(DEFAULT_DISCOUNT_FACTOR),
         utilization_factor,
         initial_interest_rate,
-        FixedU128::saturating_from_rational(1, 1000000),
+        BALANCE_ONE / 1_000_000,
         ROOT,
         1
     )
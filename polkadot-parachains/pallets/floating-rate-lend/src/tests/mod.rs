@@ -8,3 +8,6 @@ mod withdraw;
 mod borrow;
 mod repay;
 mod liquidation;
+mod migrations;
+mod on_behalf;
+mod batch;
@@ -0,0 +1,137 @@
+use crate::{Error, PoolStorage, PoolUserDebts, PoolUserSupplies};
+use crate::tests::mock::{*};
+
+use frame_support::{assert_noop, assert_ok};
+use crate::pool::PoolRepository;
+use polkadot_parachain_primitives::BALANCE_ONE;
+use crate::types::UserAccountUtil;
+
+/// Saves an enabled pool with some supply and debt, so that interest accrues
+fn setup_pool() {
+    let mut pool = pool_proxy(0, false);
+    pool.increment_supply(&(1000 * BALANCE_ONE)).unwrap();
+    pool.increment_debt(&(500 * BALANCE_ONE)).unwrap();
+    PoolRepository::save(pool);
+    FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
+}
+
+fn last_event() -> Event {
+    System::events().pop().expect("event expected").event
+}
+
+#[test]
+fn floating_lend_supply_on_behalf_zero_balance() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_noop!(
+                FloatingRateLend::supply_on_behalf(Origin::signed(ROOT), ACCOUNT_1, 0, 0),
+                Error::<Runtime>::BalanceTooLow
+            );
+        });
+}
+
+#[test]
+fn floating_lend_supply_on_behalf_success() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            setup_pool();
+
+            assert_ok!(FloatingRateLend::supply_on_behalf(Origin::signed(ROOT), ACCOUNT_1, 0, 100 * BALANCE_ONE));
+            assert_eq!(
+                last_event(),
+                Event::FloatingRateLend(crate::Event::SupplyOnBehalfSuccessful(0, ROOT, ACCOUNT_1, 100 * BALANCE_ONE))
+            );
+
+            // the supply belongs to the beneficiary only
+            assert_eq!(PoolUserSupplies::<Runtime>::get(0, ROOT).is_none(), true);
+            let supply = PoolUserSupplies::<Runtime>::get(0, ACCOUNT_1).unwrap();
+            assert_eq!(supply.shares(), 100 * BALANCE_ONE);
+            assert_eq!(UserAccountUtil::<Runtime>::get_supply_pools(ACCOUNT_1), vec![0]);
+            assert_eq!(UserAccountUtil::<Runtime>::get_supply_pools(ROOT).is_empty(), true);
+
+            // the payer supplies the same amount for itself, both accrue the same interest
+            assert_ok!(FloatingRateLend::supply(Origin::signed(ROOT), 0, 100 * BALANCE_ONE));
+            System::set_block_number(1_000_001);
+
+            let beneficiary_balance = FloatingRateLend::user_supply_balance(0, ACCOUNT_1).unwrap();
+            let payer_balance = FloatingRateLend::user_supply_balance(0, ROOT).unwrap();
+            assert!(beneficiary_balance > 100 * BALANCE_ONE);
+            assert_eq!(beneficiary_balance, payer_balance);
+
+            // only the beneficiary can withdraw its supply
+            assert_noop!(
+                FloatingRateLend::withdraw(Origin::signed(ACCOUNT_2), 0, 100 * BALANCE_ONE),
+                Error::<Runtime>::UserNoSupplyInPool
+            );
+            assert_ok!(FloatingRateLend::withdraw(Origin::signed(ACCOUNT_1), 0, beneficiary_balance));
+            assert_eq!(PoolUserSupplies::<Runtime>::get(0, ACCOUNT_1).is_none(), true);
+        });
+}
+
+#[test]
+fn floating_lend_repay_on_behalf_no_debt() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            setup_pool();
+            let pool = PoolRepository::<Runtime>::find_without_price(0).unwrap();
+            // the payer has debt, the beneficiary does not
+            UserAccountUtil::<Runtime>::increment_debt(&pool, ROOT, &(100 * BALANCE_ONE)).unwrap();
+
+            assert_noop!(
+                FloatingRateLend::repay_on_behalf(Origin::signed(ROOT), ACCOUNT_1, 0, 100 * BALANCE_ONE),
+                Error::<Runtime>::UserNoDebtInPool
+            );
+        });
+}
+
+#[test]
+fn floating_lend_repay_on_behalf_success() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            setup_pool();
+            let pool = PoolRepository::<Runtime>::find_without_price(0).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&pool, ROOT, &(100 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&pool, ACCOUNT_1, &(100 * BALANCE_ONE)).unwrap();
+
+            // interest accrues for both accounts
+            System::set_block_number(1_000_001);
+            let beneficiary_debt = FloatingRateLend::user_debt_balance(0, ACCOUNT_1).unwrap();
+            let payer_debt = FloatingRateLend::user_debt_balance(0, ROOT).unwrap();
+            assert!(beneficiary_debt > 100 * BALANCE_ONE);
+            assert_eq!(beneficiary_debt, payer_debt);
+
+            // a partial repayment only reduces the debt of the beneficiary
+            let amount = 50 * BALANCE_ONE;
+            assert_ok!(FloatingRateLend::repay_on_behalf(Origin::signed(ROOT), ACCOUNT_1, 0, amount));
+            assert_eq!(
+                last_event(),
+                Event::FloatingRateLend(crate::Event::RepayOnBehalfSuccessful(0, ROOT, ACCOUNT_1, amount))
+            );
+
+            let remaining = FloatingRateLend::user_debt_balance(0, ACCOUNT_1).unwrap();
+            // the burnt shares are rounded down, the remaining debt never drops below what is owed
+            assert!(remaining >= beneficiary_debt - amount);
+            assert!(remaining <= beneficiary_debt - amount + 1);
+            assert_eq!(FloatingRateLend::user_debt_balance(0, ROOT).unwrap(), payer_debt);
+
+            // repaying more than owed only takes the outstanding debt of the beneficiary
+            assert_ok!(FloatingRateLend::repay_on_behalf(Origin::signed(ACCOUNT_2), ACCOUNT_1, 0, 1000 * BALANCE_ONE));
+            assert_eq!(
+                last_event(),
+                Event::FloatingRateLend(crate::Event::RepayOnBehalfSuccessful(0, ACCOUNT_2, ACCOUNT_1, remaining))
+            );
+            assert_eq!(PoolUserDebts::<Runtime>::get(0, ACCOUNT_1).is_none(), true);
+            assert_eq!(UserAccountUtil::<Runtime>::get_debt_pools(ACCOUNT_1).is_empty(), true);
+            assert_eq!(PoolUserDebts::<Runtime>::get(0, ROOT).unwrap().shares(), 100 * BALANCE_ONE);
+
+            // all the shares of the beneficiary are burnt from the pool
+            let pool = PoolStorage::<Runtime>::get(0).unwrap();
+            assert_eq!(pool.scaled_debt(), 400 * BALANCE_ONE);
+        });
+}
@@ -5,9 +5,15 @@
 use sp_runtime::{FixedU128, FixedPointNumber};
 use sp_runtime::traits::{Zero, One};
 use crate::pool::Pool;
-use crate::types::Convertor;
+use crate::types::{Convertor, Rounding};
 use crate::tests::mock::{*};
-use sp_std::ops::{Mul};
+use polkadot_parachain_primitives::BALANCE_ONE;
+
+/// Asserts the two balances differ by at most `tolerance`, for values that went through rounding
+fn assert_approx(actual: u128, expected: u128, tolerance: u128) {
+    let diff = if actual > expected { actual - expected } else { expected - actual };
+    assert!(diff <= tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+}
 
 
 /// This tests the initialization and the decrement/increment operations on the pool
@@ -19,19 +25,23 @@ fn floating_lend_pool_increment_decrement() {
             let mut p = default_test_pool();
 
             // supply
-            assert_eq!(p.supply(), FixedU128::zero());
-            let amount = FixedU128::saturating_from_integer(100);
-            p.increment_supply(&amount);
-            assert_eq!(p.supply(), amount);
-            p.decrement_supply(&amount);
-            assert_eq!(p.supply(), FixedU128::zero());
+            assert_eq!(p.supply().unwrap(), 0);
+            let amount = 100 * BALANCE_ONE;
+            p.increment_supply(&amount).unwrap();
+            assert_eq!(p.supply().unwrap(), amount);
+            p.decrement_supply(&amount).unwrap();
+            assert_eq!(p.supply().unwrap(), 0);
 
             // debt
-            assert_eq!(p.debt(), FixedU128::zero());
-            p.increment_debt(&amount);
-            assert_eq!(p.debt(), amount);
-            p.decrement_debt(&amount);
-            assert_eq!(p.debt(), FixedU128::zero());
+            assert_eq!(p.debt().unwrap(), 0);
+            p.increment_debt(&amount).unwrap();
+            assert_eq!(p.debt().unwrap(), amount);
+            p.decrement_debt(&amount).unwrap();
+            assert_eq!(p.debt().unwrap(), 0);
+
+            // cannot go below zero
+            assert!(p.decrement_supply(&1).is_err());
+            assert!(p.decrement_debt(&1).is_err());
         });
 }
 
@@ -43,12 +53,12 @@ fn floating_lend_pool_closable_amount_below_min() {
             let p = default_test_pool();
 
             // below the min closable amount, will just take all
-            let amount = FixedU128::saturating_from_integer(100);
+            let amount = 100 * BALANCE_ONE;
             let price = FixedU128::one();
             let closable_amount = p.closable_amount(&amount, &price);
             assert_eq!(amount, closable_amount);
 
-            let amount = FixedU128::saturating_from_integer(100);
+            let amount = 100 * BALANCE_ONE;
             let price = FixedU128::saturating_from_rational(90, 100);
             let closable_amount = p.closable_amount(&amount, &price);
             assert_eq!(amount, closable_amount);
@@ -63,11 +73,11 @@ fn floating_lend_pool_closable_amount_above_min() {
             let p = default_test_pool();
 
             // below the min closable amount, will just take all
-            let amount = FixedU128::saturating_from_integer(100);
+            let amount = 100 * BALANCE_ONE;
             let price = FixedU128::from(2);
 
             let closable_amount = p.closable_amount(&amount, &price);
-            assert_eq!(FixedU128::from(50), closable_amount);
+            assert_eq!(50 * BALANCE_ONE, closable_amount);
         });
 }
 
@@ -98,18 +108,14 @@ fn floating_lend_pool_check_interest_rates() {
             assert_eq!(p.debt_interest_rate().unwrap(), initial_interest_rate);
             assert_eq!(p.supply_interest_rate().unwrap(), FixedU128::zero());
 
-            let supply = FixedU128::from(1000);
-            let debt = FixedU128::from(500);
             // only supply, no debt, interest should be `initial_interest_rate`
-            let supply_amount = FixedU128::from(supply);
-            p.increment_supply(&supply_amount);
+            p.increment_supply(&(1000 * BALANCE_ONE)).unwrap();
             assert_eq!(p.debt_interest_rate().unwrap(), initial_interest_rate);
 
             // with supply and debt, interest should be updated
             let expected_debt_rate = FixedU128::saturating_from_rational(22831050228 as u128, FixedU128::accuracy());
             let expected_supply_rate = FixedU128::saturating_from_rational(11415525114 as u128, FixedU128::accuracy());
-            let debt_amount = FixedU128::from(debt);
-            p.increment_debt(&debt_amount);
+            p.increment_debt(&(500 * BALANCE_ONE)).unwrap();
             // Rate calculation:
             //  initial_interest_rate + utilization_factor / FixedU128::from(2)
             assert_eq!(p.debt_interest_rate().unwrap(), expected_debt_rate);
@@ -133,14 +139,14 @@ fn floating_lend_pool_accrue_interest_past_blocks() {
         .build()
         .execute_with(|| {
             let mut p = default_test_pool();
-            let s = p.supply();
-            let d = p.debt();
+            let s = p.supply().unwrap();
+            let d = p.debt().unwrap();
             let s_i = p.total_supply_index();
             let d_i = p.total_debt_index();
             p.accrue_interest(1).unwrap();
 
-            assert_eq!(p.supply(), s);
-            assert_eq!(p.debt(), d);
+            assert_eq!(p.supply().unwrap(), s);
+            assert_eq!(p.debt().unwrap(), d);
             assert_eq!(p.total_supply_index(), s_i);
             assert_eq!(p.total_debt_index(), d_i);
         });
@@ -152,10 +158,10 @@ fn floating_lend_pool_accrue_interest_multiple_times() {
         .build()
         .execute_with(|| {
             let mut p = default_test_pool();
-            let supply_amount = FixedU128::from(2);
-            let borrow_amount = FixedU128::from(1);
-            p.increment_supply(&supply_amount);
-            p.increment_debt(&borrow_amount);
+            let supply_amount = 2 * BALANCE_ONE;
+            let borrow_amount = BALANCE_ONE;
+            p.increment_supply(&supply_amount).unwrap();
+            p.increment_debt(&borrow_amount).unwrap();
 
             System::set_block_number(11);
 
@@ -167,17 +173,69 @@ fn floating_lend_pool_accrue_interest_multiple_times() {
             let debt_multiplier = FixedU128::saturating_from_rational(1000000231 as u128, 1000000000 as u128);
             let supply_multiplier = FixedU128::saturating_from_rational(10000001155 as u128, 10000000000 as u128);
 
-            // an extra `supply_amount` is added due to previous supply at block 11
-            let expected_supply = supply_multiplier.mul(supply_amount);
-            let expected_borrow = debt_multiplier.mul(borrow_amount);
-
             p.accrue_interest(11).unwrap();
-            assert_eq!(expected_supply, p.supply());
-            assert_eq!(expected_borrow, p.debt());
-
-            p.increment_supply(&supply_amount);
+            assert_eq!(supply_multiplier, p.total_supply_index());
+            assert_eq!(debt_multiplier, p.total_debt_index());
+            assert_eq!(2000000231000, p.supply().unwrap());
+            assert_eq!(1000000231000, p.debt().unwrap());
+
+            // an extra `supply_amount` is added at block 11, shares stay untouched by accrual
+            let shares = p.supply_shares(&supply_amount, Rounding::Down).unwrap();
+            p.increment_supply(&shares).unwrap();
+            let scaled_supply = p.scaled_supply();
+            let scaled_debt = p.scaled_debt();
             p.accrue_interest(21).unwrap();
-            assert_eq!(FixedU128::from_inner(4000000365750047781), p.supply());
-            assert_eq!(FixedU128::from_inner(1000000365750047797), p.debt());
+            assert_eq!(scaled_supply, p.scaled_supply());
+            assert_eq!(scaled_debt, p.scaled_debt());
+            assert_approx(p.supply().unwrap(), 4000000365750, 1);
+            assert_approx(p.debt().unwrap(), 1000000365750, 1);
+        });
+}
+
+
+#[test]
+fn floating_lend_pool_rounding_favours_protocol() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let mut p = default_test_pool();
+            p.increment_supply(&(3 * BALANCE_ONE)).unwrap();
+            p.increment_debt(&BALANCE_ONE).unwrap();
+            p.accrue_interest(1_000_001).unwrap();
+            assert!(p.total_supply_index() > FixedU128::one());
+            assert!(p.total_debt_index() > FixedU128::one());
+
+            // an amount that does not divide the index evenly
+            let amount = 7;
+            let down = p.supply_shares(&amount, Rounding::Down).unwrap();
+            let up = p.supply_shares(&amount, Rounding::Up).unwrap();
+            assert_eq!(down + 1, up);
+            assert!(p.supply_amount(&down, Rounding::Down).unwrap() <= amount);
+
+            let down = p.debt_shares(&amount, Rounding::Down).unwrap();
+            let up = p.debt_shares(&amount, Rounding::Up).unwrap();
+            assert_eq!(down + 1, up);
+            assert!(p.debt_amount(&up, Rounding::Up).unwrap() >= amount);
+
+            // totals are rounded in favour of the protocol as well
+            assert_eq!(p.supply().unwrap(), p.supply_amount(&p.scaled_supply(), Rounding::Down).unwrap());
+            assert_eq!(p.debt().unwrap(), p.debt_amount(&p.scaled_debt(), Rounding::Up).unwrap());
+        });
+}
+
+#[test]
+fn floating_lend_pool_overflowing_totals_are_errors() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let mut p = default_test_pool();
+            p.increment_supply(&u128::MAX).unwrap();
+            p.increment_debt(&u128::MAX).unwrap();
+            let two = FixedU128::saturating_from_integer(2);
+            let p = p.with_indices(two, two);
+
+            // the totals no longer fit, they are reported instead of saturated
+            assert!(p.supply().is_err());
+            assert!(p.debt().is_err());
         });
 }
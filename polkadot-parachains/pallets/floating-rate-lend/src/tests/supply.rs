@@ -3,7 +3,6 @@ use crate::tests::mock::{*};
 
 use frame_support::{assert_noop, assert_ok};
 use crate::pool::PoolRepository;
-use sp_runtime::FixedU128;
 use polkadot_parachain_primitives::BALANCE_ONE;
 
 #[test]
@@ -59,9 +58,9 @@ fn floating_lend_supply_success() {
             assert_ok!(FloatingRateLend::supply(Origin::signed(ROOT), 0, 100 * BALANCE_ONE));
 
             let pool = PoolStorage::<Runtime>::get(pool.id()).unwrap();
-            assert_eq!(pool.supply(), FixedU128::from(100));
+            assert_eq!(pool.supply().unwrap(), 100 * BALANCE_ONE);
 
             let supply = PoolUserSupplies::<Runtime>::get(pool.id, ROOT).unwrap();
-            assert_eq!(supply.amount(), FixedU128::from(100));
+            assert_eq!(supply.shares(), 100 * BALANCE_ONE);
         });
 }
\ No newline at end of file
@@ -3,16 +3,16 @@
 #![cfg(test)]
 
 use sp_runtime::{FixedU128, FixedPointNumber};
-use sp_runtime::traits::{One, Convert};
-use crate::types::{Convertor, UserData, UserAccountUtil};
+use sp_runtime::traits::{One, Convert, Zero};
+use crate::types::{Convertor, Rounding, UserData, UserAccountUtil};
 use polkadot_parachain_primitives::{BALANCE_ONE, PoolId};
 use crate::tests::mock::{*};
-use sp_std::ops::{Add, Mul};
+use sp_std::ops::Add;
 use crate::{PoolUserSupplies, Error, UserSupplySet, PoolUserDebts};
 use sp_std::collections::btree_map::BTreeMap;
 
-fn default_user_data() -> UserData {
-    UserData::new(FixedU128::one())
+fn default_user_data() -> UserData<u128> {
+    UserData::new(BALANCE_ONE)
 }
 
 #[test]
@@ -33,51 +33,63 @@ fn test_conversion() {
         Conversion::convert(1000 * BALANCE_ONE),
         FixedU128::from(1000)
     );
-
-    assert_eq!(
-        1000 * BALANCE_ONE,
-        Conversion::convert(FixedU128::from(1000))
-    );
 }
 
 #[test]
-fn floating_lend_types_user_data() {
-    ExtBuilder::default()
-        .build()
-        .execute_with(|| {
-            let mut p = default_user_data();
+fn convert_shares_with_rounding() {
+    let index = FixedU128::saturating_from_rational(3, 2);
 
-            let increment = FixedU128::from(100);
-            p.increment(&increment);
-            // we have 1 in the default pool
-            assert_eq!(p.amount(), increment.add(FixedU128::one()));
+    assert_eq!(Convertor::to_shares(10u128, &index, Rounding::Down).unwrap(), 6);
+    assert_eq!(Convertor::to_shares(10u128, &index, Rounding::Up).unwrap(), 7);
+    assert_eq!(Convertor::to_shares(9u128, &index, Rounding::Up).unwrap(), 6);
 
-            let decrement = FixedU128::from(10);
-            p.decrement(&decrement);
-            // we have 1 in the default pool
-            assert_eq!(p.amount(), FixedU128::from(91));
-        });
+    assert_eq!(Convertor::to_amount(7u128, &index, Rounding::Down).unwrap(), 10);
+    assert_eq!(Convertor::to_amount(7u128, &index, Rounding::Up).unwrap(), 11);
+    assert_eq!(Convertor::to_amount(6u128, &index, Rounding::Up).unwrap(), 9);
+
+    // the intermediate product does not overflow, only the result can
+    assert_eq!(Convertor::to_amount(u128::max_value(), &FixedU128::one(), Rounding::Down).unwrap(), u128::max_value());
+    assert!(Convertor::to_amount(u128::max_value(), &FixedU128::from(2), Rounding::Down).is_err());
+    assert!(Convertor::to_shares(1u128, &FixedU128::zero(), Rounding::Down).is_err());
 }
 
 #[test]
-fn floating_lend_types_user_data_accrue_interest() {
+fn convert_by_price_with_rounding() {
+    let from = FixedU128::from(2);
+    let to = FixedU128::saturating_from_rational(45, 100);
+    assert_eq!(Convertor::convert_by_price(30u128, &from, &to, Rounding::Down).unwrap(), 133);
+    assert_eq!(Convertor::convert_by_price(30u128, &from, &to, Rounding::Up).unwrap(), 134);
+}
+
+#[test]
+fn convert_fixed_to_balance() {
+    let unit = Conversion::convert(1);
+    assert_eq!(Convertor::fixed_to_balance::<u128>(&FixedU128::from(1000), &unit, Rounding::Down).unwrap(), 1000 * BALANCE_ONE);
+    // anything below a single balance unit
+    let dust = FixedU128::from_inner(1);
+    assert_eq!(Convertor::fixed_to_balance::<u128>(&dust, &unit, Rounding::Down).unwrap(), 0);
+    assert_eq!(Convertor::fixed_to_balance::<u128>(&dust, &unit, Rounding::Up).unwrap(), 1);
+}
+
+#[test]
+fn floating_lend_types_user_data() {
     ExtBuilder::default()
         .build()
         .execute_with(|| {
             let mut p = default_user_data();
 
-            let increment = FixedU128::from(1256);
-            p.increment(&increment);
-            let pool_index = FixedU128::saturating_from_rational(15, 10);
-            let target_amount = FixedU128::saturating_from_rational(18855, 10);
-            p.accrue_interest(&pool_index).unwrap();
-            assert_eq!(p.amount(), target_amount);
-            assert_eq!(p.index(), pool_index);
-
-            let next_pool_index = FixedU128::saturating_from_rational(12, 10);
-            p.accrue_interest(&next_pool_index).unwrap();
-            assert_eq!(p.amount(), target_amount);
-            assert_eq!(p.index(), pool_index);
+            let increment = 100 * BALANCE_ONE;
+            p.increment(&increment).unwrap();
+            // we have 1 in the default user data
+            assert_eq!(p.shares(), increment.add(BALANCE_ONE));
+
+            let decrement = 10 * BALANCE_ONE;
+            p.decrement(&decrement).unwrap();
+            assert_eq!(p.shares(), 91 * BALANCE_ONE);
+
+            assert!(p.decrement(&(92 * BALANCE_ONE)).is_err());
+            p.decrement(&(91 * BALANCE_ONE)).unwrap();
+            assert!(p.is_empty());
         });
 }
 
@@ -95,33 +107,30 @@ fn floating_lend_increment_user_supply() {
             assert_eq!(user_data_1.is_none(), true);
             assert_eq!(user_data_2.is_none(), true);
 
-            let amount_1 = FixedU128::from(100);
-            let amount_2 = FixedU128::from(200);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ACCOUNT_1.clone(), &amount_1).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ACCOUNT_2.clone(), &amount_2).ok();
+            let amount_1 = 100 * BALANCE_ONE;
+            let amount_2 = 200 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ACCOUNT_1.clone(), &amount_1).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ACCOUNT_2.clone(), &amount_2).ok();
 
             // now, we should have supply already
             let user_data_1 = PoolUserSupplies::<Runtime>::get(pool.id(), ACCOUNT_1.clone());
             assert_eq!(user_data_1.is_some(), true);
             let user_data_1 = user_data_1.unwrap();
-            assert_eq!(user_data_1.amount(), amount_1);
-            assert_eq!(user_data_1.index(), FixedU128::one());
+            assert_eq!(user_data_1.shares(), amount_1);
 
             // now, we supply again
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ACCOUNT_1.clone(), &amount_1).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ACCOUNT_1.clone(), &amount_1).ok();
 
             let user_data_1 = PoolUserSupplies::<Runtime>::get(pool.id(), ACCOUNT_1.clone());
             assert_eq!(user_data_1.is_some(), true);
             let user_data_1 = user_data_1.unwrap();
-            assert_eq!(user_data_1.amount(), amount_1 * FixedU128::from(2));
-            assert_eq!(user_data_1.index(), FixedU128::one());
+            assert_eq!(user_data_1.shares(), amount_1 * 2);
 
             // check user 2
             let user_data_2 = PoolUserSupplies::<Runtime>::get(pool.id(), ACCOUNT_2.clone());
             assert_eq!(user_data_2.is_some(), true);
             let user_data_2 = user_data_2.unwrap();
-            assert_eq!(user_data_2.amount(), amount_2);
-            assert_eq!(user_data_2.index(), FixedU128::one());
+            assert_eq!(user_data_2.shares(), amount_2);
 
             // update block time
             // TODO
@@ -139,18 +148,17 @@ fn floating_lend_decrement_user_supply() {
             // ensure user has no data first
             assert_eq!(user_data.is_none(), true);
 
-            let amount = FixedU128::from(100);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ROOT.clone(), &amount).ok();
+            let amount = 100 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ROOT.clone(), &amount).ok();
             let user_data = PoolUserSupplies::<Runtime>::get(pool.id(), ROOT.clone()).unwrap();
 
-            let amount_deduct = FixedU128::from(50);
+            let amount_deduct = 50 * BALANCE_ONE;
             UserAccountUtil::<Runtime>::decrement_supply(&pool, ROOT.clone(), &amount_deduct, user_data).ok();
 
             let user_data = PoolUserSupplies::<Runtime>::get(pool.id(), ROOT.clone());
             assert_eq!(user_data.is_some(), true);
             let user_data = user_data.unwrap();
-            assert_eq!(user_data.amount(), amount - amount_deduct);
-            assert_eq!(user_data.index(), FixedU128::one());
+            assert_eq!(user_data.shares(), amount - amount_deduct);
             let supplies = UserSupplySet::<Runtime>::get(ROOT.clone());
             assert_eq!(supplies.contains(&(pool.id(), pool.currency_id())), true);
 
@@ -175,20 +183,20 @@ fn floating_lend_decrement_user_supply_over_deduction() {
             // ensure user has no data first
             assert_eq!(user_data.is_none(), true);
 
-            let amount = FixedU128::from(100);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ROOT.clone(), &amount).ok();
+            let amount = 100 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ROOT.clone(), &amount).ok();
             let user_data = PoolUserSupplies::<Runtime>::get(pool.id(), ROOT.clone()).unwrap();
 
             // now, we attempt to over deduct
             let result = UserAccountUtil::<Runtime>::decrement_supply(
                 &pool,
                 ROOT.clone(),
-                &(user_data.clone().amount().add(FixedU128::one())),
+                &(user_data.shares() + 1),
                 user_data.clone())
                 .map_err(|e| e.error);
             let expected = Err(Error::<Runtime>::UserSupplyTooLow.into());
             assert_eq!(expected, result);
-            assert_eq!(user_data.amount(), amount);
+            assert_eq!(user_data.shares(), amount);
         })
 }
 
@@ -203,12 +211,12 @@ fn floating_lend_increment_debt_no_debt() {
             // ensure user has no data first
             assert_eq!(user_data.is_none(), true);
 
-            let amount = FixedU128::from(100);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool, ROOT.clone(), &amount).ok();
+            let amount = 100 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_debt(&pool, ROOT.clone(), &amount).ok();
             let user_data = PoolUserDebts::<Runtime>::get(pool.id(), ROOT.clone());
             assert_eq!(user_data.is_some(), true);
             let user_data = user_data.unwrap();
-            assert_eq!(user_data.amount(), amount);
+            assert_eq!(user_data.shares(), amount);
         })
 }
 
@@ -223,13 +231,13 @@ fn floating_lend_increment_debt_got_debt() {
             // ensure user has no data first
             assert_eq!(user_data.is_none(), true);
 
-            let amount = FixedU128::from(100);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool, ROOT.clone(), &amount).ok();
+            let amount = 100 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_debt(&pool, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_debt(&pool, ROOT.clone(), &amount).ok();
             let user_data = PoolUserDebts::<Runtime>::get(pool.id(), ROOT.clone());
             assert_eq!(user_data.is_some(), true);
             let user_data = user_data.unwrap();
-            assert_eq!(user_data.amount(), FixedU128::from(200));
+            assert_eq!(user_data.shares(), 200 * BALANCE_ONE);
 
             // TODO: update block time
         })
@@ -240,16 +248,16 @@ fn floating_lend_get_supply_pools() {
     ExtBuilder::default()
         .build()
         .execute_with(|| {
-            let amount = FixedU128::from(100);
+            let amount = 100 * BALANCE_ONE;
             let pool_0 = pool_proxy(0, true);
             let pool_1 = pool_proxy(1, true);
             let pool_2 = pool_proxy(2, true);
             let pool_3 = pool_proxy(3, true);
 
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_0, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_1, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_0, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_1, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).ok();
 
             let user_data = UserAccountUtil::<Runtime>::get_supply_pools(ROOT.clone());
             assert_eq!(user_data.contains(&0), true);
@@ -265,16 +273,16 @@ fn floating_lend_get_debt_pools() {
     ExtBuilder::default()
         .build()
         .execute_with(|| {
-            let amount = FixedU128::from(100);
+            let amount = 100 * BALANCE_ONE;
             let pool_0 = pool_proxy(0, true);
             let pool_1 = pool_proxy(1, true);
             let pool_2 = pool_proxy(2, true);
             let pool_3 = pool_proxy(3, true);
 
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_0, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_1, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_2, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_3, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_0, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_1, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_2, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_3, ROOT.clone(), &amount).ok();
 
             let user_data = UserAccountUtil::<Runtime>::get_debt_pools(ROOT.clone());
             assert_eq!(user_data.contains(&0), true);
@@ -286,7 +294,7 @@ fn floating_lend_get_debt_pools() {
 }
 
 #[test]
-fn floating_lend_accrue_interest_for_user() {
+fn floating_lend_user_supply_debt() {
     ExtBuilder::default()
         .build()
         .execute_with(|| {
@@ -296,34 +304,31 @@ fn floating_lend_accrue_interest_for_user() {
             let mut pool_2 = pool_proxy(2, true);
             let mut pool_3 = pool_proxy(3, true);
 
-            let supply = FixedU128::from(100);
-            let borrow = FixedU128::from(50);
+            let supply = 100 * BALANCE_ONE;
+            let borrow = 50 * BALANCE_ONE;
 
-            pool_0.increment_supply(&supply);
-            pool_0.increment_debt(&borrow);
+            pool_0.increment_supply(&supply).unwrap();
+            pool_0.increment_debt(&borrow).unwrap();
             pool_0.accrue_interest().ok();
 
-            pool_1.increment_supply(&supply);
-            pool_1.increment_debt(&borrow);
+            pool_1.increment_supply(&supply).unwrap();
+            pool_1.increment_debt(&borrow).unwrap();
             pool_1.accrue_interest().ok();
 
-            pool_2.increment_supply(&supply);
-            pool_2.increment_debt(&borrow);
+            pool_2.increment_supply(&supply).unwrap();
+            pool_2.increment_debt(&borrow).unwrap();
             pool_2.accrue_interest().ok();
 
-            pool_3.increment_supply(&supply);
-            pool_3.increment_debt(&borrow);
+            pool_3.increment_supply(&supply).unwrap();
+            pool_3.increment_debt(&borrow).unwrap();
             pool_3.accrue_interest().ok();
 
-            let amount = FixedU128::from(100);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_0, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_1, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_2, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).ok();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).ok();
-
-            // println!("{}", pool_3.total_debt_index());
-            // println!("{}", pool_3.total_supply_index());
+            let amount = 100 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_debt(&pool_0, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_1, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_2, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).ok();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).ok();
 
             let mut pool_map = BTreeMap::new();
             pool_map.insert(0 as PoolId, pool_0.clone());
@@ -331,27 +336,27 @@ fn floating_lend_accrue_interest_for_user() {
             pool_map.insert(2 as PoolId, pool_2.clone());
             pool_map.insert(3 as PoolId, pool_3.clone());
 
-            let result = UserAccountUtil::<Runtime>::accrue_interest_for_user(ROOT.clone(), &pool_map).unwrap();
+            let result = UserAccountUtil::<Runtime>::user_supply_debt(ROOT.clone(), &pool_map).unwrap();
 
             let s = result.supply.get(&3).unwrap();
-            assert_eq!(s.amount(), amount.mul(pool_3.total_supply_index()));
-            assert_eq!(s.index(), pool_3.total_supply_index());
+            assert_eq!(*s, pool_3.supply_amount(&amount, Rounding::Down).unwrap());
+            assert!(*s > amount);
 
             let s = result.supply.get(&2).unwrap();
-            assert_eq!(s.amount(), amount.mul(pool_2.total_supply_index()));
-            assert_eq!(s.index(), pool_2.total_supply_index());
+            assert_eq!(*s, pool_2.supply_amount(&amount, Rounding::Down).unwrap());
+            assert!(*s > amount);
 
             let s = result.debt.get(&0).unwrap();
-            assert_eq!(s.amount(), amount.mul(pool_0.total_debt_index()));
-            assert_eq!(s.index(), pool_0.total_debt_index());
+            assert_eq!(*s, pool_0.debt_amount(&amount, Rounding::Up).unwrap());
+            assert!(*s > amount);
 
             let s = result.debt.get(&1).unwrap();
-            assert_eq!(s.amount(), amount.mul(pool_1.total_debt_index()));
-            assert_eq!(s.index(), pool_1.total_debt_index());
+            assert_eq!(*s, pool_1.debt_amount(&amount, Rounding::Up).unwrap());
+            assert!(*s > amount);
 
             let s = result.debt.get(&2).unwrap();
-            assert_eq!(s.amount(), amount.mul(pool_2.total_debt_index()));
-            assert_eq!(s.index(), pool_2.total_debt_index());
+            assert_eq!(*s, pool_2.debt_amount(&amount, Rounding::Up).unwrap());
+            assert!(*s > amount);
 
             assert_eq!(result.supply.len(), 2);
             assert_eq!(result.debt.len(), 3);
@@ -360,7 +365,7 @@ fn floating_lend_accrue_interest_for_user() {
 
 /// TODO
 // #[test]
-// fn floating_lend_accrue_interest_for_user_inconsistency() {
+// fn floating_lend_user_supply_debt_inconsistency() {
 //     ExtBuilder::default()
 //         .build()
 //         .execute_with(|| {
@@ -392,15 +397,15 @@ fn floating_lend_get_user_balances() {
                 pools.insert(2, pool_2.clone());
                 pools.insert(3, pool_3.clone());
                 pools.insert(4, pool_4.clone());
-                let amount = FixedU128::from(100);
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_0, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_1, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_2, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_4, ROOT.clone(), &amount).ok();
+                let amount = 100 * BALANCE_ONE;
+                UserAccountUtil::<Runtime>::increment_debt(&pool_0, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_debt(&pool_1, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_debt(&pool_2, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_4, ROOT.clone(), &amount).ok();
 
-                let user_supply_debt = UserAccountUtil::<Runtime>::accrue_interest_for_user(ROOT.clone(), &pools).unwrap();
+                let user_supply_debt = UserAccountUtil::<Runtime>::user_supply_debt(ROOT.clone(), &pools).unwrap();
 
                 let balances = UserAccountUtil::<Runtime>::user_balances(&user_supply_debt, &pools).unwrap();
                 assert_eq!(balances.debt_balance, FixedU128::from(300));
@@ -424,16 +429,16 @@ fn floating_lend_get_check_withdraw_against_liquidation_trigger() {
                 pools.insert(2, pool_2.clone());
                 pools.insert(3, pool_3.clone());
                 pools.insert(4, pool_4.clone());
-                let amount = FixedU128::from(100);
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_0, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_4, ROOT.clone(), &amount).ok();
+                let amount = 100 * BALANCE_ONE;
+                UserAccountUtil::<Runtime>::increment_debt(&pool_0, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_4, ROOT.clone(), &amount).ok();
 
-                let user_supply_debt = UserAccountUtil::<Runtime>::accrue_interest_for_user(ROOT.clone(), &pools).unwrap();
+                let user_supply_debt = UserAccountUtil::<Runtime>::user_supply_debt(ROOT.clone(), &pools).unwrap();
 
                 let is_trigger = UserAccountUtil::<Runtime>::is_withdraw_trigger_liquidation(
-                        (4, FixedU128::from(89)),
+                        (4, 89 * BALANCE_ONE),
                         user_supply_debt,
                         pools,
                         FixedU128::one(),
@@ -457,16 +462,16 @@ fn floating_lend_get_check_withdraw_against_liquidation_not_trigger() {
                 pools.insert(2, pool_2.clone());
                 pools.insert(3, pool_3.clone());
                 pools.insert(4, pool_4.clone());
-                let amount = FixedU128::from(100);
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_0, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_4, ROOT.clone(), &amount).ok();
+                let amount = 100 * BALANCE_ONE;
+                UserAccountUtil::<Runtime>::increment_debt(&pool_0, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_4, ROOT.clone(), &amount).ok();
 
-                let user_supply_debt = UserAccountUtil::<Runtime>::accrue_interest_for_user(ROOT.clone(), &pools).unwrap();
+                let user_supply_debt = UserAccountUtil::<Runtime>::user_supply_debt(ROOT.clone(), &pools).unwrap();
 
                 let is_trigger = UserAccountUtil::<Runtime>::is_withdraw_trigger_liquidation(
-                        (4, FixedU128::from(88)),
+                        (4, 88 * BALANCE_ONE),
                         user_supply_debt,
                         pools,
                         FixedU128::one(),
@@ -490,16 +495,16 @@ fn floating_lend_get_check_borrow_against_liquidation_not_trigger() {
                 pools.insert(2, pool_2.clone());
                 pools.insert(3, pool_3.clone());
                 pools.insert(4, pool_4.clone());
-                let amount = FixedU128::from(100);
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_0, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_4, ROOT.clone(), &amount).ok();
+                let amount = 100 * BALANCE_ONE;
+                UserAccountUtil::<Runtime>::increment_debt(&pool_0, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_4, ROOT.clone(), &amount).ok();
 
-                let user_supply_debt = UserAccountUtil::<Runtime>::accrue_interest_for_user(ROOT.clone(), &pools).unwrap();
+                let user_supply_debt = UserAccountUtil::<Runtime>::user_supply_debt(ROOT.clone(), &pools).unwrap();
 
                 let is_trigger = UserAccountUtil::<Runtime>::is_borrow_trigger_liquidation(
-                        (0, FixedU128::from(80)),
+                        (0, 80 * BALANCE_ONE),
                         user_supply_debt,
                         pools,
                         FixedU128::one(),
@@ -523,20 +528,57 @@ fn floating_lend_get_check_borrow_against_liquidation_trigger() {
                 pools.insert(2, pool_2.clone());
                 pools.insert(3, pool_3.clone());
                 pools.insert(4, pool_4.clone());
-                let amount = FixedU128::from(100);
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_0, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).ok();
-                UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_4, ROOT.clone(), &amount).ok();
+                let amount = 100 * BALANCE_ONE;
+                UserAccountUtil::<Runtime>::increment_debt(&pool_0, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).ok();
+                UserAccountUtil::<Runtime>::increment_supply(&pool_4, ROOT.clone(), &amount).ok();
 
-                let user_supply_debt = UserAccountUtil::<Runtime>::accrue_interest_for_user(ROOT.clone(), &pools).unwrap();
+                let user_supply_debt = UserAccountUtil::<Runtime>::user_supply_debt(ROOT.clone(), &pools).unwrap();
 
                 let is_trigger = UserAccountUtil::<Runtime>::is_borrow_trigger_liquidation(
-                        (0, FixedU128::from_float(80.1)),
+                        (0, 801 * BALANCE_ONE / 10),
                         user_supply_debt,
                         pools,
                         FixedU128::one(),
                 ).unwrap();
                 assert_eq!(is_trigger, true);
         })
-}
\ No newline at end of file
+}
+#[test]
+fn floating_lend_withdrawable_and_repayable() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1_000_001);
+            let mut pool = pool_proxy(0, true);
+            pool.increment_supply(&(300 * BALANCE_ONE)).unwrap();
+            pool.increment_debt(&(100 * BALANCE_ONE)).unwrap();
+            pool.accrue_interest().unwrap();
+
+            let user = UserData::new(100 * BALANCE_ONE);
+            let balance = pool.supply_amount(&user.shares(), Rounding::Down).unwrap();
+            assert!(balance > 100 * BALANCE_ONE);
+
+            // withdrawing more than the balance takes the balance and burns all the shares
+            let (amount, shares) = UserAccountUtil::<Runtime>::withdrawable(&pool, &user, balance + 1).unwrap();
+            assert_eq!(amount, balance);
+            assert_eq!(shares, user.shares());
+
+            // partial withdrawal never burns fewer shares than the amount is worth
+            let (amount, shares) = UserAccountUtil::<Runtime>::withdrawable(&pool, &user, 7).unwrap();
+            assert_eq!(amount, 7);
+            assert!(pool.supply_amount(&shares, Rounding::Down).unwrap() >= amount);
+
+            let balance = pool.debt_amount(&user.shares(), Rounding::Up).unwrap();
+            let (amount, shares) = UserAccountUtil::<Runtime>::repayable(&pool, &user, balance).unwrap();
+            assert_eq!(amount, balance);
+            assert_eq!(shares, user.shares());
+
+            // partial repayment never clears more debt than paid
+            let (amount, shares) = UserAccountUtil::<Runtime>::repayable(&pool, &user, 7).unwrap();
+            assert_eq!(amount, 7);
+            assert!(pool.debt_amount(&shares, Rounding::Down).unwrap() <= amount);
+            assert!(shares < user.shares());
+        })
+}
@@ -3,9 +3,7 @@ use crate::tests::mock::{*};
 
 use frame_support::{assert_noop, assert_ok};
 use crate::pool::PoolRepository;
-use sp_runtime::FixedU128;
 use polkadot_parachain_primitives::BALANCE_ONE;
-use sp_runtime::traits::{One};
 use crate::types::UserAccountUtil;
 
 #[test]
@@ -40,7 +38,7 @@ fn floating_lend_withdraw_not_enough_liquidity() {
         .build()
         .execute_with(|| {
             let mut pool = default_pool_proxy();
-            pool.increment_supply(&FixedU128::one());
+            pool.increment_supply(&BALANCE_ONE).unwrap();
             PoolRepository::save(pool.clone());
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
@@ -58,7 +56,7 @@ fn floating_lend_repay_no_supply() {
             .build()
             .execute_with(|| {
                     let mut pool = default_pool_proxy();
-                    pool.increment_supply(&FixedU128::one());
+                    pool.increment_supply(&BALANCE_ONE).unwrap();
                     PoolRepository::save(pool.clone());
 
                     FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
@@ -75,12 +73,12 @@ fn floating_lend_withdraw_full_not_collateral() {
         .build()
         .execute_with(|| {
             let mut pool = default_pool_proxy();
-            pool.increment_supply(&FixedU128::from(3000));
+            pool.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(pool.clone());
 
             // this is to simulate the user supply. User supplied 1000 pool has 3000
-            let amount = FixedU128::from(1000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ROOT.clone(), &amount).unwrap();
+            let amount = 1000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ROOT.clone(), &amount).unwrap();
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             assert_ok!(
@@ -89,7 +87,7 @@ fn floating_lend_withdraw_full_not_collateral() {
             );
 
             let pool = PoolStorage::<Runtime>::get(pool.id()).unwrap();
-            assert_eq!(pool.supply(), FixedU128::from(2000));
+            assert_eq!(pool.supply().unwrap(), 2000 * BALANCE_ONE);
             let user_supply = PoolUserSupplies::<Runtime>::get(pool.id, ROOT.clone());
             assert_eq!(user_supply.is_none(), true);
         });
@@ -101,12 +99,12 @@ fn floating_lend_withdraw_full_not_collateral_pool_empty() {
         .build()
         .execute_with(|| {
             let mut pool = default_pool_proxy();
-            pool.increment_supply(&FixedU128::from(3000));
+            pool.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(pool.clone());
 
             // this is to simulate the user supply. User supplied 1000 pool has 3000
-            let amount = FixedU128::from(3000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ROOT.clone(), &amount).unwrap();
+            let amount = 3000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ROOT.clone(), &amount).unwrap();
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             assert_ok!(
@@ -115,7 +113,7 @@ fn floating_lend_withdraw_full_not_collateral_pool_empty() {
             );
 
             let pool = PoolStorage::<Runtime>::get(pool.id()).unwrap();
-            assert_eq!(pool.supply(), FixedU128::from(0));
+            assert_eq!(pool.supply().unwrap(), 0 * BALANCE_ONE);
             let user_supply = PoolUserSupplies::<Runtime>::get(pool.id, ROOT.clone());
             assert_eq!(user_supply.is_none(), true);
         });
@@ -127,12 +125,12 @@ fn floating_lend_withdraw_partial_not_collateral() {
         .build()
         .execute_with(|| {
             let mut pool = default_pool_proxy();
-            pool.increment_supply(&FixedU128::from(3000));
+            pool.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
             PoolRepository::save(pool.clone());
 
             // this is to simulate the user supply. User supplied 1000 pool has 3000
-            let amount = FixedU128::from(1000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool, ROOT.clone(), &amount).unwrap();
+            let amount = 1000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool, ROOT.clone(), &amount).unwrap();
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             assert_ok!(
@@ -141,9 +139,9 @@ fn floating_lend_withdraw_partial_not_collateral() {
             );
 
             let pool = PoolStorage::<Runtime>::get(pool.id()).unwrap();
-            assert_eq!(pool.supply(), FixedU128::from(2500));
+            assert_eq!(pool.supply().unwrap(), 2500 * BALANCE_ONE);
             let user_supply = PoolUserSupplies::<Runtime>::get(pool.id, ROOT.clone()).unwrap();
-            assert_eq!(user_supply.amount(), FixedU128::from(500));
+            assert_eq!(user_supply.shares(), 500 * BALANCE_ONE);
         });
 }
 
@@ -158,20 +156,20 @@ fn floating_lend_withdraw_partial_collateral_success() {
             let mut pool_3 = pool_proxy(3, false);
             let mut pool_4 = pool_proxy(4, true);
 
-            pool_0.increment_supply(&FixedU128::from(3000));
-            pool_0.increment_debt(&FixedU128::from(1000));
+            pool_0.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_0.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
 
-            pool_1.increment_supply(&FixedU128::from(3000));
-            pool_1.increment_debt(&FixedU128::from(2000));
+            pool_1.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_1.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_2.increment_supply(&FixedU128::from(3000));
-            pool_2.increment_debt(&FixedU128::from(2000));
+            pool_2.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_2.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_3.increment_supply(&FixedU128::from(3000));
-            pool_3.increment_debt(&FixedU128::from(2000));
+            pool_3.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_3.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_4.increment_supply(&FixedU128::from(3000));
-            pool_4.increment_debt(&FixedU128::from(1000));
+            pool_4.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_4.increment_debt(&(1000 * BALANCE_ONE)).unwrap();
 
             PoolRepository::save(pool_0.clone());
             PoolRepository::save(pool_1.clone());
@@ -180,19 +178,19 @@ fn floating_lend_withdraw_partial_collateral_success() {
             PoolRepository::save(pool_4.clone());
 
             // this is to simulate the user supply. User supplied 1000 pool has 3000
-            let amount = FixedU128::from(1000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_0, ROOT.clone(), &FixedU128::from(2000)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_4, ROOT.clone(), &amount).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_1, ROOT.clone(), &&FixedU128::from(1800)).unwrap();
+            let amount = 1000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool_0, ROOT.clone(), &(2000 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_4, ROOT.clone(), &amount).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_1, ROOT.clone(), &(1800 * BALANCE_ONE)).unwrap();
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 4).ok();
             FloatingRateLend::update_liquidation_threshold(Origin::signed(ROOT), 100).ok();
             FloatingRateLend::withdraw(Origin::signed(ROOT), 4, 1 * BALANCE_ONE).ok();
             let user_supply = PoolUserSupplies::<Runtime>::get(pool_4.id(), ROOT.clone()).unwrap();
-            assert_eq!(user_supply.amount(), FixedU128::from(999));
+            assert_eq!(user_supply.shares(), 999 * BALANCE_ONE);
 
             // this is just to test we can withdraw if within liquidation threshold
             assert_ok!(FloatingRateLend::withdraw(Origin::signed(ROOT), 4, 1000 * BALANCE_ONE));
@@ -202,7 +200,7 @@ fn floating_lend_withdraw_partial_collateral_success() {
             assert_eq!(user_supply.is_none(), true);
 
             let pool = PoolStorage::<Runtime>::get(pool_4.id()).unwrap();
-            assert_eq!(pool.supply(), FixedU128::from(2000));
+            assert_eq!(pool.supply().unwrap(), 2000 * BALANCE_ONE);
         });
 }
 
@@ -216,17 +214,17 @@ fn floating_lend_withdraw_partial_collateral() {
             let mut pool_2 = pool_proxy(2, false);
             let mut pool_3 = pool_proxy(3, false);
 
-            pool_0.increment_supply(&FixedU128::from(3000));
-            pool_0.increment_debt(&FixedU128::from(2000));
+            pool_0.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_0.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_1.increment_supply(&FixedU128::from(3000));
-            pool_1.increment_debt(&FixedU128::from(2000));
+            pool_1.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_1.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_2.increment_supply(&FixedU128::from(3000));
-            pool_2.increment_debt(&FixedU128::from(2000));
+            pool_2.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_2.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
-            pool_3.increment_supply(&FixedU128::from(3000));
-            pool_3.increment_debt(&FixedU128::from(2000));
+            pool_3.increment_supply(&(3000 * BALANCE_ONE)).unwrap();
+            pool_3.increment_debt(&(2000 * BALANCE_ONE)).unwrap();
 
             PoolRepository::save(pool_0.clone());
             PoolRepository::save(pool_1.clone());
@@ -234,11 +232,11 @@ fn floating_lend_withdraw_partial_collateral() {
             PoolRepository::save(pool_3.clone());
 
             // this is to simulate the user supply. User supplied 1000 pool has 3000
-            let amount = FixedU128::from(1000);
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_0, ROOT.clone(), &FixedU128::from(2000)).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_2, ROOT.clone(), &amount).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_supply(&pool_3, ROOT.clone(), &amount).unwrap();
-            UserAccountUtil::<Runtime>::accrue_interest_and_increment_debt(&pool_1, ROOT.clone(), &&FixedU128::from(1800)).unwrap();
+            let amount = 1000 * BALANCE_ONE;
+            UserAccountUtil::<Runtime>::increment_supply(&pool_0, ROOT.clone(), &(2000 * BALANCE_ONE)).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_2, ROOT.clone(), &amount).unwrap();
+            UserAccountUtil::<Runtime>::increment_supply(&pool_3, ROOT.clone(), &amount).unwrap();
+            UserAccountUtil::<Runtime>::increment_debt(&pool_1, ROOT.clone(), &(1800 * BALANCE_ONE)).unwrap();
 
             FloatingRateLend::enable_pool(Origin::signed(ROOT), 0).ok();
             FloatingRateLend::update_liquidation_threshold(Origin::signed(ROOT), 100).ok();
@@ -1,18 +1,19 @@
 use codec::{Decode, Encode};
 use frame_support::{sp_runtime::FixedU128};
 use frame_support::dispatch::DispatchResultWithPostInfo;
+use sp_core::U256;
 use sp_runtime::{FixedPointNumber, RuntimeDebug};
-use sp_runtime::traits::{CheckedMul, One};
-use sp_runtime::traits::{Saturating, Zero};
+use sp_runtime::traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedSub, Convert, Saturating, Zero};
 use sp_std::{vec::Vec};
 use sp_std::collections::btree_map::BTreeMap;
+use sp_std::convert::{TryFrom, TryInto};
 use sp_std::marker;
-use sp_std::ops::{Add, Div, Mul, Sub};
+use sp_std::ops::{Add, Div, Mul};
 
 use polkadot_parachain_primitives::CustomError;
 use polkadot_parachain_primitives::PoolId;
 
-use crate::{Config, Error, PoolStorage, PoolUserDebts, PoolUserSupplies, UserDebtSet, UserSupplySet};
+use crate::{BalanceOf, Config, Error, PoolStorage, PoolUserDebts, PoolUserSupplies, UserDebtSet, UserSupplySet};
 use crate::pool::{PoolProxy};
 
 pub struct UserBalanceStats{
@@ -34,45 +35,56 @@ impl UserBalanceStats {
     }
 
     pub fn decrement_collateral(&mut self, amount: FixedU128) {
-        self.collateral_balance = self.collateral_balance.sub(amount);
+        self.collateral_balance = self.collateral_balance.saturating_sub(amount);
     }
 }
 
+/// A single lending action of a `batch_actions` call, the amounts are in the pool currency
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug)]
+pub enum LendingAction<Balance> {
+    /// Supply to the pool[pool_id, balance]
+    Supply(PoolId, Balance),
+    /// Withdraw up to the balance from the pool[pool_id, balance]
+    Withdraw(PoolId, Balance),
+    /// Borrow from the pool[pool_id, balance]
+    Borrow(PoolId, Balance),
+    /// Repay up to the balance to the pool[pool_id, balance]
+    Repay(PoolId, Balance),
+}
+
+/// The rounding direction when converting between balances and pool shares.
+/// Callers should always pick the direction in favour of the protocol.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
 // TODO: urgent! Added a proxy layer and ensure the account is in the proxy
+/// The position of a user in a pool, expressed in pool shares.
+/// The balance it is worth is `shares * pool index`, so interest accrues without touching it.
 #[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode)]
-pub struct UserData {
-    amount: FixedU128,
-    index: FixedU128,
+pub struct UserData<Balance> {
+    shares: Balance,
 }
 
-impl UserData {
-    pub fn accrue_interest(&mut self, pool_index: &FixedU128) -> Result<(), CustomError> {
-        // TODO: maybe should consider throw an error here
-        if pool_index.le(&self.index) { return Ok(()); }
-        self.amount = pool_index
-            .div(self.index)
-            .checked_mul(&self.amount)
-            .ok_or(CustomError::FlownError)?;
-        self.index = *pool_index;
+impl <Balance: AtLeast32BitUnsigned + Copy> UserData<Balance> {
+    pub fn increment(&mut self, shares: &Balance) -> Result<(), CustomError> {
+        self.shares = self.shares.checked_add(shares).ok_or(CustomError::FlownError)?;
         Ok(())
     }
 
-    pub fn increment(&mut self, amount: &FixedU128) {
-        self.amount = self.amount.add(*amount);
-    }
-
-    pub fn decrement(&mut self, amount: &FixedU128) {
-        self.amount = self.amount.sub(*amount);
+    pub fn decrement(&mut self, shares: &Balance) -> Result<(), CustomError> {
+        self.shares = self.shares.checked_sub(shares).ok_or(CustomError::FlownError)?;
+        Ok(())
     }
 
-    /// Checks if the amount below zero
-    pub fn below_zero(&self) -> bool { self.amount.lt(&FixedU128::zero()) }
-
-    pub fn amount(&self) -> FixedU128 { self.amount }
+    /// Checks if all the shares are gone
+    pub fn is_empty(&self) -> bool { self.shares.is_zero() }
 
-    pub fn index(&self) -> FixedU128 { self.index }
+    pub fn shares(&self) -> Balance { self.shares }
 
-    pub fn new(amount: FixedU128) -> Self { UserData {amount, index: FixedU128::one()} }
+    pub fn new(shares: Balance) -> Self { UserData { shares } }
 }
 
 pub const BLOCKS_IN_YEAR: u128 = 365 * 24 * 3600 / 6;
@@ -87,47 +99,104 @@ impl Convertor {
     pub fn convert_percentage(a: u64) -> FixedU128 {
         FixedU128::saturating_from_rational(a, 100)
     }
+
+    /// Converts a balance into shares at the index, i.e. `amount / index`
+    pub fn to_shares<Balance: AtLeast32BitUnsigned + Copy>(
+        amount: Balance,
+        index: &FixedU128,
+        rounding: Rounding,
+    ) -> Result<Balance, CustomError> {
+        Self::mul_div(amount, FixedU128::accuracy(), index.into_inner(), rounding)
+    }
+
+    /// Converts shares into the balance they are worth at the index, i.e. `shares * index`
+    pub fn to_amount<Balance: AtLeast32BitUnsigned + Copy>(
+        shares: Balance,
+        index: &FixedU128,
+        rounding: Rounding,
+    ) -> Result<Balance, CustomError> {
+        Self::mul_div(shares, index.into_inner(), FixedU128::accuracy(), rounding)
+    }
+
+    /// Converts a balance of one currency into another given their prices, i.e. `amount * from / to`
+    pub fn convert_by_price<Balance: AtLeast32BitUnsigned + Copy>(
+        amount: Balance,
+        price_from: &FixedU128,
+        price_to: &FixedU128,
+        rounding: Rounding,
+    ) -> Result<Balance, CustomError> {
+        Self::mul_div(amount, price_from.into_inner(), price_to.into_inner(), rounding)
+    }
+
+    /// Converts a legacy `FixedU128` amount into balance, where `unit` is the value of one balance unit
+    pub fn fixed_to_balance<Balance: AtLeast32BitUnsigned + Copy>(
+        value: &FixedU128,
+        unit: &FixedU128,
+        rounding: Rounding,
+    ) -> Result<Balance, CustomError> {
+        let value = <Balance as TryFrom<u128>>::try_from(value.into_inner()).map_err(|_| CustomError::FlownError)?;
+        Self::mul_div(value, 1, unit.into_inner(), rounding)
+    }
+
+    /// Computes `a * b / c` without intermediate overflow and with explicit rounding
+    fn mul_div<Balance: AtLeast32BitUnsigned + Copy>(
+        a: Balance,
+        b: u128,
+        c: u128,
+        rounding: Rounding,
+    ) -> Result<Balance, CustomError> {
+        if c.is_zero() { return Err(CustomError::FlownError); }
+        let a: u128 = a.try_into().map_err(|_| CustomError::FlownError)?;
+
+        // both operands are below 2^128, the product cannot overflow 256 bits
+        let product = U256::from(a) * U256::from(b);
+        let c = U256::from(c);
+        let mut result = product / c;
+        if rounding == Rounding::Up && !(product % c).is_zero() {
+            result = result + U256::one();
+        }
+
+        if result > U256::from(u128::max_value()) { return Err(CustomError::FlownError); }
+        <Balance as TryFrom<u128>>::try_from(result.low_u128()).map_err(|_| CustomError::FlownError)
+    }
 }
 
 /// The util functions for user accounts
 pub struct UserAccountUtil<T>(marker::PhantomData<T>);
 
 impl <T: Config> UserAccountUtil<T> {
-    /// Accrue interest for the user, including all the pools the user has participated in.
+    /// Collect the balances of the user, including all the pools the user has participated in.
+    /// Supplies are rounded down and debts rounded up.
     /// Ensure the pool_map contains all the pools the user participated in
-    pub fn accrue_interest_for_user(
+    pub fn user_supply_debt(
         account: T::AccountId,
         pool_map: &BTreeMap<PoolId, PoolProxy<T>>,
-    ) -> Result<UserSupplyDebtData, CustomError> {
+    ) -> Result<UserSupplyDebtData<BalanceOf<T>>, CustomError> {
         let mut supply_map = BTreeMap::new();
         for id in Self::get_supply_pools(account.clone()) {
             if let Some(pool) = pool_map.get(&id) {
-                if let Some(mut supply) = PoolUserSupplies::<T>::get(pool.id(), account.clone()) {
-                    supply.accrue_interest(&pool.total_supply_index())?;
-                    PoolUserSupplies::<T>::insert(pool.id(), account.clone(), supply.clone());
-                    supply_map.insert(pool.id(), supply);
+                if let Some(supply) = PoolUserSupplies::<T>::get(pool.id(), account.clone()) {
+                    let amount = pool.supply_amount(&supply.shares(), Rounding::Down)?;
+                    supply_map.insert(pool.id(), amount);
                     continue;
                 }
             }
 
-            // TODO: more context here
-            log::error!("Inconsistent state, check!");
+            log::error!("Inconsistent state, supply of pool {:?} not found", id);
             return Err(CustomError::InconsistentState);
         }
 
         let mut debt_map = BTreeMap::new();
         for id in Self::get_debt_pools(account.clone()) {
             if let Some(pool) = pool_map.get(&id) {
-                if let Some(mut debt) = PoolUserDebts::<T>::get(pool.id(), account.clone()) {
-                    debt.accrue_interest(&pool.total_debt_index())?;
-                    PoolUserDebts::<T>::insert(pool.id(), account.clone(), debt.clone());
-                    debt_map.insert(pool.id(), debt);
+                if let Some(debt) = PoolUserDebts::<T>::get(pool.id(), account.clone()) {
+                    let amount = pool.debt_amount(&debt.shares(), Rounding::Up)?;
+                    debt_map.insert(pool.id(), amount);
                     continue;
                 }
             }
 
-            // TODO: more context here
-            log::error!("Inconsistent state, check!");
+            log::error!("Inconsistent state, debt of pool {:?} not found", id);
             return Err(CustomError::InconsistentState);
         }
 
@@ -148,28 +217,51 @@ impl <T: Config> UserAccountUtil<T> {
             .collect::<Vec<PoolId>>()
     }
 
-    /// Increment user supply.
-    /// Ensure the amount is above pool.minimal_amount() before this function is actually called
-    pub fn accrue_interest_and_increment_supply(pool: &PoolProxy<T>, account: T::AccountId, amount: &FixedU128) -> DispatchResultWithPostInfo {
+    /// The amount and supply shares to burn for withdrawing up to `amount`.
+    /// Shares are rounded up in favour of the protocol, withdrawing everything burns all the shares.
+    pub fn withdrawable(
+        pool: &PoolProxy<T>,
+        user_supply: &UserData<BalanceOf<T>>,
+        amount: BalanceOf<T>,
+    ) -> Result<(BalanceOf<T>, BalanceOf<T>), CustomError> {
+        let balance = pool.supply_amount(&user_supply.shares(), Rounding::Down)?;
+        if amount >= balance { return Ok((balance, user_supply.shares())); }
+        let shares = pool.supply_shares(&amount, Rounding::Up)?;
+        Ok((amount, shares.min(user_supply.shares())))
+    }
+
+    /// The amount and debt shares to burn for repaying up to `amount`.
+    /// Shares are rounded down in favour of the protocol, repaying everything burns all the shares.
+    pub fn repayable(
+        pool: &PoolProxy<T>,
+        user_debt: &UserData<BalanceOf<T>>,
+        amount: BalanceOf<T>,
+    ) -> Result<(BalanceOf<T>, BalanceOf<T>), CustomError> {
+        let balance = pool.debt_amount(&user_debt.shares(), Rounding::Up)?;
+        if amount >= balance { return Ok((balance, user_debt.shares())); }
+        let shares = pool.debt_shares(&amount, Rounding::Down)?;
+        Ok((amount, shares))
+    }
+
+    /// Increment user supply shares.
+    pub fn increment_supply(pool: &PoolProxy<T>, account: T::AccountId, shares: &BalanceOf<T>) -> DispatchResultWithPostInfo {
         if let Some(mut user_supply) = PoolUserSupplies::<T>::get(pool.id(), account.clone()) {
-            user_supply.accrue_interest(&pool.total_supply_index())?;
-            user_supply.increment(&amount);
+            user_supply.increment(shares)?;
             PoolUserSupplies::<T>::insert(pool.id(), account, user_supply);
         } else {
-            Self::add_user_supply(&pool, account, amount);
+            Self::add_user_supply(&pool, account, shares);
         }
         Ok(().into())
     }
 
-    /// Decrement user supply.
-    /// Ensure the amount is never zero before this function is actually called
-    pub fn decrement_supply(pool: &PoolProxy<T>, account: T::AccountId, amount: &FixedU128, mut user_supply: UserData) -> DispatchResultWithPostInfo {
+    /// Decrement user supply shares.
+    /// The user supply is removed once all the shares are gone.
+    pub fn decrement_supply(pool: &PoolProxy<T>, account: T::AccountId, shares: &BalanceOf<T>, mut user_supply: UserData<BalanceOf<T>>) -> DispatchResultWithPostInfo {
         // this should not have happened, caller should always ensure zero is never reached
-        if user_supply.amount() < *amount { return Err(Error::<T>::UserSupplyTooLow.into()); }
+        if user_supply.shares() < *shares { return Err(Error::<T>::UserSupplyTooLow.into()); }
 
-        user_supply.decrement(amount);
-        // TODO: we need to handle dust here! Currently minimal_amount is just zero
-        if user_supply.amount() <= pool.minimal_amount() {
+        user_supply.decrement(shares)?;
+        if user_supply.is_empty() {
             PoolUserSupplies::<T>::remove(pool.id(), account.clone());
             Self::remove_user_supply(pool.id(), account);
         } else {
@@ -178,60 +270,52 @@ impl <T: Config> UserAccountUtil<T> {
         Ok(().into())
     }
 
-    /// Increment user debt
-    pub fn accrue_interest_and_increment_debt(pool: &PoolProxy<T>, account: T::AccountId, amount: &FixedU128) -> DispatchResultWithPostInfo {
+    /// Increment user debt shares
+    pub fn increment_debt(pool: &PoolProxy<T>, account: T::AccountId, shares: &BalanceOf<T>) -> DispatchResultWithPostInfo {
         if let Some(mut user_debt) = PoolUserDebts::<T>::get(pool.id(), account.clone()) {
-            user_debt.accrue_interest(&pool.total_debt_index())?;
-            user_debt.increment(&amount);
+            user_debt.increment(shares)?;
             PoolUserDebts::<T>::insert(pool.id(), account.clone(), user_debt);
         } else {
-            Self::new_debt(&pool, account.clone(), &amount);
+            Self::new_debt(&pool, account.clone(), shares);
         }
         Ok(().into())
     }
 
     /// Get the user supply balance for the user in a pool
-    pub fn supply_balance_with_interest(pool_id: PoolId, user: T::AccountId) -> Result<FixedU128, CustomError> {
-        if let Some(mut user_supply) = PoolUserSupplies::<T>::get(pool_id, user) {
+    pub fn supply_balance_with_interest(pool_id: PoolId, user: T::AccountId) -> Result<BalanceOf<T>, CustomError> {
+        if let Some(user_supply) = PoolUserSupplies::<T>::get(pool_id, user) {
             if let Some(mut pool) = PoolStorage::<T>::get(pool_id) {
                 pool.accrue_interest(<frame_system::Pallet<T>>::block_number())?;
-                user_supply.accrue_interest(&pool.total_supply_index())?;
-                return Ok(user_supply.amount());
+                return pool.supply_amount(&user_supply.shares(), Rounding::Down);
             }
         }
-        Ok(FixedU128::zero())
+        Ok(Zero::zero())
     }
 
     /// Get the user debt balance for the user in a pool
-    pub fn debt_balance_with_interest(pool_id: PoolId, user: T::AccountId) -> Result<FixedU128, CustomError> {
-        if let Some(mut user_debt) = PoolUserDebts::<T>::get(pool_id, user) {
+    pub fn debt_balance_with_interest(pool_id: PoolId, user: T::AccountId) -> Result<BalanceOf<T>, CustomError> {
+        if let Some(user_debt) = PoolUserDebts::<T>::get(pool_id, user) {
             if let Some(mut pool) = PoolStorage::<T>::get(pool_id) {
                 pool.accrue_interest(<frame_system::Pallet<T>>::block_number())?;
-                user_debt.accrue_interest(&pool.total_debt_index())?;
-                return Ok(user_debt.amount());
+                return pool.debt_amount(&user_debt.shares(), Rounding::Up);
             }
         }
-        Ok(FixedU128::zero())
+        Ok(Zero::zero())
     }
 
     /// Calculate the user balances
     pub fn user_balances(
-        user_supply_debt: &UserSupplyDebtData,
+        user_supply_debt: &UserSupplyDebtData<BalanceOf<T>>,
         pool_map: &BTreeMap<PoolId, PoolProxy<T>>,
     ) -> Result<UserBalanceStats, CustomError> {
         // calculate supply related
         let mut supply_balance = FixedU128::zero();
         let mut collateral_balance = FixedU128::zero();
-        for pool_id in user_supply_debt.supply.keys() {
+        for (pool_id, amount) in user_supply_debt.supply.iter() {
             let pool = pool_map.get(pool_id).ok_or(CustomError::InconsistentState)?;
             if !pool.price_ready() { return Err(CustomError::PriceNotReady); }
 
-            let amount = user_supply_debt.supply
-                .get(pool_id)
-                .ok_or(CustomError::InconsistentState)?
-                .amount();
-
-            let mut balance = pool.price().saturating_mul(amount);
+            let mut balance = Self::value_of(pool, amount);
             supply_balance = supply_balance.add(balance);
 
             if pool.can_be_collateral() {
@@ -242,15 +326,10 @@ impl <T: Config> UserAccountUtil<T> {
 
         // calculate debt
         let mut debt_balance = FixedU128::zero();
-        for pool_id in user_supply_debt.debt.keys() {
+        for (pool_id, amount) in user_supply_debt.debt.iter() {
             let pool = pool_map.get(pool_id).ok_or(CustomError::InconsistentState)?;
             if !pool.price_ready() { return Err(CustomError::PriceNotReady); }
-            let amount = user_supply_debt.debt
-                .get(pool_id)
-                .ok_or(CustomError::InconsistentState)?
-                .amount();
-            let balance = pool.price().saturating_mul(amount);
-            debt_balance = debt_balance.add(balance);
+            debt_balance = debt_balance.add(Self::value_of(pool, amount));
         }
 
         Ok(UserBalanceStats {
@@ -262,8 +341,8 @@ impl <T: Config> UserAccountUtil<T> {
 
     /// Checks if the withdraw will not trigger liquidation
     pub fn is_withdraw_trigger_liquidation(
-        to_withdraw: (PoolId, FixedU128),
-        user_supply_debt: UserSupplyDebtData,
+        to_withdraw: (PoolId, BalanceOf<T>),
+        user_supply_debt: UserSupplyDebtData<BalanceOf<T>>,
         pool_map: BTreeMap<PoolId, PoolProxy<T>>,
         liquidation_threshold: FixedU128,
     ) -> Result<bool, CustomError> {
@@ -272,15 +351,14 @@ impl <T: Config> UserAccountUtil<T> {
 
         let pool = pool_map.get(&pool_id).ok_or(CustomError::InconsistentState)?;
         if !pool.price_ready() { return Err(CustomError::PriceNotReady.into()); }
-        let price = pool.price();
 
-        user_balance_stats.decrement_collateral((price * pool.safe_factor()).mul(amount));
+        user_balance_stats.decrement_collateral(Self::value_of(pool, &amount).mul(pool.safe_factor()));
         Ok(user_balance_stats.is_liquidated(liquidation_threshold))
     }
 
     pub fn is_borrow_trigger_liquidation(
-        to_borrow: (PoolId, FixedU128),
-        user_supply_debt: UserSupplyDebtData,
+        to_borrow: (PoolId, BalanceOf<T>),
+        user_supply_debt: UserSupplyDebtData<BalanceOf<T>>,
         pool_map: BTreeMap<PoolId, PoolProxy<T>>,
         liquidation_threshold: FixedU128,
     ) -> Result<bool, CustomError> {
@@ -289,18 +367,22 @@ impl <T: Config> UserAccountUtil<T> {
 
         let pool = pool_map.get(&pool_id).ok_or(CustomError::InconsistentState)?;
         if !pool.price_ready() { return Err(CustomError::PriceNotReady.into()); }
-        let price = pool.price();
 
-        user_balance_stats.increment_debt(amount.mul(price));
+        user_balance_stats.increment_debt(Self::value_of(pool, &amount));
         Ok(user_balance_stats.is_liquidated(liquidation_threshold))
     }
 
     /* -------- Internal helper methods --------- */
 
+    /// The value of the amount at the price of the pool
+    fn value_of(pool: &PoolProxy<T>, amount: &BalanceOf<T>) -> FixedU128 {
+        pool.price().saturating_mul(T::Conversion::convert(*amount))
+    }
+
     /// Add to user supplies.
     /// This function contains write action, ensure the checks are performed in advance
-    fn add_user_supply(pool: &PoolProxy<T>, account: T::AccountId, amount: &FixedU128) {
-        let user_supply = UserData::new(*amount);
+    fn add_user_supply(pool: &PoolProxy<T>, account: T::AccountId, shares: &BalanceOf<T>) {
+        let user_supply = UserData::new(*shares);
         PoolUserSupplies::<T>::insert(pool.id(), account.clone(), user_supply);
 
         // update user's supply asset set
@@ -313,7 +395,7 @@ impl <T: Config> UserAccountUtil<T> {
 
     /// Remove to user supplies.
     /// This function contains write action, ensure the checks are performed in advance
-    fn remove_user_supply(pool_id: PoolId, account: T::AccountId) {
+    pub(crate) fn remove_user_supply(pool_id: PoolId, account: T::AccountId) {
         let mut pool_currency_tuples = UserSupplySet::<T>::get(account.clone());
         pool_currency_tuples.retain(|p| p.0 != pool_id);
         UserSupplySet::<T>::insert(account, pool_currency_tuples);
@@ -321,7 +403,7 @@ impl <T: Config> UserAccountUtil<T> {
 
     /// Remove to user debts.
     /// This function contains write action, ensure the checks are performed in advance
-    fn remove_user_debt(pool_id: PoolId, account: T::AccountId) {
+    pub(crate) fn remove_user_debt(pool_id: PoolId, account: T::AccountId) {
         let mut pool_currency_tuples = UserDebtSet::<T>::get(account.clone());
         pool_currency_tuples.retain(|p| p.0 != pool_id);
         UserDebtSet::<T>::insert(account, pool_currency_tuples);
@@ -330,8 +412,8 @@ impl <T: Config> UserAccountUtil<T> {
 
     /// Add to user debts.
     /// This function contains write action, ensure the checks are performed in advance
-    fn new_debt(pool: &PoolProxy<T>, account: T::AccountId, amount: &FixedU128) {
-        let user_debt = UserData::new(*amount);
+    fn new_debt(pool: &PoolProxy<T>, account: T::AccountId, shares: &BalanceOf<T>) {
+        let user_debt = UserData::new(*shares);
         PoolUserDebts::<T>::insert(pool.id(), account.clone(), user_debt);
 
         // update user's supply asset set
@@ -342,12 +424,13 @@ impl <T: Config> UserAccountUtil<T> {
         }
     }
 
-    /// Decrement user debt.
-    /// Ensure the amount is above pool.minimal_amount() before this function is actually called
-    pub fn decrement_debt(pool: &PoolProxy<T>, account: T::AccountId, amount: &FixedU128, mut user_debt: UserData) -> DispatchResultWithPostInfo {
-        user_debt.decrement(amount);
-        // TODO: we need to handle dust here! Currently minimal_amount is just zero
-        if user_debt.amount() <= pool.minimal_amount() {
+    /// Decrement user debt shares.
+    /// The user debt is removed once all the shares are gone.
+    pub fn decrement_debt(pool: &PoolProxy<T>, account: T::AccountId, shares: &BalanceOf<T>, mut user_debt: UserData<BalanceOf<T>>) -> DispatchResultWithPostInfo {
+        if user_debt.shares() < *shares { return Err(Error::<T>::UserDebtTooLow.into()); }
+
+        user_debt.decrement(shares)?;
+        if user_debt.is_empty() {
             PoolUserDebts::<T>::remove(pool.id(), account.clone());
             UserAccountUtil::<T>::remove_user_debt(pool.id(), account);
         } else {
@@ -357,7 +440,8 @@ impl <T: Config> UserAccountUtil<T> {
     }
 }
 
-pub struct UserSupplyDebtData {
-    pub supply: BTreeMap<PoolId, UserData>,
-    pub debt: BTreeMap<PoolId, UserData>,
-}
\ No newline at end of file
+/// The balances of a user keyed by pool, including interest
+pub struct UserSupplyDebtData<Balance> {
+    pub supply: BTreeMap<PoolId, Balance>,
+    pub debt: BTreeMap<PoolId, Balance>,
+}
@@ -0,0 +1,41 @@
+use frame_support::traits::Get;
+use sp_runtime::traits::Saturating;
+use sp_std::{marker, prelude::*};
+
+use orml_traits::CombineData;
+use polkadot_parachain_primitives::CurrencyId;
+
+use crate::{Config, TimestampedValueOf};
+
+/// Sort by value and returns the median timestamped value.
+/// Returns the prev_value if the number of values that have not expired is less than
+/// MinimumCount, or None if the prev_value has expired as well.
+pub struct DefaultCombineData<T, MinimumCount, ExpiresIn>(marker::PhantomData<(T, MinimumCount, ExpiresIn)>);
+
+impl<T, MinimumCount, ExpiresIn> CombineData<CurrencyId, TimestampedValueOf<T>>
+    for DefaultCombineData<T, MinimumCount, ExpiresIn>
+where
+    T: Config,
+    MinimumCount: Get<u32>,
+    ExpiresIn: Get<T::BlockNumber>,
+{
+    fn combine_data(
+        _key: &CurrencyId,
+        mut values: Vec<TimestampedValueOf<T>>,
+        prev_value: Option<TimestampedValueOf<T>>,
+    ) -> Option<TimestampedValueOf<T>> {
+        let expires_in = ExpiresIn::get();
+        let now = <frame_system::Pallet<T>>::block_number();
+        let is_fresh = |v: &TimestampedValueOf<T>| v.timestamp.saturating_add(expires_in) >= now;
+
+        values.retain(|v| is_fresh(v));
+
+        if values.is_empty() || (values.len() as u32) < MinimumCount::get() {
+            return prev_value.filter(|v| is_fresh(v));
+        }
+
+        values.sort_by(|a, b| a.value.cmp(&b.value));
+        let median_index = values.len() / 2;
+        Some(values[median_index])
+    }
+}
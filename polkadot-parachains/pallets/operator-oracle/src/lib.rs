@@ -0,0 +1,325 @@
+// This file is part of Konomi.
+
+// Copyright (C) 2020-2021 Konomi Foundation.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Operator oracle module.
+//!
+//! Whitelisted operators feed prices per currency. The raw values of all the operators are
+//! combined, by default with the median of the values that have not expired, and exposed
+//! as a `PriceProvider` so that it can be used without a Chainlink node.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod default_combine_data;
+mod mock;
+mod tests;
+
+pub use default_combine_data::DefaultCombineData;
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+
+    /* -------- Substrate Libs ------- */
+    use frame_support::{
+        pallet_prelude::*,
+        traits::EnsureOrigin,
+    };
+    use frame_system::{pallet_prelude::*};
+    use sp_std::vec::Vec;
+
+    /* ------- ORML Libs -------- */
+    use orml_traits::{CombineData, DataFeeder, DataProvider, DataProviderExtended, OnNewData, TimestampedValue};
+
+    /* ------- Local Libs -------- */
+    use polkadot_parachain_primitives::{CurrencyId, Price, PriceValue};
+    use pallet_traits::PriceProvider;
+
+    /// The price fed by an operator, timestamped with the block number it was fed at
+    pub type TimestampedValueOf<T> = TimestampedValue<PriceValue, <T as frame_system::Config>::BlockNumber>;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// Hook on new data fed by an operator
+        type OnNewData: OnNewData<Self::AccountId, CurrencyId, PriceValue>;
+        /// Combines the raw values fed by the operators into a single value
+        type CombineData: CombineData<CurrencyId, TimestampedValueOf<Self>>;
+        /// The origin allowed to add and remove operators
+        type OperatorOrigin: EnsureOrigin<Self::Origin>;
+        /// The maximum number of operators
+        #[pallet::constant]
+        type MaxOperators: Get<u32>;
+        /// The maximum number of values an operator can feed at once
+        #[pallet::constant]
+        type MaxFeedValues: Get<u32>;
+        /// The number of blocks a combined price stays valid for
+        #[pallet::constant]
+        type ExpiresIn: Get<Self::BlockNumber>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// The raw values fed by each operator
+    #[pallet::storage]
+    #[pallet::getter(fn raw_values)]
+    pub type RawValues<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        Twox64Concat,
+        CurrencyId,
+        TimestampedValueOf<T>,
+    >;
+
+    /// The combined value of each currency, updated whenever an operator feeds a new value
+    #[pallet::storage]
+    #[pallet::getter(fn values)]
+    pub type Values<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, TimestampedValueOf<T>>;
+
+    /// The operators who have fed values in the current block
+    #[pallet::storage]
+    pub(super) type HasDispatched<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+    /// The whitelisted operators, kept sorted
+    #[pallet::storage]
+    #[pallet::getter(fn operators)]
+    pub type Operators<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        pub operators: Vec<T::AccountId>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self { operators: Default::default() }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            let mut operators = self.operators.clone();
+            operators.sort();
+            operators.dedup();
+            assert!(operators.len() as u32 <= T::MaxOperators::get(), "too many operators");
+            Operators::<T>::put(operators);
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    #[pallet::metadata(T::AccountId = "AccountId")]
+    pub enum Event<T: Config> {
+        /// New values are fed by the operator[operator, values]
+        NewFeedData(T::AccountId, Vec<(CurrencyId, PriceValue)>),
+        /// The operator is added[operator]
+        OperatorAdded(T::AccountId),
+        /// The operator and its raw values are removed[operator]
+        OperatorRemoved(T::AccountId),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The sender is not an operator
+        NoPermission,
+        /// The operator has already fed values in this block
+        AlreadyFeeded,
+        /// The account is already an operator
+        AlreadyOperator,
+        /// The account is not an operator
+        NotOperator,
+        /// The maximum number of operators is reached
+        TooManyOperators,
+        /// More values are fed than `MaxFeedValues`
+        TooManyValues,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            // cleared in `on_finalize`
+            T::DbWeight::get().writes(1)
+        }
+
+        fn on_finalize(_n: T::BlockNumber) {
+            HasDispatched::<T>::kill();
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Feed the prices of the currencies, only once per block for each operator
+        #[pallet::weight(T::DbWeight::get().reads_writes(3, 2 * values.len() as u64 + 1).saturating_add(10_000))]
+        pub fn feed_values(
+            origin: OriginFor<T>,
+            values: Vec<(CurrencyId, PriceValue)>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            Self::do_feed_values(who, values)?;
+            // operators do not pay for feeding
+            Ok(Pays::No.into())
+        }
+
+        /// Add a new operator
+        #[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+        pub fn add_operator(origin: OriginFor<T>, who: T::AccountId) -> DispatchResultWithPostInfo {
+            T::OperatorOrigin::ensure_origin(origin)?;
+
+            Operators::<T>::try_mutate(|operators| -> DispatchResult {
+                let index = match operators.binary_search(&who) {
+                    Ok(_) => return Err(Error::<T>::AlreadyOperator.into()),
+                    Err(index) => index,
+                };
+                if operators.len() as u32 >= T::MaxOperators::get() {
+                    return Err(Error::<T>::TooManyOperators.into());
+                }
+                operators.insert(index, who.clone());
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::OperatorAdded(who));
+            Ok(().into())
+        }
+
+        /// Remove an operator, its raw values are removed and the combined values of the
+        /// currencies it fed are recomputed without them
+        #[pallet::weight(T::DbWeight::get().reads_writes(
+            1 + (T::MaxOperators::get() as u64 + 1) * T::MaxFeedValues::get() as u64,
+            1 + 2 * T::MaxFeedValues::get() as u64,
+        ))]
+        pub fn remove_operator(origin: OriginFor<T>, who: T::AccountId) -> DispatchResultWithPostInfo {
+            T::OperatorOrigin::ensure_origin(origin)?;
+
+            Operators::<T>::try_mutate(|operators| -> DispatchResult {
+                let index = operators.binary_search(&who).map_err(|_| Error::<T>::NotOperator)?;
+                operators.remove(index);
+                Ok(())
+            })?;
+            for (key, _) in RawValues::<T>::drain_prefix(&who) {
+                // the previous value could be the one of the removed operator, do not fall back to it
+                match T::CombineData::combine_data(&key, Self::read_raw_values(&key), None) {
+                    Some(combined) => Values::<T>::insert(key, combined),
+                    None => Values::<T>::remove(key),
+                }
+            }
+
+            Self::deposit_event(Event::OperatorRemoved(who));
+            Ok(().into())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Checks if the account is a whitelisted operator
+        pub fn is_operator(who: &T::AccountId) -> bool {
+            Operators::<T>::get().binary_search(who).is_ok()
+        }
+
+        /// Reads the raw values of all the operators for the currency
+        pub fn read_raw_values(key: &CurrencyId) -> Vec<TimestampedValueOf<T>> {
+            Operators::<T>::get()
+                .iter()
+                .filter_map(|who| RawValues::<T>::get(who, key))
+                .collect()
+        }
+
+        /// Checks if the combined value is still valid at the current block
+        pub fn is_fresh(value: &TimestampedValueOf<T>) -> bool {
+            let now = <frame_system::Pallet<T>>::block_number();
+            value.timestamp.saturating_add(T::ExpiresIn::get()) >= now
+        }
+
+        /// Combines the raw values of the currency, without updating the stored value
+        fn combined(key: &CurrencyId) -> Option<TimestampedValueOf<T>> {
+            let values = Self::read_raw_values(key);
+            T::CombineData::combine_data(key, values, Values::<T>::get(key))
+        }
+
+        fn do_feed_values(who: T::AccountId, values: Vec<(CurrencyId, PriceValue)>) -> DispatchResult {
+            if !Self::is_operator(&who) {
+                return Err(Error::<T>::NoPermission.into());
+            }
+            if values.len() as u32 > T::MaxFeedValues::get() {
+                return Err(Error::<T>::TooManyValues.into());
+            }
+
+            HasDispatched::<T>::try_mutate(|dispatched| -> DispatchResult {
+                if dispatched.contains(&who) {
+                    return Err(Error::<T>::AlreadyFeeded.into());
+                }
+                dispatched.push(who.clone());
+                Ok(())
+            })?;
+
+            let now = <frame_system::Pallet<T>>::block_number();
+            for (key, value) in values.iter() {
+                let timestamped = TimestampedValue { value: *value, timestamp: now };
+                RawValues::<T>::insert(&who, key, timestamped);
+
+                // update the combined value right away, so that reads stay cheap
+                match Self::combined(key) {
+                    Some(combined) => Values::<T>::insert(key, combined),
+                    None => Values::<T>::remove(key),
+                }
+
+                T::OnNewData::on_new_data(&who, key, value);
+            }
+
+            Self::deposit_event(Event::NewFeedData(who, values));
+            Ok(())
+        }
+    }
+
+    impl<T: Config> DataProvider<CurrencyId, PriceValue> for Pallet<T> {
+        fn get(key: &CurrencyId) -> Option<PriceValue> {
+            Self::get_no_op(key).map(|v| v.value)
+        }
+    }
+
+    impl<T: Config> DataProviderExtended<CurrencyId, TimestampedValueOf<T>> for Pallet<T> {
+        fn get_no_op(key: &CurrencyId) -> Option<TimestampedValueOf<T>> {
+            Values::<T>::get(key).filter(Self::is_fresh)
+        }
+
+        fn get_all_values() -> Vec<(CurrencyId, Option<TimestampedValueOf<T>>)> {
+            Values::<T>::iter()
+                .map(|(key, value)| (key, Some(value).filter(Self::is_fresh)))
+                .collect()
+        }
+    }
+
+    impl<T: Config> DataFeeder<CurrencyId, PriceValue, T::AccountId> for Pallet<T> {
+        fn feed_value(who: T::AccountId, key: CurrencyId, value: PriceValue) -> DispatchResult {
+            Self::do_feed_values(who, sp_std::vec![(key, value)])
+        }
+    }
+
+    impl<T: Config> PriceProvider<T> for Pallet<T> {
+        type CurrencyId = CurrencyId;
+
+        fn price(currency_id: Self::CurrencyId) -> Price<T> {
+            match Self::get_no_op(&currency_id) {
+                Some(v) => Price::new(v.value, v.timestamp),
+                None => Price::invalid_price(),
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Mocks for the operator oracle module.
+
+#![cfg(test)]
+
+use frame_support::{construct_runtime, parameter_types};
+use frame_support::traits::{GenesisBuild, OnFinalize};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{testing::Header, traits::IdentityLookup};
+
+use polkadot_parachain_primitives::{Basic, CurrencyId};
+
+use crate as pallet_operator_oracle;
+use crate::{Config, DefaultCombineData};
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+pub type AccountId = u64;
+impl frame_system::Config for Runtime {
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = ::sp_runtime::traits::BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type DbWeight = ();
+    type BaseCallFilter = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const MinimumCount: u32 = 3;
+	pub const ExpiresIn: u64 = 10;
+	pub const MaxOperators: u32 = 4;
+	pub const MaxFeedValues: u32 = 2;
+}
+
+impl Config for Runtime {
+    type Event = Event;
+    type OnNewData = ();
+    type CombineData = DefaultCombineData<Runtime, MinimumCount, ExpiresIn>;
+    type OperatorOrigin = EnsureRoot<AccountId>;
+    type MaxOperators = MaxOperators;
+    type MaxFeedValues = MaxFeedValues;
+    type ExpiresIn = ExpiresIn;
+}
+
+// Runtime construction
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+construct_runtime!(
+	pub enum Runtime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Storage, Config, Event<T>},
+		OperatorOracle: pallet_operator_oracle::{Pallet, Call, Storage, Config<T>, Event<T>},
+	}
+);
+
+pub const OPERATOR_1: AccountId = 1;
+pub const OPERATOR_2: AccountId = 2;
+pub const OPERATOR_3: AccountId = 3;
+pub const OTHER: AccountId = 10;
+
+pub const BTC: CurrencyId = CurrencyId::Basic(Basic { id: 0 });
+pub const ETH: CurrencyId = CurrencyId::Basic(Basic { id: 1 });
+
+pub struct ExtBuilder {
+    operators: Vec<AccountId>,
+}
+
+impl Default for ExtBuilder {
+    fn default() -> Self {
+        Self {
+            operators: vec![OPERATOR_1, OPERATOR_2, OPERATOR_3],
+        }
+    }
+}
+
+impl ExtBuilder {
+    pub fn build(self) -> sp_io::TestExternalities {
+        let mut t = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+
+        pallet_operator_oracle::GenesisConfig::<Runtime> {
+            operators: self.operators,
+        }
+            .assimilate_storage(&mut t)
+            .unwrap();
+
+        let mut ext = sp_io::TestExternalities::new(t);
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+}
+
+/// Moves to the next block, so that the operators can feed again
+pub fn next_block() {
+    let n = System::block_number();
+    OperatorOracle::on_finalize(n);
+    System::set_block_number(n + 1);
+}
@@ -0,0 +1,165 @@
+// This file includes the tests for the operator oracle module
+#![cfg(test)]
+
+use crate::mock::{*};
+use crate::Error;
+use frame_support::{assert_noop, assert_ok};
+use frame_support::error::BadOrigin;
+use orml_traits::{DataFeeder, DataProvider};
+use pallet_traits::PriceProvider;
+use polkadot_parachain_primitives::PriceValue;
+
+fn feed_all(values: [u128; 3]) {
+    assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_1), vec![(BTC, PriceValue::from(values[0]))]));
+    assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_2), vec![(BTC, PriceValue::from(values[1]))]));
+    assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_3), vec![(BTC, PriceValue::from(values[2]))]));
+}
+
+#[test]
+fn operator_oracle_feed_values_not_operator() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_noop!(
+                OperatorOracle::feed_values(Origin::signed(OTHER), vec![(BTC, PriceValue::from(1))]),
+                Error::<Runtime>::NoPermission
+            );
+        });
+}
+
+#[test]
+fn operator_oracle_feed_values_too_many() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let values = vec![(BTC, PriceValue::from(1)), (ETH, PriceValue::from(1))];
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_1), values.clone()));
+
+            next_block();
+            let mut values = values;
+            values.push((BTC, PriceValue::from(2)));
+            assert_noop!(
+                OperatorOracle::feed_values(Origin::signed(OPERATOR_1), values),
+                Error::<Runtime>::TooManyValues
+            );
+        });
+}
+
+#[test]
+fn operator_oracle_feed_values_twice_in_block() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_1), vec![(BTC, PriceValue::from(1))]));
+            assert_noop!(
+                OperatorOracle::feed_values(Origin::signed(OPERATOR_1), vec![(ETH, PriceValue::from(1))]),
+                Error::<Runtime>::AlreadyFeeded
+            );
+
+            next_block();
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_1), vec![(ETH, PriceValue::from(1))]));
+        });
+}
+
+#[test]
+fn operator_oracle_combines_with_median() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_1), vec![(BTC, PriceValue::from(30))]));
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_2), vec![(BTC, PriceValue::from(10))]));
+            // not enough values yet
+            assert_eq!(OperatorOracle::get(&BTC), None);
+
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OPERATOR_3), vec![(BTC, PriceValue::from(20))]));
+            assert_eq!(OperatorOracle::get(&BTC), Some(PriceValue::from(20)));
+            assert_eq!(OperatorOracle::read_raw_values(&BTC).len(), 3);
+        });
+}
+
+#[test]
+fn operator_oracle_remove_operator_recomputes_values() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_ok!(OperatorOracle::add_operator(Origin::root(), OTHER));
+            feed_all([10, 20, 30]);
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OTHER), vec![(BTC, PriceValue::from(40))]));
+            assert_eq!(OperatorOracle::get(&BTC), Some(PriceValue::from(30)));
+
+            // the median is taken again without the removed operator
+            assert_ok!(OperatorOracle::remove_operator(Origin::root(), OPERATOR_3));
+            assert_eq!(OperatorOracle::get(&BTC), Some(PriceValue::from(20)));
+
+            // not enough raw values left, the value of the removed operator is not kept
+            assert_ok!(OperatorOracle::remove_operator(Origin::root(), OTHER));
+            assert_eq!(OperatorOracle::get(&BTC), None);
+            assert_eq!(OperatorOracle::values(BTC), None);
+        });
+}
+
+#[test]
+fn operator_oracle_price_provider() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert!(!OperatorOracle::price(BTC).price_ready());
+
+            feed_all([10, 20, 30]);
+            let price = OperatorOracle::price(BTC);
+            assert!(price.price_ready());
+            assert_eq!(price.value(), PriceValue::from(20));
+
+            System::set_block_number(12);
+            assert!(!OperatorOracle::price(BTC).price_ready());
+        });
+}
+
+#[test]
+fn operator_oracle_add_operator() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            assert_noop!(
+                OperatorOracle::add_operator(Origin::signed(OPERATOR_1), OTHER),
+                BadOrigin
+            );
+            assert_noop!(
+                OperatorOracle::add_operator(Origin::root(), OPERATOR_1),
+                Error::<Runtime>::AlreadyOperator
+            );
+
+            assert!(!OperatorOracle::is_operator(&OTHER));
+            assert_ok!(OperatorOracle::add_operator(Origin::root(), OTHER));
+            assert!(OperatorOracle::is_operator(&OTHER));
+            assert_ok!(OperatorOracle::feed_values(Origin::signed(OTHER), vec![(BTC, PriceValue::from(1))]));
+
+            assert_noop!(
+                OperatorOracle::add_operator(Origin::root(), OTHER + 1),
+                Error::<Runtime>::TooManyOperators
+            );
+        });
+}
+
+#[test]
+fn operator_oracle_remove_operator() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            feed_all([10, 20, 30]);
+
+            assert_noop!(
+                OperatorOracle::remove_operator(Origin::root(), OTHER),
+                Error::<Runtime>::NotOperator
+            );
+            assert_ok!(OperatorOracle::remove_operator(Origin::root(), OPERATOR_3));
+            assert!(!OperatorOracle::is_operator(&OPERATOR_3));
+
+            assert_eq!(OperatorOracle::raw_values(OPERATOR_3, BTC), None);
+            assert_eq!(OperatorOracle::read_raw_values(&BTC).len(), 2);
+            assert_noop!(
+                OperatorOracle::feed_values(Origin::signed(OPERATOR_3), vec![(BTC, PriceValue::from(1))]),
+                Error::<Runtime>::NoPermission
+            );
+        });
+}
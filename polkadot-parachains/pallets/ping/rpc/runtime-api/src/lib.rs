@@ -0,0 +1,37 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for querying the ping statistics kept by `cumulus_ping`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+pub use cumulus_ping::PingSummary;
+pub use cumulus_primitives_core::ParaId;
+
+sp_api::decl_runtime_apis! {
+	pub trait PingApi<BlockNumber> where
+		BlockNumber: Codec,
+	{
+		/// Ping statistics of all siblings that were ever pinged.
+		fn ping_stats() -> Vec<(ParaId, PingSummary<BlockNumber>)>;
+
+		/// Ping statistics of `para`, if it was ever pinged.
+		fn ping_stats_of(para: ParaId) -> Option<PingSummary<BlockNumber>>;
+	}
+}
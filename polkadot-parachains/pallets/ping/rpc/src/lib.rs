@@ -0,0 +1,103 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC interface for querying the ping statistics of siblings, e.g. to alert on degraded HRMP
+//! channels.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use cumulus_ping_rpc_runtime_api::{ParaId, PingApi as PingRuntimeApi, PingSummary};
+
+/// Error code of a failed runtime api call
+const RUNTIME_ERROR: i64 = 1;
+
+#[rpc]
+pub trait PingApi<BlockHash, BlockNumber> {
+	#[rpc(name = "ping_stats")]
+	fn ping_stats(&self, at: Option<BlockHash>) -> Result<Vec<(ParaId, PingSummary<BlockNumber>)>>;
+
+	#[rpc(name = "ping_statsOf")]
+	fn ping_stats_of(
+		&self,
+		para: ParaId,
+		at: Option<BlockHash>,
+	) -> Result<Option<PingSummary<BlockNumber>>>;
+}
+
+/// A struct that implements the `PingApi`.
+pub struct Ping<C, M> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<M>,
+}
+
+impl<C, M> Ping<C, M> {
+	/// Create new `Ping` instance with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Unable to query the ping statistics.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, BlockNumber> PingApi<<Block as BlockT>::Hash, BlockNumber> for Ping<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static,
+	C: ProvideRuntimeApi<Block>,
+	C: HeaderBackend<Block>,
+	C::Api: PingRuntimeApi<Block, BlockNumber>,
+	BlockNumber: Codec,
+{
+	fn ping_stats(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(ParaId, PingSummary<BlockNumber>)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.ping_stats(&at).map_err(runtime_error)
+	}
+
+	fn ping_stats_of(
+		&self,
+		para: ParaId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<PingSummary<BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.ping_stats_of(&at, para).map_err(runtime_error)
+	}
+}
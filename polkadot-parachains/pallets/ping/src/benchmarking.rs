@@ -0,0 +1,115 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarking setup for cumulus-ping
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Ping;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_support::traits::{Get, OnFinalize, OnInitialize};
+use frame_system::RawOrigin;
+
+const PARA: u32 = 2000;
+
+fn payload() -> Vec<u8> {
+	vec![0; 32]
+}
+
+fn fill_targets<T: Config>(count: u32) {
+	Targets::<T>::put((0..count).map(|_| (ParaId::from(PARA), payload())).collect::<Vec<_>>());
+}
+
+/// Fill the stats of `para`, so recording an outcome drops the oldest one.
+fn fill_stats<T: Config>(para: ParaId) {
+	let window = T::StatsWindow::get() as usize;
+	Stats::<T>::insert(para, PingStats { recent: vec![None; window], unknown_pongs: 0 });
+}
+
+benchmarks! {
+	where_clause { where <T as frame_system::Config>::Origin: From<CumulusOrigin> }
+
+	start {
+		fill_targets::<T>(T::MaxTargets::get() - 1);
+	}: _(RawOrigin::Root, PARA.into(), payload())
+	verify {
+		assert_eq!(Targets::<T>::decode_len(), Some(T::MaxTargets::get() as usize));
+	}
+
+	start_many {
+		let c in 1 .. T::MaxTargets::get();
+	}: _(RawOrigin::Root, PARA.into(), c, payload())
+	verify {
+		assert_eq!(Targets::<T>::decode_len(), Some(c as usize));
+	}
+
+	stop {
+		fill_targets::<T>(T::MaxTargets::get());
+	}: _(RawOrigin::Root, PARA.into())
+	verify {
+		assert_eq!(Targets::<T>::decode_len(), Some(T::MaxTargets::get() as usize - 1));
+	}
+
+	stop_all {
+		fill_targets::<T>(T::MaxTargets::get());
+	}: _(RawOrigin::Root, Some(PARA.into()))
+	verify {
+		assert!(Targets::<T>::get().is_empty());
+	}
+
+	ping {
+		let origin: <T as frame_system::Config>::Origin = CumulusOrigin::SiblingParachain(PARA.into()).into();
+	}: _(origin, 1, payload())
+
+	pong {
+		let para = ParaId::from(PARA);
+		Pings::<T>::insert(1, (para, T::BlockNumber::from(1u32)));
+		fill_stats::<T>(para);
+		frame_system::Pallet::<T>::set_block_number(2u32.into());
+		let origin: <T as frame_system::Config>::Origin = CumulusOrigin::SiblingParachain(para).into();
+	}: _(origin, 1, payload())
+	verify {
+		assert!(!Pings::<T>::contains_key(1));
+		assert_eq!(Ping::<T>::stats(para).recent.last(), Some(&Some(1u32.into())));
+	}
+
+	// pings `t` targets and expires `x` pings.
+	on_initialize {
+		let t in 0 .. T::MaxTargets::get();
+		let x in 0 .. T::MaxTargets::get();
+
+		let para = ParaId::from(PARA);
+		fill_targets::<T>(t);
+		fill_stats::<T>(para);
+		let sent_at = T::BlockNumber::from(1u32);
+		for seq in 0..x {
+			Pings::<T>::insert(seq, (para, sent_at));
+		}
+		SentAt::<T>::insert(sent_at, (0..x).collect::<Vec<_>>());
+		PingCount::<T>::put(x);
+		let n = sent_at + T::PingTimeout::get();
+	}: {
+		Ping::<T>::on_initialize(n);
+		Ping::<T>::on_finalize(n);
+	} verify {
+		assert_eq!(PingCount::<T>::get(), x + t);
+		assert!(!SentAt::<T>::contains_key(sent_at));
+		assert!((0..x).all(|seq| !Pings::<T>::contains_key(seq)));
+	}
+}
+
+impl_benchmark_test_suite!(Ping, crate::mock::new_test_ext(), crate::mock::Test,);
@@ -15,17 +15,106 @@
 // along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Pallet to spam the XCM/UMP.
+//!
+//! Besides sending pings to the configured [`Targets`], the pallet keeps rolling statistics per
+//! sibling about the outcome of the last [`Config::StatsWindow`] pings, so degraded channels can be
+//! detected. Pings that are not answered within [`Config::PingTimeout`] blocks count as failures.
+//! The statistics are summarized by [`Pallet::summary`] for the runtime API.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use sp_std::prelude::*;
-use sp_runtime::traits::Saturating;
+use sp_runtime::{Perbill, RuntimeDebug, traits::{CheckedSub, Saturating}};
 use frame_system::Config as SystemConfig;
 use cumulus_primitives_core::ParaId;
 use cumulus_pallet_xcm::{Origin as CumulusOrigin, ensure_sibling_para};
 use xcm::v0::{Xcm, Error as XcmError, SendXcm, OriginKind, MultiLocation, Junction};
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 
 pub use pallet::*;
+pub use weights::WeightInfo;
+
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Storage layout versions of the pallet.
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	/// [`Pings`] only holds the block a ping was sent at.
+	V1,
+	/// [`Pings`] holds the pinged sibling, too.
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
+/// Outcome of the most recent pings to a sibling, oldest first.
+#[derive(Clone, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct PingStats<BlockNumber> {
+	/// The round-trip latency of each ping, `None` if it could not be sent or timed out.
+	pub recent: Vec<Option<BlockNumber>>,
+	/// Number of pongs received for pings that are not in flight.
+	pub unknown_pongs: u32,
+}
+
+/// Summary of the [`PingStats`] of a sibling.
+#[derive(Clone, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct PingSummary<BlockNumber> {
+	/// Number of pings the summary is based on.
+	pub samples: u32,
+	/// Portion of the pings that were answered in time.
+	pub success_rate: Perbill,
+	/// Median round-trip latency in blocks.
+	pub p50_latency: Option<BlockNumber>,
+	/// 95th percentile of the round-trip latency in blocks.
+	pub p95_latency: Option<BlockNumber>,
+	/// Number of pongs received for pings that are not in flight.
+	pub unknown_pongs: u32,
+}
+
+impl<BlockNumber: Ord + Copy> PingStats<BlockNumber> {
+	/// Summarize the stats, using nearest-rank percentiles.
+	pub fn summary(&self) -> PingSummary<BlockNumber> {
+		let mut latencies = self.recent.iter().filter_map(|l| *l).collect::<Vec<_>>();
+		latencies.sort();
+		let percentile = |p: usize| if latencies.is_empty() {
+			None
+		} else {
+			let rank = (p * latencies.len() + 99) / 100;
+			latencies.get(rank.saturating_sub(1)).copied()
+		};
+
+		let samples = self.recent.len() as u32;
+		let success_rate = if samples == 0 {
+			Perbill::zero()
+		} else {
+			Perbill::from_parts((latencies.len() as u64 * 1_000_000_000 / samples as u64) as u32)
+		};
+
+		PingSummary {
+			samples,
+			success_rate,
+			p50_latency: percentile(50),
+			p95_latency: percentile(95),
+			unknown_pongs: self.unknown_pongs,
+		}
+	}
+}
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -49,6 +138,18 @@ pub mod pallet {
 		type Call: From<Call<Self>> + Encode;
 
 		type XcmSender: SendXcm;
+
+		/// Maximum number of entries in [`Targets`].
+		type MaxTargets: Get<u32>;
+
+		/// Number of most recent pings per sibling the statistics are based on.
+		type StatsWindow: Get<u32>;
+
+		/// Number of blocks after which an unanswered ping counts as failed. Must not be zero.
+		type PingTimeout: Get<Self::BlockNumber>;
+
+		/// Weight information for the extrinsics and hooks of this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	/// The target parachains to ping.
@@ -67,16 +168,41 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
-	/// The sent pings.
+	/// The sent pings, with their target and the block they were sent at.
 	#[pallet::storage]
 	pub(super) type Pings<T: Config> = StorageMap<
 		_,
 		Blake2_128Concat,
 		u32,
-		T::BlockNumber,
+		(ParaId, T::BlockNumber),
 		OptionQuery,
 	>;
 
+	/// The sequence numbers of the pings sent at each block, used to expire them.
+	#[pallet::storage]
+	pub(super) type SentAt<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Vec<u32>,
+		ValueQuery,
+	>;
+
+	/// The storage layout version, [`Releases::V1`] until the first runtime upgrade.
+	#[pallet::storage]
+	pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+	/// Rolling statistics of the pings to each sibling.
+	#[pallet::storage]
+	#[pallet::getter(fn stats)]
+	pub(super) type Stats<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		ParaId,
+		PingStats<T::BlockNumber>,
+		ValueQuery,
+	>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	#[pallet::metadata(T::BlockNumber = "BlockNumber")]
@@ -88,64 +214,97 @@ pub mod pallet {
 		ErrorSendingPing(XcmError, ParaId, u32, Vec<u8>),
 		ErrorSendingPong(XcmError, ParaId, u32, Vec<u8>),
 		UnknownPong(ParaId, u32, Vec<u8>),
+		PingTimedOut(ParaId, u32),
 	}
 
 	#[pallet::error]
-	pub enum Error<T> {}
+	pub enum Error<T> {
+		/// [`Config::MaxTargets`] would be exceeded.
+		TooManyTargets,
+	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::<T>::get() == Releases::V2 {
+				return T::DbWeight::get().reads(1)
+			}
+			// The pings in flight don't record their target, so their pongs can't be matched.
+			// Dropping them makes those pongs count as unknown instead of corrupting the stats.
+			Pings::<T>::remove_all();
+			StorageVersion::<T>::put(Releases::V2);
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			let mut expired = 0;
+			if let Some(deadline) = n.checked_sub(&T::PingTimeout::get()) {
+				for seq in SentAt::<T>::take(deadline) {
+					if let Some((para, _)) = Pings::<T>::take(seq) {
+						Self::record(para, None);
+						Self::deposit_event(Event::PingTimedOut(para, seq));
+						expired += 1;
+					}
+				}
+			}
+			// account for the pings sent in `on_finalize`, too.
+			let targets = Targets::<T>::decode_len().unwrap_or_default() as u32;
+			T::WeightInfo::on_initialize(targets, expired)
+		}
+
 		fn on_finalize(
 			n: T::BlockNumber,
 		) {
+			let mut sent = Vec::new();
 			for (para, payload) in Targets::<T>::get().into_iter() {
 				let seq = PingCount::<T>::mutate(|seq| { *seq += 1; *seq });
 				match T::XcmSender::send_xcm(
 					MultiLocation::X2(Junction::Parent, Junction::Parachain(para.into())),
 					Xcm::Transact {
 						origin_type: OriginKind::Native,
-						require_weight_at_most: 1_000,
+						require_weight_at_most: T::WeightInfo::ping(),
 						call: <T as Config>::Call::from(Call::<T>::ping(seq, payload.clone())).encode().into(),
 					},
 				) {
 					Ok(()) => {
-						Pings::<T>::insert(seq, n);
+						Pings::<T>::insert(seq, (para, n));
+						sent.push(seq);
 						Self::deposit_event(Event::PingSent(para, seq, payload));
 					},
 					Err(e) => {
+						Self::record(para, None);
 						Self::deposit_event(Event::ErrorSendingPing(e, para, seq, payload));
 					}
 				}
 			}
+			if !sent.is_empty() {
+				SentAt::<T>::insert(n, sent);
+			}
 		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::start())]
 		pub fn start(origin: OriginFor<T>, para: ParaId, payload: Vec<u8>) -> DispatchResult {
 			ensure_root(origin)?;
-			Targets::<T>::mutate(|t| t.push((para, payload)));
-			Ok(())
+			Self::add_targets(para, 1, payload)
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::start_many((*count).min(T::MaxTargets::get())))]
 		pub fn start_many(origin: OriginFor<T>, para: ParaId, count: u32, payload: Vec<u8>) -> DispatchResult {
 			ensure_root(origin)?;
-			for _ in 0..count {
-				Targets::<T>::mutate(|t| t.push((para, payload.clone())));
-			}
-			Ok(())
+			Self::add_targets(para, count, payload)
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::stop())]
 		pub fn stop(origin: OriginFor<T>, para: ParaId) -> DispatchResult {
 			ensure_root(origin)?;
 			Targets::<T>::mutate(|t| if let Some(p) = t.iter().position(|(p, _)| p == &para) { t.swap_remove(p); });
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::stop_all())]
 		pub fn stop_all(origin: OriginFor<T>, maybe_para: Option<ParaId>) -> DispatchResult {
 			ensure_root(origin)?;
 			if let Some(para) = maybe_para {
@@ -156,7 +315,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::ping())]
 		pub fn ping(origin: OriginFor<T>, seq: u32, payload: Vec<u8>) -> DispatchResult {
 			// Only accept pings from other chains.
 			let para = ensure_sibling_para(<T as Config>::Origin::from(origin))?;
@@ -166,7 +325,7 @@ pub mod pallet {
 				MultiLocation::X2(Junction::Parent, Junction::Parachain(para.into())),
 				Xcm::Transact {
 					origin_type: OriginKind::Native,
-					require_weight_at_most: 1_000,
+					require_weight_at_most: T::WeightInfo::pong(),
 					call: <T as Config>::Call::from(Call::<T>::pong(seq, payload.clone())).encode().into(),
 				},
 			) {
@@ -176,18 +335,62 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::pong())]
 		pub fn pong(origin: OriginFor<T>, seq: u32, payload: Vec<u8>) -> DispatchResult {
 			// Only accept pings from other chains.
 			let para = ensure_sibling_para(<T as Config>::Origin::from(origin))?;
 
-			if let Some(sent_at) = Pings::<T>::take(seq) {
-				Self::deposit_event(Event::Ponged(para, seq, payload, frame_system::Pallet::<T>::block_number().saturating_sub(sent_at)));
-			} else {
-				// Pong received for a ping we apparently didn't send?!
-				Self::deposit_event(Event::UnknownPong(para, seq, payload));
+			match Pings::<T>::get(seq) {
+				Some((target, sent_at)) if target == para => {
+					Pings::<T>::remove(seq);
+					let latency = frame_system::Pallet::<T>::block_number().saturating_sub(sent_at);
+					Self::record(para, Some(latency));
+					Self::deposit_event(Event::Ponged(para, seq, payload, latency));
+				},
+				_ => {
+					// Pong received for a ping we apparently didn't send, or that already timed out?!
+					Stats::<T>::mutate(para, |stats| stats.unknown_pongs = stats.unknown_pongs.saturating_add(1));
+					Self::deposit_event(Event::UnknownPong(para, seq, payload));
+				},
 			}
 			Ok(())
 		}
 	}
+
+	impl<T: Config> Pallet<T> {
+		/// Summary of the ping statistics of `para`, if it was ever pinged.
+		pub fn summary(para: ParaId) -> Option<PingSummary<T::BlockNumber>> {
+			if Stats::<T>::contains_key(para) {
+				Some(Self::stats(para).summary())
+			} else {
+				None
+			}
+		}
+
+		/// Summaries of the ping statistics of all siblings that were ever pinged.
+		pub fn summaries() -> Vec<(ParaId, PingSummary<T::BlockNumber>)> {
+			Stats::<T>::iter().map(|(para, stats)| (para, stats.summary())).collect()
+		}
+
+		fn add_targets(para: ParaId, count: u32, payload: Vec<u8>) -> DispatchResult {
+			Targets::<T>::try_mutate(|t| {
+				let total = (t.len() as u32).saturating_add(count);
+				ensure!(total <= T::MaxTargets::get(), Error::<T>::TooManyTargets);
+				t.extend((0..count).map(|_| (para, payload.clone())));
+				Ok(())
+			})
+		}
+
+		/// Record the outcome of a ping to `para`, dropping the oldest outcomes beyond the window.
+		fn record(para: ParaId, latency: Option<T::BlockNumber>) {
+			Stats::<T>::mutate(para, |stats| {
+				stats.recent.push(latency);
+				let window = T::StatsWindow::get() as usize;
+				if stats.recent.len() > window {
+					let excess = stats.recent.len() - window;
+					stats.recent.drain(..excess);
+				}
+			});
+		}
+	}
 }
@@ -0,0 +1,143 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate as cumulus_ping;
+use cumulus_primitives_core::ParaId;
+use frame_support::{parameter_types, weights::Weight};
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
+use sp_runtime::testing::Header;
+use std::cell::RefCell;
+use xcm::v0::{Error as XcmError, ExecuteXcm, Junction, MultiLocation, Outcome, Result as XcmResult, SendXcm, Xcm};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		CumulusXcm: cumulus_pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
+		Ping: cumulus_ping::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type BlockLength = ();
+	type BlockWeights = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+/// Fails every message, the pallet only dispatches calls from already executed messages.
+pub struct NoExec;
+impl ExecuteXcm<Call> for NoExec {
+	fn execute_xcm_in_credit(
+		_origin: MultiLocation,
+		_message: Xcm<Call>,
+		_weight_limit: Weight,
+		_credit: Weight,
+	) -> Outcome {
+		Outcome::Error(XcmError::Unimplemented)
+	}
+}
+
+impl cumulus_pallet_xcm::Config for Test {
+	type Event = Event;
+	type XcmExecutor = NoExec;
+	type BaseAllowedCalls = ();
+	type ControllerOrigin = frame_system::EnsureRoot<u64>;
+}
+
+/// Messages to this sibling can't be sent.
+pub const UNREACHABLE: u32 = 2002;
+
+std::thread_local! {
+	/// The messages sent through [`TestSender`] with their destination.
+	pub static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+}
+
+/// Records the sent messages and fails to send to [`UNREACHABLE`].
+pub struct TestSender;
+impl SendXcm for TestSender {
+	fn send_xcm(dest: MultiLocation, msg: Xcm<()>) -> XcmResult {
+		if dest == MultiLocation::X2(Junction::Parent, Junction::Parachain(UNREACHABLE)) {
+			return Err(XcmError::Unroutable)
+		}
+		SENT.with(|sent| sent.borrow_mut().push((dest, msg)));
+		Ok(())
+	}
+}
+
+parameter_types! {
+	pub const MaxTargets: u32 = 3;
+	pub const StatsWindow: u32 = 4;
+	pub const PingTimeout: u64 = 5;
+}
+
+impl cumulus_ping::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type Call = Call;
+	type XcmSender = TestSender;
+	type MaxTargets = MaxTargets;
+	type StatsWindow = StatsWindow;
+	type PingTimeout = PingTimeout;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// The origin of a `Transact` from the sibling `para`.
+pub fn sibling(para: u32) -> Origin {
+	cumulus_pallet_xcm::Origin::SiblingParachain(ParaId::from(para)).into()
+}
+
+/// Take the messages sent so far.
+pub fn take_sent() -> Vec<(MultiLocation, Xcm<()>)> {
+	SENT.with(|sent| sent.borrow_mut().drain(..).collect())
+}
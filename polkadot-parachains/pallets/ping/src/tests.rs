@@ -0,0 +1,248 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::mock::{self, new_test_ext, sibling, take_sent, Ping, System, Test, UNREACHABLE};
+use frame_support::{assert_noop, assert_ok, traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade}};
+use sp_runtime::traits::BadOrigin;
+
+fn ping_events() -> Vec<Event<Test>> {
+	System::events()
+		.into_iter()
+		.filter_map(|r| if let mock::Event::Ping(e) = r.event { Some(e) } else { None })
+		.collect()
+}
+
+/// Run the hooks of block `n`.
+fn run_block(n: u64) {
+	System::set_block_number(n);
+	Ping::on_initialize(n);
+	Ping::on_finalize(n);
+}
+
+fn root() -> mock::Origin {
+	mock::Origin::root()
+}
+
+#[test]
+fn empty_stats_summary() {
+	let stats = PingStats::<u32>::default();
+	assert_eq!(
+		stats.summary(),
+		PingSummary {
+			samples: 0,
+			success_rate: Perbill::zero(),
+			p50_latency: None,
+			p95_latency: None,
+			unknown_pongs: 0,
+		},
+	);
+}
+
+#[test]
+fn summary_uses_nearest_rank_percentiles() {
+	let mut recent = (1..=20u32).rev().map(Some).collect::<Vec<_>>();
+	recent.extend(vec![None; 5]);
+	let stats = PingStats { recent, unknown_pongs: 3 };
+
+	assert_eq!(
+		stats.summary(),
+		PingSummary {
+			samples: 25,
+			success_rate: Perbill::from_percent(80),
+			p50_latency: Some(10),
+			p95_latency: Some(19),
+			unknown_pongs: 3,
+		},
+	);
+}
+
+#[test]
+fn single_sample_summary() {
+	let stats = PingStats { recent: vec![Some(4u32)], unknown_pongs: 0 };
+	let summary = stats.summary();
+
+	assert_eq!(summary.success_rate, Perbill::one());
+	assert_eq!(summary.p50_latency, Some(4));
+	assert_eq!(summary.p95_latency, Some(4));
+}
+
+#[test]
+fn only_failures_summary() {
+	let stats = PingStats::<u32> { recent: vec![None, None], unknown_pongs: 0 };
+	let summary = stats.summary();
+
+	assert_eq!(summary.samples, 2);
+	assert_eq!(summary.success_rate, Perbill::zero());
+	assert_eq!(summary.p50_latency, None);
+}
+
+#[test]
+fn targets_are_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Ping::start(mock::Origin::signed(1), 2000.into(), vec![]), BadOrigin);
+
+		assert_ok!(Ping::start_many(root(), 2000.into(), 2, vec![1]));
+		assert_noop!(
+			Ping::start_many(root(), 2001.into(), 2, vec![2]),
+			Error::<Test>::TooManyTargets,
+		);
+		assert_ok!(Ping::start(root(), 2001.into(), vec![2]));
+		assert_noop!(Ping::start(root(), 2001.into(), vec![2]), Error::<Test>::TooManyTargets);
+
+		assert_ok!(Ping::stop(root(), 2000.into()));
+		assert_eq!(Targets::<Test>::get().len(), 2);
+		assert_ok!(Ping::stop_all(root(), Some(2000.into())));
+		assert_eq!(Targets::<Test>::get(), vec![(2001.into(), vec![2])]);
+		assert_ok!(Ping::stop_all(root(), None));
+		assert!(Targets::<Test>::get().is_empty());
+	});
+}
+
+#[test]
+fn pongs_record_the_latency() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Ping::start(root(), 2000.into(), b"hi".to_vec()));
+		run_block(1);
+
+		let sent = take_sent();
+		assert_eq!(sent.len(), 1);
+		assert_eq!(sent[0].0, MultiLocation::X2(Junction::Parent, Junction::Parachain(2000)));
+		assert_eq!(Pings::<Test>::get(1), Some((2000.into(), 1)));
+		assert_eq!(SentAt::<Test>::get(1), vec![1]);
+
+		// only siblings can answer.
+		assert_noop!(Ping::pong(root(), 1, b"hi".to_vec()), BadOrigin);
+
+		System::set_block_number(3);
+		assert_ok!(Ping::pong(sibling(2000), 1, b"hi".to_vec()));
+		assert_eq!(Pings::<Test>::get(1), None);
+		assert_eq!(Ping::stats(ParaId::from(2000)).recent, vec![Some(2)]);
+		assert_eq!(ping_events().last(), Some(&Event::Ponged(2000.into(), 1, b"hi".to_vec(), 2)));
+	});
+}
+
+#[test]
+fn unknown_pongs_are_counted() {
+	new_test_ext().execute_with(|| {
+		// never sent.
+		assert_ok!(Ping::pong(sibling(2000), 7, vec![]));
+		assert_eq!(Ping::stats(ParaId::from(2000)).unknown_pongs, 1);
+		assert_eq!(ping_events().last(), Some(&Event::UnknownPong(2000.into(), 7, vec![])));
+
+		// sent to another sibling.
+		assert_ok!(Ping::start(root(), 2000.into(), vec![]));
+		run_block(1);
+		assert_ok!(Ping::pong(sibling(2001), 1, vec![]));
+		assert_eq!(Ping::stats(ParaId::from(2001)).unknown_pongs, 1);
+		assert!(Pings::<Test>::contains_key(1));
+		assert!(Ping::stats(ParaId::from(2000)).recent.is_empty());
+	});
+}
+
+#[test]
+fn unanswered_pings_time_out() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Ping::start(root(), 2000.into(), vec![]));
+		run_block(1);
+		assert_ok!(Ping::stop_all(root(), None));
+
+		// still in flight before the timeout.
+		run_block(5);
+		assert!(Pings::<Test>::contains_key(1));
+
+		run_block(6);
+		assert_eq!(Pings::<Test>::get(1), None);
+		assert!(SentAt::<Test>::get(1).is_empty());
+		assert_eq!(Ping::stats(ParaId::from(2000)).recent, vec![None]);
+		assert_eq!(ping_events().last(), Some(&Event::PingTimedOut(2000.into(), 1)));
+
+		// a late pong is unknown and does not count as success.
+		assert_ok!(Ping::pong(sibling(2000), 1, vec![]));
+		let summary = Ping::summary(2000.into()).unwrap();
+		assert_eq!(summary.success_rate, Perbill::zero());
+		assert_eq!(summary.unknown_pongs, 1);
+	});
+}
+
+#[test]
+fn failed_sends_count_as_failures() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Ping::start(root(), UNREACHABLE.into(), vec![]));
+		run_block(1);
+
+		assert!(take_sent().is_empty());
+		assert_eq!(Pings::<Test>::get(1), None);
+		assert_eq!(Ping::stats(ParaId::from(UNREACHABLE)).recent, vec![None]);
+		assert_eq!(
+			ping_events().last(),
+			Some(&Event::ErrorSendingPing(XcmError::Unroutable, UNREACHABLE.into(), 1, vec![])),
+		);
+	});
+}
+
+#[test]
+fn stats_keep_the_most_recent_pings() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Ping::start(root(), 2000.into(), vec![]));
+		for n in 1..=6 {
+			run_block(n);
+			assert_ok!(Ping::pong(sibling(2000), n as u32, vec![]));
+		}
+
+		assert_eq!(Ping::stats(ParaId::from(2000)).recent, vec![Some(0); 4]);
+		assert_eq!(Ping::summary(2000.into()).unwrap().samples, 4);
+		assert_eq!(Ping::summary(2001.into()), None);
+	});
+}
+
+#[test]
+fn pings_are_answered() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Ping::ping(root(), 1, vec![]), BadOrigin);
+
+		assert_ok!(Ping::ping(sibling(2000), 3, b"x".to_vec()));
+		let sent = take_sent();
+		assert_eq!(sent.len(), 1);
+		assert_eq!(sent[0].0, MultiLocation::X2(Junction::Parent, Junction::Parachain(2000)));
+		assert_eq!(ping_events().last(), Some(&Event::PongSent(2000.into(), 3, b"x".to_vec())));
+
+		assert_ok!(Ping::ping(sibling(UNREACHABLE), 4, vec![]));
+		assert_eq!(
+			ping_events().last(),
+			Some(&Event::ErrorSendingPong(XcmError::Unroutable, UNREACHABLE.into(), 4, vec![])),
+		);
+	});
+}
+
+#[test]
+fn upgrade_clears_the_pings_of_the_old_layout() {
+	new_test_ext().execute_with(|| {
+		// the old layout only held the block the ping was sent at.
+		let key = Pings::<Test>::hashed_key_for(1);
+		sp_io::storage::set(&key, &5u64.encode());
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V1);
+
+		Ping::on_runtime_upgrade();
+		assert_eq!(sp_io::storage::get(&key), None);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V2);
+
+		// later upgrades keep the pings in flight.
+		Pings::<Test>::insert(2, (ParaId::from(2000), 5));
+		Ping::on_runtime_upgrade();
+		assert!(Pings::<Test>::contains_key(2));
+	});
+}
@@ -7,7 +7,10 @@ use frame_support::sp_runtime::traits::AtLeast32BitUnsigned;
 use sp_std::fmt::Debug;
 use frame_support::traits::MaxEncodedLen;
 pub use frame_support::traits::{Imbalance, Currency as BasicCurrency};
-use codec::FullCodec;
+use codec::{Decode, Encode, FullCodec};
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+use frame_support::RuntimeDebug;
 
 /// A trait to provide the price for a currency
 pub trait PriceProvider<T> where T: Config {
@@ -146,3 +149,56 @@ pub trait CrossChainTransfer<AccountId> {
 		amount: Self::Balance
 	) -> DispatchResult;
 }
+
+/// The storage layout version of a pallet, used to decide which migrations to run on a runtime
+/// upgrade
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+	V1_0_0,
+	V2_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+/// A pallet that stores the [`Releases`] of its storage layout, the version is written at genesis
+/// and by [`StorageVersioned::upgrade_storage`]
+pub trait StorageVersioned<T> where T: Config {
+	/// The release of the current storage layout
+	const LATEST: Releases;
+
+	/// The stored release, `None` if the storage predates the stored version
+	fn storage_version() -> Option<Releases>;
+
+	/// Stores the release of the storage layout
+	fn put_storage_version(version: Releases);
+
+	/// Migrates the storage from the release `from` to [`StorageVersioned::LATEST`], returns the
+	/// consumed weight
+	fn migrate(_from: Option<Releases>) -> Weight {
+		0
+	}
+
+	/// Migrates the storage if it is not at the latest release and stores the latest release
+	fn upgrade_storage() -> Weight {
+		let version = Self::storage_version();
+		if version == Some(Self::LATEST) {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let weight = Self::migrate(version);
+		Self::put_storage_version(Self::LATEST);
+		weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+	}
+
+	/// Checks that the storage is at the latest release, used by the `post_upgrade` checks
+	fn ensure_latest_storage_version() -> Result<(), &'static str> {
+		if Self::storage_version() != Some(Self::LATEST) {
+			return Err("storage version is not the latest");
+		}
+		Ok(())
+	}
+}
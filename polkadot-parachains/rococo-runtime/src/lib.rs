@@ -18,6 +18,7 @@
 // `construct_runtime!` does a lot of recursion and requires us to increase the limit to 256.
 #![recursion_limit = "256"]
 
+pub mod priority_lane;
 mod weights;
 
 // Make the WASM binary available.
@@ -26,7 +27,7 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use sp_api::impl_runtime_apis;
 use sp_core::OpaqueMetadata;
-use sp_runtime::{create_runtime_str, generic, impl_opaque_keys, traits::{AccountIdLookup, BlakeTwo256, Block as BlockT}, transaction_validity::{TransactionSource, TransactionValidity}, ApplyExtrinsicResult, FixedU128, FixedPointNumber};
+use sp_runtime::{create_runtime_str, generic, impl_opaque_keys, traits::{AccountIdLookup, BlakeTwo256, Block as BlockT}, transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity}, ApplyExtrinsicResult, FixedU128, FixedPointNumber};
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
@@ -53,7 +54,7 @@ pub use sp_runtime::{Perbill, Permill};
 // XCM imports
 use pallet_xcm::{EnsureXcm, IsMajorityOfBody, XcmPassthrough};
 use polkadot_parachain::primitives::Sibling;
-use xcm::v0::{BodyId, Junction::*, MultiAsset, MultiLocation, MultiLocation::*, NetworkId, Xcm};
+use xcm::v0::{BodyId, Junction::*, MultiAsset, MultiLocation, MultiLocation::*, NetworkId, OriginKind, Xcm};
 use xcm_builder::{
 	AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, CurrencyAdapter,
 	EnsureXcmOrigin, FixedWeightBounds, LocationInverter,
@@ -78,14 +79,20 @@ pub use orml_tokens;
 use pallet_chainlink_oracle;
 pub use pallet_chainlink_feed::RoundId;
 use weights::chainlink::WeightInfo as ChainlinkWeightInfo;
+use pallet_chainlink_oracle_rpc_runtime_api::{
+	FeedConfigInfo as ChainlinkFeedConfigInfo,
+	OracleStatusInfo as ChainlinkOracleStatusInfo,
+	RoundInfo as ChainlinkRoundInfo,
+	RoundRange as ChainlinkRoundRange,
+	MAX_ROUNDS_PER_QUERY,
+};
 
 use sp_runtime::{traits::{Convert, AccountIdConversion, Zero}};
 use frame_support::{PalletId};
 use orml_traits::GetByKey;
-use sp_std::convert::TryInto;
 use sp_runtime::traits::{CheckedConversion};
 use sp_core::sp_std::convert::TryFrom;
-use frame_support::traits::{Get, Contains};
+use frame_support::traits::{Get, Contains, PalletInfo as _};
 use pallet_xcm_support::{XCMCurrencyAdapter};
 use pallet_xcm_token;
 use xcm_executor::traits::{MatchesFungible, Convert as XCMConvert, ShouldExecute, FilterAssetLocation};
@@ -110,10 +117,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("test-parachain"),
 	impl_name: create_runtime_str!("test-parachain"),
 	authoring_version: 1,
-	spec_version: 14,
+	spec_version: 16,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -178,6 +185,13 @@ parameter_types! {
 	pub const SS58Prefix: u8 = 42;
 }
 
+parameter_types! {
+	/// Share of the `Normal` block weight that is reserved for liquidations and oracle submissions.
+	pub const PriorityLaneReservedRatio: Perbill = Perbill::from_percent(20);
+	/// Transaction pool priority added to liquidations and oracle submissions.
+	pub const PriorityLaneBoost: TransactionPriority = TransactionPriority::max_value() / 2;
+}
+
 impl frame_system::Config for Runtime {
 	/// The identifier used to distinguish between accounts.
 	type AccountId = AccountId;
@@ -267,6 +281,7 @@ impl pallet_sudo::Config for Runtime {
 parameter_types! {
 	pub const ReservedXcmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
 	pub const ReservedDmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
+	pub const UpgradeCooldown: BlockNumber = 6 * HOURS;
 }
 
 impl cumulus_pallet_parachain_system::Config for Runtime {
@@ -278,13 +293,17 @@ impl cumulus_pallet_parachain_system::Config for Runtime {
 	type ReservedDmpWeight = ReservedDmpWeight;
 	type XcmpMessageHandler = XcmpQueue;
 	type ReservedXcmpWeight = ReservedXcmpWeight;
+	type RelayStateReader = RelayRandomness;
+	type UpgradeCooldown = UpgradeCooldown;
 }
 
 impl parachain_info::Config for Runtime {}
 
-impl cumulus_pallet_aura_ext::Config for Runtime {}
+impl cumulus_pallet_relay_randomness::Config for Runtime {
+	type Event = Event;
+}
 
-impl pallet_randomness_collective_flip::Config for Runtime {}
+impl cumulus_pallet_aura_ext::Config for Runtime {}
 
 parameter_types! {
 	pub const RocLocation: MultiLocation = X1(Parent);
@@ -358,13 +377,26 @@ match_type! {
 	};
 }
 
-pub type Barrier = (
+/// Only the `Transact` calls allowed by `CumulusXcm` pass, whatever the origin.
+pub type Barrier = cumulus_pallet_xcm::AllowedTransacts<Runtime, (
 	TakeWeightCredit,
 	AllowTopLevelPaidExecutionFrom<All<MultiLocation>>,
 	AllowUnpaidExecutionFrom<ParentOrParentsUnitPlurality>,
 	KonomiAllowedExecution,
 	// ^^^ Parent & its unit plurality gets free execution
-);
+)>;
+
+/// The XCM executor, recording every `Transact` in `CumulusXcm` events.
+pub type AuditedXcmExecutor = cumulus_pallet_xcm::AuditedXcmExecutor<Runtime, XcmExecutor<XcmConfig>>;
+
+/// Siblings may always ping and pong, the rest is allowed through `CumulusXcm::allow_call`.
+pub struct SpambotCalls;
+impl Contains<(OriginKind, u8, u8)> for SpambotCalls {
+	fn contains(&(origin_type, pallet_index, _): &(OriginKind, u8, u8)) -> bool {
+		origin_type == OriginKind::Native &&
+			<Runtime as frame_system::Config>::PalletInfo::index::<Spambot>() == Some(pallet_index as usize)
+	}
+}
 
 
 pub struct XcmConfig;
@@ -401,7 +433,7 @@ impl pallet_xcm::Config for Runtime {
 	type XcmRouter = XcmRouter;
 	type ExecuteXcmOrigin = EnsureXcmOrigin<Origin, LocalOriginToLocation>;
 	type XcmExecuteFilter = All<(MultiLocation, Xcm<Call>)>;
-	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmExecutor = AuditedXcmExecutor;
 	type XcmTeleportFilter = All<(MultiLocation, Vec<MultiAsset>)>;
 	type XcmReserveTransferFilter = ();
 	type Weigher = FixedWeightBounds<UnitWeightCost, Call>;
@@ -409,26 +441,40 @@ impl pallet_xcm::Config for Runtime {
 
 impl cumulus_pallet_xcm::Config for Runtime {
 	type Event = Event;
-	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmExecutor = AuditedXcmExecutor;
+	type BaseAllowedCalls = SpambotCalls;
+	type ControllerOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 impl cumulus_pallet_xcmp_queue::Config for Runtime {
 	type Event = Event;
-	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmExecutor = AuditedXcmExecutor;
 	type ChannelInfo = ParachainSystem;
+	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type ControllerOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 impl cumulus_pallet_dmp_queue::Config for Runtime {
 	type Event = Event;
-	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmExecutor = AuditedXcmExecutor;
 	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
+parameter_types! {
+	pub const MaxPingTargets: u32 = 100;
+	pub const PingStatsWindow: u32 = 100;
+	pub const PingTimeout: BlockNumber = 10;
+}
+
 impl cumulus_ping::Config for Runtime {
 	type Event = Event;
 	type Origin = Origin;
 	type Call = Call;
 	type XcmSender = XcmRouter;
+	type MaxTargets = MaxPingTargets;
+	type StatsWindow = PingStatsWindow;
+	type PingTimeout = PingTimeout;
+	type WeightInfo = cumulus_ping::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
@@ -652,13 +698,6 @@ impl Convert<Balance, FixedU128> for Conversion {
 	}
 }
 
-impl Convert<FixedU128, Balance> for Conversion {
-	fn convert(a: FixedU128) -> Balance {
-		let accuracy = FixedU128::accuracy();
-		(a.into_inner() / (accuracy / BALANCE_ONE)).try_into().unwrap()
-	}
-}
-
 impl pallet_floating_rate_lend::Config for Runtime {
 	type Event = Event;
 	type Currency = Currencies;
@@ -713,6 +752,23 @@ impl pallet_chainlink_oracle::Config for Runtime {
 	type CurrencyFeedConvertor = CurrencyToFeedIdConverter;
 }
 
+parameter_types! {
+	pub const OracleMinimumCount: u32 = 1;
+	pub const OracleExpiresIn: BlockNumber = 10 * MINUTES;
+	pub const OracleMaxOperators: u32 = 16;
+	pub const OracleMaxFeedValues: u32 = 32;
+}
+
+impl pallet_operator_oracle::Config for Runtime {
+	type Event = Event;
+	type OnNewData = ();
+	type CombineData = pallet_operator_oracle::DefaultCombineData<Runtime, OracleMinimumCount, OracleExpiresIn>;
+	type OperatorOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxOperators = OracleMaxOperators;
+	type MaxFeedValues = OracleMaxFeedValues;
+	type ExpiresIn = OracleExpiresIn;
+}
+
 pub struct XCMAssetConverter;
 impl Convert<(CurrencyId, Balance), Option<MultiAsset>> for XCMAssetConverter {
 	fn convert(a: (CurrencyId, u128)) -> Option<MultiAsset> {
@@ -754,7 +810,7 @@ impl Convert<ParachainId, Option<MultiLocation>> for XCMDestinationConverter {
 }
 
 impl pallet_xcm_token::Config for Runtime {
-	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmExecutor = AuditedXcmExecutor;
 	type Weigher = FixedWeightBounds<UnitWeightCost, Call>;
 	type XCMAssetConverter = XCMAssetConverter;
 	type XCMSelfLocConverter = XCMSelfLocConverter;
@@ -773,11 +829,11 @@ construct_runtime! {
 		System: frame_system::{Pallet, Call, Storage, Config, Event<T>},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		Sudo: pallet_sudo::{Pallet, Call, Storage, Config<T>, Event<T>},
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Call, Storage},
-		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
+		TransactionPayment: pallet_transaction_payment::{Pallet, Storage} = 4,
 
 		ParachainSystem: cumulus_pallet_parachain_system::{Pallet, Call, Config, Storage, Inherent, Event<T>, ValidateUnsigned} = 20,
 		ParachainInfo: parachain_info::{Pallet, Storage, Config} = 21,
+		RelayRandomness: cumulus_pallet_relay_randomness::{Pallet, Storage, Event<T>} = 22,
 
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 30,
 		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>} = 31,
@@ -787,15 +843,16 @@ construct_runtime! {
 
 		CrossTokens: pallet_xcm_token::{Pallet, Storage},
 		Tokens: orml_tokens::{Pallet, Storage, Config<T>, Event<T>} = 11,
-		Currencies: pallet_currencies::{Pallet, Call, Storage, Event<T>},
-		Oracle: pallet_chainlink_oracle::{Pallet, Call, Storage},
+		Currencies: pallet_currencies::{Pallet, Call, Storage, Config, Event<T>},
+		Oracle: pallet_chainlink_oracle::{Pallet, Call, Storage, Config},
 		ChainlinkFeed: pallet_chainlink_feed::{Pallet, Call, Storage, Config<T>, Event<T>} = 55,
+		OperatorOracle: pallet_operator_oracle::{Pallet, Call, Storage, Config<T>, Event<T>} = 56,
 		FloatingRateLend: pallet_floating_rate_lend::{Pallet, Call, Storage, Config<T>, Event<T>} = 15,
 
 		// XCM helpers.
 		XcmpQueue: cumulus_pallet_xcmp_queue::{Pallet, Call, Storage, Event<T>} = 50,
 		PolkadotXcm: pallet_xcm::{Pallet, Call, Event<T>, Origin} = 51,
-		CumulusXcm: cumulus_pallet_xcm::{Pallet, Call, Event<T>, Origin} = 52,
+		CumulusXcm: cumulus_pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin} = 52,
 		DmpQueue: cumulus_pallet_dmp_queue::{Pallet, Call, Storage, Event<T>} = 53,
 
 		Spambot: cumulus_ping::{Pallet, Call, Storage, Event<T>} = 99,
@@ -831,6 +888,7 @@ pub type SignedExtra = (
 	frame_system::CheckGenesis<Runtime>,
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
+	priority_lane::CheckPriorityLane,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
 );
@@ -845,8 +903,20 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPallets,
+	RemoveRandomnessCollectiveFlip,
 >;
 
+/// Clears the storage of `pallet_randomness_collective_flip`, which was replaced by
+/// `RelayRandomness`.
+pub struct RemoveRandomnessCollectiveFlip;
+
+impl frame_support::traits::OnRuntimeUpgrade for RemoveRandomnessCollectiveFlip {
+	fn on_runtime_upgrade() -> Weight {
+		frame_support::storage::migration::remove_storage_prefix(b"RandomnessCollectiveFlip", b"RandomMaterial", b"");
+		RocksDbWeight::get().writes(1)
+	}
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -931,6 +1001,87 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_primitives_core::CollectRelayStateKeys<Block> for Runtime {
+		fn relay_state_keys() -> Vec<Vec<u8>> {
+			ParachainSystem::relay_state_keys()
+		}
+	}
+
+	impl cumulus_primitives_core::UpgradeStatusApi<Block> for Runtime {
+		fn upgrade_status() -> cumulus_primitives_core::UpgradeStatus<Hash, BlockNumber> {
+			ParachainSystem::upgrade_status()
+		}
+	}
+
+	impl pallet_chainlink_oracle_rpc_runtime_api::ChainlinkFeedApi<Block, FeedId, AccountId, Balance, BlockNumber, Value> for Runtime {
+		fn feed_ids() -> Vec<FeedId> {
+			ChainlinkFeed::feed_ids()
+		}
+
+		fn feed_config(feed_id: FeedId) -> Option<ChainlinkFeedConfigInfo<AccountId, Balance, BlockNumber, Value>> {
+			ChainlinkFeed::feed_config(feed_id).map(Into::into)
+		}
+
+		fn oracle_statuses(feed_id: FeedId) -> Vec<ChainlinkOracleStatusInfo<AccountId, Value>> {
+			ChainlinkFeed::oracle_statuses(feed_id).into_iter().map(Into::into).collect()
+		}
+
+		fn round_range(feed_id: FeedId) -> Option<ChainlinkRoundRange> {
+			ChainlinkFeed::round_range(feed_id).map(|(first, last)| ChainlinkRoundRange { first, last })
+		}
+
+		fn rounds(feed_id: FeedId, from: RoundId, to: RoundId) -> Vec<ChainlinkRoundInfo<BlockNumber, Value>> {
+			let to = to.min(from.saturating_add(MAX_ROUNDS_PER_QUERY - 1));
+			ChainlinkFeed::rounds_in(feed_id, from, to).into_iter().map(Into::into).collect()
+		}
+	}
+
+	impl cumulus_ping_rpc_runtime_api::PingApi<Block, BlockNumber> for Runtime {
+		fn ping_stats() -> Vec<(ParaId, cumulus_ping::PingSummary<BlockNumber>)> {
+			Spambot::summaries()
+		}
+
+		fn ping_stats_of(para: ParaId) -> Option<cumulus_ping::PingSummary<BlockNumber>> {
+			Spambot::summary(para)
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	impl frame_benchmarking::Benchmark<Block> for Runtime {
+		fn dispatch_benchmark(
+			config: frame_benchmarking::BenchmarkConfig
+		) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
+			use frame_benchmarking::{Benchmarking, BenchmarkBatch, add_benchmark, TrackedStorageKey};
+
+			let whitelist: Vec<TrackedStorageKey> = vec![
+				// Block Number
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
+				// Execution Phase
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7ff553b5a9862a516939d82b3d3d8661a").to_vec().into(),
+				// Event Count
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef70a98fdbe9ce6c55837576c60c7af3850").to_vec().into(),
+				// System Events
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7").to_vec().into(),
+			];
+
+			let mut batches = Vec::<BenchmarkBatch>::new();
+			let params = (&config, &whitelist);
+
+			add_benchmark!(params, batches, cumulus_ping, Spambot);
+
+			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
+			Ok(batches)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> Result<(Weight, Weight), sp_runtime::RuntimeString> {
+			let weight = Executive::try_runtime_upgrade()?;
+			Ok((weight, RuntimeBlockWeights::get().max_block))
+		}
+	}
+
 	// impl pallet_floating_rate_lend_rpc_runtime_api::LendingApi<Block, PoolId, FixedU128, AccountId> for Runtime {
     //     fn supply_rate(id: PoolId) -> FixedU128 {
     //         FloatingRateLend::supply_rate(id)
@@ -0,0 +1,305 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Priority lane for the transactions that keep the lending market solvent.
+//!
+//! During market crashes liquidations and oracle price submissions compete with ordinary
+//! transactions for block space. [`CheckPriorityLane`] raises the transaction pool priority of
+//! these calls by [`PriorityLaneBoost`] and keeps [`PriorityLaneReservedRatio`] of the `Normal`
+//! block weight free for them, so they can still be included when a block is full.
+//!
+//! Only eligible calls are accepted into the pool: liquidations of positions that can be
+//! liquidated and price submissions of authorised oracles. Everyone else would otherwise get the
+//! boost and the reserved weight for calls that fail anyway.
+
+use crate::{
+	AccountId, Call, ChainlinkFeed, FloatingRateLend, OperatorOracle, PriorityLaneBoost,
+	PriorityLaneReservedRatio, Runtime, RuntimeBlockWeights,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::Get,
+	weights::{DispatchClass, Weight},
+};
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	RuntimeDebug,
+};
+
+/// Returns `true` if `call` is allowed to use the priority lane.
+pub fn is_priority_call(call: &Call) -> bool {
+	matches!(
+		call,
+		Call::FloatingRateLend(pallet_floating_rate_lend::Call::liquidate(..))
+			| Call::ChainlinkFeed(pallet_chainlink_feed::Call::submit(..))
+			| Call::OperatorOracle(pallet_operator_oracle::Call::feed_values(..))
+	)
+}
+
+/// Returns `true` if `who` may use the priority lane for `call`.
+///
+/// The liquidated position must be liquidatable and price submissions must come from an oracle
+/// of the feed, respectively a whitelisted operator.
+pub fn is_eligible(who: &AccountId, call: &Call) -> bool {
+	match call {
+		Call::FloatingRateLend(pallet_floating_rate_lend::Call::liquidate(target, ..)) =>
+			FloatingRateLend::is_liquidatable(target.clone()),
+		Call::ChainlinkFeed(pallet_chainlink_feed::Call::submit(feed_id, ..)) =>
+			ChainlinkFeed::oracle_status(feed_id, who).is_some(),
+		Call::OperatorOracle(pallet_operator_oracle::Call::feed_values(..)) =>
+			OperatorOracle::is_operator(who),
+		_ => false,
+	}
+}
+
+/// The `Normal` block weight that ordinary transactions are allowed to use.
+///
+/// The remaining [`PriorityLaneReservedRatio`] of the `Normal` block weight is only available to
+/// priority calls.
+pub fn ordinary_weight_limit() -> Weight {
+	let weights = RuntimeBlockWeights::get();
+	let normal = weights
+		.get(DispatchClass::Normal)
+		.max_total
+		.unwrap_or(weights.max_block);
+
+	normal.saturating_sub(PriorityLaneReservedRatio::get() * normal)
+}
+
+/// Signed extension that gives liquidations and oracle submissions precedence over ordinary
+/// transactions.
+///
+/// This needs to come before [`frame_system::CheckWeight`] in the signed extensions, as the
+/// reservation is checked against the block weight consumed before the transaction.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct CheckPriorityLane;
+
+impl CheckPriorityLane {
+	/// Ensure that an ordinary transaction doesn't use the weight reserved for priority calls.
+	fn check_reserved_weight(
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+	) -> Result<(), TransactionValidityError> {
+		if info.class != DispatchClass::Normal || is_priority_call(call) {
+			return Ok(());
+		}
+
+		let base_extrinsic = RuntimeBlockWeights::get()
+			.get(DispatchClass::Normal)
+			.base_extrinsic;
+		let consumed = frame_system::Pallet::<Runtime>::block_weight()
+			.get(DispatchClass::Normal)
+			.saturating_add(info.weight)
+			.saturating_add(base_extrinsic);
+
+		if consumed > ordinary_weight_limit() {
+			Err(InvalidTransaction::ExhaustsResources.into())
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl SignedExtension for CheckPriorityLane {
+	const IDENTIFIER: &'static str = "CheckPriorityLane";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_: &DispatchInfoOf<Self::Call>,
+		_: usize,
+	) -> TransactionValidity {
+		// Like `CheckWeight`, the block weight is only checked when building a block. Ordinary
+		// transactions that don't fit into the current block stay in the pool for the next one.
+		let priority = if is_priority_call(call) {
+			if !is_eligible(who, call) {
+				return Err(InvalidTransaction::Call.into());
+			}
+			PriorityLaneBoost::get()
+		} else {
+			0
+		};
+
+		Ok(ValidTransaction {
+			priority,
+			..Default::default()
+		})
+	}
+
+	fn pre_dispatch(
+		self,
+		_: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		_: usize,
+	) -> Result<(), TransactionValidityError> {
+		Self::check_reserved_weight(call, info)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Address;
+	use frame_support::weights::GetDispatchInfo;
+	use pallet_chainlink_feed::OracleStatus;
+	use sp_runtime::BuildStorage;
+
+	const LEN: usize = 100;
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap()
+			.into();
+		ext.execute_with(|| frame_system::Pallet::<Runtime>::set_block_number(1));
+		ext
+	}
+
+	fn who() -> AccountId {
+		AccountId::from([1; 32])
+	}
+
+	fn transfer() -> Call {
+		Call::Balances(pallet_balances::Call::transfer(
+			Address::Id(AccountId::from([2; 32])),
+			1,
+		))
+	}
+
+	fn liquidate() -> Call {
+		Call::FloatingRateLend(pallet_floating_rate_lend::Call::liquidate(
+			AccountId::from([2; 32]),
+			0,
+			1_000,
+			1,
+		))
+	}
+
+	fn submit() -> Call {
+		Call::ChainlinkFeed(pallet_chainlink_feed::Call::submit(0, 1, 42))
+	}
+
+	fn feed_values() -> Call {
+		Call::OperatorOracle(pallet_operator_oracle::Call::feed_values(vec![]))
+	}
+
+	/// Make [`who`] an oracle of feed 0 and a whitelisted operator.
+	fn authorise_oracle() {
+		pallet_chainlink_feed::OracleStatuses::<Runtime>::insert(0, who(), OracleStatus::default());
+		pallet_operator_oracle::Operators::<Runtime>::put(vec![who()]);
+	}
+
+	fn validate(call: &Call) -> TransactionValidity {
+		CheckPriorityLane.validate(&who(), call, &call.get_dispatch_info(), LEN)
+	}
+
+	/// Fill the `Normal` block weight up to the limit of ordinary transactions.
+	fn fill_ordinary_weight() {
+		frame_system::Pallet::<Runtime>::register_extra_weight_unchecked(
+			ordinary_weight_limit(),
+			DispatchClass::Normal,
+		);
+	}
+
+	fn pre_dispatch(call: &Call) -> Result<(), TransactionValidityError> {
+		let info = call.get_dispatch_info();
+		(CheckPriorityLane, frame_system::CheckWeight::<Runtime>::new())
+			.pre_dispatch(&who(), call, &info, LEN)
+			.map(|_| ())
+	}
+
+	#[test]
+	fn priority_calls_are_boosted() {
+		new_test_ext().execute_with(|| {
+			authorise_oracle();
+			let priority = |call: &Call| validate(call).unwrap().priority;
+
+			assert_eq!(priority(&submit()), PriorityLaneBoost::get());
+			assert_eq!(priority(&feed_values()), PriorityLaneBoost::get());
+			assert_eq!(priority(&transfer()), 0);
+		});
+	}
+
+	#[test]
+	fn ineligible_priority_calls_are_rejected() {
+		new_test_ext().execute_with(|| {
+			let rejected = Err(InvalidTransaction::Call.into());
+
+			// the target has no position at all
+			assert_eq!(validate(&liquidate()), rejected);
+			assert_eq!(validate(&submit()), rejected);
+			assert_eq!(validate(&feed_values()), rejected);
+
+			authorise_oracle();
+			assert!(validate(&submit()).is_ok());
+			assert!(validate(&feed_values()).is_ok());
+			assert_eq!(validate(&liquidate()), rejected);
+		});
+	}
+
+	#[test]
+	fn ordinary_calls_are_included_below_the_reservation() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(pre_dispatch(&transfer()), Ok(()));
+		});
+	}
+
+	#[test]
+	fn priority_calls_are_included_when_blocks_are_full() {
+		new_test_ext().execute_with(|| {
+			fill_ordinary_weight();
+
+			assert_eq!(
+				pre_dispatch(&transfer()),
+				Err(InvalidTransaction::ExhaustsResources.into())
+			);
+			assert_eq!(pre_dispatch(&liquidate()), Ok(()));
+			assert_eq!(pre_dispatch(&submit()), Ok(()));
+		});
+	}
+
+	#[test]
+	fn priority_calls_can_not_exceed_the_normal_block_weight() {
+		new_test_ext().execute_with(|| {
+			let normal = RuntimeBlockWeights::get()
+				.get(DispatchClass::Normal)
+				.max_total
+				.unwrap();
+			frame_system::Pallet::<Runtime>::register_extra_weight_unchecked(
+				normal,
+				DispatchClass::Normal,
+			);
+
+			assert_eq!(
+				pre_dispatch(&liquidate()),
+				Err(InvalidTransaction::ExhaustsResources.into())
+			);
+		});
+	}
+}
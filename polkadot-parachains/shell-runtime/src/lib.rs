@@ -167,6 +167,8 @@ impl cumulus_pallet_parachain_system::Config for Runtime {
 	type ReservedDmpWeight = ReservedDmpWeight;
 	type XcmpMessageHandler = ();
 	type ReservedXcmpWeight = ();
+	type RelayStateReader = ();
+	type UpgradeCooldown = ();
 }
 
 impl parachain_info::Config for Runtime {}
@@ -217,6 +219,8 @@ impl Config for XcmConfig {
 impl cumulus_pallet_xcm::Config for Runtime {
 	type Event = Event;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type BaseAllowedCalls = ();
+	type ControllerOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 construct_runtime! {
@@ -230,7 +234,7 @@ construct_runtime! {
 		ParachainInfo: parachain_info::{Pallet, Storage, Config},
 
 		// DMP handler.
-		CumulusXcm: cumulus_pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
+		CumulusXcm: cumulus_pallet_xcm::{Pallet, Event<T>, Origin},
 	}
 }
 
@@ -367,6 +371,18 @@ impl_runtime_apis! {
 			ParachainSystem::collect_collation_info()
 		}
 	}
+
+	impl cumulus_primitives_core::CollectRelayStateKeys<Block> for Runtime {
+		fn relay_state_keys() -> Vec<Vec<u8>> {
+			ParachainSystem::relay_state_keys()
+		}
+	}
+
+	impl cumulus_primitives_core::UpgradeStatusApi<Block> for Runtime {
+		fn upgrade_status() -> cumulus_primitives_core::UpgradeStatus<Hash, BlockNumber> {
+			ParachainSystem::upgrade_status()
+		}
+	}
 }
 
 struct CheckInherents;
@@ -303,6 +303,11 @@ fn testnet_genesis(
 				),
 			],
 		},
+		operator_oracle: rococo_parachain_runtime::OperatorOracleConfig {
+			operators: vec![root_key.clone()],
+		},
+		currencies: Default::default(),
+		oracle: Default::default(),
 		aura_ext: Default::default(),
 		parachain_system: Default::default(),
 	}
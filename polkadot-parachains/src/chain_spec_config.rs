@@ -0,0 +1,608 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chain specifications of Konomi networks built from a declarative TOML file.
+//!
+//! `build-spec --from-config <file.toml>` reads the genesis of a network from a file like
+//! `res/konomi-local.toml` instead of the values hard-coded in [`crate::chain_spec`]. The whole
+//! file is validated before the chain spec is built and all problems are reported at once.
+//!
+//! Accounts and collator keys are given as SS58 address, hex encoded sr25519 public key or secret
+//! URI like `//Alice`. Balances are given as strings, as TOML integers are limited to 64 bits.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::Path,
+};
+
+use cumulus_primitives_core::ParaId;
+use polkadot_parachain_primitives::{CurrencyId, BTC, DORA, DOT, ETH, KONO, LIT};
+use rococo_parachain_runtime::{
+	AccountId, AuraId, Balance, BlockNumber, CurrencyToFeedIdConverter, FeedId, FeedLimit,
+	OracleCountLimit, StringLimit,
+};
+use sc_chain_spec::Properties;
+use sc_service::ChainType;
+use serde::Deserialize;
+use sp_core::{crypto::Ss58Codec, sr25519, Pair};
+use sp_runtime::{
+	traits::{Convert, Get, IdentifyAccount, Zero},
+	FixedPointNumber, FixedU128, MultiSigner,
+};
+
+use crate::chain_spec::{ChainSpec, Extensions};
+
+/// The currencies that can be referred to by their symbol.
+const CURRENCIES: [(&str, CurrencyId); 6] = [
+	("KONO", KONO),
+	("DOT", DOT),
+	("ETH", ETH),
+	("BTC", BTC),
+	("DORA", DORA),
+	("LIT", LIT),
+];
+
+/// The contents of a chain spec config file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainSpecConfig {
+	/// The human readable name of the network.
+	pub name: String,
+	/// The id of the network, also used as name of its database directory.
+	pub id: String,
+	/// `Development`, `Local` or `Live`.
+	pub chain_type: ChainType,
+	/// The relay chain the parachain is registered on.
+	pub relay_chain: String,
+	/// The id of the parachain.
+	pub para_id: u32,
+	/// The sudo key, also the default owner of the pools and feeds.
+	pub root_key: String,
+	/// The Aura keys of the collators.
+	pub collators: Vec<String>,
+	/// The chain properties, like `tokenSymbol` and `tokenDecimals`.
+	#[serde(default)]
+	pub properties: Option<Properties>,
+	/// The initial balances.
+	#[serde(default)]
+	pub endowments: Vec<EndowmentConfig>,
+	/// The lending pallet genesis.
+	pub lending: LendingConfig,
+	/// The chainlink feed pallet genesis.
+	#[serde(default)]
+	pub chainlink: ChainlinkConfig,
+	/// The operators of the operator oracle. Default is the root key.
+	#[serde(default)]
+	pub operators: Option<Vec<String>>,
+}
+
+/// The initial balances of an account.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndowmentConfig {
+	/// The endowed account.
+	pub account: String,
+	/// The balance of the native currency.
+	#[serde(default)]
+	pub native: Option<String>,
+	/// The balances of the other currencies by their symbol.
+	#[serde(default)]
+	pub tokens: BTreeMap<String, String>,
+}
+
+/// The genesis of the floating rate lending pallet.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LendingConfig {
+	/// The liquidation threshold as decimal number, e.g. `"1.05"`.
+	pub liquidation_threshold: String,
+	/// The pools, listed in the order of their pool ids.
+	#[serde(default)]
+	pub pools: Vec<PoolConfig>,
+}
+
+/// A lending pool.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PoolConfig {
+	/// The unique name of the pool.
+	pub name: String,
+	/// The symbol of the currency of the pool.
+	pub currency: String,
+	/// Whether the supplies to the pool can be used as collateral.
+	#[serde(default)]
+	pub can_be_collateral: bool,
+	/// The owner of the pool. Default is the root key.
+	#[serde(default)]
+	pub owner: Option<String>,
+}
+
+/// The genesis of the chainlink feed pallet.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainlinkConfig {
+	/// The pallet admin. Default is the root key.
+	#[serde(default)]
+	pub pallet_admin: Option<String>,
+	/// The accounts allowed to create feeds. Default is the root key.
+	#[serde(default)]
+	pub feed_creators: Option<Vec<String>>,
+	/// The feeds, listed in the order of their feed ids.
+	#[serde(default)]
+	pub feeds: Vec<FeedConfig>,
+}
+
+/// A chainlink feed.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeedConfig {
+	/// The description of the feed, e.g. `"DOT / USD"`.
+	pub description: String,
+	/// The currency the feed provides the price of, if the runtime reads prices from it.
+	#[serde(default)]
+	pub currency: Option<String>,
+	/// The owner of the feed. Default is the root key.
+	#[serde(default)]
+	pub owner: Option<String>,
+	/// The payment of an oracle for a submission.
+	pub payment: String,
+	/// The number of blocks after that a round times out.
+	pub timeout: BlockNumber,
+	/// The minimum number of submissions for a round to be answered.
+	pub min_submissions: u32,
+	/// The number of decimals of the submitted values.
+	pub decimals: u8,
+	/// The oracles of the feed.
+	pub oracles: Vec<OracleConfig>,
+}
+
+/// An oracle of a chainlink feed.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OracleConfig {
+	/// The account that submits the values.
+	pub account: String,
+	/// The account that manages the oracle. Default is the root key.
+	#[serde(default)]
+	pub admin: Option<String>,
+}
+
+/// A chainlink feed as expected by the genesis config: owner, payment, timeout, minimum number of
+/// submissions, decimals, description and the oracles with their admins.
+type GenesisFeed = (
+	AccountId,
+	Balance,
+	BlockNumber,
+	u32,
+	u8,
+	Vec<u8>,
+	Vec<(AccountId, AccountId)>,
+);
+
+/// The validated genesis values.
+#[derive(Clone)]
+struct KonomiGenesis {
+	root_key: AccountId,
+	authorities: Vec<AuraId>,
+	balances: Vec<(AccountId, Balance)>,
+	tokens: Vec<(AccountId, CurrencyId, Balance)>,
+	liquidation_threshold: FixedU128,
+	pools: Vec<(bool, Vec<u8>, CurrencyId, AccountId)>,
+	pallet_admin: AccountId,
+	feed_creators: Vec<AccountId>,
+	feeds: Vec<GenesisFeed>,
+	operators: Vec<AccountId>,
+	para_id: ParaId,
+}
+
+impl KonomiGenesis {
+	fn genesis_config(&self) -> rococo_parachain_runtime::GenesisConfig {
+		rococo_parachain_runtime::GenesisConfig {
+			system: rococo_parachain_runtime::SystemConfig {
+				code: rococo_parachain_runtime::WASM_BINARY
+					.expect("WASM binary was not build, please build it!")
+					.to_vec(),
+				changes_trie_config: Default::default(),
+			},
+			balances: rococo_parachain_runtime::BalancesConfig {
+				balances: self.balances.clone(),
+			},
+			sudo: rococo_parachain_runtime::SudoConfig {
+				key: self.root_key.clone(),
+			},
+			parachain_info: rococo_parachain_runtime::ParachainInfoConfig {
+				parachain_id: self.para_id,
+			},
+			aura: rococo_parachain_runtime::AuraConfig {
+				authorities: self.authorities.clone(),
+			},
+			tokens: rococo_parachain_runtime::TokensConfig {
+				balances: self.tokens.clone(),
+			},
+			floating_rate_lend: rococo_parachain_runtime::FloatingRateLendConfig {
+				liquidation_threshold: self.liquidation_threshold,
+				pools: self.pools.clone(),
+			},
+			chainlink_feed: rococo_parachain_runtime::ChainlinkFeedConfig {
+				pallet_admin: Some(self.pallet_admin.clone()),
+				feed_creators: self.feed_creators.clone(),
+				feeds: self.feeds.clone(),
+			},
+			operator_oracle: rococo_parachain_runtime::OperatorOracleConfig {
+				operators: self.operators.clone(),
+			},
+			currencies: Default::default(),
+			oracle: Default::default(),
+			aura_ext: Default::default(),
+			parachain_system: Default::default(),
+		}
+	}
+}
+
+/// Collects the problems found while validating a config.
+#[derive(Default)]
+struct Validator {
+	errors: Vec<String>,
+}
+
+impl Validator {
+	fn error(&mut self, field: &str, message: impl std::fmt::Display) {
+		self.errors.push(format!("{}: {}", field, message));
+	}
+
+	fn ensure(&mut self, condition: bool, field: &str, message: impl std::fmt::Display) {
+		if !condition {
+			self.error(field, message);
+		}
+	}
+
+	fn ok<T>(&mut self, field: &str, result: Result<T, String>) -> Option<T> {
+		result.map_err(|e| self.error(field, e)).ok()
+	}
+
+	fn public(&mut self, field: &str, value: &str) -> Option<sr25519::Public> {
+		self.ok(field, parse_public(value))
+	}
+
+	fn account(&mut self, field: &str, value: &str) -> Option<AccountId> {
+		self.public(field, value)
+			.map(|public| MultiSigner::from(public).into_account())
+	}
+
+	/// Parse `value` or fall back to `default` if there is no value.
+	fn account_or(
+		&mut self,
+		field: &str,
+		value: &Option<String>,
+		default: &Option<AccountId>,
+	) -> Option<AccountId> {
+		match value {
+			Some(value) => self.account(field, value),
+			None => default.clone(),
+		}
+	}
+
+	fn balance(&mut self, field: &str, value: &str) -> Option<Balance> {
+		self.ok(field, parse_balance(value))
+	}
+
+	fn currency(&mut self, field: &str, symbol: &str) -> Option<CurrencyId> {
+		self.ok(field, parse_currency(symbol))
+	}
+}
+
+/// Parse an sr25519 public key from an SS58 address, a hex encoded key or a secret URI.
+fn parse_public(value: &str) -> Result<sr25519::Public, String> {
+	if value.starts_with("//") {
+		sr25519::Pair::from_string(value, None)
+			.map(|pair| pair.public())
+			.map_err(|e| format!("invalid secret URI `{}`: {:?}", value, e))
+	} else if value.starts_with("0x") {
+		let bytes = sp_core::bytes::from_hex(value)
+			.map_err(|e| format!("invalid hex key `{}`: {}", value, e))?;
+		if bytes.len() != 32 {
+			return Err(format!("`{}` is not a 32 byte key", value));
+		}
+		let mut raw = [0u8; 32];
+		raw.copy_from_slice(&bytes);
+		Ok(sr25519::Public::from_raw(raw))
+	} else {
+		sr25519::Public::from_ss58check(value)
+			.map_err(|e| format!("invalid SS58 address `{}`: {:?}", value, e))
+	}
+}
+
+/// Parse a balance like `"1000000000000"` or `"1_000_000_000_000"`.
+fn parse_balance(value: &str) -> Result<Balance, String> {
+	value
+		.replace('_', "")
+		.parse()
+		.map_err(|e| format!("invalid balance `{}`: {}", value, e))
+}
+
+/// Parse a decimal number like `"1.05"`.
+fn parse_fixed(value: &str) -> Result<FixedU128, String> {
+	let invalid = || format!("invalid decimal number `{}`", value);
+	let (integer, fraction) = match value.find('.') {
+		Some(dot) => (&value[..dot], &value[dot + 1..]),
+		None => (value, ""),
+	};
+	let accuracy = FixedU128::accuracy();
+	let decimals = accuracy.to_string().len() - 1;
+	if integer.is_empty() || fraction.len() > decimals {
+		return Err(invalid());
+	}
+
+	let integer: u128 = integer.parse().map_err(|_| invalid())?;
+	let fraction: u128 = if fraction.is_empty() {
+		0
+	} else {
+		fraction.parse::<u128>().map_err(|_| invalid())?
+			* 10u128.pow((decimals - fraction.len()) as u32)
+	};
+
+	integer
+		.checked_mul(accuracy)
+		.and_then(|inner| inner.checked_add(fraction))
+		.map(FixedU128::from_inner)
+		.ok_or_else(invalid)
+}
+
+/// The symbol of the currency, if it can be referred to by one.
+pub fn currency_symbol(currency: &CurrencyId) -> Option<&'static str> {
+	CURRENCIES.iter().find(|(_, c)| c == currency).map(|(symbol, _)| *symbol)
+}
+
+fn parse_currency(symbol: &str) -> Result<CurrencyId, String> {
+	CURRENCIES
+		.iter()
+		.find(|(s, _)| *s == symbol)
+		.map(|(_, currency)| *currency)
+		.ok_or_else(|| {
+			let known = CURRENCIES.iter().map(|(s, _)| *s).collect::<Vec<_>>();
+			format!("unknown currency `{}`, expected one of {}", symbol, known.join(", "))
+		})
+}
+
+impl ChainSpecConfig {
+	/// Read the config from the TOML file at `path`.
+	pub fn from_file(path: &Path) -> Result<Self, String> {
+		let content = std::fs::read_to_string(path)
+			.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+		toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+	}
+
+	/// Validate the config and build the chain spec it describes.
+	pub fn build(self) -> Result<ChainSpec, String> {
+		let genesis = self.validate().map_err(|errors| {
+			format!("Invalid chain spec config:\n  {}", errors.join("\n  "))
+		})?;
+
+		Ok(ChainSpec::from_genesis(
+			&self.name,
+			&self.id,
+			self.chain_type.clone(),
+			move || genesis.genesis_config(),
+			Vec::new(),
+			None,
+			None,
+			self.properties.clone(),
+			Extensions {
+				relay_chain: self.relay_chain.clone(),
+				para_id: self.para_id,
+			},
+		))
+	}
+
+	fn validate(&self) -> Result<KonomiGenesis, Vec<String>> {
+		let mut v = Validator::default();
+
+		v.ensure(!self.name.is_empty(), "name", "must not be empty");
+		v.ensure(!self.id.is_empty(), "id", "must not be empty");
+		v.ensure(!self.relay_chain.is_empty(), "relay_chain", "must not be empty");
+
+		let root_key = v.account("root_key", &self.root_key);
+
+		v.ensure(!self.collators.is_empty(), "collators", "at least one collator is required");
+		let mut authorities = Vec::new();
+		for (i, collator) in self.collators.iter().enumerate() {
+			let field = format!("collators[{}]", i);
+			if let Some(public) = v.public(&field, collator) {
+				let key = AuraId::from(public);
+				v.ensure(!authorities.contains(&key), &field, "duplicate collator");
+				authorities.push(key);
+			}
+		}
+
+		let mut balances = Vec::new();
+		let mut tokens = Vec::new();
+		let mut endowed = BTreeSet::new();
+		for (i, endowment) in self.endowments.iter().enumerate() {
+			let field = format!("endowments[{}]", i);
+			let account = match v.account(&format!("{}.account", field), &endowment.account) {
+				Some(account) => account,
+				None => continue,
+			};
+			v.ensure(
+				endowed.insert(account.clone()),
+				&format!("{}.account", field),
+				"account is endowed more than once",
+			);
+
+			if let Some(native) = &endowment.native {
+				if let Some(balance) = v.balance(&format!("{}.native", field), native) {
+					balances.push((account.clone(), balance));
+				}
+			}
+
+			let mut currencies = BTreeSet::new();
+			for (symbol, amount) in &endowment.tokens {
+				let field = format!("{}.tokens.{}", field, symbol);
+				let currency = v.currency(&field, symbol);
+				let balance = v.balance(&field, amount);
+				if let (Some(currency), Some(balance)) = (currency, balance) {
+					v.ensure(
+						currency != KONO,
+						&field,
+						"the native currency is endowed with `native`",
+					);
+					v.ensure(currencies.insert(currency), &field, "duplicate currency");
+					tokens.push((account.clone(), currency, balance));
+				}
+			}
+		}
+
+		let liquidation_threshold = v.ok(
+			"lending.liquidation_threshold",
+			parse_fixed(&self.lending.liquidation_threshold),
+		);
+		if let Some(threshold) = liquidation_threshold {
+			v.ensure(
+				!threshold.is_zero(),
+				"lending.liquidation_threshold",
+				"must be greater than zero",
+			);
+		}
+
+		let mut pools = Vec::new();
+		let mut pool_names = BTreeSet::new();
+		for (i, pool) in self.lending.pools.iter().enumerate() {
+			let field = format!("lending.pools[{}]", i);
+			v.ensure(!pool.name.is_empty(), &format!("{}.name", field), "must not be empty");
+			v.ensure(
+				pool_names.insert(pool.name.as_str()),
+				&format!("{}.name", field),
+				format!("duplicate pool name `{}`", pool.name),
+			);
+			let currency = v.currency(&format!("{}.currency", field), &pool.currency);
+			let owner = v.account_or(&format!("{}.owner", field), &pool.owner, &root_key);
+			if let (Some(currency), Some(owner)) = (currency, owner) {
+				pools.push((pool.can_be_collateral, pool.name.as_bytes().to_vec(), currency, owner));
+			}
+		}
+
+		let chainlink = &self.chainlink;
+		let pallet_admin = v.account_or("chainlink.pallet_admin", &chainlink.pallet_admin, &root_key);
+		let feed_creators = match &chainlink.feed_creators {
+			Some(creators) => creators
+				.iter()
+				.enumerate()
+				.filter_map(|(i, c)| v.account(&format!("chainlink.feed_creators[{}]", i), c))
+				.collect(),
+			None => root_key.iter().cloned().collect(),
+		};
+
+		v.ensure(
+			chainlink.feeds.len() <= FeedLimit::get() as usize,
+			"chainlink.feeds",
+			format!("at most {} feeds are supported", FeedLimit::get()),
+		);
+		let mut feeds = Vec::new();
+		for (i, feed) in chainlink.feeds.iter().enumerate() {
+			let field = format!("chainlink.feeds[{}]", i);
+			v.ensure(
+				feed.description.len() <= StringLimit::get() as usize,
+				&format!("{}.description", field),
+				format!("must be at most {} bytes", StringLimit::get()),
+			);
+			if let Some(symbol) = &feed.currency {
+				let field = format!("{}.currency", field);
+				if let Some(currency) = v.currency(&field, symbol) {
+					match CurrencyToFeedIdConverter::convert(currency) {
+						Some(id) => v.ensure(
+							id == i as FeedId,
+							&field,
+							format!("the runtime reads the {} price from feed {}", symbol, id),
+						),
+						None => v.error(&field, format!("the runtime has no price feed for {}", symbol)),
+					}
+				}
+			}
+			let owner = v.account_or(&format!("{}.owner", field), &feed.owner, &root_key);
+			let payment = v.balance(&format!("{}.payment", field), &feed.payment);
+			v.ensure(feed.timeout > 0, &format!("{}.timeout", field), "must be greater than zero");
+
+			v.ensure(!feed.oracles.is_empty(), &format!("{}.oracles", field), "at least one oracle is required");
+			v.ensure(
+				feed.oracles.len() <= OracleCountLimit::get() as usize,
+				&format!("{}.oracles", field),
+				format!("at most {} oracles are supported", OracleCountLimit::get()),
+			);
+			v.ensure(
+				feed.min_submissions > 0 && feed.min_submissions as usize <= feed.oracles.len(),
+				&format!("{}.min_submissions", field),
+				"must be between one and the number of oracles",
+			);
+			let mut oracles: Vec<(AccountId, AccountId)> = Vec::new();
+			for (j, oracle) in feed.oracles.iter().enumerate() {
+				let field = format!("{}.oracles[{}]", field, j);
+				let account = v.account(&format!("{}.account", field), &oracle.account);
+				let admin = v.account_or(&format!("{}.admin", field), &oracle.admin, &root_key);
+				if let (Some(account), Some(admin)) = (account, admin) {
+					v.ensure(
+						oracles.iter().all(|(a, _)| *a != account),
+						&format!("{}.account", field),
+						"duplicate oracle",
+					);
+					oracles.push((account, admin));
+				}
+			}
+
+			if let (Some(owner), Some(payment)) = (owner, payment) {
+				feeds.push((
+					owner,
+					payment,
+					feed.timeout,
+					feed.min_submissions,
+					feed.decimals,
+					feed.description.as_bytes().to_vec(),
+					oracles,
+				));
+			}
+		}
+
+		let operators = match &self.operators {
+			Some(operators) => operators
+				.iter()
+				.enumerate()
+				.filter_map(|(i, o)| v.account(&format!("operators[{}]", i), o))
+				.collect(),
+			None => root_key.iter().cloned().collect(),
+		};
+
+		match (root_key, liquidation_threshold, pallet_admin) {
+			(Some(root_key), Some(liquidation_threshold), Some(pallet_admin))
+				if v.errors.is_empty() =>
+			{
+				Ok(KonomiGenesis {
+					root_key,
+					authorities,
+					balances,
+					tokens,
+					liquidation_threshold,
+					pools,
+					pallet_admin,
+					feed_creators,
+					feeds,
+					operators,
+					para_id: self.para_id.into(),
+				})
+			}
+			_ => Err(v.errors),
+		}
+	}
+}
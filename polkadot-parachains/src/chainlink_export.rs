@@ -0,0 +1,179 @@
+//! Export of the historical chainlink feed rounds stored in the local database.
+//!
+//! The feed pallet prunes old rounds, so the rounds are collected from the states of
+//! several blocks, walking back from the newest one. Reading older states requires the
+//! node to keep them, i.e. to run with `--pruning archive`.
+
+use std::{collections::BTreeMap, fs::File, io::Write, str::FromStr, sync::Arc};
+
+use log::warn;
+use pallet_chainlink_oracle_rpc::{ChainlinkFeedRuntimeApi, RoundId, RoundInfo, MAX_ROUNDS_PER_QUERY};
+use rococo_parachain_runtime::{AccountId, Balance, BlockNumber, FeedId, Value};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::generic::BlockId;
+
+use crate::{cli::ExportChainlinkRoundsCommand, service::Block};
+
+/// The format of the exported rounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+	/// One line per round, with a header line
+	Csv,
+	/// A json array of the rounds
+	Json,
+}
+
+impl FromStr for ExportFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"csv" => Ok(ExportFormat::Csv),
+			"json" => Ok(ExportFormat::Json),
+			other => Err(format!("Unknown export format: {}", other)),
+		}
+	}
+}
+
+/// A round as written to the export
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedRound {
+	feed_id: FeedId,
+	#[serde(flatten)]
+	round: RoundInfo<BlockNumber, Value>,
+}
+
+type Rounds = BTreeMap<(FeedId, RoundId), RoundInfo<BlockNumber, Value>>;
+
+impl ExportChainlinkRoundsCommand {
+	/// Run the export with the given client.
+	pub fn run<C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+		C::Api: ChainlinkFeedRuntimeApi<Block, FeedId, AccountId, Balance, BlockNumber, Value>,
+	{
+		let best = client.info().best_number;
+		let to_block = self.to_block.map_or(best, |b| b.min(best));
+		if to_block < self.from_block {
+			return Err(sc_cli::Error::Input(format!(
+				"The last block {} is before the first block {}",
+				to_block, self.from_block
+			)));
+		}
+		let step = self.step.max(1);
+
+		// Walk back from the newest block, so that the latest data of a round is kept and
+		// the older states only add the rounds that have been pruned since.
+		let mut rounds = Rounds::new();
+		let mut number = to_block;
+		loop {
+			if let Err(e) = self.collect_at(&*client, &BlockId::Number(number), &mut rounds) {
+				warn!("Skipping block {}, its state can not be read: {:?}", number, e);
+			}
+			if number == self.from_block {
+				break;
+			}
+			number = number.saturating_sub(step).max(self.from_block);
+		}
+
+		let mut output: Box<dyn Write> = match &self.output {
+			Some(path) => Box::new(File::create(path)?),
+			None => Box::new(std::io::stdout()),
+		};
+		match self.format {
+			ExportFormat::Csv => write_csv(&mut output, rounds)?,
+			ExportFormat::Json => write_json(&mut output, rounds)?,
+		}
+		output.flush()?;
+
+		Ok(())
+	}
+
+	fn collect_at<C>(
+		&self,
+		client: &C,
+		at: &BlockId<Block>,
+		rounds: &mut Rounds,
+	) -> Result<(), sp_api::ApiError>
+	where
+		C: ProvideRuntimeApi<Block>,
+		C::Api: ChainlinkFeedRuntimeApi<Block, FeedId, AccountId, Balance, BlockNumber, Value>,
+	{
+		let api = client.runtime_api();
+		let feed_ids = match self.feed_id {
+			Some(id) => vec![id],
+			None => api.feed_ids(at)?,
+		};
+
+		for feed_id in feed_ids {
+			let range = match api.round_range(at, feed_id)? {
+				Some(range) => range,
+				None => continue,
+			};
+
+			let mut from = range.first;
+			while from <= range.last {
+				let to = range.last.min(from.saturating_add(MAX_ROUNDS_PER_QUERY - 1));
+				for round in api.rounds(at, feed_id, from, to)? {
+					rounds.entry((feed_id, round.round_id)).or_insert(round);
+				}
+				if to == RoundId::MAX {
+					break;
+				}
+				from = to + 1;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+fn write_csv(output: &mut dyn Write, rounds: Rounds) -> sc_cli::Result<()> {
+	fn opt<T: ToString>(v: Option<T>) -> String {
+		v.map(|v| v.to_string()).unwrap_or_default()
+	}
+
+	writeln!(output, "feed_id,round_id,started_at,answer,updated_at,answered_in_round")?;
+	for ((feed_id, round_id), round) in rounds {
+		writeln!(
+			output,
+			"{},{},{},{},{},{}",
+			feed_id,
+			round_id,
+			round.started_at,
+			opt(round.answer),
+			opt(round.updated_at),
+			opt(round.answered_in_round),
+		)?;
+	}
+	Ok(())
+}
+
+fn write_json(output: &mut dyn Write, rounds: Rounds) -> sc_cli::Result<()> {
+	let rounds: Vec<ExportedRound> = rounds
+		.into_iter()
+		.map(|((feed_id, _), round)| ExportedRound { feed_id, round })
+		.collect();
+	serde_json::to_writer_pretty(&mut *output, &rounds)
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to write the rounds: {}", e)))?;
+	writeln!(output)?;
+	Ok(())
+}
+
+impl CliConfiguration for ExportChainlinkRoundsCommand {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
@@ -15,6 +15,7 @@
 // along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::chain_spec;
+use crate::chainlink_export::ExportFormat;
 use sc_cli;
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -31,7 +32,7 @@ pub enum Subcommand {
 	ExportGenesisWasm(ExportGenesisWasmCommand),
 
 	/// Build a chain specification.
-	BuildSpec(sc_cli::BuildSpecCmd),
+	BuildSpec(BuildSpecCommand),
 
 	/// Validate blocks.
 	CheckBlock(sc_cli::CheckBlockCmd),
@@ -42,6 +43,10 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the lending pools and user positions of a given block to CSV or JSON.
+	#[structopt(name = "export-lending-positions")]
+	ExportLendingPositions(ExportLendingPositionsCommand),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
@@ -51,9 +56,43 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Export the historical chainlink feed rounds to CSV or JSON.
+	#[structopt(name = "export-chainlink-rounds")]
+	ExportChainlinkRounds(ExportChainlinkRoundsCommand),
+
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Try the runtime upgrade of the Konomi runtime against the state of a live chain or of a
+	/// snapshot file, running the `pre_upgrade` and `post_upgrade` checks of the pallets.
+	#[cfg(feature = "try-runtime")]
+	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Try the runtime upgrade against a chain state. The node needs to be built with
+	/// `--features try-runtime` for this.
+	#[cfg(not(feature = "try-runtime"))]
+	TryRuntime,
+}
+
+/// Command for building a chain specification
+#[derive(Debug, StructOpt)]
+pub struct BuildSpecCommand {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub base: sc_cli::BuildSpecCmd,
+
+	/// Build the chain specification of a Konomi network from the given TOML file instead of
+	/// using `--chain`.
+	#[structopt(long, parse(from_os_str))]
+	pub from_config: Option<PathBuf>,
+
+	/// Also write the genesis storage without the keys of the shell pallets to the given file.
+	///
+	/// These are the values that need to be set when upgrading a shell based chain to the runtime
+	/// of the chain specification.
+	#[structopt(long, parse(from_os_str))]
+	pub genesis_values: Option<PathBuf>,
 }
 
 /// Command for exporting the genesis state of the parachain
@@ -94,6 +133,76 @@ pub struct ExportGenesisWasmCommand {
 	pub chain: Option<String>,
 }
 
+/// Command for exporting the historical rounds of the chainlink feeds
+#[derive(Debug, StructOpt)]
+pub struct ExportChainlinkRoundsCommand {
+	/// Output file name or stdout if unspecified.
+	#[structopt(short, long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Output format, `csv` or `json`.
+	#[structopt(long, default_value = "csv", possible_values = &["csv", "json"])]
+	pub format: ExportFormat,
+
+	/// The feed to export. All the feeds are exported if unspecified.
+	#[structopt(long)]
+	pub feed_id: Option<u32>,
+
+	/// The first block to read the rounds at.
+	#[structopt(long, default_value = "0")]
+	pub from_block: u32,
+
+	/// The last block to read the rounds at. Default is the best block.
+	#[structopt(long)]
+	pub to_block: Option<u32>,
+
+	/// The number of blocks between two reads. Rounds that are started and pruned
+	/// within fewer blocks are missing from the export.
+	#[structopt(long, default_value = "1000")]
+	pub step: u32,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: sc_cli::PruningParams,
+}
+
+/// Command for exporting the lending pools and user positions of a block
+#[derive(Debug, StructOpt)]
+pub struct ExportLendingPositionsCommand {
+	/// Output file name or stdout if unspecified.
+	#[structopt(short, long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Output format, `csv` or `json`. The CSV has one line per user position, the JSON
+	/// additionally contains the pools.
+	#[structopt(long, default_value = "csv", possible_values = &["csv", "json"])]
+	pub format: ExportFormat,
+
+	/// The block to export the positions at. Default is the best block.
+	#[structopt(long)]
+	pub block: Option<u32>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: sc_cli::PruningParams,
+}
+
 #[derive(Debug, StructOpt)]
 #[structopt(settings = &[
 	structopt::clap::AppSettings::GlobalVersion,
@@ -107,6 +216,25 @@ pub struct Cli {
 	#[structopt(flatten)]
 	pub run: cumulus_client_cli::RunCmd,
 
+	/// Run a rococo parachain node without a relay chain that seals its own blocks.
+	///
+	/// A block is sealed as soon as a transaction arrives in the pool, or with `--dev-seal-interval`
+	/// on a timer. The validation data is mocked, so the relay chain arguments are ignored.
+	#[structopt(long)]
+	pub dev_seal: bool,
+
+	/// Seal a block every given number of milliseconds instead of on transaction arrival.
+	#[structopt(long, requires = "dev-seal")]
+	pub dev_seal_interval: Option<u64>,
+
+	/// Follow the relay chain through the JSON-RPC websocket endpoint of a relay chain node at the
+	/// given URL instead of running an embedded relay chain node.
+	///
+	/// Only supported by full nodes, collating needs the embedded relay chain node. The relay chain
+	/// arguments are ignored.
+	#[structopt(long, conflicts_with_all = &["collator", "validator"])]
+	pub relay_chain_rpc_url: Option<String>,
+
 	/// Relaychain arguments
 	#[structopt(raw = true)]
 	pub relaychain_args: Vec<String>,
@@ -16,6 +16,7 @@
 
 use crate::{
 	chain_spec,
+	chain_spec_config::ChainSpecConfig,
 	cli::{Cli, RelayChainCli, Subcommand},
 	service::{
 		StatemineRuntimeExecutor, StatemintRuntimeExecutor, WestmintRuntimeExecutor, new_partial,
@@ -23,6 +24,8 @@ use crate::{
 	},
 };
 use codec::Encode;
+use hex_literal::hex;
+use cumulus_client_consensus_common::PoVBudget;
 use cumulus_client_service::genesis::generate_genesis_block;
 use cumulus_primitives_core::ParaId;
 use log::info;
@@ -32,9 +35,9 @@ use sc_cli::{
 	NetworkParams, Result, RuntimeVersion, SharedParams, SubstrateCli,
 };
 use sc_service::config::{BasePath, PrometheusConfig};
-use sp_core::hexdisplay::HexDisplay;
-use sp_runtime::traits::Block as BlockT;
-use std::{io::Write, net::SocketAddr};
+use sp_core::{hexdisplay::HexDisplay, twox_128};
+use sp_runtime::{traits::Block as BlockT, Percent};
+use std::{collections::BTreeMap, io::Write, net::SocketAddr, path::Path, time::Duration};
 
 // default to the Statemint/Statemine/Westmint id
 const DEFAULT_PARA_ID: u32 = 1000;
@@ -228,6 +231,57 @@ fn extract_genesis_wasm(chain_spec: &Box<dyn sc_service::ChainSpec>) -> Result<V
 		.ok_or_else(|| "Could not find wasm file in genesis state!".into())
 }
 
+/// Write the genesis storage of `chain_spec` without the keys of the shell pallets as json object
+/// of hex encoded keys and values.
+fn write_genesis_values(chain_spec: &Box<dyn sc_service::ChainSpec>, path: &Path) -> Result<()> {
+	// The values of the shell pallets and the well known keys are kept when upgrading.
+	let well_known_keys: &[&[u8]] = &[
+		b":code",
+		b":heappages",
+		b":extrinsic_index",
+		b":changes_trie",
+		b":child_storage",
+	];
+	let shell_pallets = ["System", "ParachainSystem", "ParachainInfo", "CumulusXcm"]
+		.iter()
+		.map(|pallet| twox_128(pallet.as_bytes()).to_vec());
+	// The well known keys of Polkadot, they are not expected in the genesis storage of a
+	// parachain, but are excluded to make sure.
+	let polkadot_well_known_keys: &[&[u8]] = &[
+		&hex!["06de3d8a54d27e44a9d5ce189618f22db4b49d95320d9021994c850f25b8e385"],
+		&hex!["f5207f03cfdce586301014700e2c2593fad157e461d71fd4c1f936839a5f1f3e"],
+		&hex!["6a0da05ca59913bc38a8630590f2627cb6604cff828a6e3f579ca6c59ace013d"],
+		&hex!["6a0da05ca59913bc38a8630590f2627c1d3719f5b0b12c7105c073c507445948"],
+		&hex!["6a0da05ca59913bc38a8630590f2627cf12b746dcf32e843354583c9702cc020"],
+		&hex!["63f78c98723ddc9073523ef3beefda0c4d7fefc408aac59dbfe80a72ac8e3ce5"],
+	];
+	let excluded = well_known_keys
+		.iter()
+		.map(|key| key.to_vec())
+		.chain(shell_pallets)
+		.chain(polkadot_well_known_keys.iter().map(|key| key.to_vec()))
+		.collect::<Vec<_>>();
+
+	let values = chain_spec
+		.build_storage()?
+		.top
+		.into_iter()
+		.filter(|(key, _)| !excluded.iter().any(|prefix| key.starts_with(prefix)))
+		.map(|(key, value)| {
+			(
+				format!("0x{:?}", HexDisplay::from(&key)),
+				format!("0x{:?}", HexDisplay::from(&value)),
+			)
+		})
+		.collect::<BTreeMap<_, _>>();
+
+	let json = serde_json::to_vec(&values)
+		.map_err(|e| format!("Failed to encode the genesis values: {}", e))?;
+	std::fs::write(path, json)?;
+
+	Ok(())
+}
+
 macro_rules! construct_async_run {
 	(|$components:ident, $cli:ident, $cmd:ident, $config:ident| $( $code:tt )* ) => {{
 		let runner = $cli.create_runner($cmd)?;
@@ -290,8 +344,19 @@ pub fn run() -> Result<()> {
 
 	match &cli.subcommand {
 		Some(Subcommand::BuildSpec(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
-			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
+			let runner = cli.create_runner(&cmd.base)?;
+			runner.sync_run(|config| {
+				let chain_spec: Box<dyn sc_service::ChainSpec> = match &cmd.from_config {
+					Some(path) => Box::new(ChainSpecConfig::from_file(path)?.build()?),
+					None => config.chain_spec,
+				};
+
+				if let Some(path) = &cmd.genesis_values {
+					write_genesis_values(&chain_spec, path)?;
+				}
+
+				cmd.base.run(chain_spec, config.network)
+			})
 		}
 		Some(Subcommand::CheckBlock(cmd)) => {
 			construct_async_run!(|components, cli, cmd, config| {
@@ -382,6 +447,34 @@ pub fn run() -> Result<()> {
 
 			Ok(())
 		}
+		Some(Subcommand::ExportLendingPositions(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let components = new_partial::<
+					rococo_parachain_runtime::RuntimeApi,
+					RococoParachainRuntimeExecutor,
+					_
+				>(
+					&config,
+					crate::service::rococo_parachain_build_import_queue,
+				)?;
+				cmd.run(components.client)
+			})
+		}
+		Some(Subcommand::ExportChainlinkRounds(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let components = new_partial::<
+					rococo_parachain_runtime::RuntimeApi,
+					RococoParachainRuntimeExecutor,
+					_
+				>(
+					&config,
+					crate::service::rococo_parachain_build_import_queue,
+				)?;
+				cmd.run(components.client)
+			})
+		}
 		Some(Subcommand::Benchmark(cmd)) => {
 			if cfg!(feature = "runtime-benchmarks") {
 				let runner = cli.create_runner(cmd)?;
@@ -391,6 +484,8 @@ pub fn run() -> Result<()> {
 					runner.sync_run(|config| cmd.run::<Block, WestmintRuntimeExecutor>(config))
 				} else if runner.config().chain_spec.is_statemint() {
 					runner.sync_run(|config| cmd.run::<Block, StatemintRuntimeExecutor>(config))
+				} else if !runner.config().chain_spec.is_shell() {
+					runner.sync_run(|config| cmd.run::<Block, RococoParachainRuntimeExecutor>(config))
 				} else {
 					Err("Chain doesn't support benchmarking".into())
 				}
@@ -400,6 +495,22 @@ pub fn run() -> Result<()> {
 					.into())
 			}
 		}
+		#[cfg(feature = "try-runtime")]
+		Some(Subcommand::TryRuntime(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				// The tasks of the command are bound to the lifetime of this task manager.
+				let registry = config.prometheus_config.as_ref().map(|cfg| &cfg.registry);
+				let task_manager = sc_service::TaskManager::new(config.task_executor.clone(), registry)
+					.map_err(|e| sc_cli::Error::Service(sc_service::Error::Prometheus(e)))?;
+
+				Ok((cmd.run::<Block, RococoParachainRuntimeExecutor>(config), task_manager))
+			})
+		}
+		#[cfg(not(feature = "try-runtime"))]
+		Some(Subcommand::TryRuntime) => Err("TryRuntime wasn't enabled when building the node. \
+			You can enable it with `--features try-runtime`."
+			.into()),
 		None => {
 			let runner = cli.create_runner(&cli.run.normalize())?;
 
@@ -415,6 +526,31 @@ pub fn run() -> Result<()> {
 				);
 
 				let id = ParaId::from(cli.run.parachain_id.or(para_id).unwrap_or(DEFAULT_PARA_ID));
+				let pov_budget = cli
+					.run
+					.pov_budget
+					.map(|percent| PoVBudget::new(Percent::from_percent(percent)))
+					.unwrap_or_default();
+
+				if cli.dev_seal {
+					if config.chain_spec.is_statemint()
+						|| config.chain_spec.is_statemine()
+						|| config.chain_spec.is_westmint()
+						|| config.chain_spec.is_shell()
+					{
+						return Err("`--dev-seal` is only supported for the rococo parachain runtime".into());
+					}
+
+					info!("Parachain id: {:?}", id);
+					info!("Sealing blocks without a relay chain");
+
+					return crate::service::start_rococo_dev_seal_node(
+						config,
+						id,
+						cli.dev_seal_interval.map(Duration::from_millis),
+					)
+					.map_err(Into::into);
+				}
 
 				let parachain_account =
 					AccountIdConversion::<polkadot_primitives::v0::AccountId>::into_account(&id);
@@ -444,7 +580,9 @@ pub fn run() -> Result<()> {
 					crate::service::start_statemint_node::<statemint_runtime::RuntimeApi, StatemintRuntimeExecutor>(
 						config,
 						polkadot_config,
+						cli.relay_chain_rpc_url.clone(),
 						id,
+						pov_budget,
 					)
 						.await
 						.map(|r| r.0)
@@ -453,7 +591,9 @@ pub fn run() -> Result<()> {
 					crate::service::start_statemint_node::<statemine_runtime::RuntimeApi, StatemineRuntimeExecutor>(
 						config,
 						polkadot_config,
+						cli.relay_chain_rpc_url.clone(),
 						id,
+						pov_budget,
 					)
 						.await
 						.map(|r| r.0)
@@ -462,18 +602,32 @@ pub fn run() -> Result<()> {
 					crate::service::start_statemint_node::<westmint_runtime::RuntimeApi, WestmintRuntimeExecutor>(
 						config,
 						polkadot_config,
+						cli.relay_chain_rpc_url.clone(),
 						id,
+						pov_budget,
 					)
 						.await
 						.map(|r| r.0)
 						.map_err(Into::into)
 				} else if config.chain_spec.is_shell() {
-					crate::service::start_shell_node(config, polkadot_config, id)
+					crate::service::start_shell_node(
+						config,
+						polkadot_config,
+						cli.relay_chain_rpc_url.clone(),
+						id,
+						pov_budget,
+					)
 						.await
 						.map(|r| r.0)
 						.map_err(Into::into)
 				} else {
-					crate::service::start_rococo_parachain_node(config, polkadot_config, id)
+					crate::service::start_rococo_parachain_node(
+						config,
+						polkadot_config,
+						cli.relay_chain_rpc_url.clone(),
+						id,
+						pov_budget,
+					)
 						.await
 						.map(|r| r.0)
 						.map_err(Into::into)
@@ -0,0 +1,611 @@
+//! Export of the lending pools and user positions of a block stored in the local database.
+//!
+//! The storage of the lending pallet and of the price feeds is read from the state of the block
+//! and the positions are valued by the lending pallet itself, executed natively on top of that
+//! state. Interest is accrued up to the exported block. Reading the state of an older block
+//! requires the node to keep it, i.e. to run with `--pruning archive`.
+//!
+//! Only blocks with the lending storage version of the runtime the node was built with can be
+//! exported. The export fails instead of leaving out pools or positions that can't be decoded.
+
+use std::{collections::BTreeMap, fs::File, io::Write, sync::Arc};
+
+use codec::Decode;
+use frame_support::traits::PalletInfo as _;
+use log::warn;
+use pallet_traits::{PriceProvider, Releases, StorageVersioned};
+use polkadot_parachain_primitives::{CurrencyId, CustomError, PoolId};
+use rococo_parachain_runtime::{
+	AccountId, Balance, BlockNumber, ChainlinkFeed, FloatingRateLend, Oracle, PalletInfo, Runtime,
+};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{Backend, StorageProvider};
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::Ss58Codec,
+	storage::{Storage, StorageKey},
+	twox_128,
+};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{CheckedDiv, Zero},
+	FixedPointNumber, FixedU128,
+};
+
+use crate::{
+	chain_spec_config::currency_symbol,
+	chainlink_export::ExportFormat,
+	cli::ExportLendingPositionsCommand,
+	service::Block,
+};
+
+/// The length of a `Twox64Concat` hash, before the key itself
+const TWOX_64_LEN: usize = 8;
+
+/// A pool as written to the export
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedPool {
+	id: PoolId,
+	name: String,
+	currency: String,
+	enabled: bool,
+	can_be_collateral: bool,
+	safe_factor: String,
+	/// Missing if the oracle has no price for the currency
+	price: Option<String>,
+	supply: String,
+	debt: String,
+	supply_index: String,
+	debt_index: String,
+	supply_rate: String,
+	debt_rate: String,
+}
+
+/// The balances of a user in a pool, including interest
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedPosition {
+	pool_id: PoolId,
+	currency: String,
+	supplied: String,
+	borrowed: String,
+}
+
+/// The value of all the positions of a user, at the prices of the oracle
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Valuation {
+	supply_value: String,
+	collateral_value: String,
+	debt_value: String,
+	/// The collateral value over the debt value, missing without debt
+	health_factor: Option<String>,
+	liquidatable: bool,
+}
+
+/// A user as written to the export
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedAccount {
+	account: String,
+	/// Missing if a price of one of the pools of the user is not ready
+	#[serde(flatten)]
+	valuation: Option<Valuation>,
+	positions: Vec<ExportedPosition>,
+}
+
+/// The lending state of a block
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LendingSnapshot {
+	block_number: BlockNumber,
+	block_hash: String,
+	liquidation_threshold: String,
+	pools: Vec<ExportedPool>,
+	accounts: Vec<ExportedAccount>,
+}
+
+impl ExportLendingPositionsCommand {
+	/// Run the export with the given client.
+	pub fn run<C, B>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		C: StorageProvider<Block, B> + HeaderBackend<Block>,
+		B: Backend<Block>,
+	{
+		let number = self.block.unwrap_or_else(|| client.info().best_number);
+		let hash = client
+			.hash(number)?
+			.ok_or_else(|| sc_cli::Error::Input(format!("Block {} not found", number)))?;
+
+		let mut top = BTreeMap::new();
+		for prefix in &[pallet_prefix::<FloatingRateLend>(), pallet_prefix::<ChainlinkFeed>()] {
+			let pairs = client
+				.storage_pairs(&BlockId::Hash(hash), &StorageKey(prefix.clone()))
+				.map_err(|e| {
+					sc_cli::Error::Input(format!(
+						"The state of block {} can not be read, it may have been pruned: {}",
+						number, e
+					))
+				})?;
+			top.extend(pairs.into_iter().map(|(key, value)| (key.0, value.0)));
+		}
+		let snapshot = lending_snapshot(top, number, format!("{:?}", hash))?;
+
+		let mut output: Box<dyn Write> = match &self.output {
+			Some(path) => Box::new(File::create(path)?),
+			None => Box::new(std::io::stdout()),
+		};
+		match self.format {
+			ExportFormat::Csv => write_csv(&mut output, &snapshot)?,
+			ExportFormat::Json => write_json(&mut output, &snapshot)?,
+		}
+		output.flush()?;
+
+		Ok(())
+	}
+}
+
+/// The lending state of the block from `top`, the storage of the lending pallet and of the price
+/// feeds in the block.
+fn lending_snapshot(
+	top: BTreeMap<Vec<u8>, Vec<u8>>,
+	number: BlockNumber,
+	block_hash: String,
+) -> sc_cli::Result<LendingSnapshot> {
+	let lending_prefix = pallet_prefix::<FloatingRateLend>();
+	check_storage_version(&top, &lending_prefix, number)?;
+
+	let mut pool_ids = storage_keys(&top, &storage_prefix(&lending_prefix, b"PoolStorage"))
+		.map(|key| {
+			key.get(TWOX_64_LEN..)
+				.and_then(|mut key| PoolId::decode(&mut key).ok())
+				.ok_or_else(|| undecodable(number, "pool id"))
+		})
+		.collect::<sc_cli::Result<Vec<_>>>()?;
+	// the keys are ordered by the hash of the pool id
+	pool_ids.sort_unstable();
+	let supplies = user_keys(&top, &storage_prefix(&lending_prefix, b"PoolUserSupplies"), number)?;
+	let debts = user_keys(&top, &storage_prefix(&lending_prefix, b"PoolUserDebts"), number)?;
+
+	let mut ext = sp_io::TestExternalities::new(Storage {
+		top,
+		children_default: Default::default(),
+	});
+	ext.execute_with(|| {
+		frame_system::Pallet::<Runtime>::set_block_number(number);
+		Ok(LendingSnapshot {
+			block_number: number,
+			block_hash,
+			liquidation_threshold: fixed(FloatingRateLend::get_liquidation_threshold()),
+			pools: pool_ids
+				.into_iter()
+				.map(|id| export_pool(id, number))
+				.collect::<sc_cli::Result<_>>()?,
+			accounts: export_accounts(supplies, debts),
+		})
+	})
+}
+
+/// Ensures the lending storage of the block has the layout of the runtime the node was built
+/// with, a different layout would be decoded into wrong pools and positions.
+fn check_storage_version(
+	top: &BTreeMap<Vec<u8>, Vec<u8>>,
+	lending_prefix: &[u8],
+	number: BlockNumber,
+) -> sc_cli::Result<()> {
+	// a missing version is read as the default, like the pallet does
+	let version = match top.get(&storage_prefix(lending_prefix, b"StorageVersion")) {
+		Some(value) => Releases::decode(&mut &value[..])
+			.map_err(|_| undecodable(number, "storage version"))?,
+		None => Releases::default(),
+	};
+	let supported = <FloatingRateLend as StorageVersioned<Runtime>>::LATEST;
+	if version != supported {
+		return Err(sc_cli::Error::Input(format!(
+			"The lending storage of block {} is at version {:?}, the export only supports {:?}",
+			number, version, supported
+		)));
+	}
+	Ok(())
+}
+
+/// The error for a value of the lending storage that can't be decoded
+fn undecodable(number: BlockNumber, what: &str) -> sc_cli::Error {
+	sc_cli::Error::Input(format!(
+		"A lending {} of block {} can not be decoded, the storage layout may be different from the \
+		runtime the node was built with",
+		what, number
+	))
+}
+
+/// The pool with interest accrued up to the block. Needs to run on the state of the block.
+fn export_pool(id: PoolId, number: BlockNumber) -> sc_cli::Result<ExportedPool> {
+	let mut pool = FloatingRateLend::pool(id).ok_or_else(|| undecodable(number, "pool"))?;
+	if let Err(e) = pool.accrue_interest(number) {
+		warn!("Failed to accrue the interest of pool {}: {:?}", id, e);
+	}
+	let price = <Oracle as PriceProvider<Runtime>>::price(pool.currency_id);
+	let overflow = |what: &str, e: CustomError| sc_cli::Error::Input(format!(
+		"The {} of pool {} overflows at block {}: {:?}", what, id, number, e
+	));
+	let supply = pool.supply().map_err(|e| overflow("supply", e))?;
+	let debt = pool.debt().map_err(|e| overflow("debt", e))?;
+
+	Ok(ExportedPool {
+		id,
+		name: String::from_utf8_lossy(&pool.name).into_owned(),
+		currency: currency_name(&pool.currency_id),
+		enabled: pool.enabled,
+		can_be_collateral: pool.can_be_collateral,
+		safe_factor: fixed(pool.safe_factor),
+		price: if price.price_ready() { Some(fixed(price.value())) } else { None },
+		supply: supply.to_string(),
+		debt: debt.to_string(),
+		supply_index: fixed(pool.total_supply_index()),
+		debt_index: fixed(pool.total_debt_index()),
+		supply_rate: fixed(FloatingRateLend::supply_rate(id)),
+		debt_rate: fixed(FloatingRateLend::debt_rate(id)),
+	})
+}
+
+/// The positions and valuations of the users. Needs to run on the state of the block.
+fn export_accounts(
+	supplies: Vec<(PoolId, AccountId)>,
+	debts: Vec<(PoolId, AccountId)>,
+) -> Vec<ExportedAccount> {
+	let mut positions = BTreeMap::<AccountId, BTreeMap<PoolId, (Balance, Balance)>>::new();
+	for (pool_id, account) in supplies {
+		match FloatingRateLend::user_supply_balance(pool_id, account.clone()) {
+			Ok(amount) => positions.entry(account).or_default().entry(pool_id).or_default().0 = amount,
+			Err(e) => warn!("Failed to read the supply of {} in pool {}: {:?}", account, pool_id, e),
+		}
+	}
+	for (pool_id, account) in debts {
+		match FloatingRateLend::user_debt_balance(pool_id, account.clone()) {
+			Ok(amount) => positions.entry(account).or_default().entry(pool_id).or_default().1 = amount,
+			Err(e) => warn!("Failed to read the debt of {} in pool {}: {:?}", account, pool_id, e),
+		}
+	}
+
+	let liquidation_threshold = FloatingRateLend::get_liquidation_threshold();
+	positions
+		.into_iter()
+		.map(|(account, pools)| {
+			let valuation = match FloatingRateLend::user_balances(account.clone()) {
+				Ok(stats) => Some(Valuation {
+					supply_value: fixed(stats.supply_balance),
+					collateral_value: fixed(stats.collateral_balance),
+					debt_value: fixed(stats.debt_balance),
+					health_factor: if stats.debt_balance.is_zero() {
+						None
+					} else {
+						stats.collateral_balance.checked_div(&stats.debt_balance).map(fixed)
+					},
+					liquidatable: stats.is_liquidated(liquidation_threshold),
+				}),
+				Err(e) => {
+					warn!("Failed to value the positions of {}: {:?}", account, e);
+					None
+				}
+			};
+
+			ExportedAccount {
+				account: account.to_ss58check(),
+				valuation,
+				positions: pools
+					.into_iter()
+					.map(|(pool_id, (supplied, borrowed))| ExportedPosition {
+						pool_id,
+						currency: FloatingRateLend::pool(pool_id)
+							.map(|pool| currency_name(&pool.currency_id))
+							.unwrap_or_default(),
+						supplied: supplied.to_string(),
+						borrowed: borrowed.to_string(),
+					})
+					.collect(),
+			}
+		})
+		.collect()
+}
+
+fn write_csv(output: &mut dyn Write, snapshot: &LendingSnapshot) -> sc_cli::Result<()> {
+	fn opt<T: ToString>(v: Option<T>) -> String {
+		v.map(|v| v.to_string()).unwrap_or_default()
+	}
+
+	let prices = snapshot
+		.pools
+		.iter()
+		.map(|pool| (pool.id, pool.price.as_deref()))
+		.collect::<BTreeMap<_, _>>();
+
+	writeln!(
+		output,
+		"block_number,account,pool_id,currency,price,supplied,borrowed,\
+		supply_value,collateral_value,debt_value,health_factor,liquidatable"
+	)?;
+	for account in &snapshot.accounts {
+		let valuation = account.valuation.as_ref();
+		for position in &account.positions {
+			writeln!(
+				output,
+				"{},{},{},{},{},{},{},{},{},{},{},{}",
+				snapshot.block_number,
+				account.account,
+				position.pool_id,
+				position.currency,
+				opt(prices.get(&position.pool_id).copied().flatten()),
+				position.supplied,
+				position.borrowed,
+				opt(valuation.map(|v| &v.supply_value)),
+				opt(valuation.map(|v| &v.collateral_value)),
+				opt(valuation.map(|v| &v.debt_value)),
+				opt(valuation.and_then(|v| v.health_factor.as_ref())),
+				opt(valuation.map(|v| v.liquidatable)),
+			)?;
+		}
+	}
+	Ok(())
+}
+
+fn write_json(output: &mut dyn Write, snapshot: &LendingSnapshot) -> sc_cli::Result<()> {
+	serde_json::to_writer_pretty(&mut *output, snapshot)
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to write the positions: {}", e)))?;
+	writeln!(output)?;
+	Ok(())
+}
+
+/// The storage prefix of the pallet in the runtime
+fn pallet_prefix<P: 'static>() -> Vec<u8> {
+	let name = PalletInfo::name::<P>().expect("pallet is part of the runtime; qed");
+	twox_128(name.as_bytes()).to_vec()
+}
+
+/// The storage prefix of a storage item of the pallet
+fn storage_prefix(pallet_prefix: &[u8], item: &[u8]) -> Vec<u8> {
+	[pallet_prefix, &twox_128(item)[..]].concat()
+}
+
+/// The keys below `prefix`, without the prefix
+fn storage_keys<'a>(
+	top: &'a BTreeMap<Vec<u8>, Vec<u8>>,
+	prefix: &'a [u8],
+) -> impl Iterator<Item = &'a [u8]> + 'a {
+	top.range(prefix.to_vec()..)
+		.map(|(key, _)| key)
+		.take_while(move |key| key.starts_with(prefix))
+		.map(move |key| &key[prefix.len()..])
+}
+
+/// The pools and users of a `(PoolId, AccountId)` double map with `Twox64Concat` hashers
+fn user_keys(
+	top: &BTreeMap<Vec<u8>, Vec<u8>>,
+	prefix: &[u8],
+	number: BlockNumber,
+) -> sc_cli::Result<Vec<(PoolId, AccountId)>> {
+	storage_keys(top, prefix)
+		.map(|key| {
+			let decode = |mut key: &[u8]| {
+				key = key.get(TWOX_64_LEN..)?;
+				let pool_id = PoolId::decode(&mut key).ok()?;
+				key = key.get(TWOX_64_LEN..)?;
+				let account = AccountId::decode(&mut key).ok()?;
+				Some((pool_id, account))
+			};
+			decode(key).ok_or_else(|| undecodable(number, "user position key"))
+		})
+		.collect()
+}
+
+/// The symbol of the currency, or its debug representation if it has none
+fn currency_name(currency: &CurrencyId) -> String {
+	currency_symbol(currency).map(Into::into).unwrap_or_else(|| format!("{:?}", currency))
+}
+
+/// Formats a fixed point number with all its decimals, e.g. `1.050000000000000000`
+fn fixed(value: FixedU128) -> String {
+	let accuracy = FixedU128::accuracy();
+	format!("{}.{:018}", value.into_inner() / accuracy, value.into_inner() % accuracy)
+}
+
+impl CliConfiguration for ExportLendingPositionsCommand {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use frame_support::{assert_ok, traits::GenesisBuild};
+	use polkadot_parachain_primitives::{BALANCE_ONE, DOT, ETH};
+	use rococo_parachain_runtime::{ChainlinkFeedConfig, FloatingRateLendConfig, Origin, TokensConfig};
+	use sp_core::twox_64;
+
+	/// The block of the export, 100_000 blocks after the positions were opened
+	const NUMBER: BlockNumber = 100_001;
+	const DOT_POOL: PoolId = 0;
+	const ETH_POOL: PoolId = 1;
+
+	fn root() -> AccountId {
+		AccountId::from([0; 32])
+	}
+
+	fn alice() -> AccountId {
+		AccountId::from([1; 32])
+	}
+
+	fn bob() -> AccountId {
+		AccountId::from([2; 32])
+	}
+
+	/// Bob supplies 1000 DOT, Alice borrows 500 DOT against 1000 ETH of collateral. DOT is priced
+	/// at 1 and ETH at 2.
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap();
+		GenesisBuild::<Runtime>::assimilate_storage(
+			&FloatingRateLendConfig {
+				liquidation_threshold: FixedU128::from(1),
+				pools: vec![
+					(false, b"DOT".to_vec(), DOT, root()),
+					(true, b"ETH".to_vec(), ETH, root()),
+				],
+			},
+			&mut t,
+		)
+		.unwrap();
+		// the feeds of KONO, DOT and ETH
+		GenesisBuild::<Runtime>::assimilate_storage(
+			&ChainlinkFeedConfig {
+				pallet_admin: Some(root()),
+				feed_creators: vec![root()],
+				feeds: (0..3)
+					.map(|_| (root(), 0, 600, 1, 8, b"USD".to_vec(), vec![(root(), root())]))
+					.collect(),
+			},
+			&mut t,
+		)
+		.unwrap();
+		GenesisBuild::<Runtime>::assimilate_storage(
+			&TokensConfig {
+				balances: vec![(alice(), ETH, 1_000 * BALANCE_ONE), (bob(), DOT, 1_000 * BALANCE_ONE)],
+			},
+			&mut t,
+		)
+		.unwrap();
+
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| {
+			frame_system::Pallet::<Runtime>::set_block_number(1);
+			for (feed_id, price) in [(1, 1), (2, 2)].iter() {
+				assert_ok!(ChainlinkFeed::submit(Origin::signed(root()), *feed_id, 1, price * 100_000_000));
+			}
+			assert_ok!(FloatingRateLend::supply(Origin::signed(bob()), DOT_POOL, 1_000 * BALANCE_ONE));
+			assert_ok!(FloatingRateLend::supply(Origin::signed(alice()), ETH_POOL, 1_000 * BALANCE_ONE));
+			assert_ok!(FloatingRateLend::borrow(Origin::signed(alice()), DOT_POOL, 500 * BALANCE_ONE));
+		});
+		ext
+	}
+
+	/// All the storage of the externalities
+	fn storage() -> BTreeMap<Vec<u8>, Vec<u8>> {
+		let mut top = BTreeMap::new();
+		let mut key = Vec::new();
+		while let Some(next) = sp_io::storage::next_key(&key) {
+			if let Some(value) = sp_io::storage::get(&next) {
+				top.insert(next.clone(), value);
+			}
+			key = next;
+		}
+		top
+	}
+
+	fn lending_key(item: &[u8]) -> Vec<u8> {
+		storage_prefix(&pallet_prefix::<FloatingRateLend>(), item)
+	}
+
+	/// Parses a number formatted by [`fixed`]
+	fn parse_fixed(value: &str) -> FixedU128 {
+		let (int, frac) = value.split_at(value.find('.').unwrap());
+		FixedU128::from_inner(
+			int.parse::<u128>().unwrap() * FixedU128::accuracy() + frac[1..].parse::<u128>().unwrap(),
+		)
+	}
+
+	fn input_error(result: sc_cli::Result<LendingSnapshot>) -> String {
+		match result {
+			Err(sc_cli::Error::Input(message)) => message,
+			_ => panic!("the export should fail"),
+		}
+	}
+
+	#[test]
+	fn positions_are_exported_with_accrued_interest() {
+		let top = new_test_ext().execute_with(storage);
+		let snapshot = lending_snapshot(top, NUMBER, "0x00".into()).unwrap();
+		let balance = |value: &str| value.parse::<Balance>().unwrap();
+
+		assert_eq!(snapshot.pools.len(), 2);
+		let dot = &snapshot.pools[0];
+		assert_eq!((dot.id, dot.currency.as_str()), (DOT_POOL, "DOT"));
+		assert_eq!(dot.price.as_deref(), Some("1.000000000000000000"));
+		assert!(balance(&dot.debt) > 500 * BALANCE_ONE);
+		assert!(balance(&dot.supply) > 1_000 * BALANCE_ONE);
+		// nothing is borrowed from the ETH pool, so its supply doesn't earn interest
+		assert_eq!(balance(&snapshot.pools[1].supply), 1_000 * BALANCE_ONE);
+
+		assert_eq!(snapshot.accounts.len(), 2);
+		let account = |who: AccountId| {
+			snapshot
+				.accounts
+				.iter()
+				.find(|account| account.account == who.to_ss58check())
+				.unwrap()
+		};
+
+		let alice = account(alice());
+		assert_eq!(alice.positions.len(), 2);
+		let borrowed = balance(&alice.positions[0].borrowed);
+		assert_eq!(alice.positions[0].pool_id, DOT_POOL);
+		assert!(borrowed > 500 * BALANCE_ONE);
+		assert_eq!(balance(&alice.positions[0].supplied), 0);
+		assert_eq!(balance(&alice.positions[1].supplied), 1_000 * BALANCE_ONE);
+
+		let valuation = alice.valuation.as_ref().unwrap();
+		// 1000 ETH at 2 with a safe factor of 0.9
+		assert_eq!(valuation.collateral_value, "1800.000000000000000000");
+		assert_eq!(
+			valuation.debt_value,
+			fixed(FixedU128::saturating_from_rational(borrowed, BALANCE_ONE))
+		);
+		let health_factor = parse_fixed(&valuation.collateral_value)
+			.checked_div(&parse_fixed(&valuation.debt_value))
+			.unwrap();
+		assert_eq!(valuation.health_factor, Some(fixed(health_factor)));
+		assert!(health_factor < FixedU128::saturating_from_rational(36, 10));
+		assert!(!valuation.liquidatable);
+
+		let bob = account(bob());
+		assert_eq!(bob.positions.len(), 1);
+		assert!(balance(&bob.positions[0].supplied) > 1_000 * BALANCE_ONE);
+		assert_eq!(bob.valuation.as_ref().unwrap().health_factor, None);
+	}
+
+	#[test]
+	fn other_storage_versions_are_rejected() {
+		let mut top = new_test_ext().execute_with(storage);
+		top.insert(lending_key(b"StorageVersion"), Releases::V1_0_0.encode());
+
+		let error = input_error(lending_snapshot(top.clone(), NUMBER, "0x00".into()));
+		assert!(error.contains("is at version V1_0_0"), "{}", error);
+
+		// a missing version is the first version
+		top.remove(&lending_key(b"StorageVersion"));
+		let error = input_error(lending_snapshot(top, NUMBER, "0x00".into()));
+		assert!(error.contains("is at version V1_0_0"), "{}", error);
+	}
+
+	#[test]
+	fn undecodable_pools_are_rejected() {
+		let mut top = new_test_ext().execute_with(storage);
+		let id = ETH_POOL.encode();
+		let key = [lending_key(b"PoolStorage"), twox_64(&id).to_vec(), id].concat();
+		top.insert(key, vec![1, 2, 3]);
+
+		let error = input_error(lending_snapshot(top, NUMBER, "0x00".into()));
+		assert!(error.contains("pool of block"), "{}", error);
+	}
+}
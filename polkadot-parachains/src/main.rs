@@ -20,6 +20,9 @@
 #![warn(unused_extern_crates)]
 
 mod chain_spec;
+mod chain_spec_config;
+mod chainlink_export;
+mod lending_export;
 #[macro_use]
 mod service;
 mod cli;
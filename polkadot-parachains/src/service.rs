@@ -18,17 +18,26 @@ use cumulus_client_consensus_aura::{
 	build_aura_consensus, BuildAuraConsensusParams, SlotProportion,
 };
 use cumulus_client_consensus_common::{
-	ParachainConsensus, ParachainCandidate, ParachainBlockImport,
+	ParachainConsensus, ParachainCandidate, ParachainBlockImport, PoVBudget,
 };
+use cumulus_client_inclusion_rpc::{Inclusion, InclusionApi, InclusionTracker};
 use cumulus_client_network::build_block_announce_validator;
+use cumulus_relay_chain_interface::RelayChainInterface;
+use cumulus_relay_chain_local::build_relay_chain_interface;
+use cumulus_relay_chain_rpc_interface::RelayChainRpcInterface;
 use cumulus_client_service::{
 	prepare_node_config, start_collator, start_full_node, StartCollatorParams, StartFullNodeParams,
 };
 use cumulus_primitives_core::{
 	ParaId, relay_chain::v1::{Hash as PHash, PersistedValidationData},
 };
+use cumulus_primitives_parachain_inherent::MockValidationDataInherentDataProvider;
 
 use sc_client_api::ExecutorProvider;
+use sc_consensus_manual_seal::{
+	rpc::{ManualSeal, ManualSealApi},
+	EngineCommand, ManualSealParams,
+};
 use sc_executor::native_executor_instance;
 use sc_network::NetworkService;
 use sc_service::{Configuration, PartialComponents, Role, TFullBackend, TFullClient, TaskManager};
@@ -40,10 +49,11 @@ use sp_consensus::{
 };
 use sp_consensus_aura::{sr25519::AuthorityId as AuraId, AuraApi};
 use sp_keystore::SyncCryptoStorePtr;
+use sp_blockchain::HeaderBackend;
 use sp_runtime::{traits::{BlakeTwo256, Header as HeaderT}, generic::BlockId};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use substrate_prometheus_endpoint::Registry;
-use futures::lock::Mutex;
+use futures::{lock::Mutex, StreamExt};
 use cumulus_client_consensus_relay_chain::Verifier as RelayChainVerifier;
 
 pub use sc_executor::NativeExecutor;
@@ -58,6 +68,7 @@ native_executor_instance!(
 	pub RococoParachainRuntimeExecutor,
 	rococo_parachain_runtime::api::dispatch,
 	rococo_parachain_runtime::native_version,
+	frame_benchmarking::benchmarking::HostFunctions,
 );
 
 // Native executor instance.
@@ -190,11 +201,15 @@ where
 
 /// Start a node with the given parachain `Configuration` and relay chain `Configuration`.
 ///
+/// If `relay_chain_rpc_url` is given, the relay chain is followed through the RPC endpoint of a
+/// relay chain node instead of an embedded one, which is only supported by full nodes.
+///
 /// This is the actual implementation that is abstract over the executor and the runtime api.
 #[sc_tracing::logging::prefix_logs_with("Parachain")]
 async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 	parachain_config: Configuration,
 	polkadot_config: Configuration,
+	relay_chain_rpc_url: Option<String>,
 	id: ParaId,
 	rpc_ext_builder: RB,
 	build_import_queue: BIQ,
@@ -213,7 +228,8 @@ where
 			StateBackend = sc_client_api::StateBackendFor<TFullBackend<Block>, Block>,
 		> + sp_offchain::OffchainWorkerApi<Block>
 		+ sp_block_builder::BlockBuilder<Block>
-		+ cumulus_primitives_core::CollectCollationInfo<Block>,
+		+ cumulus_primitives_core::CollectCollationInfo<Block>
+		+ cumulus_primitives_core::CollectRelayStateKeys<Block>,
 	sc_client_api::StateBackendFor<TFullBackend<Block>, Block>: sp_api::StateBackend<BlakeTwo256>,
 	Executor: sc_executor::NativeExecutionDispatch + 'static,
 	RB: Fn(
@@ -248,30 +264,49 @@ where
 
 	let parachain_config = prepare_node_config(parachain_config);
 
+	let validator = parachain_config.role.is_authority();
+	if validator && relay_chain_rpc_url.is_some() {
+		return Err("Collating requires an embedded relay chain node, \
+			`--relay-chain-rpc-url` is only supported by full nodes.".into());
+	}
+
 	let params = new_partial::<RuntimeApi, Executor, BIQ>(&parachain_config, build_import_queue)?;
 	let (mut telemetry, telemetry_worker_handle) = params.other;
 
-	let relay_chain_full_node = cumulus_client_service::build_polkadot_full_node(
-		polkadot_config,
-		telemetry_worker_handle,
-	)
-	.map_err(|e| match e {
-		polkadot_service::Error::Sub(x) => x,
-		s => format!("{}", s).into(),
-	})?;
+	let (relay_chain_full_node, relay_chain_interface) = match relay_chain_rpc_url {
+		Some(url) => {
+			log::info!("Following the relay chain through the RPC endpoint at {}", url);
+			let relay_chain_interface: Arc<dyn RelayChainInterface> = Arc::new(
+				RelayChainRpcInterface::new(&url)
+					.await
+					.map_err(|e| format!("Failed to connect to the relay chain node at {}: {}", url, e))?,
+			);
+			(None, relay_chain_interface)
+		}
+		None => {
+			let relay_chain_full_node = cumulus_client_service::build_polkadot_full_node(
+				polkadot_config,
+				telemetry_worker_handle,
+			)
+			.map_err(|e| match e {
+				polkadot_service::Error::Sub(x) => x,
+				s => format!("{}", s).into(),
+			})?;
+			let relay_chain_interface = build_relay_chain_interface(&relay_chain_full_node);
+			(Some(relay_chain_full_node), relay_chain_interface)
+		}
+	};
 
 	let client = params.client.clone();
 	let backend = params.backend.clone();
-	let block_announce_validator = build_block_announce_validator(
-		relay_chain_full_node.client.clone(),
-		id,
-		Box::new(relay_chain_full_node.network.clone()),
-		relay_chain_full_node.backend.clone(),
-	);
+	let prometheus_registry = parachain_config.prometheus_registry().cloned();
+	let block_announce_metrics =
+		cumulus_client_network::Metrics::register(prometheus_registry.as_ref())
+			.map_err(|e| format!("Failed to register block announce metrics: {}", e))?;
+	let block_announce_validator =
+		build_block_announce_validator(relay_chain_interface.clone(), id, block_announce_metrics);
 
 	let force_authoring = parachain_config.force_authoring;
-	let validator = parachain_config.role.is_authority();
-	let prometheus_registry = parachain_config.prometheus_registry().cloned();
 	let transaction_pool = params.transaction_pool.clone();
 	let mut task_manager = params.task_manager;
 	let import_queue = cumulus_client_service::SharedImportQueue::new(params.import_queue);
@@ -296,8 +331,22 @@ where
 	// 		crate::rpc::create_full(deps)
 	// 	})
 	// };
+	let inclusion_tracker = InclusionTracker::new();
+	task_manager.spawn_handle().spawn(
+		"parachain-inclusion-tracker",
+		inclusion_tracker.clone().run(id, relay_chain_interface.clone()),
+	);
+
 	let rpc_client = client.clone();
-	let rpc_extensions_builder = Box::new(move |_, _| rpc_ext_builder(rpc_client.clone()));
+	let rpc_extensions_builder =
+		Box::new(move |_, subscription_executor: sc_rpc::SubscriptionTaskExecutor| {
+			let mut io = rpc_ext_builder(rpc_client.clone());
+			io.extend_with(InclusionApi::to_delegate(Inclusion::new(
+				inclusion_tracker.clone(),
+				Arc::new(subscription_executor),
+			)));
+			io
+		});
 
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		on_demand: None,
@@ -320,12 +369,16 @@ where
 	};
 
 	if validator {
+		let relay_chain_full_node = relay_chain_full_node
+			.as_ref()
+			.expect("`--relay-chain-rpc-url` is rejected for collators above; qed");
+		let parachain_network = network.clone();
 		let parachain_consensus = build_consensus(
 			client.clone(),
 			prometheus_registry.as_ref(),
 			telemetry.as_ref().map(|t| t.handle()),
 			&task_manager,
-			&relay_chain_full_node,
+			relay_chain_full_node,
 			transaction_pool,
 			network,
 			params.keystore_container.sync_keystore(),
@@ -340,10 +393,14 @@ where
 			announce_block,
 			client: client.clone(),
 			task_manager: &mut task_manager,
-			relay_chain_full_node,
+			relay_chain_interface,
+			collator_key: relay_chain_full_node.collator_key.clone(),
 			spawner,
 			parachain_consensus,
 			import_queue,
+			prometheus_registry: prometheus_registry.as_ref(),
+			pov_recovery_config: Default::default(),
+			parachain_block_requester: Some(parachain_network),
 		};
 
 		start_collator(params).await?;
@@ -353,12 +410,16 @@ where
 			announce_block,
 			task_manager: &mut task_manager,
 			para_id: id,
-			relay_chain_full_node,
+			relay_chain_interface,
 		};
 
 		start_full_node(params)?;
 	}
 
+	if let Some(relay_chain_full_node) = relay_chain_full_node {
+		task_manager.add_child(relay_chain_full_node.relay_chain_full_node.task_manager);
+	}
+
 	start_network.start_network();
 
 	Ok((task_manager, client))
@@ -411,7 +472,9 @@ pub fn rococo_parachain_build_import_queue(
 pub async fn start_rococo_parachain_node(
 	parachain_config: Configuration,
 	polkadot_config: Configuration,
+	relay_chain_rpc_url: Option<String>,
 	id: ParaId,
+	pov_budget: PoVBudget,
 ) -> sc_service::error::Result<(
 	TaskManager,
 	Arc<TFullClient<Block, rococo_parachain_runtime::RuntimeApi, RococoParachainRuntimeExecutor>>,
@@ -419,8 +482,9 @@ pub async fn start_rococo_parachain_node(
 	start_node_impl::<rococo_parachain_runtime::RuntimeApi, RococoParachainRuntimeExecutor, _, _, _>(
 		parachain_config,
 		polkadot_config,
+		relay_chain_rpc_url,
 		id,
-		|_| Default::default(),
+		|client| crate::rpc::create_full(crate::rpc::FullDeps { client }),
 		rococo_parachain_build_import_queue,
 		|client,
 		 prometheus_registry,
@@ -443,6 +507,7 @@ pub async fn start_rococo_parachain_node(
 
 			let relay_chain_backend = relay_chain_node.backend.clone();
 			let relay_chain_client = relay_chain_node.client.clone();
+			let para_client = client.clone();
 			Ok(build_aura_consensus::<
 				sp_consensus_aura::sr25519::AuthorityPair,
 				_,
@@ -456,7 +521,9 @@ pub async fn start_rococo_parachain_node(
 				_,
 			>(BuildAuraConsensusParams {
 				proposer_factory,
-				create_inherent_data_providers: move |_, (relay_parent, validation_data)| {
+				create_inherent_data_providers: move |parent, (relay_parent, validation_data)| {
+					let relay_state_keys =
+						cumulus_primitives_parachain_inherent::collect_relay_state_keys(&*para_client, parent);
 					let parachain_inherent =
 					cumulus_primitives_parachain_inherent::ParachainInherentData::create_at_with_client(
 						relay_parent,
@@ -464,6 +531,7 @@ pub async fn start_rococo_parachain_node(
 						&*relay_chain_backend,
 						&validation_data,
 						id,
+						&relay_state_keys,
 					);
 					async move {
 						let time = sp_timestamp::InherentDataProvider::from_system_time();
@@ -494,12 +562,168 @@ pub async fn start_rococo_parachain_node(
 				// We got around 500ms for proposing
 				block_proposal_slot_portion: SlotProportion::new(1f32 / 24f32),
 				telemetry,
+				pov_budget,
 			}))
 		},
 	)
 	.await
 }
 
+/// Start a rococo parachain node that seals its own blocks, without any relay chain.
+///
+/// Blocks are sealed with manual seal, either as soon as a transaction is imported into the pool
+/// or every `seal_interval`. Blocks can also be sealed on demand through the `engine_createBlock`
+/// RPC. The parachain inherent is filled with mocked validation data, so `parachain-system`
+/// accepts the blocks as it would accept blocks built on a real relay chain.
+///
+/// This is meant for frontends and integration tests that need a single process chain.
+pub fn start_rococo_dev_seal_node(
+	config: Configuration,
+	id: ParaId,
+	seal_interval: Option<Duration>,
+) -> sc_service::error::Result<TaskManager> {
+	if matches!(config.role, Role::Light) {
+		return Err("Light client not supported!".into());
+	}
+
+	let params = new_partial::<rococo_parachain_runtime::RuntimeApi, RococoParachainRuntimeExecutor, _>(
+		&config,
+		|client, config, _, task_manager| {
+			Ok(sc_consensus_manual_seal::import_queue(
+				Box::new(client.clone()),
+				&task_manager.spawn_essential_handle(),
+				config.prometheus_registry(),
+			))
+		},
+	)?;
+	let (mut telemetry, _) = params.other;
+
+	let client = params.client.clone();
+	let backend = params.backend.clone();
+	let transaction_pool = params.transaction_pool.clone();
+	let prometheus_registry = config.prometheus_registry().cloned();
+	let mut task_manager = params.task_manager;
+
+	let (network, system_rpc_tx, start_network) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue: params.import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+		})?;
+
+	let (command_sink, rpc_commands) = futures::channel::mpsc::channel(1024);
+
+	let rpc_client = client.clone();
+	let rpc_extensions_builder = Box::new(move |_, _| {
+		let mut io = crate::rpc::create_full(crate::rpc::FullDeps {
+			client: rpc_client.clone(),
+		});
+		io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink.clone())));
+		io
+	});
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		on_demand: None,
+		remote_blockchain: None,
+		rpc_extensions_builder,
+		client: client.clone(),
+		transaction_pool: transaction_pool.clone(),
+		task_manager: &mut task_manager,
+		config,
+		keystore: params.keystore_container.sync_keystore(),
+		backend: backend.clone(),
+		network,
+		system_rpc_tx,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|t| t.handle()),
+	);
+
+	let seal_commands = match seal_interval {
+		Some(interval) => futures::stream::unfold((), move |_| async move {
+			futures_timer::Delay::new(interval).await;
+			Some(((), ()))
+		})
+		.map(|_| EngineCommand::SealNewBlock {
+			create_empty: true,
+			finalize: true,
+			parent_hash: None,
+			sender: None,
+		})
+		.boxed(),
+		None => transaction_pool
+			.pool()
+			.validated_pool()
+			.import_notification_stream()
+			.map(|_| EngineCommand::SealNewBlock {
+				create_empty: false,
+				finalize: true,
+				parent_hash: None,
+				sender: None,
+			})
+			.boxed(),
+	};
+	let commands_stream = futures::stream::select(rpc_commands, seal_commands);
+
+	// The timestamp advances by one slot per block, no matter how fast blocks are sealed. Otherwise
+	// sealing two blocks within `MinimumPeriod` would fail in the timestamp pallet.
+	let genesis_timestamp = sp_timestamp::Timestamp::current();
+	let inherent_client = client.clone();
+	let create_inherent_data_providers = move |parent: Hash, ()| {
+		let current_para_block = inherent_client
+			.number(parent)
+			.ok()
+			.flatten()
+			.map_or(0, |number| number + 1);
+
+		async move {
+			let time = sp_timestamp::InherentDataProvider::new(
+				(*genesis_timestamp
+					+ rococo_parachain_runtime::SLOT_DURATION * current_para_block as u64)
+					.into(),
+			);
+
+			let mocked_parachain = MockValidationDataInherentDataProvider {
+				current_para_block,
+				relay_offset: 1000,
+				relay_blocks_per_para_block: 2,
+				para_id: id,
+			};
+
+			Ok((time, mocked_parachain))
+		}
+	};
+
+	let authorship_future = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import: client.clone(),
+		env: proposer_factory,
+		client: client.clone(),
+		pool: transaction_pool.pool().clone(),
+		commands_stream,
+		select_chain: sc_consensus::LongestChain::new(backend),
+		consensus_data_provider: None,
+		create_inherent_data_providers,
+	});
+
+	task_manager
+		.spawn_essential_handle()
+		.spawn_blocking("manual-seal", authorship_future);
+
+	start_network.start_network();
+
+	Ok(task_manager)
+}
+
 /// Build the import queue for the shell runtime.
 pub fn shell_build_import_queue(
 	client: Arc<TFullClient<Block, shell_runtime::RuntimeApi, ShellRuntimeExecutor>>,
@@ -527,7 +751,9 @@ pub fn shell_build_import_queue(
 pub async fn start_shell_node(
 	parachain_config: Configuration,
 	polkadot_config: Configuration,
+	relay_chain_rpc_url: Option<String>,
 	id: ParaId,
+	pov_budget: PoVBudget,
 ) -> sc_service::error::Result<(
 	TaskManager,
 	Arc<TFullClient<Block, shell_runtime::RuntimeApi, ShellRuntimeExecutor>>,
@@ -535,6 +761,7 @@ pub async fn start_shell_node(
 	start_node_impl::<shell_runtime::RuntimeApi, ShellRuntimeExecutor, _, _, _>(
 		parachain_config,
 		polkadot_config,
+		relay_chain_rpc_url,
 		id,
 		|_| Default::default(),
 		shell_build_import_queue,
@@ -557,6 +784,7 @@ pub async fn start_shell_node(
 
 			let relay_chain_backend = relay_chain_node.backend.clone();
 			let relay_chain_client = relay_chain_node.client.clone();
+			let para_client = client.clone();
 
 			Ok(
 				cumulus_client_consensus_relay_chain::build_relay_chain_consensus(
@@ -567,7 +795,9 @@ pub async fn start_shell_node(
 						relay_chain_client: relay_chain_node.client.clone(),
 						relay_chain_backend: relay_chain_node.backend.clone(),
 						create_inherent_data_providers:
-							move |_, (relay_parent, validation_data)| {
+							move |parent, (relay_parent, validation_data)| {
+								let relay_state_keys =
+									cumulus_primitives_parachain_inherent::collect_relay_state_keys(&*para_client, parent);
 								let parachain_inherent =
 					cumulus_primitives_parachain_inherent::ParachainInherentData::create_at_with_client(
 						relay_parent,
@@ -575,6 +805,7 @@ pub async fn start_shell_node(
 						&*relay_chain_backend,
 							&validation_data,
 							id,
+							&relay_state_keys,
 					);
 								async move {
 									let parachain_inherent =
@@ -586,6 +817,7 @@ pub async fn start_shell_node(
 									Ok(parachain_inherent)
 								}
 							},
+						pov_budget,
 					},
 				),
 			)
@@ -793,7 +1025,9 @@ where
 pub async fn start_statemint_node<RuntimeApi, Executor>(
 	parachain_config: Configuration,
 	polkadot_config: Configuration,
+	relay_chain_rpc_url: Option<String>,
 	id: ParaId,
+	pov_budget: PoVBudget,
 ) -> sc_service::error::Result<(
 	TaskManager,
 	Arc<TFullClient<Block, RuntimeApi, Executor>>,
@@ -812,6 +1046,7 @@ where
 		> + sp_offchain::OffchainWorkerApi<Block>
 		+ sp_block_builder::BlockBuilder<Block>
 		+ cumulus_primitives_core::CollectCollationInfo<Block>
+		+ cumulus_primitives_core::CollectRelayStateKeys<Block>
 		+ sp_consensus_aura::AuraApi<Block, AuraId>,
 	sc_client_api::StateBackendFor<TFullBackend<Block>, Block>: sp_api::StateBackend<BlakeTwo256>,
 	Executor: sc_executor::NativeExecutionDispatch + 'static,
@@ -819,6 +1054,7 @@ where
 	start_node_impl::<RuntimeApi, Executor, _, _, _>(
 		parachain_config,
 		polkadot_config,
+		relay_chain_rpc_url,
 		id,
 		|_| Default::default(),
 		statemint_build_import_queue,
@@ -855,6 +1091,7 @@ where
 
 					let relay_chain_backend2 = relay_chain_backend.clone();
 					let relay_chain_client2 = relay_chain_client.clone();
+					let para_client = client2.clone();
 
 					build_aura_consensus::<
 						sp_consensus_aura::sr25519::AuthorityPair,
@@ -870,7 +1107,9 @@ where
 					>(BuildAuraConsensusParams {
 						proposer_factory,
 						create_inherent_data_providers:
-							move |_, (relay_parent, validation_data)| {
+							move |parent, (relay_parent, validation_data)| {
+								let relay_state_keys =
+									cumulus_primitives_parachain_inherent::collect_relay_state_keys(&*para_client, parent);
 								let parachain_inherent =
 								cumulus_primitives_parachain_inherent::ParachainInherentData::create_at_with_client(
 									relay_parent,
@@ -878,6 +1117,7 @@ where
 									&*relay_chain_backend,
 									&validation_data,
 									id,
+									&relay_state_keys,
 								);
 								async move {
 									let time =
@@ -910,6 +1150,7 @@ where
 						// We got around 500ms for proposing
 						block_proposal_slot_portion: SlotProportion::new(1f32 / 24f32),
 						telemetry: telemetry2,
+						pov_budget,
 					})
 				}),
 			));
@@ -924,6 +1165,7 @@ where
 
 			let relay_chain_backend = relay_chain_node.backend.clone();
 			let relay_chain_client = relay_chain_node.client.clone();
+			let para_client = client.clone();
 
 			let relay_chain_consensus =
 				cumulus_client_consensus_relay_chain::build_relay_chain_consensus(
@@ -934,7 +1176,9 @@ where
 						relay_chain_client: relay_chain_node.client.clone(),
 						relay_chain_backend: relay_chain_node.backend.clone(),
 						create_inherent_data_providers:
-							move |_, (relay_parent, validation_data)| {
+							move |parent, (relay_parent, validation_data)| {
+								let relay_state_keys =
+									cumulus_primitives_parachain_inherent::collect_relay_state_keys(&*para_client, parent);
 								let parachain_inherent =
 									cumulus_primitives_parachain_inherent::ParachainInherentData::create_at_with_client(
 										relay_parent,
@@ -942,6 +1186,7 @@ where
 										&*relay_chain_backend,
 										&validation_data,
 										id,
+										&relay_state_keys,
 									);
 								async move {
 									let parachain_inherent =
@@ -953,6 +1198,7 @@ where
 									Ok(parachain_inherent)
 								}
 							},
+						pov_budget,
 					},
 				);
 
@@ -58,7 +58,8 @@ use sp_runtime::Perbill;
 pub use statemint_common as common;
 use statemint_common::{
 	impls::DealWithFees, AccountId, AuraId, Balance, BlockNumber, Hash, Header, Index, Signature,
-	AVERAGE_ON_INITIALIZE_RATIO, HOURS, MAXIMUM_BLOCK_WEIGHT, NORMAL_DISPATCH_RATIO, SLOT_DURATION,
+	AVERAGE_ON_INITIALIZE_RATIO, DAYS, HOURS, MAXIMUM_BLOCK_WEIGHT, NORMAL_DISPATCH_RATIO,
+	SLOT_DURATION,
 };
 
 #[cfg(any(feature = "std", test))]
@@ -453,6 +454,7 @@ impl pallet_proxy::Config for Runtime {
 parameter_types! {
 	pub const ReservedXcmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
 	pub const ReservedDmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
+	pub const UpgradeCooldown: BlockNumber = 6 * HOURS;
 }
 
 impl cumulus_pallet_parachain_system::Config for Runtime {
@@ -464,6 +466,8 @@ impl cumulus_pallet_parachain_system::Config for Runtime {
 	type OutboundXcmpMessageSource = XcmpQueue;
 	type XcmpMessageHandler = XcmpQueue;
 	type ReservedXcmpWeight = ReservedXcmpWeight;
+	type RelayStateReader = ();
+	type UpgradeCooldown = UpgradeCooldown;
 }
 
 impl parachain_info::Config for Runtime {}
@@ -593,12 +597,16 @@ impl pallet_xcm::Config for Runtime {
 impl cumulus_pallet_xcm::Config for Runtime {
 	type Event = Event;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type BaseAllowedCalls = ();
+	type ControllerOrigin = EnsureRoot<AccountId>;
 }
 
 impl cumulus_pallet_xcmp_queue::Config for Runtime {
 	type Event = Event;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
 	type ChannelInfo = ParachainSystem;
+	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type ControllerOrigin = EnsureRoot<AccountId>;
 }
 
 impl cumulus_pallet_dmp_queue::Config for Runtime {
@@ -638,6 +646,12 @@ parameter_types! {
 	pub const MaxCandidates: u32 = 1000;
 	pub const SessionLength: BlockNumber = 6 * HOURS;
 	pub const MaxInvulnerables: u32 = 100;
+	pub const MinDelegation: Balance = UNITS;
+	pub const MaxDelegatorsPerCandidate: u32 = 100;
+	pub const UnbondDelay: BlockNumber = 7 * DAYS;
+	// one week of eras
+	pub const HistoryDepth: u32 = 28;
+	pub const KickSlash: Perbill = Perbill::zero();
 }
 
 /// We allow root and the Relay Chain council to execute privileged collator selection operations.
@@ -656,6 +670,16 @@ impl pallet_collator_selection::Config for Runtime {
 	type MaxInvulnerables = MaxInvulnerables;
 	// should be a multiple of session or things will get inconsistent
 	type KickThreshold = Period;
+	type MinDelegation = MinDelegation;
+	type MaxDelegatorsPerCandidate = MaxDelegatorsPerCandidate;
+	// delegations count from the next session on
+	type BondDelay = Period;
+	type UnbondDelay = UnbondDelay;
+	// rewards come from the fee share only
+	type RewardPerBlock = ();
+	type HistoryDepth = HistoryDepth;
+	type KickSlash = KickSlash;
+	type Slash = ();
 	type WeightInfo = weights::pallet_collator_selection::WeightInfo<Runtime>;
 }
 
@@ -849,6 +873,18 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_primitives_core::CollectRelayStateKeys<Block> for Runtime {
+		fn relay_state_keys() -> Vec<Vec<u8>> {
+			ParachainSystem::relay_state_keys()
+		}
+	}
+
+	impl cumulus_primitives_core::UpgradeStatusApi<Block> for Runtime {
+		fn upgrade_status() -> cumulus_primitives_core::UpgradeStatus<Hash, BlockNumber> {
+			ParachainSystem::upgrade_status()
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -167,6 +167,14 @@ mod tests {
 		type MaxCandidates = MaxCandidates;
 		type MaxInvulnerables = MaxInvulnerables;
 		type KickThreshold = ();
+		type MinDelegation = ();
+		type MaxDelegatorsPerCandidate = ();
+		type BondDelay = ();
+		type UnbondDelay = ();
+		type RewardPerBlock = ();
+		type HistoryDepth = ();
+		type KickSlash = ();
+		type Slash = ();
 		type WeightInfo = ();
 	}
 
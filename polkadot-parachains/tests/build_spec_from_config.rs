@@ -0,0 +1,73 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+
+const LOCAL_CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/konomi-local.toml");
+
+#[test]
+fn build_spec_from_config_works() {
+	let base_path = tempfile::tempdir().unwrap();
+	let genesis_values = base_path.path().join("genesis_values.json");
+
+	let output = Command::new(cargo_bin("polkadot-collator"))
+		.args(&["build-spec", "--disable-default-bootnode", "--from-config", LOCAL_CONFIG])
+		.arg("--genesis-values")
+		.arg(&genesis_values)
+		.arg("-d")
+		.arg(base_path.path())
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	let spec: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(spec["id"], "konomi_local_testnet");
+	assert_eq!(spec["para_id"], 1000);
+	assert_eq!(spec["relay_chain"], "westend");
+
+	let values: serde_json::Map<String, serde_json::Value> =
+		serde_json::from_slice(&std::fs::read(&genesis_values).unwrap()).unwrap();
+	assert!(!values.is_empty());
+	// `:code` is kept when upgrading, so it is not part of the genesis values.
+	assert!(!values.contains_key("0x3a636f6465"));
+}
+
+#[test]
+fn build_spec_from_config_reports_invalid_values() {
+	let base_path = tempfile::tempdir().unwrap();
+	let config = base_path.path().join("invalid.toml");
+	let content = std::fs::read_to_string(LOCAL_CONFIG)
+		.unwrap()
+		.replace("currency = \"LIT\"", "currency = \"XYZ\"")
+		.replace("liquidation_threshold = \"1.0\"", "liquidation_threshold = \"one\"")
+		.replace("collators = [\"//Alice\", \"//Bob\"]", "collators = [\"//Alice\", \"//Alice\"]");
+	std::fs::write(&config, content).unwrap();
+
+	let output = Command::new(cargo_bin("polkadot-collator"))
+		.args(&["build-spec", "--from-config"])
+		.arg(&config)
+		.arg("-d")
+		.arg(base_path.path())
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("collators[1]: duplicate collator"), "{}", stderr);
+	assert!(stderr.contains("lending.liquidation_threshold: invalid decimal number `one`"), "{}", stderr);
+	assert!(stderr.contains("lending.pools[5].currency: unknown currency `XYZ`"), "{}", stderr);
+}
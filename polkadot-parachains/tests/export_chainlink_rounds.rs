@@ -0,0 +1,76 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::{path::Path, process::Command};
+
+const LOCAL_CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/konomi-local.toml");
+
+/// Write the chain spec of the local config to the base path.
+fn build_chain_spec(base_path: &Path) -> std::path::PathBuf {
+	let chain_spec = base_path.join("konomi-local.json");
+	let output = Command::new(cargo_bin("polkadot-collator"))
+		.args(&["build-spec", "--disable-default-bootnode", "--from-config", LOCAL_CONFIG])
+		.arg("-d")
+		.arg(base_path)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	std::fs::write(&chain_spec, &output.stdout).unwrap();
+	chain_spec
+}
+
+fn export_rounds(base_path: &Path, args: &[&str]) -> std::process::Output {
+	Command::new(cargo_bin("polkadot-collator"))
+		.arg("export-chainlink-rounds")
+		.args(args)
+		.arg("--chain")
+		.arg(build_chain_spec(base_path))
+		.arg("-d")
+		.arg(base_path)
+		.output()
+		.unwrap()
+}
+
+#[test]
+fn export_chainlink_rounds_works() {
+	let base_path = tempfile::tempdir().unwrap();
+
+	let output = export_rounds(base_path.path(), &["--format", "csv"]);
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	let csv = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(
+		csv.lines().next(),
+		Some("feed_id,round_id,started_at,answer,updated_at,answered_in_round")
+	);
+
+	let output = export_rounds(base_path.path(), &["--format", "json"]);
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	let rounds: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+	// one line per round in the csv, the genesis feeds are the only ones
+	assert_eq!(rounds.len(), csv.lines().count() - 1);
+	assert!(rounds.iter().all(|r| r["feedId"].as_u64().unwrap() < 6));
+}
+
+#[test]
+fn export_chainlink_rounds_rejects_an_empty_block_range() {
+	let base_path = tempfile::tempdir().unwrap();
+
+	let output = export_rounds(base_path.path(), &["--from-block", "5"]);
+	assert!(!output.status.success());
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("The last block 0 is before the first block 5"), "{}", stderr);
+}
@@ -0,0 +1,53 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+
+const LOCAL_CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/konomi-local.toml");
+
+#[test]
+fn export_lending_positions_of_genesis_works() {
+	let base_path = tempfile::tempdir().unwrap();
+	let chain_spec = base_path.path().join("konomi-local.json");
+
+	let output = Command::new(cargo_bin("polkadot-collator"))
+		.args(&["build-spec", "--disable-default-bootnode", "--from-config", LOCAL_CONFIG])
+		.arg("-d")
+		.arg(base_path.path())
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	std::fs::write(&chain_spec, &output.stdout).unwrap();
+
+	let output = Command::new(cargo_bin("polkadot-collator"))
+		.args(&["export-lending-positions", "--format", "json", "--block", "0", "--chain"])
+		.arg(&chain_spec)
+		.arg("-d")
+		.arg(base_path.path())
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+	let snapshot: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(snapshot["blockNumber"], 0);
+	assert_eq!(snapshot["liquidationThreshold"], "1.000000000000000000");
+	let pools = snapshot["pools"].as_array().unwrap();
+	assert_eq!(pools.len(), 6);
+	assert_eq!(pools[1]["currency"], "DOT");
+	assert_eq!(pools[1]["supply"], "0");
+	assert!(snapshot["accounts"].as_array().unwrap().is_empty());
+}
@@ -19,6 +19,10 @@
 // --output=./runtime/statemint/src/weights/
 
 
+// TODO: rerun the benchmarks. The weights of `update_bond`, `delegate`, `undelegate`,
+// `withdraw_unbonded`, `set_commission` and `payout` and the delegation components of
+// `leave_intent` and `new_session` are estimates.
+
 #![allow(unused_parens)]
 #![allow(unused_imports)]
 
@@ -49,27 +53,69 @@ impl<T: frame_system::Config> pallet_collator_selection::WeightInfo for WeightIn
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
-	fn leave_intent(c: u32, ) -> Weight {
+	fn leave_intent(c: u32, d: u32, ) -> Weight {
 		(55_336_000 as Weight)
 			// Standard Error: 0
 			.saturating_add((151_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add((38_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(d as Weight)))
+	}
+	fn update_bond(c: u32, ) -> Weight {
+		(49_000_000 as Weight)
+			.saturating_add((150_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn delegate() -> Weight {
+		(58_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn undelegate() -> Weight {
+		(52_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn withdraw_unbonded() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
+	fn set_commission() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn payout(d: u32, ) -> Weight {
+		(62_000_000 as Weight)
+			.saturating_add((48_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(d as Weight)))
+	}
 	fn note_author() -> Weight {
 		(71_461_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
-	fn new_session(r: u32, c: u32, ) -> Weight {
+	fn new_session(r: u32, c: u32, d: u32, e: u32, ) -> Weight {
 		(0 as Weight)
 			// Standard Error: 1_010_000
 			.saturating_add((109_961_000 as Weight).saturating_mul(r as Weight))
 			// Standard Error: 1_010_000
 			.saturating_add((151_952_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((38_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((9_000_000 as Weight).saturating_mul(e as Weight))
 			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(e as Weight)))
 			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(d as Weight)))
 	}
 }
@@ -20,7 +20,7 @@
 
 use codec::{Decode, Encode};
 use frame_support::weights::Weight;
-use sp_runtime::{traits::Block as BlockT, RuntimeDebug};
+use sp_runtime::{traits::{Block as BlockT, NumberFor}, RuntimeDebug};
 use sp_std::prelude::*;
 
 pub use polkadot_core_primitives::InboundDownwardMessage;
@@ -264,6 +264,29 @@ pub struct CollationInfo {
 	pub hrmp_watermark: relay_chain::v1::BlockNumber,
 }
 
+/// A runtime upgrade authorized by governance and waiting to be enacted.
+#[derive(Clone, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct AuthorizedUpgradeInfo<Hash, BlockNumber> {
+	/// The hash of the authorized code.
+	pub code_hash: Hash,
+	/// The parachain block from which the upgrade may be enacted.
+	pub enactable_at: BlockNumber,
+}
+
+/// The state of the runtime upgrades of a parachain.
+#[derive(Clone, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct UpgradeStatus<Hash, BlockNumber> {
+	/// The authorized upgrade, if any.
+	pub authorized: Option<AuthorizedUpgradeInfo<Hash, BlockNumber>>,
+	/// The relay chain block at which the validation code sent to the relay chain is applied, if
+	/// an upgrade is in progress.
+	pub scheduled_at: Option<RelayBlockNumber>,
+}
+
+/// The maximum number of additional relay chain storage keys a runtime may ask for in the relay
+/// chain state proof of a block.
+pub const MAX_ADDITIONAL_RELAY_STATE_KEYS: usize = 64;
+
 sp_api::decl_runtime_apis! {
 	/// Runtime api to collect information about a collation.
 	pub trait CollectCollationInfo {
@@ -271,4 +294,21 @@ sp_api::decl_runtime_apis! {
 		#[skip_initialize_block]
 		fn collect_collation_info() -> CollationInfo;
 	}
+
+	/// Runtime api to collect the relay chain storage keys the runtime reads on top of the
+	/// well known ones.
+	pub trait CollectRelayStateKeys {
+		/// Collect the relay chain storage keys which should be included in the relay chain state
+		/// proof of the next block.
+		///
+		/// At most [`MAX_ADDITIONAL_RELAY_STATE_KEYS`] keys are returned.
+		#[skip_initialize_block]
+		fn relay_state_keys() -> Vec<Vec<u8>>;
+	}
+
+	/// Runtime api to query the state of the runtime upgrades.
+	pub trait UpgradeStatusApi {
+		/// The authorized and the scheduled runtime upgrade, if any.
+		fn upgrade_status() -> UpgradeStatus<<Block as BlockT>::Hash, NumberFor<Block>>;
+	}
 }
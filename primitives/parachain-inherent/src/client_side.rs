@@ -24,12 +24,13 @@ use cumulus_primitives_core::{
 		v1::{HrmpChannelId, ParachainHost},
 		Block as PBlock, Hash as PHash,
 	},
-	InboundDownwardMessage, InboundHrmpMessage, ParaId, PersistedValidationData,
+	CollectRelayStateKeys, InboundDownwardMessage, InboundHrmpMessage, ParaId,
+	PersistedValidationData, MAX_ADDITIONAL_RELAY_STATE_KEYS,
 };
 use polkadot_client::{Client, ClientHandle, ExecuteWithClient};
 use sc_client_api::Backend;
 use sp_api::ProvideRuntimeApi;
-use sp_runtime::generic::BlockId;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 use sp_state_machine::Backend as _;
 use std::collections::BTreeMap;
 
@@ -97,12 +98,37 @@ where
 		.ok()
 }
 
+/// Returns the additional relay chain storage keys the parachain runtime reads when building on
+/// top of `parent`.
+///
+/// Returns no keys in case of an error, e.g. when the runtime doesn't support the
+/// [`CollectRelayStateKeys`] runtime api.
+pub fn collect_relay_state_keys<B, C>(para_client: &C, parent: B::Hash) -> Vec<Vec<u8>>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: CollectRelayStateKeys<B>,
+{
+	para_client
+		.runtime_api()
+		.relay_state_keys(&BlockId::hash(parent))
+		.map_err(|e| {
+			tracing::debug!(
+				target: LOG_TARGET,
+				error = ?e,
+				"Cannot collect the relay chain state keys of the runtime.",
+			)
+		})
+		.unwrap_or_default()
+}
+
 /// Collect the relevant relay chain state in form of a proof for putting it into the validation
 /// data inherent.
 fn collect_relay_storage_proof(
 	polkadot_backend: &impl Backend<PBlock>,
 	para_id: ParaId,
 	relay_parent: PHash,
+	additional_keys: &[Vec<u8>],
 ) -> Option<sp_state_machine::StorageProof> {
 	use relay_chain::well_known_keys as relay_well_known_keys;
 
@@ -184,6 +210,12 @@ fn collect_relay_storage_proof(
 			recipient,
 		})
 	}));
+	relevant_keys.extend(
+		additional_keys
+			.iter()
+			.take(MAX_ADDITIONAL_RELAY_STATE_KEYS)
+			.cloned(),
+	);
 
 	sp_state_machine::prove_read(relay_parent_state_backend, relevant_keys)
 		.map_err(|e| {
@@ -200,6 +232,9 @@ fn collect_relay_storage_proof(
 impl ParachainInherentData {
 	/// Create the [`ParachainInherentData`] at the given `relay_parent`.
 	///
+	/// The `additional_relay_state_keys` are included in the relay chain state proof on top of
+	/// the well known keys, see [`collect_relay_state_keys`].
+	///
 	/// Returns `None` if the creation failed.
 	pub fn create_at<PClient>(
 		relay_parent: PHash,
@@ -207,13 +242,18 @@ impl ParachainInherentData {
 		polkadot_backend: &impl Backend<PBlock>,
 		validation_data: &PersistedValidationData,
 		para_id: ParaId,
+		additional_relay_state_keys: &[Vec<u8>],
 	) -> Option<ParachainInherentData>
 	where
 		PClient: ProvideRuntimeApi<PBlock>,
 		PClient::Api: ParachainHost<PBlock>,
 	{
-		let relay_chain_state =
-			collect_relay_storage_proof(polkadot_backend, para_id, relay_parent)?;
+		let relay_chain_state = collect_relay_storage_proof(
+			polkadot_backend,
+			para_id,
+			relay_parent,
+			additional_relay_state_keys,
+		)?;
 		let downward_messages = retrieve_dmq_contents(polkadot_client, para_id, relay_parent)?;
 		let horizontal_messages =
 			retrieve_all_inbound_hrmp_channel_contents(polkadot_client, para_id, relay_parent)?;
@@ -235,12 +275,14 @@ impl ParachainInherentData {
 		relay_chain_backend: &impl Backend<PBlock>,
 		validation_data: &PersistedValidationData,
 		para_id: ParaId,
+		additional_relay_state_keys: &[Vec<u8>],
 	) -> Option<ParachainInherentData> {
 		polkadot_client.execute_with(CreateAtWithClient {
 			relay_chain_backend,
 			validation_data,
 			para_id,
 			relay_parent,
+			additional_relay_state_keys,
 		})
 	}
 }
@@ -269,6 +311,7 @@ struct CreateAtWithClient<'a, B> {
 	relay_chain_backend: &'a B,
 	validation_data: &'a PersistedValidationData,
 	para_id: ParaId,
+	additional_relay_state_keys: &'a [Vec<u8>],
 }
 
 impl<'a, B> ExecuteWithClient for CreateAtWithClient<'a, B>
@@ -291,6 +334,7 @@ where
 			self.relay_chain_backend,
 			self.validation_data,
 			self.para_id,
+			self.additional_relay_state_keys,
 		)
 	}
 }
@@ -38,6 +38,10 @@ use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
 mod client_side;
 #[cfg(feature = "std")]
 pub use client_side::*;
+#[cfg(feature = "std")]
+mod mock;
+#[cfg(feature = "std")]
+pub use mock::MockValidationDataInherentDataProvider;
 
 /// The identifier for the parachain inherent.
 pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"sysi1337";
@@ -55,6 +59,7 @@ pub struct ParachainInherentData {
 	/// - the relay dispatch queue sizes
 	/// - the list of egress HRMP channels (in the list of recipients form)
 	/// - the metadata for the egress HRMP channels
+	/// - the additional keys read by the runtime
 	pub relay_chain_state: sp_trie::StorageProof,
 	/// Downward messages in the order they were sent.
 	pub downward_messages: Vec<InboundDownwardMessage>,
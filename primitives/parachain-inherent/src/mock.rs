@@ -0,0 +1,83 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mocked parachain inherent for nodes that run without a relay chain.
+
+use crate::{ParachainInherentData, INHERENT_IDENTIFIER};
+use cumulus_primitives_core::{ParaId, PersistedValidationData};
+use cumulus_test_relay_sproof_builder::RelayStateSproofBuilder;
+use sp_inherents::{InherentData, InherentDataProvider, InherentIdentifier};
+
+/// Inherent data provider that supplies mocked validation data.
+///
+/// This is useful when running a node that is not actually backed by any relay chain, for
+/// example a dev node that seals its own blocks. The relay parent number is derived from the
+/// parachain block number, so it advances with every block:
+///
+/// `relay_parent_number = relay_offset + relay_blocks_per_para_block * current_para_block`
+///
+/// The relay chain state proof is built with the [`RelayStateSproofBuilder`] defaults, so the
+/// parachain sees no downward or horizontal messages and no HRMP channels.
+pub struct MockValidationDataInherentDataProvider {
+	/// The number of the parachain block that is being built.
+	pub current_para_block: u32,
+	/// The relay block number the parachain genesis is pretended to be built upon.
+	pub relay_offset: u32,
+	/// The number of relay blocks between two parachain blocks.
+	pub relay_blocks_per_para_block: u32,
+	/// The id of the parachain the data is mocked for.
+	pub para_id: ParaId,
+}
+
+#[async_trait::async_trait]
+impl InherentDataProvider for MockValidationDataInherentDataProvider {
+	fn provide_inherent_data(
+		&self,
+		inherent_data: &mut InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		let relay_parent_number =
+			self.relay_offset + self.relay_blocks_per_para_block * self.current_para_block;
+
+		let sproof_builder = RelayStateSproofBuilder {
+			para_id: self.para_id,
+			current_slot: (relay_parent_number as u64).into(),
+			..Default::default()
+		};
+		let (relay_parent_storage_root, relay_chain_state) =
+			sproof_builder.into_state_root_and_proof();
+
+		let data = ParachainInherentData {
+			validation_data: PersistedValidationData {
+				relay_parent_number,
+				relay_parent_storage_root,
+				..Default::default()
+			},
+			relay_chain_state,
+			downward_messages: Default::default(),
+			horizontal_messages: Default::default(),
+		};
+
+		inherent_data.put_data(INHERENT_IDENTIFIER, &data)
+	}
+
+	async fn try_handle_error(
+		&self,
+		_: &InherentIdentifier,
+		_: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		None
+	}
+}
@@ -10,8 +10,9 @@ chain_id=$1
 
 [ -z "$chain_id" ] && usage
 
-pushd generate_genesis_values
-yarn
-popd
-
-node generate_genesis_values ../polkadot-parachains/res/$chain_id.json ../polkadot-parachains/res/${chain_id}_genesis_values.json
+../target/release/polkadot-collator build-spec \
+    --chain ../polkadot-parachains/res/$chain_id.json \
+    --raw \
+    --disable-default-bootnode \
+    --genesis-values ../polkadot-parachains/res/${chain_id}_genesis_values.json \
+    > /dev/null
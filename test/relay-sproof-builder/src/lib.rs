@@ -41,6 +41,9 @@ pub struct RelayStateSproofBuilder {
 	pub hrmp_egress_channel_index: Option<Vec<ParaId>>,
 	pub hrmp_channels: BTreeMap<relay_chain::v1::HrmpChannelId, AbridgedHrmpChannel>,
 	pub current_slot: relay_chain::v1::Slot,
+	/// Raw values of additional relay chain storage keys, e.g. the ones asked for by a
+	/// `RelayStateReader`.
+	pub additional_key_values: Vec<(Vec<u8>, Vec<u8>)>,
 }
 
 impl Default for RelayStateSproofBuilder {
@@ -64,6 +67,7 @@ impl Default for RelayStateSproofBuilder {
 			hrmp_egress_channel_index: None,
 			hrmp_channels: BTreeMap::new(),
 			current_slot: 0.into(),
+			additional_key_values: vec![],
 		}
 	}
 }
@@ -160,6 +164,10 @@ impl RelayStateSproofBuilder {
 				relay_chain::well_known_keys::CURRENT_SLOT.to_vec(),
 				self.current_slot.encode(),
 			);
+
+			for (key, value) in self.additional_key_values {
+				insert(key, value);
+			}
 		}
 
 		let root = backend.root().clone();
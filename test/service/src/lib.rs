@@ -28,6 +28,8 @@ use cumulus_client_service::{
 	prepare_node_config, start_collator, start_full_node, StartCollatorParams, StartFullNodeParams,
 };
 use cumulus_primitives_core::ParaId;
+use cumulus_relay_chain_interface::RelayChainInterface;
+use cumulus_relay_chain_local::RelayChainLocal;
 use cumulus_test_runtime::{Hash, Header, NodeBlock as Block, RuntimeApi};
 use polkadot_primitives::v1::{CollatorPair, Hash as PHash, PersistedValidationData};
 use sc_client_api::execution_extensions::ExecutionStrategies;
@@ -193,16 +195,23 @@ where
 
 	let client = params.client.clone();
 	let backend = params.backend.clone();
-	let block_announce_validator = BlockAnnounceValidator::new(
+	let relay_chain_interface: Arc<dyn RelayChainInterface> = Arc::new(RelayChainLocal::new(
 		relay_chain_full_node.client.clone(),
-		para_id,
-		Box::new(relay_chain_full_node.network.clone()),
 		relay_chain_full_node.backend.clone(),
-		relay_chain_full_node.client.clone(),
-	);
-	let block_announce_validator_builder = move |_| Box::new(block_announce_validator) as Box<_>;
+		Box::new(relay_chain_full_node.network.clone()),
+		relay_chain_full_node.overseer_handler.clone(),
+	));
 
 	let prometheus_registry = parachain_config.prometheus_registry().cloned();
+	let block_announce_metrics =
+		cumulus_client_network::Metrics::register(prometheus_registry.as_ref())
+			.map_err(|e| format!("Failed to register block announce metrics: {}", e))?;
+	let block_announce_validator = BlockAnnounceValidator::new(
+		relay_chain_interface.clone(),
+		para_id,
+		block_announce_metrics,
+	);
+	let block_announce_validator_builder = move |_| Box::new(block_announce_validator) as Box<_>;
 	let import_queue = cumulus_client_service::SharedImportQueue::new(params.import_queue);
 	let (network, system_rpc_tx, start_network) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -258,12 +267,18 @@ where
 
 				let relay_chain_client = relay_chain_full_node.client.clone();
 				let relay_chain_backend = relay_chain_full_node.backend.clone();
+				let para_client = client.clone();
 
 				Box::new(
 					cumulus_client_consensus_relay_chain::RelayChainConsensus::new(
 						para_id,
 						proposer_factory,
-						move |_, (relay_parent, validation_data)| {
+						move |parent, (relay_parent, validation_data)| {
+							let relay_state_keys =
+								cumulus_primitives_parachain_inherent::collect_relay_state_keys(
+									&*para_client,
+									parent,
+								);
 							let parachain_inherent =
 								cumulus_primitives_parachain_inherent::ParachainInherentData::create_at(
 									relay_parent,
@@ -271,6 +286,7 @@ where
 									&*relay_chain_backend,
 									&validation_data,
 									para_id,
+									&relay_state_keys,
 								);
 
 							async move {
@@ -287,15 +303,13 @@ where
 						client.clone(),
 						relay_chain_full_node.client.clone(),
 						relay_chain_full_node.backend.clone(),
+						Default::default(),
 					),
 				)
 			}
 			Consensus::Null => Box::new(NullConsensus),
 		};
 
-		let relay_chain_full_node =
-			relay_chain_full_node.with_client(polkadot_test_service::TestClient);
-
 		let params = StartCollatorParams {
 			block_status: client.clone(),
 			announce_block,
@@ -304,32 +318,29 @@ where
 			task_manager: &mut task_manager,
 			para_id,
 			parachain_consensus,
-			relay_chain_full_node: cumulus_client_service::RFullNode {
-				relay_chain_full_node,
-				collator_key,
-			},
+			relay_chain_interface,
+			collator_key,
 			import_queue,
+			prometheus_registry: prometheus_registry.as_ref(),
+			pov_recovery_config: Default::default(),
+			parachain_block_requester: None,
 		};
 
 		start_collator(params).await?;
 	} else {
-		let relay_chain_full_node =
-			relay_chain_full_node.with_client(polkadot_test_service::TestClient);
-
 		let params = StartFullNodeParams {
 			client: client.clone(),
 			announce_block,
 			task_manager: &mut task_manager,
 			para_id,
-			relay_chain_full_node: cumulus_client_service::RFullNode {
-				relay_chain_full_node,
-				collator_key: CollatorPair::generate().0,
-			},
+			relay_chain_interface,
 		};
 
 		start_full_node(params)?;
 	}
 
+	task_manager.add_child(relay_chain_full_node.task_manager);
+
 	start_network.start_network();
 
 	Ok((task_manager, client, network, rpc_handlers))